@@ -5,7 +5,7 @@ use crate::{ArgumentPolicy, ConditionCache, condition::ConditionError, has_acces
 use hasura_authn_core::SessionVariables;
 use metadata_resolve::{
     Conditions, ModelAuthorizationRule, ModelPredicate, RelationalDeletePermission,
-    RelationalInsertPermission, RelationalOperation, RelationalUpdatePermission,
+    RelationalInsertPermission, RelationalOperation, RelationalUpdatePermission, RowLimits,
 };
 use open_dds::query::ArgumentName;
 
@@ -19,6 +19,7 @@ pub struct ModelPermission<'a> {
     pub relational_insert: Option<RelationalInsertPermission>,
     pub relational_update: Option<RelationalUpdatePermission>,
     pub relational_delete: Option<RelationalDeletePermission>,
+    pub row_limits: RowLimits,
 }
 
 impl Default for ModelPermission<'_> {
@@ -36,6 +37,7 @@ impl ModelPermission<'_> {
             relational_insert: None,
             relational_update: None,
             relational_delete: None,
+            row_limits: RowLimits::default(),
         }
     }
 }
@@ -65,6 +67,9 @@ pub fn evaluate_model_authorization_rules<'a>(
     let mut relational_update = HasAccess::default();
     let mut relational_delete = HasAccess::default();
 
+    // the lowest row limits of all that apply
+    let mut row_limits = RowLimits::default();
+
     for model_rule in model_rules {
         match model_rule {
             ModelAuthorizationRule::Access {
@@ -189,6 +194,25 @@ pub fn evaluate_model_authorization_rules<'a>(
                     }
                 }
             }
+            ModelAuthorizationRule::RowLimits {
+                condition,
+                row_limits: rule_row_limits,
+            } => {
+                if evaluate_optional_condition_hash(
+                    condition.as_ref(),
+                    session_variables,
+                    conditions,
+                    condition_cache,
+                )? {
+                    row_limits = RowLimits {
+                        max_rows: lowest(row_limits.max_rows, rule_row_limits.max_rows),
+                        default_limit: lowest(
+                            row_limits.default_limit,
+                            rule_row_limits.default_limit,
+                        ),
+                    };
+                }
+            }
         }
     }
 
@@ -216,9 +240,17 @@ pub fn evaluate_model_authorization_rules<'a>(
         } else {
             None
         },
+        row_limits,
     }))
 }
 
+fn lowest(left: Option<u32>, right: Option<u32>) -> Option<u32> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(left.min(right)),
+        (left, right) => left.or(right),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
@@ -232,7 +264,8 @@ mod tests {
     use metadata_resolve::{
         AllowOrDeny, BinaryOperation, Condition, Conditions, ModelAuthorizationRule,
         ModelPredicate, Qualified, RelationalDeletePermission, RelationalInsertPermission,
-        RelationalOperation, RelationalUpdatePermission, UnaryComparisonOperator, ValueExpression,
+        RelationalOperation, RelationalUpdatePermission, RowLimits, UnaryComparisonOperator,
+        ValueExpression,
     };
     use open_dds::{
         identifier::{Identifier, SubgraphName},
@@ -798,7 +831,11 @@ mod tests {
         // deny takes precedence over allow
         assert_eq!(
             evaluate_model_authorization_rules(
-                &vec![allow_model_access_rule, allow_delete_rule, deny_delete_rule],
+                &vec![
+                    allow_model_access_rule.clone(),
+                    allow_delete_rule,
+                    deny_delete_rule
+                ],
                 &session.variables,
                 &conditions,
                 &mut condition_cache
@@ -806,6 +843,37 @@ mod tests {
             .unwrap(),
             Some(ModelPermission::new())
         );
+
+        let row_limits_rule = |max_rows, default_limit| ModelAuthorizationRule::RowLimits {
+            condition: Some(condition_id),
+            row_limits: RowLimits {
+                max_rows,
+                default_limit,
+            },
+        };
+
+        // the lowest row limits win
+        assert_eq!(
+            evaluate_model_authorization_rules(
+                &vec![
+                    allow_model_access_rule,
+                    row_limits_rule(Some(100), None),
+                    row_limits_rule(Some(50), Some(10)),
+                    row_limits_rule(None, Some(20)),
+                ],
+                &session.variables,
+                &conditions,
+                &mut condition_cache
+            )
+            .unwrap(),
+            Some(ModelPermission {
+                row_limits: RowLimits {
+                    max_rows: Some(50),
+                    default_limit: Some(10),
+                },
+                ..ModelPermission::new()
+            })
+        );
     }
 
     // Regression test: an unconditional allow rule followed by a deny rule
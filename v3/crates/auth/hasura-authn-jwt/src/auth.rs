@@ -71,53 +71,59 @@ pub async fn authenticate_request(
                                 },
                             )
                             .await?;
+                        identity_from_hasura_claims(
+                            &hasura_claims,
+                            allow_role_emulation_for,
+                            allow_switching_role,
+                        )
+                    }
+                })
+            },
+        )
+        .await
+}
 
-                        // Look for the `x-hasura-role` in the decoded claims.
-                        let desired_role = hasura_claims
-                            .custom_claims
-                            .get(&SESSION_VARIABLE_ROLE)
-                            .map(|v| {
-                                Ok::<_, Error>(Role::new(v.0.as_str().ok_or_else(|| {
-                                    Error::ClaimMustBeAString {
-                                        claim_name: SESSION_VARIABLE_ROLE.to_string(),
-                                    }
-                                })?))
-                            })
-                            .transpose()?;
-
-                        match desired_role {
-                            Some(role) => {
-                                // `x-hasura-role` is found, check if it's the
-                                // role that can emulate by comparing it to
-                                // `allow_role_emulation_for`, otherwise
-                                // return the specific identity.
-                                if Some(&role) == allow_role_emulation_for {
-                                    Ok(Identity::RoleEmulationEnabled(role))
-                                } else if allow_switching_role {
-                                    if (hasura_claims.allowed_roles).contains(&role) {
-                                        // Returns the specified `x-hasura-role` if it exists in the list of allowed roles.
-                                        Ok(Identity::Specific {
-                                            default_role: role,
-                                            allowed_roles: build_allowed_roles(&hasura_claims)?,
-                                        })
-                                    } else {
-                                        Err(Error::DisallowedRole)
-                                    }
-                                } else {
-                                    // If the allow_switching_role flag is false,
-                                    // keep the old behavior for backward compatibility.
-                                    Ok(Identity::Specific {
-                                        default_role: hasura_claims.default_role.clone(),
-                                        allowed_roles: build_allowed_roles(&hasura_claims)?,
-                                    })
-                                }
-                            }
-                            // `x-hasura-role` is not found, so build the specific identity.
-                            None => Ok(Identity::Specific {
-                                default_role: hasura_claims.default_role.clone(),
-                                allowed_roles: build_allowed_roles(&hasura_claims)?,
-                            }),
-                        }
+/// Authenticates the request like `authenticate_request`, verifying the JWT
+/// with the config of the issuer named in its `iss` claim.
+pub async fn authenticate_request_multi_issuer(
+    http_client: &reqwest::Client,
+    jwt_config: &JWTMultiIssuerConfig,
+    headers: &HeaderMap,
+    allow_role_emulation_for: Option<&Role>,
+    audience_validation_mode: AudienceValidationMode,
+    allow_switching_role: bool,
+) -> Result<Identity, Error> {
+    let tracer = tracing_util::global_tracer();
+    tracer
+        .in_span_async(
+            "jwt_authenticate_request",
+            "Authenticate request using JSON Web Token",
+            SpanVisibility::Internal,
+            || {
+                Box::pin({
+                    async {
+                        let authorization_token: String =
+                            get_authorization_token(&jwt_config.token_location, headers)?;
+                        let hasura_claims = tracer
+                            .in_span_async(
+                                "decode_and_parse_hasura_claims",
+                                "Decode and parse Hasura claims",
+                                SpanVisibility::Internal,
+                                || {
+                                    Box::pin(decode_and_parse_hasura_claims_multi_issuer(
+                                        http_client,
+                                        jwt_config,
+                                        authorization_token,
+                                        audience_validation_mode,
+                                    ))
+                                },
+                            )
+                            .await?;
+                        identity_from_hasura_claims(
+                            &hasura_claims,
+                            allow_role_emulation_for,
+                            allow_switching_role,
+                        )
                     }
                 })
             },
@@ -125,6 +131,59 @@ pub async fn authenticate_request(
         .await
 }
 
+fn identity_from_hasura_claims(
+    hasura_claims: &HasuraClaims,
+    allow_role_emulation_for: Option<&Role>,
+    allow_switching_role: bool,
+) -> Result<Identity, Error> {
+    // Look for the `x-hasura-role` in the decoded claims.
+    let desired_role = hasura_claims
+        .custom_claims
+        .get(&SESSION_VARIABLE_ROLE)
+        .map(|v| {
+            Ok::<_, Error>(Role::new(v.0.as_str().ok_or_else(|| {
+                Error::ClaimMustBeAString {
+                    claim_name: SESSION_VARIABLE_ROLE.to_string(),
+                }
+            })?))
+        })
+        .transpose()?;
+
+    match desired_role {
+        Some(role) => {
+            // `x-hasura-role` is found, check if it's the
+            // role that can emulate by comparing it to
+            // `allow_role_emulation_for`, otherwise
+            // return the specific identity.
+            if Some(&role) == allow_role_emulation_for {
+                Ok(Identity::RoleEmulationEnabled(role))
+            } else if allow_switching_role {
+                if (hasura_claims.allowed_roles).contains(&role) {
+                    // Returns the specified `x-hasura-role` if it exists in the list of allowed roles.
+                    Ok(Identity::Specific {
+                        default_role: role,
+                        allowed_roles: build_allowed_roles(hasura_claims)?,
+                    })
+                } else {
+                    Err(Error::DisallowedRole)
+                }
+            } else {
+                // If the allow_switching_role flag is false,
+                // keep the old behavior for backward compatibility.
+                Ok(Identity::Specific {
+                    default_role: hasura_claims.default_role.clone(),
+                    allowed_roles: build_allowed_roles(hasura_claims)?,
+                })
+            }
+        }
+        // `x-hasura-role` is not found, so build the specific identity.
+        None => Ok(Identity::Specific {
+            default_role: hasura_claims.default_role.clone(),
+            allowed_roles: build_allowed_roles(hasura_claims)?,
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -404,4 +463,102 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_multi_issuer() -> anyhow::Result<()> {
+        // Authenticates a token from `issuer`, signed with `secret`
+        async fn authenticate(
+            http_client: &reqwest::Client,
+            jwt_config: &JWTMultiIssuerConfig,
+            issuer: Option<&str>,
+            secret: &str,
+        ) -> anyhow::Result<Result<Identity, Error>> {
+            let mut claims = get_claims(
+                &serde_json::to_value(get_default_hasura_claims())?,
+                jsonptr::PointerBuf::from_tokens([DEFAULT_HASURA_CLAIMS_NAMESPACE]).as_str(),
+            )?;
+            claims.iss = issuer.map(ToString::to_string);
+            let encoded_claims = encode(
+                &jwt::Header::default(),
+                &claims,
+                &EncodingKey::from_secret(secret.as_ref()),
+            )?;
+            let mut header_map = HeaderMap::new();
+            header_map.insert(
+                AUTHORIZATION,
+                ("Bearer ".to_owned() + &encoded_claims).parse()?,
+            );
+            Ok(authenticate_request_multi_issuer(
+                http_client,
+                jwt_config,
+                &header_map,
+                None,
+                AudienceValidationMode::Required,
+                false,
+            )
+            .await)
+        }
+
+        let issuer_config = |issuer: &str, secret: &str| {
+            json!({
+                "issuer": issuer,
+                "key": {
+                    "fixed": {
+                        "algorithm": "HS256",
+                        "key": {
+                           "value": secret,
+                        },
+                    }
+                },
+                "claimsConfig": {
+                    "namespace": {
+                        "claimsFormat": "Json",
+                        "location": jsonptr::PointerBuf::from_tokens([DEFAULT_HASURA_CLAIMS_NAMESPACE]),
+                    },
+                },
+            })
+        };
+        let jwt_config: JWTMultiIssuerConfig = serde_json::from_value(json!({
+            "tokenLocation": {
+                "type": "BearerAuthorization",
+            },
+            "issuers": [
+                issuer_config("first", "first_token"),
+                issuer_config("second", "second_token"),
+            ],
+        }))?;
+
+        let http_client = reqwest::Client::new();
+
+        let expected_identity = get_specific_identity(Role::new("user"));
+        assert_eq!(
+            authenticate(&http_client, &jwt_config, Some("first"), "first_token").await??,
+            expected_identity
+        );
+        assert_eq!(
+            authenticate(&http_client, &jwt_config, Some("second"), "second_token").await??,
+            expected_identity
+        );
+
+        // Each issuer's tokens are verified with that issuer's key
+        assert!(
+            authenticate(&http_client, &jwt_config, Some("second"), "first_token")
+                .await?
+                .is_err_and(|err| matches!(err, Error::JWTValidationError(_)))
+        );
+        assert!(
+            authenticate(&http_client, &jwt_config, Some("third"), "first_token")
+                .await?
+                .is_err_and(
+                    |err| matches!(err, Error::UnknownIssuer { issuer } if issuer == "third")
+                )
+        );
+        assert!(
+            authenticate(&http_client, &jwt_config, None, "first_token")
+                .await?
+                .is_err_and(|err| matches!(err, Error::IssuerClaimNotFound))
+        );
+
+        Ok(())
+    }
 }
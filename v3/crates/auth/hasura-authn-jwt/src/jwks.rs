@@ -0,0 +1,326 @@
+//! Caching the JWK sets fetched for the `jwkFromUrl` key mode.
+//!
+//! A JWK set is cached for as long as the `Cache-Control` or `Expires` header of the response it
+//! came in allows, or for [`DEFAULT_TTL`] if the response has neither. It is refreshed in the
+//! background shortly before it expires, and fetched again when a token names a key it doesn't
+//! have, in case the keys have been rotated.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use reqwest::header::{CACHE_CONTROL, EXPIRES, HeaderMap};
+use tracing_util::SpanVisibility;
+use url::Url;
+
+use crate::jwt::{Error, InternalError};
+
+/// How long a JWK set is cached for when its response says nothing about caching.
+const DEFAULT_TTL: Duration = Duration::from_secs(600);
+
+/// The longest a JWK set is cached for, whatever its response says.
+const MAX_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long before a cached JWK set expires it is refreshed in the background.
+const REFRESH_AHEAD: Duration = Duration::from_secs(30);
+
+/// The shortest time between fetches of a JWK set for tokens with keys it doesn't have, so that
+/// such tokens can't be used to flood the JWKS endpoint with requests.
+const MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The JWK sets fetched by the engine, by URL.
+pub(crate) static JWKS_CACHE: LazyLock<JwkSetCache> = LazyLock::new(JwkSetCache::default);
+
+#[derive(Default)]
+pub(crate) struct JwkSetCache {
+    entries: Mutex<HashMap<Url, Arc<CachedJwkSet>>>,
+}
+
+struct CachedJwkSet {
+    jwk_set: JwkSet,
+    fetched_at: Instant,
+    expires_at: Instant,
+    /// Whether a background refresh of this set has been started
+    refreshing: AtomicBool,
+}
+
+impl JwkSetCache {
+    /// The key with ID `kid` from the JWK set at `url`.
+    pub(crate) async fn get(
+        &'static self,
+        http_client: &reqwest::Client,
+        url: &Url,
+        kid: &str,
+    ) -> Result<Jwk, Error> {
+        self.get_at(http_client, url, kid, Instant::now()).await
+    }
+
+    async fn get_at(
+        &'static self,
+        http_client: &reqwest::Client,
+        url: &Url,
+        kid: &str,
+        now: Instant,
+    ) -> Result<Jwk, Error> {
+        let cached = self
+            .entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(url)
+            .cloned();
+
+        if let Some(cached) = cached
+            && now < cached.expires_at
+        {
+            if let Some(jwk) = cached.jwk_set.find(kid) {
+                if now + REFRESH_AHEAD >= cached.expires_at
+                    && !cached.refreshing.swap(true, Ordering::AcqRel)
+                {
+                    self.refresh_in_background(http_client.clone(), url.clone(), cached.clone());
+                }
+                return Ok(jwk.clone());
+            }
+            if now.duration_since(cached.fetched_at) < MIN_REFETCH_INTERVAL {
+                return Err(no_matching_jwk(kid));
+            }
+        }
+
+        let cached = self.fetch(http_client, url, now).await?;
+        cached
+            .jwk_set
+            .find(kid)
+            .cloned()
+            .ok_or_else(|| no_matching_jwk(kid))
+    }
+
+    async fn fetch(
+        &self,
+        http_client: &reqwest::Client,
+        url: &Url,
+        now: Instant,
+    ) -> Result<Arc<CachedJwkSet>, Error> {
+        let (jwk_set, ttl) = fetch_jwk_set(http_client, url).await?;
+        let cached = Arc::new(CachedJwkSet {
+            jwk_set,
+            fetched_at: now,
+            expires_at: now + ttl,
+            refreshing: AtomicBool::new(false),
+        });
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(url.clone(), cached.clone());
+        Ok(cached)
+    }
+
+    fn refresh_in_background(
+        &'static self,
+        http_client: reqwest::Client,
+        url: Url,
+        cached: Arc<CachedJwkSet>,
+    ) {
+        tokio::spawn(async move {
+            // a failed refresh leaves the cached set to be used until it expires, and lets the
+            // next request try again
+            if self
+                .fetch(&http_client, &url, Instant::now())
+                .await
+                .is_err()
+            {
+                cached.refreshing.store(false, Ordering::Release);
+            }
+        });
+    }
+}
+
+fn no_matching_jwk(kid: &str) -> Error {
+    InternalError::NoMatchingJWKFound {
+        kid: kid.to_string(),
+    }
+    .into()
+}
+
+/// Fetches the JWK set at `url`, with how long it can be cached for.
+async fn fetch_jwk_set(
+    http_client: &reqwest::Client,
+    url: &Url,
+) -> Result<(JwkSet, Duration), Error> {
+    let tracer = tracing_util::global_tracer();
+    tracer
+        .in_span_async("fetch_jwk", "Fetch JWK", SpanVisibility::Internal, || {
+            Box::pin(async {
+                let jwk_request = http_client
+                    .get(url.clone())
+                    .headers(tracing_util::get_trace_headers())
+                    .timeout(Duration::from_secs(60))
+                    .build()
+                    .map_err(InternalError::ReqwestError)?;
+
+                let jwk_response = http_client
+                    .execute(jwk_request)
+                    .await
+                    .map_err(InternalError::ErrorFetchingJWKSet)?;
+                if jwk_response.status().is_success() {
+                    let ttl = cache_ttl(jwk_response.headers());
+                    let jwk_set: JwkSet = jwk_response
+                        .json()
+                        .await
+                        .map_err(InternalError::ReqwestError)?;
+                    Ok((jwk_set, ttl))
+                } else {
+                    Err(InternalError::UnsuccessfulJWKFetch(jwk_response.status()))?
+                }
+            })
+        })
+        .await
+}
+
+/// How long a response can be cached for, going by its `Cache-Control` and `Expires` headers.
+fn cache_ttl(headers: &HeaderMap) -> Duration {
+    if let Some(cache_control) = headers
+        .get(CACHE_CONTROL)
+        .and_then(|value| value.to_str().ok())
+    {
+        let mut max_age = None;
+        for directive in cache_control.split(',').map(str::trim) {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive, None),
+            };
+            if name.eq_ignore_ascii_case("no-store") || name.eq_ignore_ascii_case("no-cache") {
+                return Duration::ZERO;
+            }
+            if name.eq_ignore_ascii_case("max-age") {
+                max_age = value
+                    .and_then(|value| value.parse().ok())
+                    .map(Duration::from_secs);
+            }
+        }
+        if let Some(max_age) = max_age {
+            return max_age.min(MAX_TTL);
+        }
+    }
+
+    if let Some(expires) = headers.get(EXPIRES).and_then(|value| value.to_str().ok()) {
+        // an invalid date means the response has already expired
+        return chrono::DateTime::parse_from_rfc2822(expires)
+            .ok()
+            .and_then(|expires| (expires.to_utc() - chrono::Utc::now()).to_std().ok())
+            .unwrap_or(Duration::ZERO)
+            .min(MAX_TTL);
+    }
+
+    DEFAULT_TTL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebkey as jwk;
+    use reqwest::header::HeaderValue;
+
+    fn ttl(headers: &[(reqwest::header::HeaderName, &str)]) -> Duration {
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            header_map.insert(name, HeaderValue::from_str(value).unwrap());
+        }
+        cache_ttl(&header_map)
+    }
+
+    #[test]
+    fn test_cache_ttl() {
+        assert_eq!(ttl(&[]), DEFAULT_TTL);
+        assert_eq!(
+            ttl(&[(CACHE_CONTROL, "public, max-age=300")]),
+            Duration::from_secs(300)
+        );
+        assert_eq!(
+            ttl(&[(CACHE_CONTROL, "max-age=\"60\"")]),
+            Duration::from_secs(60)
+        );
+        assert_eq!(ttl(&[(CACHE_CONTROL, "max-age=99999999")]), MAX_TTL);
+        assert_eq!(ttl(&[(CACHE_CONTROL, "no-cache")]), Duration::ZERO);
+        assert_eq!(
+            ttl(&[(CACHE_CONTROL, "no-store, max-age=300")]),
+            Duration::ZERO
+        );
+        // max-age takes precedence over Expires
+        assert_eq!(
+            ttl(&[
+                (CACHE_CONTROL, "max-age=300"),
+                (EXPIRES, "Wed, 21 Oct 2015 07:28:00 GMT")
+            ]),
+            Duration::from_secs(300)
+        );
+        assert_eq!(
+            ttl(&[(EXPIRES, "Wed, 21 Oct 2015 07:28:00 GMT")]),
+            Duration::ZERO
+        );
+        assert_eq!(ttl(&[(EXPIRES, "0")]), Duration::ZERO);
+        let expires = (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc2822();
+        let expires_ttl = ttl(&[(EXPIRES, &expires)]);
+        assert!(
+            expires_ttl > Duration::from_secs(3590) && expires_ttl <= Duration::from_secs(3600)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refetch_on_unknown_kid() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let mut old_key = jwk::JsonWebKey::new(jwk::Key::generate_p256());
+        old_key.key_id = Some("old_kid".to_string());
+        let mut new_key = jwk::JsonWebKey::new(jwk::Key::generate_p256());
+        new_key.key_id = Some("new_kid".to_string());
+
+        let old_keys = server
+            .mock("GET", "/jwk")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("cache-control", "max-age=3600")
+            .with_body(serde_json::json!({ "keys": [old_key] }).to_string())
+            .expect(1)
+            .create_async()
+            .await;
+
+        let cache: &'static JwkSetCache = Box::leak(Box::default());
+        let http_client = reqwest::Client::new();
+        let url = Url::parse(&(server.url() + "/jwk"))?;
+        let now = Instant::now();
+
+        cache.get_at(&http_client, &url, "old_kid", now).await?;
+        // the cached set is used while it hasn't expired
+        cache
+            .get_at(&http_client, &url, "old_kid", now + Duration::from_secs(60))
+            .await?;
+        // unknown keys are not fetched again straight away
+        assert_eq!(
+            cache
+                .get_at(&http_client, &url, "new_kid", now + Duration::from_secs(1))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Internal Error - No matching JWK found for the given kid: new_kid"
+        );
+        old_keys.assert_async().await;
+        old_keys.remove_async().await;
+
+        let new_keys = server
+            .mock("GET", "/jwk")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("cache-control", "max-age=3600")
+            .with_body(serde_json::json!({ "keys": [old_key, new_key] }).to_string())
+            .expect(1)
+            .create_async()
+            .await;
+
+        cache
+            .get_at(&http_client, &url, "new_kid", now + MIN_REFETCH_INTERVAL)
+            .await?;
+        new_keys.assert_async().await;
+        Ok(())
+    }
+}
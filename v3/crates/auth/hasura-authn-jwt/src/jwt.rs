@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::time::Duration;
 
 use axum::http::{HeaderMap, HeaderValue};
 use cookie::{self, Cookie};
@@ -17,9 +16,11 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as SerdeDeError};
 use serde_json::{Value, json};
 use std::collections::HashSet;
-use tracing_util::{ErrorVisibility, SpanVisibility, TraceableError};
+use tracing_util::{ErrorVisibility, TraceableError};
 use url::Url;
 
+use crate::jwks;
+
 /// Name of the key, which is by default used to lookup the Hasura claims
 /// in the claims obtained after decoding the JWT.
 pub(crate) const DEFAULT_HASURA_CLAIMS_NAMESPACE: &str = "claims.jwt.hasura.io";
@@ -61,6 +62,10 @@ pub enum Error {
     MissingCookieValue { cookie_name: String },
     #[error("JWT validation error: {0}")]
     JWTValidationError(jwt::errors::Error),
+    #[error("`iss` (Issuer) claim not found in the JWT")]
+    IssuerClaimNotFound,
+    #[error("The issuer {issuer} is not one of the configured issuers")]
+    UnknownIssuer { issuer: String },
     #[error("Internal Error - {0}")]
     Internal(#[from] InternalError),
 }
@@ -122,7 +127,9 @@ impl Error {
             | Error::CookieParseError { err: _ }
             | Error::MissingCookieValue { cookie_name: _ }
             | Error::ClaimMustBeAString { claim_name: _ }
-            | Error::JWTValidationError(_) => StatusCode::BAD_REQUEST,
+            | Error::JWTValidationError(_)
+            | Error::IssuerClaimNotFound
+            | Error::UnknownIssuer { issuer: _ } => StatusCode::BAD_REQUEST,
         }
     }
 
@@ -149,7 +156,9 @@ impl Error {
             | Error::CookieParseError { err: _ }
             | Error::MissingCookieValue { cookie_name: _ }
             | Error::ClaimMustBeAString { claim_name: _ }
-            | Error::JWTValidationError(_) => false,
+            | Error::JWTValidationError(_)
+            | Error::IssuerClaimNotFound
+            | Error::UnknownIssuer { issuer: _ } => false,
         };
         engine_types::MiddlewareError {
             status: self.to_status_code(),
@@ -396,6 +405,108 @@ impl JWTConfig {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+#[schemars(title = "JWTMultiIssuerConfig")]
+#[schemars(example = "JWTMultiIssuerConfig::example")]
+/// JWT config for accepting tokens from several issuers. The `iss` claim
+/// of the incoming JWT picks the issuer that the JWT is verified and
+/// decoded with.
+pub struct JWTMultiIssuerConfig {
+    /// Source of the JWT authentication token.
+    pub token_location: JWTTokenLocation,
+    /// The issuers that tokens are accepted from.
+    pub issuers: Vec<JWTIssuerConfig>,
+}
+
+impl JWTMultiIssuerConfig {
+    fn example() -> Self {
+        let value = json!(
+            {
+                "tokenLocation": {
+                    "type": "BearerAuthorization"
+                },
+                "issuers": [
+                    {
+                        "issuer": "https://auth.example.com",
+                        "key": {
+                            "jwkFromUrl": "https://auth.example.com/.well-known/jwks.json"
+                        },
+                        "claimsConfig": {
+                            "namespace": {
+                                "claimsFormat": "Json",
+                                "location": jsonptr::PointerBuf::from_tokens([DEFAULT_HASURA_CLAIMS_NAMESPACE]),
+                            }
+                        }
+                    },
+                    {
+                        "issuer": "internal",
+                        "key": {
+                            "fixed": {
+                                "algorithm": "HS256",
+                                "key": {
+                                    "value": "token"
+                                }
+                            }
+                        },
+                        "claimsConfig": {
+                            "namespace": {
+                                "claimsFormat": "Json",
+                                "location": jsonptr::PointerBuf::from_tokens([DEFAULT_HASURA_CLAIMS_NAMESPACE]),
+                            }
+                        }
+                    }
+                ]
+            }
+        );
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// The config for the issuer of the given JWT, going by its `iss` claim.
+    fn issuer_config_for(&self, jwt: &str) -> Result<&JWTIssuerConfig, Error> {
+        // The signature is checked once the issuer, and so the key, is known.
+        let mut validation = Validation::default();
+        validation.insecure_disable_signature_validation();
+        validation.validate_exp = false;
+        validation.validate_aud = false;
+        validation.required_spec_claims.clear();
+        let claims: serde_json::Value = decode(jwt, &DecodingKey::from_secret(&[]), &validation)
+            .map_err(categorize_jwt_error)?
+            .claims;
+        let issuer = claims
+            .get("iss")
+            .and_then(serde_json::Value::as_str)
+            .ok_or(Error::IssuerClaimNotFound)?;
+        self.issuers
+            .iter()
+            .find(|issuer_config| issuer_config.issuer == issuer)
+            .ok_or_else(|| Error::UnknownIssuer {
+                issuer: issuer.to_string(),
+            })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+#[schemars(title = "JWTIssuerConfig")]
+/// Config for one of the issuers of a `JWTMultiIssuerConfig`.
+pub struct JWTIssuerConfig {
+    /// The `iss` claim of the tokens from this issuer.
+    pub issuer: String,
+    /// Optional validation to check that the `aud` field is a member
+    /// of the `audience` received, otherwise will throw error.
+    pub audience: Option<HashSet<String>>,
+    /// Allowed leeway (in seconds) to the `exp` validation
+    /// to account for clock skew.
+    pub allowed_skew: Option<u64>,
+    /// Claims config. Either specified via `claims_mappings` or `claims_namespace_path`
+    pub claims_config: JWTClaimsConfig,
+    /// Mode according to which the JWT auth is configured.
+    pub key: JWTKey,
+}
+
 /// JWT Secret config according to which the
 /// incoming JWT will be decoded.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
@@ -470,42 +581,14 @@ async fn get_decoding_key_from_jwk_url(
     jwk_url: &Url,
     jwt_authorization_header: &str,
 ) -> Result<(Vec<jwt::Algorithm>, jwt::DecodingKey), Error> {
-    let tracer = tracing_util::global_tracer();
-    tracer
-        .in_span_async("fetch_jwk", "Fetch JWK", SpanVisibility::Internal, || {
-            Box::pin(async {
-                let jwk_request = http_client
-                    .get(jwk_url.clone())
-                    .headers(tracing_util::get_trace_headers())
-                    .timeout(Duration::from_secs(60))
-                    .build()
-                    .map_err(InternalError::ReqwestError)?;
-
-                let jwk_response = http_client
-                    .execute(jwk_request)
-                    .await
-                    .map_err(InternalError::ErrorFetchingJWKSet)?;
-                if jwk_response.status().is_success() {
-                    let jwk_set: jwt::jwk::JwkSet = jwk_response
-                        .json()
-                        .await
-                        .map_err(InternalError::ReqwestError)?;
-                    let decoded_header = decode_header(jwt_authorization_header)
-                        .map_err(Error::ErrorDecodingAuthorizationHeader)?;
-                    let kid = decoded_header.kid.ok_or(Error::KidHeaderNotFound)?;
-                    let jwk = jwk_set
-                        .find(kid.as_str())
-                        .ok_or(InternalError::NoMatchingJWKFound { kid })?;
-                    let decoding_key = jwt::DecodingKey::from_jwk(jwk)
-                        .map_err(InternalError::JWTDecodingKeyError)?;
-                    let acceptable_algorithms = get_acceptable_algorithms_for_key(jwk);
-                    Ok((acceptable_algorithms, decoding_key))
-                } else {
-                    Err(InternalError::UnsuccessfulJWKFetch(jwk_response.status()))?
-                }
-            })
-        })
-        .await
+    let decoded_header =
+        decode_header(jwt_authorization_header).map_err(Error::ErrorDecodingAuthorizationHeader)?;
+    let kid = decoded_header.kid.ok_or(Error::KidHeaderNotFound)?;
+    let jwk = jwks::JWKS_CACHE.get(http_client, jwk_url, &kid).await?;
+    let decoding_key =
+        jwt::DecodingKey::from_jwk(&jwk).map_err(InternalError::JWTDecodingKeyError)?;
+    let acceptable_algorithms = get_acceptable_algorithms_for_key(&jwk);
+    Ok((acceptable_algorithms, decoding_key))
 }
 
 fn get_acceptable_algorithms_for_key(jwk: &jwt::jwk::Jwk) -> Vec<jwt::Algorithm> {
@@ -605,13 +688,57 @@ fn categorize_jwt_error(jwt_error: jwt::errors::Error) -> Error {
     }
 }
 
+/// How a JWT is verified and its Hasura claims found, whether it comes from
+/// the single issuer of a `JWTConfig` or one of the issuers of a
+/// `JWTMultiIssuerConfig`.
+struct JWTVerification<'a> {
+    key: &'a JWTKey,
+    issuer: Option<&'a str>,
+    audience: Option<&'a HashSet<String>>,
+    allowed_skew: Option<u64>,
+    claims_config: &'a JWTClaimsConfig,
+}
+
 pub(crate) async fn decode_and_parse_hasura_claims(
     http_client: &reqwest::Client,
     jwt_config: &JWTConfig,
     jwt: String,
     audience_validation_mode: AudienceValidationMode,
 ) -> Result<HasuraClaims, Error> {
-    let (acceptable_algorithms, decoding_key) = match &jwt_config.key {
+    let verification = JWTVerification {
+        key: &jwt_config.key,
+        issuer: jwt_config.issuer.as_deref(),
+        audience: jwt_config.audience.as_ref(),
+        allowed_skew: jwt_config.allowed_skew,
+        claims_config: &jwt_config.claims_config,
+    };
+    verify_and_parse_hasura_claims(http_client, &verification, jwt, audience_validation_mode).await
+}
+
+pub(crate) async fn decode_and_parse_hasura_claims_multi_issuer(
+    http_client: &reqwest::Client,
+    jwt_config: &JWTMultiIssuerConfig,
+    jwt: String,
+    audience_validation_mode: AudienceValidationMode,
+) -> Result<HasuraClaims, Error> {
+    let issuer_config = jwt_config.issuer_config_for(&jwt)?;
+    let verification = JWTVerification {
+        key: &issuer_config.key,
+        issuer: Some(issuer_config.issuer.as_str()),
+        audience: issuer_config.audience.as_ref(),
+        allowed_skew: issuer_config.allowed_skew,
+        claims_config: &issuer_config.claims_config,
+    };
+    verify_and_parse_hasura_claims(http_client, &verification, jwt, audience_validation_mode).await
+}
+
+async fn verify_and_parse_hasura_claims(
+    http_client: &reqwest::Client,
+    jwt_config: &JWTVerification<'_>,
+    jwt: String,
+    audience_validation_mode: AudienceValidationMode,
+) -> Result<HasuraClaims, Error> {
+    let (acceptable_algorithms, decoding_key) = match jwt_config.key {
         JWTKey::Fixed(conf) => (vec![conf.algorithm], get_decoding_key(conf)?),
         JWTKey::JwkFromUrl(jwk_url) => {
             get_decoding_key_from_jwk_url(http_client, jwk_url, &jwt).await?
@@ -626,11 +753,11 @@ pub(crate) async fn decode_and_parse_hasura_claims(
     };
 
     // Additional validations according to the `jwt_config`.
-    if let Some(aud) = &jwt_config.audience {
+    if let Some(aud) = jwt_config.audience {
         validation.set_audience(&aud.iter().collect::<Vec<_>>());
     }
 
-    if let Some(issuer) = &jwt_config.issuer {
+    if let Some(issuer) = jwt_config.issuer {
         validation.set_issuer(&[issuer]);
     }
 
@@ -642,7 +769,7 @@ pub(crate) async fn decode_and_parse_hasura_claims(
         .map_err(categorize_jwt_error)?
         .claims;
 
-    let hasura_claims = match &jwt_config.claims_config {
+    let hasura_claims = match jwt_config.claims_config {
         // This case can be avoided, if we can use serde's `Default` and `Flatten`
         // together, but unfortunately that is not possible at the moment.
         // https://github.com/serde-rs/serde/issues/1626
@@ -192,6 +192,7 @@ impl AuthConfigV3 {
 pub enum AuthModeConfigV3 {
     Webhook(webhook::AuthHookConfigV3),
     Jwt(Box<jwt::JWTConfig>),
+    JwtMultiIssuer(Box<jwt::JWTMultiIssuerConfig>),
     NoAuth(noauth::NoAuthConfig),
 }
 
@@ -511,6 +512,15 @@ impl tracing_util::TraceableError for AuthError {
 }
 
 impl AuthError {
+    /// The auth mode that failed, or `unknown` if the requested mode could not be determined
+    pub fn mode(&self) -> &'static str {
+        match self {
+            AuthError::Jwt(_) => "jwt",
+            AuthError::Webhook(_) => "webhook",
+            AuthError::InvalidAuthModeHeader(_) => "unknown",
+        }
+    }
+
     pub fn into_middleware_error(self) -> engine_types::MiddlewareError {
         match self {
             AuthError::Jwt(e) => e.into_middleware_error(),
@@ -667,6 +677,21 @@ pub async fn authenticate(
             .map(AuthenticateResponse::new)
             .map_err(AuthError::from)
         }
+        PossibleAuthModeConfig::V3V4(AuthModeConfigV3::JwtMultiIssuer(jwt_config)) => {
+            jwt_auth::authenticate_request_multi_issuer(
+                client,
+                jwt_config,
+                headers_map,
+                allow_role_emulation_by,
+                resolved_auth_config
+                    .auth_config_flags
+                    .require_audience_validation,
+                resolved_auth_config.auth_config_flags.allow_switching_role,
+            )
+            .await
+            .map(AuthenticateResponse::new)
+            .map_err(AuthError::from)
+        }
     }?;
 
     // Apply claims-to-trace-attributes mapping if configured.
@@ -7,6 +7,13 @@ pub struct HttpContext {
     pub client: reqwest::Client,
     /// Response size limit for NDC requests
     pub ndc_response_size_limit: Option<usize>,
+    /// Maximum number of variable sets sent to a data connector in a single
+    /// remote join request. If not set, all of them are sent in one request.
+    pub remote_join_batch_size: Option<std::num::NonZeroUsize>,
+    /// Maximum number of remote join requests executed at once, for each join
+    /// and for the joins at each level of a query. If not set, a default limit
+    /// is used.
+    pub remote_join_concurrency: Option<std::num::NonZeroUsize>,
 }
 
 #[derive(Clone, serde::Serialize, Debug)]
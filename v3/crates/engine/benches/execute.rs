@@ -79,6 +79,8 @@ pub fn bench_execute(
     let http_context = HttpContext {
         client: reqwest::Client::new(),
         ndc_response_size_limit: None,
+        remote_join_batch_size: None,
+        remote_join_concurrency: None,
     };
     let runtime = Runtime::new().unwrap();
 
@@ -87,6 +89,7 @@ pub fn bench_execute(
         operation_name: None,
         query,
         variables: None,
+        extensions: None,
     };
 
     let request_headers = reqwest::header::HeaderMap::new();
@@ -264,6 +267,9 @@ pub fn bench_execute(
                     &request_headers,
                     request.clone(),
                     None,
+                    None,
+                    None,
+                    &axum::http::Method::POST,
                 )
                 .await
                 .unwrap()
@@ -1,12 +1,18 @@
+use axum::Router;
 use clap::Parser;
 use engine::{
-    StartupError, VERSION, get_base_routes, get_cors_layer, get_jsonapi_route, get_metadata_routes,
+    EngineState, ReloadSources, ReloadableRouter, SchemaDriftCheck, StartupError, VERSION,
+    get_base_routes, get_cors_layer, get_jsonapi_route, get_metadata_routes, get_metrics_route,
+    get_schema_drift_route,
     internal_flags::{UnstableFeature, resolve_unstable_features},
+    reload_on_change,
 };
 use engine_types::ExposeInternalErrors;
 use serde::Serialize;
 use std::net;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::PathBuf;
+use std::time::Duration;
 use tower_http::compression::CompressionLayer;
 use tracing_util::{SpanVisibility, add_event_on_active_span, set_attribute_on_active_span};
 
@@ -28,12 +34,19 @@ struct ServerOptions {
     /// An introspection metadata file, served over `/metadata` if provided.
     #[arg(long, value_name = "PATH", env = "INTROSPECTION_METADATA_FILE")]
     introspection_metadata: Option<PathBuf>,
+    /// Serve Prometheus metrics over `/metrics`.
+    #[arg(long, env = "ENABLE_METRICS")]
+    enable_metrics: bool,
     /// The OpenTelemetry collector endpoint.
     #[arg(long, value_name = "URL", env = "OTLP_ENDPOINT")]
     otlp_endpoint: Option<String>,
     /// The configuration file used for authentication.
     #[arg(long, value_name = "PATH", env = "AUTHN_CONFIG_PATH")]
     authn_config_path: PathBuf,
+    /// Check the metadata and auth config files for changes at this interval, and reload them
+    /// when they change. They are also reloaded when the engine receives a SIGHUP.
+    #[arg(long, value_name = "MILLISECONDS", env = "METADATA_WATCH_INTERVAL_MS")]
+    metadata_watch_interval_ms: Option<NonZeroU64>,
     /// The host IP on which the server listens, defaulting to all IPv4 and IPv6 addresses.
     #[arg(long, value_name = "HOST", env = "HOST", default_value_t = net::IpAddr::V6(net::Ipv6Addr::UNSPECIFIED))]
     host: net::IpAddr,
@@ -85,6 +98,55 @@ struct ServerOptions {
     #[arg(long, value_name = "NDC_RESPONSE_SIZE_LIMIT in bytes", env = "NDC_RESPONSE_SIZE_LIMIT", default_value_t = 30 * MB)]
     ndc_response_size_limit: usize,
 
+    /// Maximum number of variable sets sent to a data connector in a single remote join request.
+    /// Larger joins are split into several requests, executed concurrently. If not provided, all
+    /// variable sets are sent in one request.
+    #[arg(
+        long,
+        value_name = "REMOTE_JOIN_BATCH_SIZE",
+        env = "REMOTE_JOIN_BATCH_SIZE"
+    )]
+    remote_join_batch_size: Option<NonZeroUsize>,
+
+    /// Maximum number of remote join requests executed at once, for each join and for the joins
+    /// at each level of a query. Defaults to 10.
+    #[arg(
+        long,
+        value_name = "REMOTE_JOIN_CONCURRENCY",
+        env = "REMOTE_JOIN_CONCURRENCY"
+    )]
+    remote_join_concurrency: Option<NonZeroUsize>,
+
+    /// Maximum number of responses kept in the response cache. Responses to queries marked with
+    /// the `@cached` directive are only cached if this is provided.
+    #[arg(long, value_name = "RESPONSE_CACHE_SIZE", env = "RESPONSE_CACHE_SIZE")]
+    response_cache_size: Option<NonZeroUsize>,
+
+    /// Maximum number of automatic persisted queries kept in memory. Requests that only send the
+    /// hash of a persisted query are rejected unless this is provided.
+    #[arg(
+        long,
+        value_name = "PERSISTED_QUERIES_CACHE_SIZE",
+        env = "PERSISTED_QUERIES_CACHE_SIZE"
+    )]
+    persisted_queries_cache_size: Option<NonZeroUsize>,
+
+    /// Compare the schema and capabilities of each data connector with the ones embedded in the
+    /// metadata at startup, and warn about, or refuse to start with, data connectors that no
+    /// longer match.
+    #[arg(
+        long,
+        value_name = "CHECK",
+        env = "CHECK_CONNECTOR_SCHEMAS",
+        value_enum
+    )]
+    check_connector_schemas: Option<SchemaDriftCheck>,
+
+    /// Serve the differences between each data connector and its schema in the metadata over
+    /// `/v1/connector-schema-drift`, to admins whose role emulation is enabled.
+    #[arg(long, env = "ENABLE_SCHEMA_DRIFT_ENDPOINT")]
+    enable_schema_drift_endpoint: bool,
+
     /// Maximum size of request body in bytes
     #[arg(long, value_name = "REQUEST_BODY_LIMIT in bytes", env = "REQUEST_BODY_LIMIT", default_value_t = 10 * MB)]
     request_body_limit: usize,
@@ -159,18 +221,79 @@ async fn start_engine(server: &ServerOptions) -> Result<(), StartupError> {
         resolved_metadata,
         server.auth_mode_header.clone(),
         server.ndc_response_size_limit,
+        server.remote_join_batch_size,
+        server.remote_join_concurrency,
+        server.response_cache_size,
+        server.persisted_queries_cache_size,
     )
     .map_err(StartupError::ReadSchema)?;
 
-    let mut app = get_base_routes(state.clone(), server.request_body_limit);
-
-    app = app.merge(get_jsonapi_route(state.clone()));
+    // If `--check-connector-schemas` is specified we check that the data connectors still match
+    // their schemas in the metadata before starting.
+    if let Some(check) = server.check_connector_schemas {
+        engine::check_schema_drift_at_startup(
+            &state.http_context.client,
+            &opendd_metadata_json,
+            check,
+        )
+        .await
+        .map_err(StartupError::ReadSchema)?;
+    }
 
     // If `--introspection-metadata` is specified we also serve the file indicated on `/metadata`
     // and its hash on `/metadata-hash`.
-    if let Some(path) = &server.introspection_metadata {
-        app = app.merge(get_metadata_routes(path).await?);
-    }
+    let metadata_routes = match &server.introspection_metadata {
+        Some(path) => Some(get_metadata_routes(path).await?),
+        None => None,
+    };
+
+    // The routes are built from the engine state, so they are rebuilt whenever the metadata and
+    // auth config are reloaded.
+    let request_body_limit = server.request_body_limit;
+    let enable_metrics = server.enable_metrics;
+    let schema_drift_metadata_path = server
+        .enable_schema_drift_endpoint
+        .then(|| server.metadata_path.clone());
+    let build_routes = move |state: EngineState| {
+        let mut routes = get_base_routes(state.clone(), request_body_limit);
+
+        routes = routes.merge(get_jsonapi_route(state.clone()));
+
+        if let Some(metadata_routes) = &metadata_routes {
+            routes = routes.merge(metadata_routes.clone());
+        }
+
+        // If `--enable-metrics` is specified we serve Prometheus metrics on `/metrics`.
+        if enable_metrics {
+            routes = routes.merge(get_metrics_route());
+        }
+
+        // If `--enable-schema-drift-endpoint` is specified we serve the drift of the data
+        // connectors from their schemas on `/v1/connector-schema-drift`, to admins.
+        if let Some(metadata_path) = &schema_drift_metadata_path {
+            routes = routes.merge(get_schema_drift_route(state, metadata_path.clone()));
+        }
+        routes
+    };
+    let router = ReloadableRouter::new(build_routes(state.clone()));
+
+    // Reload the metadata and auth config on SIGHUP, or when either file changes if
+    // `--metadata-watch-interval-ms` is specified.
+    tokio::spawn(reload_on_change(
+        state.clone(),
+        ReloadSources {
+            metadata_path: server.metadata_path.clone(),
+            authn_config_path: server.authn_config_path.clone(),
+            metadata_resolve_configuration,
+        },
+        server
+            .metadata_watch_interval_ms
+            .map(|interval| Duration::from_millis(interval.get())),
+        router.clone(),
+        build_routes,
+    ));
+
+    let mut app = Router::new().fallback_service(router);
 
     // If `--enable-cors` is specified, we add a CORS layer to the app.
     if server.enable_cors {
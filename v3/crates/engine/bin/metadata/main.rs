@@ -0,0 +1,397 @@
+//! Checking OpenDD metadata, and printing the APIs the engine would serve for it, without starting
+//! a server.
+//!
+//! ```sh
+//! metadata validate --metadata-path metadata.json --output json
+//! metadata sdl --metadata-path metadata.json --role admin
+//! metadata introspection --metadata-path metadata.json --role admin
+//! metadata openapi --metadata-path metadata.json --role admin
+//! ```
+//!
+//! `validate` exits with status 1 if the metadata is invalid, as do the other commands if the
+//! metadata is invalid or does not use the role given. Every command exits with status 2 if the
+//! files can't be read or the arguments are wrong.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use engine::{
+    VERSION,
+    internal_flags::{UnstableFeature, resolve_unstable_features},
+};
+use graphql_schema::{GDS, GDSNamespaceGetterAgnostic, GDSRoleNamespaceGetter};
+use metadata_resolve::ContextualError;
+use open_dds::permissions::Role;
+use serde::Serialize;
+
+/// The exit status when the metadata is invalid
+const EXIT_INVALID: u8 = 1;
+/// The exit status when the metadata can't be checked at all, as clap uses for bad arguments
+const EXIT_USAGE: u8 = 2;
+
+#[derive(Parser)]
+#[command(version = VERSION)]
+struct Options {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check the metadata, printing any errors and warnings.
+    Validate {
+        #[command(flatten)]
+        metadata: MetadataOptions,
+        /// The configuration file used for authentication, which is checked too if provided.
+        #[arg(long, value_name = "PATH", env = "AUTHN_CONFIG_PATH")]
+        authn_config_path: Option<PathBuf>,
+        /// How to print errors and warnings.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Pretty)]
+        output: OutputFormat,
+    },
+    /// Print the GraphQL schema for a role as SDL, or the role-agnostic schema if no role is given.
+    Sdl {
+        #[command(flatten)]
+        metadata: MetadataOptions,
+        #[arg(long)]
+        role: Option<String>,
+    },
+    /// Print the result of the GraphQL introspection query for a role.
+    Introspection {
+        #[command(flatten)]
+        metadata: MetadataOptions,
+        #[arg(long)]
+        role: String,
+    },
+    /// Print the OpenAPI schema of the JSON:API for a role.
+    Openapi {
+        #[command(flatten)]
+        metadata: MetadataOptions,
+        #[arg(long)]
+        role: String,
+    },
+}
+
+#[derive(clap::Args)]
+struct MetadataOptions {
+    /// The path to the OpenDD metadata file.
+    #[arg(long, value_name = "PATH", env = "METADATA_PATH")]
+    metadata_path: PathBuf,
+    /// List of internal unstable features to enable, separated by commas.
+    #[arg(
+        long = "unstable-feature",
+        value_name = "UNSTABLE_FEATURES",
+        env = "UNSTABLE_FEATURES",
+        value_delimiter = ','
+    )]
+    unstable_features: Vec<UnstableFeature>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    /// Errors and warnings labelled in the metadata, for people
+    Pretty,
+    /// A JSON report on stdout, for CI
+    Json,
+}
+
+/// The report printed by `validate --output json`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ValidationReport {
+    valid: bool,
+    errors: Vec<Diagnostic>,
+    warnings: Vec<Diagnostic>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Diagnostic {
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context: Option<error_context::Context>,
+}
+
+/// Why a command failed, after its output has been printed
+enum Failure {
+    Invalid,
+    Usage(String),
+}
+
+/// Print an error that makes the metadata invalid
+#[allow(clippy::print_stderr)]
+fn invalid(error: impl std::fmt::Display) -> Failure {
+    eprintln!("Error: {error}");
+    Failure::Invalid
+}
+
+#[allow(clippy::print_stderr)]
+fn main() -> ExitCode {
+    let options = Options::parse();
+    let result = match options.command {
+        Command::Validate {
+            metadata,
+            authn_config_path,
+            output,
+        } => validate(&metadata, authn_config_path.as_deref(), output),
+        Command::Sdl { metadata, role } => print_sdl(&metadata, role.as_deref()),
+        Command::Introspection { metadata, role } => print_introspection(&metadata, &role),
+        Command::Openapi { metadata, role } => print_openapi(&metadata, &role),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Invalid) => ExitCode::from(EXIT_INVALID),
+        Err(Failure::Usage(message)) => {
+            eprintln!("Error: {message}");
+            ExitCode::from(EXIT_USAGE)
+        }
+    }
+}
+
+/// Print reports on stderr, against the metadata they point into
+fn eprint_reports<'a>(
+    reports: impl IntoIterator<Item = ariadne::Report<'a>>,
+    opendd_metadata_json: &str,
+) -> Result<(), Failure> {
+    for report in reports {
+        report
+            .eprint(ariadne::Source::from(opendd_metadata_json))
+            .map_err(|error| Failure::Usage(format!("could not print a report: {error}")))?;
+    }
+    Ok(())
+}
+
+/// Print a value as pretty JSON on stdout
+#[allow(clippy::print_stdout)]
+fn print_json(value: &impl Serialize) -> Result<(), Failure> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|error| Failure::Usage(format!("could not serialize the output: {error}")))?;
+    println!("{json}");
+    Ok(())
+}
+
+fn read_file(path: &Path) -> Result<String, Failure> {
+    std::fs::read_to_string(path)
+        .map_err(|error| Failure::Usage(format!("could not read {}: {error}", path.display())))
+}
+
+#[allow(clippy::print_stdout)]
+fn validate(
+    options: &MetadataOptions,
+    authn_config_path: Option<&Path>,
+    output: OutputFormat,
+) -> Result<(), Failure> {
+    let opendd_metadata_json = read_file(&options.metadata_path)?;
+    let raw_auth_config = authn_config_path.map(read_file).transpose()?;
+
+    let mut errors = vec![];
+    let mut warnings = vec![];
+    let mut reports: Vec<ariadne::Report> = vec![];
+    let config = ariadne::Config::new();
+
+    match open_dds::Metadata::from_json_str(&opendd_metadata_json) {
+        Err(error) => {
+            reports.push(plain_report(
+                ariadne::ReportKind::Error,
+                error.to_string(),
+                config,
+            ));
+            errors.push(Diagnostic {
+                message: error.to_string(),
+                context: None,
+            });
+        }
+        Ok(metadata) => {
+            if let Some(raw_auth_config) = raw_auth_config {
+                let flags = metadata.get_flags();
+                match hasura_authn::parse_auth_config(&raw_auth_config)
+                    .map_err(|error| error.to_string())
+                    .and_then(|auth_config| {
+                        hasura_authn::resolve_auth_config(auth_config, flags.as_ref())
+                            .map_err(|error| error.to_string())
+                    }) {
+                    Ok((_, auth_warnings)) => {
+                        for warning in auth_warnings {
+                            let message = warning.to_string();
+                            reports.push(plain_report(
+                                ariadne::ReportKind::Warning,
+                                message.clone(),
+                                config,
+                            ));
+                            warnings.push(Diagnostic {
+                                message,
+                                context: None,
+                            });
+                        }
+                    }
+                    Err(message) => {
+                        reports.push(plain_report(
+                            ariadne::ReportKind::Error,
+                            message.clone(),
+                            config,
+                        ));
+                        errors.push(Diagnostic {
+                            message,
+                            context: None,
+                        });
+                    }
+                }
+            }
+
+            match metadata_resolve::resolve(metadata, &metadata_resolve_configuration(options)) {
+                Ok((_, metadata_warnings)) => {
+                    reports.extend(metadata_resolve::to_fancy_warnings(
+                        &opendd_metadata_json,
+                        &metadata_warnings,
+                        config,
+                    ));
+                    warnings.extend(metadata_warnings.iter().map(|warning| Diagnostic {
+                        message: warning.to_string(),
+                        context: warning.create_error_context(),
+                    }));
+                }
+                Err(error) => {
+                    reports.extend(metadata_resolve::to_fancy_errors(
+                        &opendd_metadata_json,
+                        &error,
+                        config,
+                    ));
+                    errors.extend(error_diagnostics(&error));
+                }
+            }
+        }
+    }
+
+    let valid = errors.is_empty();
+    match output {
+        OutputFormat::Pretty => {
+            eprint_reports(reports, &opendd_metadata_json)?;
+            if valid {
+                println!("Metadata is valid, with {} warning(s)", warnings.len());
+            }
+        }
+        OutputFormat::Json => {
+            let report = ValidationReport {
+                valid,
+                errors,
+                warnings,
+            };
+            print_json(&report)?;
+        }
+    }
+
+    if valid { Ok(()) } else { Err(Failure::Invalid) }
+}
+
+/// A report for a problem that has no location in the metadata
+fn plain_report(
+    kind: ariadne::ReportKind<'static>,
+    message: String,
+    config: ariadne::Config,
+) -> ariadne::Report<'static> {
+    ariadne::Report::build(kind, 0..0)
+        .with_message(message)
+        .with_config(config)
+        .finish()
+}
+
+/// A diagnostic for each error that `to_fancy_errors` would print a report for
+fn error_diagnostics(
+    error: &metadata_resolve::WithContext<metadata_resolve::Error>,
+) -> Vec<Diagnostic> {
+    let diagnostic = |error: &metadata_resolve::WithContext<metadata_resolve::Error>| Diagnostic {
+        message: error.to_string(),
+        context: error.context(),
+    };
+    match error.into_inner() {
+        metadata_resolve::Error::MultipleErrors { errors } => {
+            errors.lines_of.iter().map(diagnostic).collect()
+        }
+        _ => vec![diagnostic(error)],
+    }
+}
+
+fn metadata_resolve_configuration(
+    options: &MetadataOptions,
+) -> metadata_resolve::configuration::Configuration {
+    metadata_resolve::configuration::Configuration {
+        unstable_features: resolve_unstable_features(&options.unstable_features),
+    }
+}
+
+/// Resolve the metadata for printing one of its APIs, printing any errors as `validate` would
+fn resolve(options: &MetadataOptions) -> Result<metadata_resolve::Metadata, Failure> {
+    let opendd_metadata_json = read_file(&options.metadata_path)?;
+    let metadata = open_dds::Metadata::from_json_str(&opendd_metadata_json).map_err(invalid)?;
+    match metadata_resolve::resolve(metadata, &metadata_resolve_configuration(options)) {
+        Ok((resolved_metadata, _warnings)) => Ok(resolved_metadata),
+        Err(error) => {
+            eprint_reports(
+                metadata_resolve::to_fancy_errors(
+                    &opendd_metadata_json,
+                    &error,
+                    ariadne::Config::new(),
+                ),
+                &opendd_metadata_json,
+            )?;
+            Err(Failure::Invalid)
+        }
+    }
+}
+
+/// A role that the metadata grants permissions to, as there is no API to print for any other role
+fn known_role(resolved_metadata: &metadata_resolve::Metadata, role: &str) -> Result<Role, Failure> {
+    let role = Role::new(role);
+    if resolved_metadata.roles.contains(&role) {
+        Ok(role)
+    } else {
+        Err(invalid(format!(
+            "the metadata has no permissions for role {role}"
+        )))
+    }
+}
+
+fn build_gds(options: &MetadataOptions) -> Result<GDS, Failure> {
+    Ok(GDS {
+        metadata: std::sync::Arc::new(resolve(options)?),
+    })
+}
+
+#[allow(clippy::print_stdout)]
+fn print_sdl(options: &MetadataOptions, role: Option<&str>) -> Result<(), Failure> {
+    let gds = build_gds(options)?;
+    let role = role
+        .map(|role| known_role(&gds.metadata, role))
+        .transpose()?;
+    let schema = gds.build_schema().map_err(invalid)?;
+    let sdl = match role {
+        Some(role) => schema.generate_sdl(&GDSRoleNamespaceGetter { scope: role }),
+        None => schema.generate_sdl(&GDSNamespaceGetterAgnostic),
+    };
+    println!("{sdl}");
+    Ok(())
+}
+
+fn print_introspection(options: &MetadataOptions, role: &str) -> Result<(), Failure> {
+    let gds = build_gds(options)?;
+    let role = known_role(&gds.metadata, role)?;
+    let schema = gds.build_schema().map_err(invalid)?;
+    let introspection = lang_graphql::generate_graphql_schema::build_namespace_schema(
+        &GDSRoleNamespaceGetter { scope: role },
+        &schema,
+    )
+    .map_err(invalid)?;
+    print_json(&introspection)
+}
+
+fn print_openapi(options: &MetadataOptions, role: &str) -> Result<(), Failure> {
+    let resolved_metadata = resolve(options)?;
+    let role = known_role(&resolved_metadata, role)?;
+    let (catalog, _warnings) = jsonapi::Catalog::new(&resolved_metadata);
+    let spec = match catalog.state_per_role.get(&role) {
+        Some(state) => jsonapi::openapi_schema(state).map_err(invalid)?,
+        None => jsonapi::empty_schema(),
+    };
+    print_json(&spec)
+}
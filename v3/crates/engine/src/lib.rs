@@ -1,17 +1,27 @@
 mod cors;
 pub mod internal_flags;
+mod metrics;
 mod middleware;
+mod reload;
 
 mod routes;
+mod schema_drift;
 mod state;
 mod types;
 pub use cors::build_cors_layer;
+pub use metrics::EngineWebSocketMetrics;
 pub use middleware::{
     authentication_middleware, explain_request_tracing_middleware,
     graphql_request_tracing_middleware, plugins_middleware,
 };
-pub use routes::{get_base_routes, get_cors_layer, get_jsonapi_route, get_metadata_routes};
-pub use state::{build_state, print_warnings, resolve_metadata};
+pub use reload::{ReloadSources, ReloadableRouter, reload_on_change, reload_state};
+pub use routes::{
+    get_base_routes, get_cors_layer, get_jsonapi_route, get_metadata_routes, get_metrics_route,
+};
+pub use schema_drift::{
+    SchemaDriftCheck, check_schema_drift, check_schema_drift_at_startup, get_schema_drift_route,
+};
+pub use state::{build_state, print_warnings, rebuild_state, resolve_metadata};
 pub use types::{EngineState, RequestType, StartupError};
 
 // This is set by the build.rs script.
@@ -0,0 +1,33 @@
+use graphql_ws::{WebSocketId, WebSocketMetrics};
+
+/// Records websocket connections, the operations running on them and the pollers shared by
+/// subscription cohorts in the engine's Prometheus metrics. Every operation is counted, while
+/// identical subscriptions are counted once among the pollers.
+#[derive(Clone)]
+pub struct EngineWebSocketMetrics;
+
+impl WebSocketMetrics for EngineWebSocketMetrics {
+    fn record_connection_init(&self) {
+        metrics_util::WEBSOCKET_CONNECTIONS.inc(&[]);
+    }
+
+    fn record_connection_drop(&self) {
+        metrics_util::WEBSOCKET_CONNECTIONS.dec(&[]);
+    }
+
+    fn record_poller_start(&self, _websocket_id: &WebSocketId) {
+        metrics_util::WEBSOCKET_OPERATIONS.inc(&[]);
+    }
+
+    fn record_poller_stop(&self, _websocket_id: &WebSocketId) {
+        metrics_util::WEBSOCKET_OPERATIONS.dec(&[]);
+    }
+
+    fn record_cohort_start(&self) {
+        metrics_util::WEBSOCKET_SUBSCRIPTION_POLLERS.inc(&[]);
+    }
+
+    fn record_cohort_stop(&self) {
+        metrics_util::WEBSOCKET_SUBSCRIPTION_POLLERS.dec(&[]);
+    }
+}
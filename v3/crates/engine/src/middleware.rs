@@ -3,9 +3,9 @@ use std::borrow::Cow;
 use crate::EngineState;
 use crate::VERSION;
 use axum::{
-    Extension,
-    extract::{ConnectInfo, State},
-    http::{HeaderMap, Request},
+    Extension, Router,
+    extract::{ConnectInfo, Query, State},
+    http::{HeaderMap, HeaderValue, Method, Request, StatusCode, header},
     middleware::Next,
     response::IntoResponse,
 };
@@ -17,7 +17,9 @@ use pre_parse_plugin::execute::pre_parse_plugins_handler;
 use pre_response_plugin::execute::pre_response_plugins_handler;
 
 use hasura_authn_core::Session;
+use lang_graphql as gql;
 use pre_response_plugin::execute::ProcessedPreResponsePluginResponse;
+use tower::ServiceExt;
 use tracing_util::{SpanVisibility, TraceableHttpResponse};
 
 use super::types::RequestType;
@@ -64,6 +66,91 @@ pub async fn graphql_request_tracing_middleware(
         .response
 }
 
+/// Middleware to send `GET /graphql` requests that upgrade to a websocket to `websocket_router`.
+/// Other `GET /graphql` requests send a query in the query string, and go on to authentication
+/// like any other HTTP request, while websocket connections authenticate once connected.
+pub async fn websocket_upgrade_middleware(
+    websocket_router: Router,
+    request: Request<Body>,
+    next: Next,
+) -> axum::response::Response {
+    let is_websocket_upgrade = request.method() == Method::GET
+        && request
+            .headers()
+            .get(header::UPGRADE)
+            .and_then(|upgrade| upgrade.to_str().ok())
+            .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"));
+    if is_websocket_upgrade {
+        let Ok(response) = websocket_router.oneshot(request).await;
+        response
+    } else {
+        next.run(request).await
+    }
+}
+
+/// The query string of a `GET /graphql` request.
+/// <https://graphql.github.io/graphql-over-http/draft/#sec-GET>
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetRequestParams {
+    #[serde(default)]
+    query: String,
+    operation_name: Option<String>,
+    /// JSON-encoded
+    variables: Option<String>,
+    /// JSON-encoded
+    extensions: Option<String>,
+}
+
+/// Middleware to turn the query string of a `GET /graphql` request into a JSON body, so that
+/// plugins and the handler see it like a `POST` request. The method is kept, so that the handler
+/// can refuse to execute mutations.
+pub async fn graphql_get_request_middleware(
+    mut request: Request<Body>,
+    next: Next,
+) -> axum::response::Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let body = match get_request_body(&request) {
+        Ok(body) => body,
+        Err(message) => {
+            return gql::http::Response::error_message_with_status(
+                StatusCode::BAD_REQUEST,
+                message,
+                false,
+            )
+            .into_response();
+        }
+    };
+    let headers = request.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    headers.remove(header::CONTENT_LENGTH);
+    *request.body_mut() = Body::from(body);
+    next.run(request).await
+}
+
+fn get_request_body(request: &Request<Body>) -> Result<Vec<u8>, String> {
+    let Query(params) =
+        Query::<GetRequestParams>::try_from_uri(request.uri()).map_err(|err| err.body_text())?;
+    let parse_json = |name: &str, value: Option<String>| {
+        value
+            .map(|value| serde_json::from_str::<serde_json::Value>(&value))
+            .transpose()
+            .map_err(|err| format!("{name} must be JSON: {err}"))
+    };
+    let raw_request = serde_json::json!({
+        "query": params.query,
+        "operationName": params.operation_name,
+        "variables": parse_json("variables", params.variables)?,
+        "extensions": parse_json("extensions", params.extensions)?,
+    });
+    serde_json::to_vec(&raw_request).map_err(|err| err.to_string())
+}
+
 /// Middleware to start tracing of the `/v1/explain` request.
 /// This middleware must be active for the entire duration
 /// of the request i.e. this middleware should be the
@@ -122,7 +209,10 @@ pub async fn authentication_middleware(
             },
         )
         .await
-        .map_err(|err| state.handle_error(err.into_middleware_error()))?;
+        .map_err(|err| {
+            metrics_util::AUTH_FAILURES.inc(&[err.mode()]);
+            state.handle_error(err.into_middleware_error())
+        })?;
 
     request.extensions_mut().insert(auth_response.identity);
 
@@ -159,6 +249,22 @@ pub async fn plugins_middleware(
         .to_bytes();
     let raw_request = bytes.clone();
 
+    // plugins are sent a single request, so they can't be used with batches
+    let plugin_configs = &engine_state.resolved_metadata.plugin_configs;
+    let is_batch = bytes.iter().find(|byte| !byte.is_ascii_whitespace()) == Some(&b'[');
+    if is_batch
+        && (!plugin_configs.pre_parse_plugins.is_empty()
+            || !plugin_configs.pre_response_plugins.is_empty())
+    {
+        return Err(gql::http::Response::error_message_with_status(
+            StatusCode::BAD_REQUEST,
+            "batched requests are not supported with pre-parse or pre-response plugins".into(),
+            false,
+        )
+        .into_response()
+        .into());
+    }
+
     // Check if the pre_parse_plugins_config is empty
     let response = match nonempty::NonEmpty::from_slice(
         &engine_state
@@ -269,3 +375,41 @@ pub async fn pre_route_request_tracing_middleware(
         .await
         .response
 }
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use pretty_assertions::assert_eq;
+
+    use super::get_request_body;
+
+    fn body(uri: &str) -> Result<serde_json::Value, String> {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        get_request_body(&request).map(|body| serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn test_get_request_body() {
+        assert_eq!(
+            body(
+                "/graphql?query=query%20Q(%24id%3A%20Int)%20%7B%20a%20%7D&operationName=Q&variables=%7B%22id%22%3A1%7D"
+            ),
+            Ok(serde_json::json!({
+                "query": "query Q($id: Int) { a }",
+                "operationName": "Q",
+                "variables": { "id": 1 },
+                "extensions": null,
+            }))
+        );
+        assert_eq!(
+            body("/graphql?query=%7B%20a%20%7D"),
+            Ok(serde_json::json!({
+                "query": "{ a }",
+                "operationName": null,
+                "variables": null,
+                "extensions": null,
+            }))
+        );
+        assert!(body("/graphql?query=%7B%20a%20%7D&variables=%7Bid").is_err());
+    }
+}
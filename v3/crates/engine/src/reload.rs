@@ -0,0 +1,267 @@
+//! Reloading metadata and auth config without restarting the engine.
+//!
+//! The engine's routes are built from an `EngineState` and served through a `ReloadableRouter`.
+//! On a reload, the metadata and auth config are resolved again and, if that succeeds, routes
+//! built from the new state are swapped in. Each request is served by the routes that were
+//! current when it arrived, so in-flight requests finish on the schema they started with. If the
+//! new metadata or auth config fail to resolve, the previous routes keep serving.
+//!
+//! Websocket connections are not closed on a reload. A connection is given the schema and
+//! metadata when it is opened, so its operations, including subscriptions started after the
+//! reload, keep using the previous schema until the client reconnects.
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use axum::Router;
+use axum::extract::Request;
+use axum::routing::future::RouteFuture;
+
+use crate::{EngineState, rebuild_state, resolve_metadata};
+
+/// A service that serves requests with the current routes, which can be swapped while the server
+/// is running.
+#[derive(Clone)]
+pub struct ReloadableRouter {
+    current: Arc<RwLock<Router>>,
+}
+
+impl ReloadableRouter {
+    pub fn new(router: Router) -> Self {
+        ReloadableRouter {
+            current: Arc::new(RwLock::new(router)),
+        }
+    }
+
+    /// Serve new requests with `router`. Requests already being served are unaffected.
+    pub fn swap(&self, router: Router) {
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = router;
+    }
+}
+
+impl tower::Service<Request> for ReloadableRouter {
+    type Response = axum::response::Response;
+    type Error = Infallible;
+    type Future = RouteFuture<Infallible>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        // `Router` is cheap to clone, and holding on to the clone keeps the routes (and the
+        // state they were built from) alive until the request is served
+        let mut router = self
+            .current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        tower::Service::call(&mut router, request)
+    }
+}
+
+/// Where the metadata and auth config are read from on a reload
+#[derive(Clone)]
+pub struct ReloadSources {
+    pub metadata_path: PathBuf,
+    pub authn_config_path: PathBuf,
+    pub metadata_resolve_configuration: metadata_resolve::configuration::Configuration,
+}
+
+/// Read and resolve the metadata and auth config again, and build a new state from them
+pub fn reload_state(
+    previous_state: &EngineState,
+    sources: &ReloadSources,
+) -> Result<EngineState, anyhow::Error> {
+    let raw_auth_config = std::fs::read_to_string(&sources.authn_config_path)?;
+    let opendd_metadata_json = std::fs::read_to_string(&sources.metadata_path)?;
+    let (resolved_metadata, auth_config) = resolve_metadata(
+        &opendd_metadata_json,
+        &raw_auth_config,
+        &sources.metadata_resolve_configuration,
+    )?;
+    rebuild_state(previous_state, auth_config, resolved_metadata)
+}
+
+/// Reload the metadata and auth config whenever the process receives a SIGHUP or, if a
+/// `watch_interval` is given, whenever either file changes. After a successful reload the routes
+/// built by `build_routes` from the new state are swapped into `router`.
+#[allow(clippy::print_stdout)]
+pub async fn reload_on_change<F>(
+    mut state: EngineState,
+    sources: ReloadSources,
+    watch_interval: Option<Duration>,
+    router: ReloadableRouter,
+    build_routes: F,
+) where
+    F: Fn(EngineState) -> Router,
+{
+    #[cfg(unix)]
+    let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            println!(
+                "Error while installing the SIGHUP handler, metadata and auth config will not be reloaded: {err}"
+            );
+            return;
+        }
+    };
+    let mut last_modified = modified_times(&sources);
+
+    loop {
+        #[cfg(unix)]
+        let hangup_received = hangup.recv();
+        #[cfg(not(unix))]
+        let hangup_received = std::future::pending::<Option<()>>();
+
+        tokio::select! {
+            _ = hangup_received => (),
+            () = files_changed(&sources, watch_interval, &mut last_modified) => (),
+        }
+
+        // resolving metadata is CPU-bound, so keep it off the threads serving requests
+        let reloaded = tokio::task::spawn_blocking({
+            let state = state.clone();
+            let sources = sources.clone();
+            move || reload_state(&state, &sources)
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|reloaded| reloaded);
+
+        match reloaded {
+            Ok(new_state) => {
+                router.swap(build_routes(new_state.clone()));
+                state = new_state;
+                println!("Reloaded metadata and auth config");
+            }
+            Err(err) => {
+                println!(
+                    "Error while reloading metadata and auth config, keeping the previous metadata and auth config: {err}"
+                );
+            }
+        }
+    }
+}
+
+/// Resolves when the modified time of the metadata or auth config file changes. Never resolves
+/// when files are not watched.
+async fn files_changed(
+    sources: &ReloadSources,
+    watch_interval: Option<Duration>,
+    last_modified: &mut [Option<SystemTime>; 2],
+) {
+    let Some(watch_interval) = watch_interval else {
+        return std::future::pending().await;
+    };
+    loop {
+        tokio::time::sleep(watch_interval).await;
+        let modified = modified_times(sources);
+        if modified != *last_modified {
+            *last_modified = modified;
+            return;
+        }
+    }
+}
+
+fn modified_times(sources: &ReloadSources) -> [Option<SystemTime>; 2] {
+    [&sources.metadata_path, &sources.authn_config_path].map(|path| {
+        std::fs::metadata(path)
+            .and_then(|file| file.modified())
+            .ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    async fn get_body(router: &ReloadableRouter) -> axum::body::Bytes {
+        let response = router
+            .clone()
+            .oneshot(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_swapped_routes_serve_new_requests() {
+        let router = ReloadableRouter::new(Router::new().route("/", get(|| async { "old" })));
+
+        assert_eq!(get_body(&router).await, "old");
+
+        router.swap(Router::new().route("/", get(|| async { "new" })));
+
+        assert_eq!(get_body(&router).await, "new");
+    }
+
+    #[tokio::test]
+    async fn test_failed_reload_keeps_previous_routes() {
+        const METADATA: &str = include_str!(
+            "../../metadata-resolve/tests/passing/supergraph/no_subgraphs/metadata.json"
+        );
+        const AUTH_CONFIG: &str = include_str!("../../../static/auth/noauth_config_v3.json");
+
+        let dir = std::env::temp_dir().join(format!("engine-reload-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let sources = ReloadSources {
+            metadata_path: dir.join("metadata.json"),
+            authn_config_path: dir.join("auth_config.json"),
+            metadata_resolve_configuration: metadata_resolve::configuration::Configuration::default(
+            ),
+        };
+        std::fs::write(&sources.metadata_path, METADATA).unwrap();
+        std::fs::write(&sources.authn_config_path, AUTH_CONFIG).unwrap();
+
+        let (resolved_metadata, auth_config) = resolve_metadata(
+            METADATA,
+            AUTH_CONFIG,
+            &sources.metadata_resolve_configuration,
+        )
+        .unwrap();
+        let state = crate::build_state(
+            engine_types::ExposeInternalErrors::Censor,
+            auth_config,
+            resolved_metadata,
+            "X-Hasura-Auth-Mode".to_string(),
+            usize::MAX,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // every reload that succeeds swaps in routes serving "reloaded"
+        let router = ReloadableRouter::new(Router::new().route("/", get(|| async { "initial" })));
+        let reloader = tokio::spawn(reload_on_change(
+            state,
+            sources.clone(),
+            Some(Duration::from_millis(10)),
+            router.clone(),
+            |_| Router::new().route("/", get(|| async { "reloaded" })),
+        ));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // metadata that fails to parse is not swapped in
+        std::fs::write(&sources.metadata_path, "not metadata").unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(get_body(&router).await, "initial");
+
+        // and the reloader keeps running, so fixing the metadata reloads it
+        std::fs::write(&sources.metadata_path, METADATA).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(get_body(&router).await, "reloaded");
+
+        reloader.abort();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,7 +6,7 @@ pub use jsonapi::create_json_api_router;
 use axum::{
     Router,
     extract::DefaultBodyLimit,
-    response::Html,
+    response::{Html, IntoResponse},
     routing::{get, post},
 };
 use base64::engine::Engine;
@@ -19,7 +19,11 @@ use tower_http::trace::TraceLayer;
 use crate::{
     EngineState, StartupError, authentication_middleware, build_cors_layer,
     explain_request_tracing_middleware, graphql_request_tracing_middleware,
-    middleware::pre_route_request_tracing_middleware, plugins_middleware,
+    middleware::{
+        graphql_get_request_middleware, pre_route_request_tracing_middleware,
+        websocket_upgrade_middleware,
+    },
+    plugins_middleware,
 };
 
 use super::types::RequestType;
@@ -37,11 +41,12 @@ pub fn get_base_routes(state: EngineState, request_body_limit: usize) -> Router
         .with_state(state.clone());
 
     let graphql_route = Router::new()
-        .route("/graphql", post(handle_request))
+        .route("/graphql", post(handle_request).get(handle_request))
         .layer(axum::middleware::from_fn_with_state(
             graphql_frontend::build_state_with_middleware_error_converter(state.clone()),
             plugins_middleware,
         ))
+        .layer(axum::middleware::from_fn(graphql_get_request_middleware))
         .layer(axum::middleware::from_fn_with_state(
             graphql_frontend::build_state_with_middleware_error_converter(()),
             hasura_authn_core::resolve_session,
@@ -57,7 +62,10 @@ pub fn get_base_routes(state: EngineState, request_body_limit: usize) -> Router
         // BEFORE THE `graphql_request_tracing_middleware`*
         // Refer to it for more details.
         .layer(TraceLayer::new_for_http())
-        .with_state(state.clone());
+        .with_state(state.clone())
+        .layer(axum::middleware::from_fn(move |request, next| {
+            websocket_upgrade_middleware(graphql_ws_route.clone(), request, next)
+        }));
 
     let explain_route = Router::new()
         .route("/v1/explain", post(handle_explain_request))
@@ -92,10 +100,8 @@ pub fn get_base_routes(state: EngineState, request_body_limit: usize) -> Router
     Router::new()
         // serve graphiql at root
         .route("/", get(graphiql))
-        // The '/graphql' route
+        // The '/graphql' route, for HTTP requests and websockets
         .merge(graphql_route)
-        // The '/graphql' route for websocket
-        .merge(graphql_ws_route)
         // The '/v1/explain' route
         .merge(explain_route)
         // The '/health' route
@@ -132,6 +138,26 @@ pub fn get_cors_layer(allow_origin: &[String]) -> CorsLayer {
     build_cors_layer(allow_origin)
 }
 
+/// Serve the engine's metrics in the Prometheus text exposition format at `/metrics`.
+pub fn get_metrics_route() -> Router {
+    Router::new().route("/metrics", get(handle_metrics))
+}
+
+async fn handle_metrics() -> axum::response::Response {
+    match metrics_util::encode() {
+        Ok(metrics) => (
+            [(axum::http::header::CONTENT_TYPE, metrics_util::CONTENT_TYPE)],
+            metrics,
+        )
+            .into_response(),
+        Err(err) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("failed to encode metrics: {err}"),
+        )
+            .into_response(),
+    }
+}
+
 /// Health check endpoint
 async fn handle_health() -> reqwest::StatusCode {
     reqwest::StatusCode::OK
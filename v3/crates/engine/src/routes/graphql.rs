@@ -7,17 +7,69 @@ use axum::{
 };
 use futures_util::FutureExt;
 
-use crate::EngineState;
+use crate::{EngineState, EngineWebSocketMetrics};
 use hasura_authn_core::Session;
 use lang_graphql as gql;
 use tracing_util::{SpanVisibility, set_status_on_current_span};
 
-#[allow(clippy::print_stdout)]
+/// The most requests a batch may contain.
+const MAX_BATCH_SIZE: usize = 100;
+
+/// Handles a GraphQL request, or a batch of requests which are answered with an array of
+/// responses in the order of the requests. The requests in a batch are executed concurrently,
+/// unless any of them may run a mutation, as later requests may depend on it. Then they are
+/// executed one after another.
 pub async fn handle_request(
+    method: axum::http::Method,
     headers: axum::http::header::HeaderMap,
     State(state): State<EngineState>,
     Extension(session): Extension<Session>,
-    Json(request): Json<gql::http::RawRequest>,
+    Json(body): Json<gql::http::RawRequestBody>,
+) -> axum::response::Response {
+    match body {
+        gql::http::RawRequestBody::Single(request) => {
+            execute_request(&method, &headers, &state, &session, request)
+                .await
+                .into_response()
+        }
+        gql::http::RawRequestBody::Batch(requests) => {
+            if requests.len() > MAX_BATCH_SIZE {
+                return gql::http::Response::error_message_with_status(
+                    axum::http::StatusCode::BAD_REQUEST,
+                    format!(
+                        "the batch has {} requests, more than the limit of {MAX_BATCH_SIZE}",
+                        requests.len()
+                    ),
+                    false,
+                )
+                .into_response();
+            }
+            let responses = if requests.iter().any(graphql_frontend::may_mutate) {
+                let mut responses = Vec::with_capacity(requests.len());
+                for request in requests {
+                    responses
+                        .push(execute_request(&method, &headers, &state, &session, request).await);
+                }
+                responses
+            } else {
+                futures_util::future::join_all(
+                    requests.into_iter().map(|request| {
+                        execute_request(&method, &headers, &state, &session, request)
+                    }),
+                )
+                .await
+            };
+            batch_response(responses)
+        }
+    }
+}
+
+async fn execute_request(
+    method: &axum::http::Method,
+    headers: &axum::http::header::HeaderMap,
+    state: &EngineState,
+    session: &Session,
+    request: gql::http::RawRequest,
 ) -> gql::http::Response {
     let tracer = tracing_util::global_tracer();
     let response = tracer
@@ -33,10 +85,13 @@ pub async fn handle_request(
                             &state.http_context,
                             &state.graphql_state,
                             &state.resolved_metadata,
-                            &session,
-                            &headers,
+                            session,
+                            headers,
                             request,
                             None,
+                            state.response_cache.as_deref(),
+                            state.persisted_queries.as_deref(),
+                            method,
                         )
                         .await;
 
@@ -57,6 +112,16 @@ pub async fn handle_request(
     response.inner()
 }
 
+/// The responses to a batch of requests, in the order of the requests. Response headers set by
+/// any of the requests are sent with the batch.
+fn batch_response(responses: Vec<gql::http::Response>) -> axum::response::Response {
+    let mut headers = axum::http::HeaderMap::new();
+    for response in &responses {
+        headers.extend(response.headers.clone());
+    }
+    (headers, Json(responses)).into_response()
+}
+
 pub async fn handle_explain_request(
     headers: axum::http::header::HeaderMap,
     State(state): State<EngineState>,
@@ -79,6 +144,7 @@ pub async fn handle_explain_request(
                         &session,
                         &headers,
                         request,
+                        state.persisted_queries.as_deref(),
                     )
                     .map(|(_operation_type, graphql_response)| graphql_response),
                 )
@@ -106,12 +172,146 @@ pub async fn handle_websocket_request(
         expose_internal_errors: engine_state.expose_internal_errors,
         schema: engine_state.graphql_state,
         auth_config: engine_state.auth_config,
-        metrics: graphql_ws::NoOpWebSocketMetrics, // No metrics implementation
+        metrics: EngineWebSocketMetrics,
         handshake_headers: Arc::new(headers), // Preserve the headers received during this handshake request.
         auth_mode_header: engine_state.auth_mode_header,
+        subscription_cohorts: engine_state
+            .graphql_websocket_server
+            .subscription_cohorts
+            .clone(),
+        key_inputs: engine_state.key_inputs,
+        persisted_queries: engine_state.persisted_queries,
     };
 
     engine_state
         .graphql_websocket_server
         .upgrade_and_handle_websocket(client_address, ws, context)
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    use super::MAX_BATCH_SIZE;
+
+    const METADATA: &str = include_str!(
+        "../../../metadata-resolve/tests/passing/supergraph/no_subgraphs/metadata.json"
+    );
+    const AUTH_CONFIG: &str = include_str!("../../../../static/auth/noauth_config_v3.json");
+
+    fn routes() -> axum::Router {
+        let (resolved_metadata, auth_config) = crate::resolve_metadata(
+            METADATA,
+            AUTH_CONFIG,
+            &metadata_resolve::configuration::Configuration::default(),
+        )
+        .unwrap();
+        let state = crate::build_state(
+            engine_types::ExposeInternalErrors::Censor,
+            auth_config,
+            resolved_metadata,
+            "X-Hasura-Auth-Mode".to_string(),
+            usize::MAX,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        crate::get_base_routes(state, usize::MAX)
+    }
+
+    async fn send(request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let (mut parts, body) = request.into_parts();
+        // the plugins middleware reads the address of the client
+        parts
+            .extensions
+            .insert(axum::extract::ConnectInfo(std::net::SocketAddr::from((
+                [127, 0, 0, 1],
+                0,
+            ))));
+        let response = routes()
+            .oneshot(Request::from_parts(parts, body))
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn post(body: &serde_json::Value) -> Request<Body> {
+        Request::post("/graphql")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_answered_in_order() {
+        let (status, body) = send(post(&serde_json::json!([
+            { "query": "query { first: __typename }" },
+            { "query": "mutation { __typename }" },
+            { "query": "query { second: __typename }" },
+        ])))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let responses = body.as_array().unwrap();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["data"]["first"], "Query");
+        assert!(responses[1].get("errors").is_some());
+        assert_eq!(responses[2]["data"]["second"], "Query");
+    }
+
+    #[tokio::test]
+    async fn test_query_batch_is_answered_in_order() {
+        let batch = (0..10)
+            .map(|i| serde_json::json!({ "query": format!("query {{ field_{i}: __typename }}") }))
+            .collect::<Vec<_>>();
+        let (status, body) = send(post(&serde_json::Value::from(batch))).await;
+        assert_eq!(status, StatusCode::OK);
+        let responses = body.as_array().unwrap();
+        assert_eq!(responses.len(), 10);
+        for (i, response) in responses.iter().enumerate() {
+            assert_eq!(response["data"][format!("field_{i}")], "Query");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_batch_is_rejected() {
+        let batch = vec![serde_json::json!({ "query": "{ __typename }" }); MAX_BATCH_SIZE + 1];
+        let (status, body) = send(post(&serde_json::Value::from(batch))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["errors"][0]["message"],
+            "the batch has 101 requests, more than the limit of 100"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_requests() {
+        let (status, body) = send(
+            Request::get("/graphql?query=query%20%7B%20__typename%20%7D")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["__typename"], "Query");
+
+        let (status, body) = send(
+            Request::get("/graphql?query=mutation%20%7B%20__typename%20%7D")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            body["errors"][0]["message"],
+            "mutations cannot be sent with GET requests"
+        );
+    }
+}
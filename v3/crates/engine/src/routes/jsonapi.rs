@@ -5,8 +5,10 @@ use axum::{
     routing::get,
 };
 use hasura_authn_core::Session;
+use std::collections::BTreeMap;
 use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tower_http::trace::TraceLayer;
 use tracing_util::{SpanVisibility, Traceable, set_status_on_current_span};
 
@@ -28,10 +30,15 @@ pub fn create_json_api_router(state: EngineState) -> axum::Router {
 fn build_router(state: EngineState, endpoint: jsonapi::EndPoint) -> axum::Router {
     Router::new()
         .route("/__schema", get(handle_jsonapi_schema))
-        // TODO: update method GET; for now we are only supporting queries. And
-        // in JSON:API spec, all queries have the GET method. Not even HEAD is
-        // supported. So this should be fine.
-        .route("/*path", get(handle_jsonapi_request))
+        // in JSON:API spec, all queries have the GET method, while resources are
+        // created, updated and deleted with POST, PATCH and DELETE respectively.
+        .route(
+            "/*path",
+            get(handle_jsonapi_request)
+                .post(handle_jsonapi_mutation_request)
+                .patch(handle_jsonapi_mutation_request)
+                .delete(handle_jsonapi_mutation_request),
+        )
         .layer(axum::middleware::from_fn_with_state(
             jsonapi::build_state_with_middleware_error_converter(()),
             hasura_authn_core::resolve_session,
@@ -104,7 +111,13 @@ async fn handle_jsonapi_request(
     axum::extract::RawQuery(raw_query): axum::extract::RawQuery,
     axum::extract::State(state): axum::extract::State<EngineState>,
     Extension(session): Extension<Session>,
-) -> impl IntoResponse {
+) -> axum::response::Response {
+    if jsonapi::is_command_request(&uri) {
+        return handle_jsonapi_command_request(request_headers, method, uri, state, session, &[])
+            .await;
+    }
+
+    let start = Instant::now();
     let tracer = tracing_util::global_tracer();
     let response = tracer
         .in_span_async(
@@ -128,6 +141,7 @@ async fn handle_jsonapi_request(
         .await;
 
     set_status_on_current_span(&response);
+    record_request_metrics("query", response.is_ok(), start.elapsed());
     match response {
         Ok(r) => (axum::http::StatusCode::OK, Json(r)).into_response(),
         Err(e) => e
@@ -135,3 +149,117 @@ async fn handle_jsonapi_request(
             .into_response(),
     }
 }
+
+async fn handle_jsonapi_mutation_request(
+    request_headers: HeaderMap,
+    method: Method,
+    uri: Uri,
+    axum::extract::State(state): axum::extract::State<EngineState>,
+    Extension(session): Extension<Session>,
+    request_body: axum::body::Bytes,
+) -> axum::response::Response {
+    if jsonapi::is_command_request(&uri) {
+        return handle_jsonapi_command_request(
+            request_headers,
+            method,
+            uri,
+            state,
+            session,
+            &request_body,
+        )
+        .await;
+    }
+
+    let start = Instant::now();
+    let tracer = tracing_util::global_tracer();
+    let response = tracer
+        .in_span_async(
+            "handle_jsonapi_mutation_request",
+            "Handle jsonapi mutation request",
+            SpanVisibility::User,
+            || {
+                Box::pin(jsonapi::mutation_handler_internal(
+                    Arc::new(request_headers),
+                    Arc::new(state.http_context.clone()),
+                    Arc::new(session),
+                    &state.jsonapi_catalog,
+                    state.resolved_metadata,
+                    method,
+                    uri,
+                    &request_body,
+                ))
+            },
+        )
+        .await;
+
+    set_status_on_current_span(&response);
+    record_request_metrics("mutation", response.is_ok(), start.elapsed());
+    match response {
+        Ok(jsonapi::MutationResponse {
+            status,
+            document: Some(document),
+        }) => (status, Json(document)).into_response(),
+        Ok(jsonapi::MutationResponse {
+            status,
+            document: None,
+        }) => status.into_response(),
+        Err(e) => e
+            .into_http_error(state.expose_internal_errors)
+            .into_response(),
+    }
+}
+
+async fn handle_jsonapi_command_request(
+    request_headers: HeaderMap,
+    method: Method,
+    uri: Uri,
+    state: EngineState,
+    session: Session,
+    request_body: &[u8],
+) -> axum::response::Response {
+    // function arguments are passed in the query string
+    let query_parameters =
+        match axum::extract::Query::<BTreeMap<String, String>>::try_from_uri(&uri) {
+            Ok(axum::extract::Query(query_parameters)) => query_parameters,
+            Err(rejection) => return rejection.into_response(),
+        };
+
+    let start = Instant::now();
+    let tracer = tracing_util::global_tracer();
+    let response = tracer
+        .in_span_async(
+            "handle_jsonapi_command_request",
+            "Handle jsonapi command request",
+            SpanVisibility::User,
+            || {
+                Box::pin(jsonapi::command_handler_internal(
+                    Arc::new(request_headers),
+                    Arc::new(state.http_context.clone()),
+                    Arc::new(state.resolved_metadata.plugin_configs.clone()),
+                    Arc::new(session),
+                    &state.jsonapi_catalog,
+                    state.resolved_metadata,
+                    method,
+                    uri,
+                    query_parameters,
+                    request_body,
+                ))
+            },
+        )
+        .await;
+
+    set_status_on_current_span(&response);
+    record_request_metrics("command", response.is_ok(), start.elapsed());
+    match response {
+        Ok(r) => (axum::http::StatusCode::OK, Json(r)).into_response(),
+        Err(e) => e
+            .into_http_error(state.expose_internal_errors)
+            .into_response(),
+    }
+}
+
+/// Record the duration of a JSON:API request by its operation, and whether it failed
+fn record_request_metrics(operation: &str, succeeded: bool, duration: Duration) {
+    let status = if succeeded { "success" } else { "error" };
+    metrics_util::JSONAPI_REQUEST_DURATION.observe(&[operation, status], duration);
+}
@@ -0,0 +1,318 @@
+//! Checking that the schemas embedded in the metadata still match the data connectors they were
+//! introspected from, at startup with `--check-connector-schemas` and on demand, for admins, at
+//! `/v1/connector-schema-drift`.
+//!
+//! Each data connector is asked for its `/capabilities` and `/schema`, which are compared with
+//! the `schema` of its `DataConnectorLink`. Anything the data connector no longer serves is
+//! reported as a `metadata_resolve` warning, or as an error with `--check-connector-schemas
+//! error`.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use axum::{Extension, Json, Router, extract::State, routing::get};
+use engine_types::ExposeInternalErrors;
+use hasura_authn_core::Identity;
+use lang_graphql::ast::common as ast;
+use metadata_resolve::{EmbeddedSchema, Qualified, SchemaDrift};
+use open_dds::data_connector::DataConnectorName;
+use reqwest::StatusCode;
+use serde::Serialize;
+
+use crate::{EngineState, authentication_middleware};
+
+/// What to do at startup when a data connector no longer matches its embedded schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaDriftCheck {
+    /// Print a warning, and start anyway
+    Warn,
+    /// Print an error, and don't start
+    Error,
+}
+
+/// How long to wait for a data connector to respond to `/capabilities` or `/schema`
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How a data connector compares with its embedded schema
+pub struct ConnectorSchemaDrift {
+    pub data_connector_name: Qualified<DataConnectorName>,
+    pub drift: Result<Vec<SchemaDrift>, FetchSchemaError>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FetchSchemaError {
+    #[error("the data connector URL cannot have a path")]
+    InvalidUrl,
+    #[error("request to the data connector failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("the data connector responded to /{endpoint} with status {status}")]
+    Status {
+        endpoint: &'static str,
+        status: StatusCode,
+    },
+    #[error("unexpected response from the data connector: {0}")]
+    InvalidResponse(#[from] serde_json::Error),
+}
+
+impl FetchSchemaError {
+    /// The error to show to a client, which leaves out why a request to the data connector
+    /// failed unless internal errors are exposed
+    fn to_client_message(&self, expose_internal_errors: ExposeInternalErrors) -> String {
+        match (self, expose_internal_errors) {
+            (Self::Request(_), ExposeInternalErrors::Censor) => {
+                "request to the data connector failed".to_owned()
+            }
+            (Self::InvalidResponse(_), ExposeInternalErrors::Censor) => {
+                "unexpected response from the data connector".to_owned()
+            }
+            (error, _) => error.to_string(),
+        }
+    }
+}
+
+/// Compare every data connector in the metadata with its embedded schema, concurrently
+pub async fn check_schema_drift(
+    http_client: &reqwest::Client,
+    opendd_metadata_json: &str,
+) -> Result<Vec<ConnectorSchemaDrift>, anyhow::Error> {
+    let metadata = open_dds::Metadata::from_json_str(opendd_metadata_json)?;
+    let embedded_schemas = metadata_resolve::embedded_schemas(metadata);
+    Ok(
+        futures_util::future::join_all(embedded_schemas.iter().map(|embedded_schema| async {
+            ConnectorSchemaDrift {
+                data_connector_name: embedded_schema.data_connector.name.clone(),
+                drift: fetch_schema_drift(http_client, embedded_schema).await,
+            }
+        }))
+        .await,
+    )
+}
+
+async fn fetch_schema_drift(
+    http_client: &reqwest::Client,
+    embedded_schema: &EmbeddedSchema,
+) -> Result<Vec<SchemaDrift>, FetchSchemaError> {
+    let capabilities = get_json(http_client, embedded_schema, "capabilities").await?;
+    let schema = get_json(http_client, embedded_schema, "schema").await?;
+    Ok(embedded_schema.check_drift(capabilities, schema)?)
+}
+
+async fn get_json(
+    http_client: &reqwest::Client,
+    embedded_schema: &EmbeddedSchema,
+    endpoint: &'static str,
+) -> Result<serde_json::Value, FetchSchemaError> {
+    let data_connector = &embedded_schema.data_connector;
+    let mut url = data_connector
+        .url
+        .get_url(ast::OperationType::Query)
+        .clone();
+    url.path_segments_mut()
+        .map_err(|()| FetchSchemaError::InvalidUrl)?
+        .pop_if_empty()
+        .push(endpoint);
+    let response = http_client
+        .get(url)
+        .headers(data_connector.headers.0.clone())
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(FetchSchemaError::Status { endpoint, status });
+    }
+    Ok(response.json().await?)
+}
+
+/// Check the data connectors when the engine starts, printing any drift against the metadata it
+/// was found in. Data connectors that can't be reached are warned about, but never stop the
+/// engine from starting.
+#[allow(clippy::print_stdout)]
+pub async fn check_schema_drift_at_startup(
+    http_client: &reqwest::Client,
+    opendd_metadata_json: &str,
+    check: SchemaDriftCheck,
+) -> Result<(), anyhow::Error> {
+    let mut drift = vec![];
+    for connector in check_schema_drift(http_client, opendd_metadata_json).await? {
+        match connector.drift {
+            Ok(connector_drift) => drift.extend(connector_drift),
+            Err(error) => println!(
+                "Warning: could not check the schema of data connector {}: {error}",
+                connector.data_connector_name
+            ),
+        }
+    }
+    if drift.is_empty() {
+        return Ok(());
+    }
+
+    match check {
+        SchemaDriftCheck::Warn => {
+            let warnings = drift
+                .into_iter()
+                .map(metadata_resolve::Warning::from)
+                .collect::<Vec<_>>();
+            for report in metadata_resolve::to_fancy_warnings(
+                opendd_metadata_json,
+                &warnings,
+                ariadne::Config::new(),
+            ) {
+                report
+                    .eprint(ariadne::Source::from(opendd_metadata_json))
+                    .unwrap();
+            }
+            Ok(())
+        }
+        SchemaDriftCheck::Error => {
+            for report in metadata_resolve::to_fancy_errors(
+                opendd_metadata_json,
+                &metadata_resolve::schema_drift_error(drift),
+                ariadne::Config::new(),
+            ) {
+                report
+                    .eprint(ariadne::Source::from(opendd_metadata_json))
+                    .unwrap();
+            }
+            Err(anyhow::anyhow!(
+                "data connectors no longer match the schemas in the metadata"
+            ))
+        }
+    }
+}
+
+/// Serve the drift of each data connector from its schema in the metadata file at
+/// `/v1/connector-schema-drift`. As each request is sent on to every data connector, only admins,
+/// whose role emulation is enabled, are answered. The metadata file is read on each request, so
+/// the check follows reloads.
+pub fn get_schema_drift_route(state: EngineState, metadata_path: PathBuf) -> Router {
+    Router::new()
+        .route("/v1/connector-schema-drift", get(handle_schema_drift))
+        .layer(axum::middleware::from_fn_with_state(
+            graphql_frontend::build_state_with_middleware_error_converter(state.clone()),
+            authentication_middleware,
+        ))
+        .with_state(SchemaDriftState {
+            engine_state: state,
+            metadata_path,
+        })
+}
+
+#[derive(Clone)]
+struct SchemaDriftState {
+    engine_state: EngineState,
+    metadata_path: PathBuf,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaDriftResponse {
+    data_connectors: Vec<DataConnectorSchemaDrift>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DataConnectorSchemaDrift {
+    name: String,
+    subgraph: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    issues: Vec<String>,
+}
+
+async fn handle_schema_drift(
+    State(state): State<SchemaDriftState>,
+    Extension(identity): Extension<Identity>,
+) -> Result<Json<SchemaDriftResponse>, (StatusCode, String)> {
+    let Identity::RoleEmulationEnabled(_) = identity else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "only admins can check the schemas of data connectors".to_owned(),
+        ));
+    };
+    let expose_internal_errors = state.engine_state.expose_internal_errors;
+    let internal_error = |error: String| {
+        let message = match expose_internal_errors {
+            ExposeInternalErrors::Expose => error,
+            ExposeInternalErrors::Censor => "internal error".to_owned(),
+        };
+        (StatusCode::INTERNAL_SERVER_ERROR, message)
+    };
+    let opendd_metadata_json = tokio::fs::read_to_string(&state.metadata_path)
+        .await
+        .map_err(|error| internal_error(error.to_string()))?;
+    let drift = check_schema_drift(
+        &state.engine_state.http_context.client,
+        &opendd_metadata_json,
+    )
+    .await
+    .map_err(|error| internal_error(error.to_string()))?;
+
+    let data_connectors = drift
+        .into_iter()
+        .map(|connector| {
+            let (error, issues) = match connector.drift {
+                Ok(drift) => (
+                    None,
+                    drift.iter().map(|drift| drift.issue.to_string()).collect(),
+                ),
+                Err(error) => (
+                    Some(error.to_client_message(expose_internal_errors)),
+                    vec![],
+                ),
+            };
+            DataConnectorSchemaDrift {
+                name: connector.data_connector_name.name.to_string(),
+                subgraph: connector.data_connector_name.subgraph.to_string(),
+                error,
+                issues,
+            }
+        })
+        .collect();
+    Ok(Json(SchemaDriftResponse { data_connectors }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    const METADATA: &str =
+        include_str!("../../metadata-resolve/tests/passing/supergraph/no_subgraphs/metadata.json");
+    const AUTH_CONFIG: &str = include_str!("../../../static/auth/noauth_config_v3.json");
+
+    #[tokio::test]
+    async fn test_schema_drift_requires_admin() {
+        let (resolved_metadata, auth_config) = crate::resolve_metadata(
+            METADATA,
+            AUTH_CONFIG,
+            &metadata_resolve::configuration::Configuration::default(),
+        )
+        .unwrap();
+        let state = crate::build_state(
+            engine_types::ExposeInternalErrors::Censor,
+            auth_config,
+            resolved_metadata,
+            "X-Hasura-Auth-Mode".to_string(),
+            usize::MAX,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        // without role emulation, the request is refused before the metadata is read
+        let routes = super::get_schema_drift_route(state, "does-not-exist.json".into());
+        let response = routes
+            .oneshot(
+                Request::get("/v1/connector-schema-drift")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
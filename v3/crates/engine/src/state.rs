@@ -1,6 +1,7 @@
-use crate::{EngineState, StartupError};
+use crate::{EngineState, EngineWebSocketMetrics, StartupError};
 use engine_types::{ExposeInternalErrors, HttpContext};
 use std::fmt::Display;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
 #[allow(clippy::print_stdout)]
@@ -55,21 +56,92 @@ pub fn build_state(
     resolved_metadata: metadata_resolve::Metadata,
     auth_mode_header: String,
     ndc_response_size_limit: usize,
+    remote_join_batch_size: Option<NonZeroUsize>,
+    remote_join_concurrency: Option<NonZeroUsize>,
+    response_cache_size: Option<NonZeroUsize>,
+    persisted_queries_cache_size: Option<NonZeroUsize>,
 ) -> Result<EngineState, anyhow::Error> {
     // Metadata
     let resolved_metadata = Arc::new(resolved_metadata);
 
+    let response_cache = response_cache_size
+        .map(|response_cache_size| {
+            graphql_frontend::ResponseCache::new(
+                Arc::new(graphql_frontend::InMemoryCache::new(response_cache_size)),
+                &resolved_metadata,
+            )
+            .map(Arc::new)
+        })
+        .transpose()?;
+
+    let persisted_queries = persisted_queries_cache_size
+        .map(|cache_size| Arc::new(graphql_frontend::PersistedQueries::new(cache_size)));
+
     let http_context = HttpContext {
         client: reqwest::Client::new(),
         ndc_response_size_limit: Some(ndc_response_size_limit),
+        remote_join_batch_size,
+        remote_join_concurrency,
     };
 
+    build_state_with_context(
+        expose_internal_errors,
+        http_context,
+        auth_config,
+        resolved_metadata,
+        Arc::new(graphql_ws::WebSocketServer::new()),
+        auth_mode_header,
+        response_cache,
+        persisted_queries,
+    )
+}
+
+/// Build the engine state for reloaded metadata and auth config. The HTTP client and the
+/// websocket server are carried over from the previous state, so open websocket connections are
+/// kept, and so are persisted queries. Responses cached for the previous metadata are not served.
+pub fn rebuild_state(
+    previous_state: &EngineState,
+    auth_config: hasura_authn::ResolvedAuthConfig,
+    resolved_metadata: metadata_resolve::Metadata,
+) -> Result<EngineState, anyhow::Error> {
+    let response_cache = previous_state
+        .response_cache
+        .as_ref()
+        .map(|response_cache| {
+            response_cache
+                .for_metadata(&resolved_metadata)
+                .map(Arc::new)
+        })
+        .transpose()?;
+    build_state_with_context(
+        previous_state.expose_internal_errors,
+        previous_state.http_context.clone(),
+        auth_config,
+        Arc::new(resolved_metadata),
+        previous_state.graphql_websocket_server.clone(),
+        previous_state.auth_mode_header.clone(),
+        response_cache,
+        previous_state.persisted_queries.clone(),
+    )
+}
+
+fn build_state_with_context(
+    expose_internal_errors: ExposeInternalErrors,
+    http_context: HttpContext,
+    auth_config: hasura_authn::ResolvedAuthConfig,
+    resolved_metadata: Arc<metadata_resolve::Metadata>,
+    graphql_websocket_server: Arc<graphql_ws::WebSocketServer<EngineWebSocketMetrics>>,
+    auth_mode_header: String,
+    response_cache: Option<Arc<graphql_frontend::ResponseCache>>,
+    persisted_queries: Option<Arc<graphql_frontend::PersistedQueries>>,
+) -> Result<EngineState, anyhow::Error> {
     let schema = graphql_schema::GDS {
         metadata: resolved_metadata.clone(),
     }
     .build_schema()?;
 
     let (jsonapi_catalog, _json_api_warnings) = jsonapi::Catalog::new(&resolved_metadata);
+    let key_inputs = graphql_frontend::KeyInputs::new(&resolved_metadata);
 
     let state = EngineState {
         expose_internal_errors,
@@ -78,8 +150,11 @@ pub fn build_state(
         jsonapi_catalog: Arc::new(jsonapi_catalog),
         resolved_metadata,
         auth_config: Arc::new(auth_config),
-        graphql_websocket_server: Arc::new(graphql_ws::WebSocketServer::new()),
+        graphql_websocket_server,
         auth_mode_header,
+        response_cache,
+        key_inputs: Arc::new(key_inputs),
+        persisted_queries,
     };
     Ok(state)
 }
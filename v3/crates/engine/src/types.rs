@@ -1,6 +1,7 @@
 use hasura_authn::ResolvedAuthConfig;
 use std::sync::Arc;
 
+use crate::EngineWebSocketMetrics;
 use engine_types::{ExposeInternalErrors, HttpContext};
 use graphql_schema::GDS;
 use lang_graphql as gql;
@@ -14,9 +15,11 @@ pub struct EngineState {
     pub resolved_metadata: Arc<metadata_resolve::Metadata>,
     pub jsonapi_catalog: Arc<jsonapi::Catalog>,
     pub auth_config: Arc<ResolvedAuthConfig>,
-    pub graphql_websocket_server:
-        Arc<graphql_ws::WebSocketServer<graphql_ws::NoOpWebSocketMetrics>>,
+    pub graphql_websocket_server: Arc<graphql_ws::WebSocketServer<EngineWebSocketMetrics>>,
     pub auth_mode_header: String,
+    pub response_cache: Option<Arc<graphql_frontend::ResponseCache>>,
+    pub key_inputs: Arc<graphql_frontend::KeyInputs>,
+    pub persisted_queries: Option<Arc<graphql_frontend::PersistedQueries>>,
 }
 
 #[derive(thiserror::Error, Debug)]
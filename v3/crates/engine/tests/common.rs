@@ -12,6 +12,7 @@ use open_dds::session_variables::{SESSION_VARIABLE_ROLE, SessionVariableName};
 use pretty_assertions::assert_eq;
 use serde_json as json;
 use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use std::{
     collections::HashMap,
@@ -37,6 +38,8 @@ pub fn setup(test_dir: &Path) -> GoldenTestContext {
     let http_context = HttpContext {
         client: reqwest::Client::new(),
         ndc_response_size_limit: None,
+        remote_join_batch_size: None,
+        remote_join_concurrency: None,
     };
     let mint = Mint::new(test_dir);
     GoldenTestContext { http_context, mint }
@@ -127,6 +130,7 @@ pub(crate) fn test_introspection_expectation(
             operation_name: None,
             query,
             variables: None,
+            extensions: None,
         };
 
         // Execute the test
@@ -141,6 +145,9 @@ pub(crate) fn test_introspection_expectation(
                 &request_headers,
                 raw_request.clone(),
                 None,
+                None,
+                None,
+                &Method::POST,
             )
             .await;
             let response = http_response.inner();
@@ -364,6 +371,15 @@ pub fn test_execution_expectation_for_multiple_ndc_versions(
 
             let schema = GDS::build_schema(&gds)?;
 
+            // Responses to queries marked with `@cached` are served from this cache, so tests
+            // with several sessions check which sessions may share a cached response.
+            let response_cache = graphql_frontend::ResponseCache::new(
+                Arc::new(graphql_frontend::InMemoryCache::new(
+                    NonZeroUsize::new(100).unwrap(),
+                )),
+                &arc_resolved_metadata,
+            )?;
+
             let query = read_to_string(&request_path)?;
 
             // Read optional GQL query variables.
@@ -420,6 +436,7 @@ pub fn test_execution_expectation_for_multiple_ndc_versions(
                         operation_name: None,
                         query: query.clone(),
                         variables: None,
+                        extensions: None,
                     };
                     for (session, request_headers) in sessions.iter().zip(request_headers.iter()) {
                         let (_, response) = execute_query(
@@ -431,6 +448,9 @@ pub fn test_execution_expectation_for_multiple_ndc_versions(
                             request_headers,
                             raw_request.clone(),
                             None,
+                            Some(&response_cache),
+                            None,
+                            &Method::POST,
                         )
                         .await;
                         let http_response = response.inner();
@@ -462,6 +482,7 @@ pub fn test_execution_expectation_for_multiple_ndc_versions(
                             operation_name: None,
                             query: query.clone(),
                             variables: Some(variables),
+                            extensions: None,
                         };
                         // do actual test
                         let (_, response) = execute_query(
@@ -473,6 +494,9 @@ pub fn test_execution_expectation_for_multiple_ndc_versions(
                             request_headers,
                             raw_request.clone(),
                             None,
+                            Some(&response_cache),
+                            None,
+                            &Method::POST,
                         )
                         .await;
                         let http_response = response.inner();
@@ -621,6 +645,7 @@ pub fn test_execute_explain(
             operation_name: None,
             query,
             variables: None,
+            extensions: None,
         };
         let (_, raw_response) = graphql_frontend::execute_explain(
             ExposeInternalErrors::Expose,
@@ -630,6 +655,7 @@ pub fn test_execute_explain(
             &session,
             &request_headers,
             raw_request,
+            None,
         )
         .await;
 
@@ -716,6 +742,7 @@ async fn run_query_graphql_ws(
     };
 
     let runtime_flags = metadata.runtime_flags.clone();
+    let key_inputs = graphql_frontend::KeyInputs::new(&metadata);
 
     let context = graphql_ws::Context {
         connection_expiry: graphql_ws::ConnectionExpiry::Never,
@@ -728,6 +755,9 @@ async fn run_query_graphql_ws(
         metrics: graphql_ws::NoOpWebSocketMetrics,
         handshake_headers: Arc::new(request_headers.clone()),
         auth_mode_header: "x-hasura-auth-mode".to_string(),
+        subscription_cohorts: graphql_ws::SubscriptionCohorts::new(),
+        key_inputs: Arc::new(key_inputs),
+        persisted_queries: None,
     };
     let (channel_sender, mut channel_receiver) =
         tokio::sync::mpsc::channel::<graphql_ws::Message>(10);
@@ -1971,6 +1971,57 @@ fn test_command_procedures_scalar_output_type_command_permissions() -> anyhow::R
     )
 }
 
+// Tests a mutation with several root fields on the same procedure, each answered under its own alias
+#[test]
+fn test_command_procedures_transactional_aliases() -> anyhow::Result<()> {
+    common::test_execution_expectation_for_multiple_ndc_versions(
+        "execute/commands/procedures/transactional/aliases",
+        &["execute/common_metadata/command_metadata.json"],
+        BTreeMap::from([
+            (
+                NdcVersion::V01,
+                vec!["execute/common_metadata/custom_connector_v01_schema.json"],
+            ),
+            (
+                NdcVersion::V02,
+                vec!["execute/common_metadata/custom_connector_v02_schema.json"],
+            ),
+        ]),
+    )
+}
+
+// Tests that a @transactional mutation with several root fields is rejected when its data connector
+// doesn't support transactional mutations
+#[test]
+fn test_command_procedures_transactional_non_transactional_data_connector() -> anyhow::Result<()> {
+    common::test_execution_expectation_for_multiple_ndc_versions(
+        "execute/commands/procedures/transactional/non_transactional_data_connector",
+        &["execute/common_metadata/command_metadata.json"],
+        BTreeMap::from([
+            (
+                NdcVersion::V01,
+                vec!["execute/common_metadata/custom_connector_v01_schema.json"],
+            ),
+            (
+                NdcVersion::V02,
+                vec!["execute/common_metadata/custom_connector_v02_schema.json"],
+            ),
+        ]),
+    )
+}
+
+// Tests that a @transactional mutation is rejected when its root fields use more than one data connector
+#[test]
+fn test_command_procedures_transactional_multiple_data_connectors() -> anyhow::Result<()> {
+    common::test_execution_expectation(
+        "execute/commands/procedures/transactional/multiple_data_connectors",
+        &[
+            "execute/common_metadata/command_metadata.json",
+            "execute/common_metadata/custom_connector_v01_schema.json",
+        ],
+    )
+}
+
 // Tests a mutation command with object (commandActor) output type (different command permissions for roles: admin, user_1, user_2)
 #[test]
 fn test_command_procedures_object_output_type_command_permissions() -> anyhow::Result<()> {
@@ -2371,6 +2422,26 @@ fn test_input_type_field_presets_on_model_arguments() -> anyhow::Result<()> {
     )
 }
 
+// Responses cached with `@cached` are only shared between sessions that agree on the session
+// variables the permissions reference
+#[test]
+fn test_model_select_many_cached_session_variables() -> anyhow::Result<()> {
+    common::test_execution_expectation_for_multiple_ndc_versions(
+        "execute/models/select_many/cached/session_variables",
+        &["execute/models/select_many/relationship_predicates/common_metadata.json"],
+        BTreeMap::from([
+            (
+                NdcVersion::V01,
+                vec!["execute/common_metadata/postgres_connector_ndc_v01_schema.json"],
+            ),
+            (
+                NdcVersion::V02,
+                vec!["execute/common_metadata/postgres_connector_ndc_v02_schema.json"],
+            ),
+        ]),
+    )
+}
+
 // Tests using relationships in predicates
 // Array relationship
 #[test]
@@ -2971,6 +3042,45 @@ fn test_aggregates_root_field_custom_count_return_type() -> anyhow::Result<()> {
     )
 }
 
+#[test]
+fn test_aggregates_group_by_simple_select() -> anyhow::Result<()> {
+    let test_path_string = "execute/aggregates/group_by/simple_select";
+    common::test_execution_expectation_for_multiple_ndc_versions(
+        test_path_string,
+        &[
+            "execute/aggregates/common_metadata/custom_connector_v02_types.json",
+            "execute/aggregates/common_metadata/supergraph.json",
+        ],
+        BTreeMap::from([
+            // Grouping is only supported by NDC v0.2.x connectors
+            (
+                NdcVersion::V02,
+                vec!["execute/common_metadata/custom_connector_v02_schema.json"],
+            ),
+        ]),
+    )
+}
+
+// The user role can select at most 2 actors, so only they are grouped
+#[test]
+fn test_aggregates_group_by_max_rows() -> anyhow::Result<()> {
+    let test_path_string = "execute/aggregates/group_by/max_rows";
+    common::test_execution_expectation_for_multiple_ndc_versions(
+        test_path_string,
+        &[
+            "execute/aggregates/common_metadata/custom_connector_v02_types.json",
+            "execute/aggregates/common_metadata/supergraph.json",
+        ],
+        BTreeMap::from([
+            // Grouping is only supported by NDC v0.2.x connectors
+            (
+                NdcVersion::V02,
+                vec!["execute/common_metadata/custom_connector_v02_schema.json"],
+            ),
+        ]),
+    )
+}
+
 #[test]
 fn test_aggregates_root_field_typename() -> anyhow::Result<()> {
     common::test_execution_expectation_for_multiple_ndc_versions(
@@ -0,0 +1,77 @@
+//! Tests of the exit statuses and output of the `metadata` binary.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const EXIT_INVALID: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+
+const VALID_METADATA: &str = "../../static/metadata.json";
+const INVALID_METADATA: &str =
+    "tests/validate_metadata_artifacts/metadata_with_duplicate_field_mappings_in_relationship.json";
+
+fn run_metadata(args: &[&str], metadata_path: &str) -> Output {
+    let metadata_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(metadata_path);
+    Command::new(env!("CARGO_BIN_EXE_metadata"))
+        .args(args)
+        .arg("--metadata-path")
+        .arg(metadata_path)
+        .output()
+        .unwrap()
+}
+
+fn stdout_json(output: &Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_validate_valid_metadata() {
+    let output = run_metadata(&["validate", "--output", "json"], VALID_METADATA);
+    assert_eq!(output.status.code(), Some(0));
+    let report = stdout_json(&output);
+    assert_eq!(report["valid"], true);
+    assert_eq!(report["errors"], serde_json::json!([]));
+    assert!(report["warnings"].is_array());
+}
+
+#[test]
+fn test_validate_invalid_metadata() {
+    let output = run_metadata(&["validate", "--output", "json"], INVALID_METADATA);
+    assert_eq!(output.status.code(), Some(EXIT_INVALID));
+    let report = stdout_json(&output);
+    assert_eq!(report["valid"], false);
+    let errors = report["errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(
+        errors[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Mapping for source field movie_id already exists")
+    );
+}
+
+#[test]
+fn test_unreadable_metadata() {
+    let output = run_metadata(&["validate"], "tests/does_not_exist.json");
+    assert_eq!(output.status.code(), Some(EXIT_USAGE));
+}
+
+#[test]
+fn test_introspection_and_openapi() {
+    let output = run_metadata(&["introspection", "--role", "admin"], VALID_METADATA);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout_json(&output).is_object());
+
+    let output = run_metadata(&["openapi", "--role", "admin"], VALID_METADATA);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout_json(&output)["openapi"].is_string());
+}
+
+#[test]
+fn test_unknown_role() {
+    for command in ["introspection", "openapi", "sdl"] {
+        let output = run_metadata(&[command, "--role", "unknown_role"], VALID_METADATA);
+        assert_eq!(output.status.code(), Some(EXIT_INVALID), "{command}");
+        assert!(output.stdout.is_empty(), "{command}");
+    }
+}
@@ -4,6 +4,7 @@ use lang_graphql as gql;
 use open_dds::relationships::RelationshipName;
 use plan_types::RemotePredicateKey;
 use serde_json as json;
+use std::sync::Arc;
 use thiserror::Error;
 use tracing_util::{ErrorVisibility, TraceableError};
 use transitive::Transitive;
@@ -46,6 +47,9 @@ pub enum FieldError {
     )]
     RelationshipPredicatesNotSupported { name: RelationshipName },
 
+    #[error("mutation was not applied, as the transaction it was part of failed: {error}")]
+    TransactionFailed { error: Arc<FieldError> },
+
     #[error("internal error: {0}")]
     InternalError(#[from] FieldInternalError),
 }
@@ -57,6 +61,7 @@ impl FieldError {
                 Some(connector_error.error_response.details().clone())
             }
             Self::InternalError(internal) => internal.get_details(),
+            Self::TransactionFailed { error } => error.get_details(),
             Self::FieldNotFoundInService { .. }
             | Self::SubscriptionsNotSupported
             | Self::RelationshipPredicatesNotSupported { .. } => None,
@@ -74,6 +79,16 @@ impl FieldError {
                 details: None,
                 is_internal: internal.is_engine_internal_error(),
             },
+            (Self::TransactionFailed { error }, _) => {
+                let response = error.to_error_response(expose_internal_errors);
+                FieldErrorResponse {
+                    message: format!(
+                        "mutation was not applied, as the transaction it was part of failed: {}",
+                        response.message
+                    ),
+                    ..response
+                }
+            }
             (e, _) => FieldErrorResponse {
                 message: e.to_string(),
                 details,
@@ -110,6 +125,7 @@ impl TraceableError for FieldError {
             | Self::FieldNotFoundInService { .. }
             | Self::RelationshipPredicatesNotSupported { .. }
             | Self::SubscriptionsNotSupported => ErrorVisibility::User,
+            Self::TransactionFailed { error } => error.visibility(),
             Self::InternalError(internal_error) => internal_error.visibility(),
         }
     }
@@ -162,7 +178,10 @@ impl FieldInternalError {
             Self::NDCUnexpected(
                 NDCUnexpectedError::BadNDCResponse { .. }
                     | NDCUnexpectedError::NDCClientError(
-                        ndc_client::Error::InvalidConnector(_) | ndc_client::Error::Connector(_)
+                        ndc_client::Error::InvalidConnector(_)
+                            | ndc_client::Error::Connector(_)
+                            | ndc_client::Error::Timeout { .. }
+                            | ndc_client::Error::CircuitOpen { .. }
                     )
             )
         )
@@ -19,7 +19,7 @@ pub use ndc_request::{
 use plan_types::{
     FUNCTION_IR_VALUE_COLUMN_NAME, JoinLocations, NDCMutationExecution, NDCQueryExecution,
     NDCSubscriptionExecution, PredicateQueryTrees, ProcessResponseAs, QueryExecutionPlan,
-    QueryExecutionTree, RemotePredicateKey, ResolvedFilterExpression,
+    QueryExecutionTree, RemotePredicateKey, ResolvedFilterExpression, StreamCursor,
 };
 pub use remote_predicates::replace_predicates_in_query_execution_plan;
 use std::collections::BTreeMap;
@@ -299,6 +299,120 @@ pub async fn resolve_ndc_mutation_execution(
     .await?
     .as_latest();
 
+    run_mutation_remote_joins(
+        http_context,
+        plugins,
+        session,
+        request_headers,
+        execution_tree.remote_join_executions,
+        execution_span_attribute,
+        &process_response_as,
+        project_id,
+        mutation_response,
+    )
+    .await
+}
+
+/// Run several mutations against the same data connector in one request, which the data
+/// connector applies transactionally: either all of the mutations are applied or none of them
+/// are. The outer `Result` is the outcome of the transaction, and the inner ones are the
+/// responses of each mutation, in the order they were given, after their remote joins.
+pub async fn resolve_ndc_mutation_transaction(
+    http_context: &HttpContext,
+    plugins: &LifecyclePluginConfigs,
+    session: &Session,
+    request_headers: &http::HeaderMap,
+    ndc_mutation_executions: Vec<NDCMutationExecution>,
+    project_id: Option<&ProjectId>,
+) -> Result<Vec<Result<ndc_models::MutationResponse, FieldError>>, FieldError> {
+    let Some(first_execution) = ndc_mutation_executions.first() else {
+        return Ok(vec![]);
+    };
+    let data_connector = first_execution.data_connector.clone();
+    let execution_span_attribute = first_execution.execution_span_attribute;
+
+    let mut mutation_requests = Vec::new();
+    let mut field_span_attributes = Vec::new();
+    let mut remote_joins = Vec::new();
+    for ndc_mutation_execution in ndc_mutation_executions {
+        let NDCMutationExecution {
+            execution_tree,
+            data_connector: _,
+            execution_span_attribute: _,
+            field_span_attribute,
+            process_response_as,
+        } = ndc_mutation_execution;
+        mutation_requests.push(ndc_request::make_ndc_mutation_request(
+            execution_tree.mutation_execution_plan,
+        )?);
+        field_span_attributes.push(field_span_attribute);
+        remote_joins.push((execution_tree.remote_join_executions, process_response_as));
+    }
+    let mutation_request = ndc_request::merge_ndc_mutation_requests(mutation_requests)?;
+
+    let mutation_response = ndc::execute_ndc_mutation(
+        http_context,
+        plugins,
+        session,
+        request_headers,
+        &mutation_request,
+        &data_connector,
+        execution_span_attribute,
+        field_span_attributes.join(", "),
+        project_id,
+    )
+    .await?
+    .as_latest();
+
+    if mutation_response.operation_results.len() != remote_joins.len() {
+        Err(error::NDCUnexpectedError::BadNDCResponse {
+            summary: format!(
+                "expected {} operation results from a transaction of {} mutations, got {}",
+                remote_joins.len(),
+                remote_joins.len(),
+                mutation_response.operation_results.len()
+            ),
+        })?;
+    }
+
+    let mut mutation_responses = Vec::new();
+    for (operation_result, (remote_join_executions, process_response_as)) in mutation_response
+        .operation_results
+        .into_iter()
+        .zip(remote_joins)
+    {
+        mutation_responses.push(
+            run_mutation_remote_joins(
+                http_context,
+                plugins,
+                session,
+                request_headers,
+                remote_join_executions,
+                execution_span_attribute,
+                &process_response_as,
+                project_id,
+                ndc_models::MutationResponse {
+                    operation_results: vec![operation_result],
+                },
+            )
+            .await,
+        );
+    }
+    Ok(mutation_responses)
+}
+
+/// Run the remote joins of a mutation on its response
+async fn run_mutation_remote_joins(
+    http_context: &HttpContext,
+    plugins: &LifecyclePluginConfigs,
+    session: &Session,
+    request_headers: &http::HeaderMap,
+    remote_join_executions: JoinLocations,
+    execution_span_attribute: &'static str,
+    process_response_as: &ProcessResponseAs,
+    project_id: Option<&ProjectId>,
+    mutation_response: ndc_models::MutationResponse,
+) -> Result<ndc_models::MutationResponse, FieldError> {
     let mutation_response_as_query_response =
         mutation_response_to_query_response(mutation_response);
     let response_rowsets = run_remote_joins(
@@ -306,17 +420,14 @@ pub async fn resolve_ndc_mutation_execution(
         plugins,
         session,
         request_headers,
-        execution_tree.remote_join_executions,
+        remote_join_executions,
         execution_span_attribute,
-        &process_response_as,
+        process_response_as,
         project_id,
         mutation_response_as_query_response.0,
     )
     .await?;
-    let mutation_response =
-        query_response_back_to_mutation_response(ndc_models::QueryResponse(response_rowsets))?;
-
-    Ok(mutation_response)
+    query_response_back_to_mutation_response(ndc_models::QueryResponse(response_rowsets))
 }
 
 /// Kludge-ily make a `MutationResponse` look like a `QueryResponse` so we can pass it to the
@@ -398,6 +509,41 @@ pub struct NDCSubscriptionQuery {
     pub data_connector: Arc<metadata_resolve::DataConnectorLink>,
     pub process_response_as: ProcessResponseAs,
     pub polling_interval_ms: u64,
+    /// Set for streaming subscriptions, whose query request changes with every batch
+    pub stream: Option<NDCSubscriptionStream>,
+}
+
+/// A streaming subscription. Each poll fetches the batch of rows after the cursor, and moves the
+/// cursor to the last row of the batch.
+pub struct NDCSubscriptionStream {
+    query_execution_plan: QueryExecutionPlan,
+    cursor: StreamCursor,
+    cursor_value: serde_json::Value,
+}
+
+impl NDCSubscriptionStream {
+    /// The query request for the batch of rows after the cursor
+    pub fn query_request(&self) -> Result<ndc::NdcQueryRequest, FieldError> {
+        make_ndc_query_request(
+            self.cursor
+                .query_execution_plan_after(&self.query_execution_plan, &self.cursor_value),
+        )
+    }
+
+    /// Moves the cursor to the last row of the batch, returning the number of rows in the batch
+    pub fn advance(&mut self, rowsets: &[ndc_models::RowSet]) -> usize {
+        let rows = rowsets
+            .iter()
+            .flat_map(|rowset| rowset.rows.iter().flatten())
+            .collect::<Vec<_>>();
+        if let Some(cursor_value) = rows
+            .last()
+            .and_then(|row| row.get(self.cursor.alias.as_str()))
+        {
+            self.cursor_value = cursor_value.0.clone();
+        }
+        rows.len()
+    }
 }
 
 /// Resolve a subscription execution plan to a NDC query.
@@ -410,15 +556,27 @@ pub async fn resolve_ndc_subscription_execution(
         field_span_attribute: _,
         process_response_as,
         polling_interval_ms,
+        stream_cursor,
     } = execution;
     // Remote relationships and relationships without NDC comparison capability are not allowed in predicates for subscriptions.
     // Only allow local relationships and fields that can be pushed down to NDC.
     let data_connector = query_execution_plan.data_connector.clone();
-    let query_request = make_ndc_query_request(query_execution_plan)?;
+    let (query_request, stream) = match stream_cursor {
+        None => (make_ndc_query_request(query_execution_plan)?, None),
+        Some(cursor) => {
+            let stream = NDCSubscriptionStream {
+                cursor_value: cursor.initial_value.clone(),
+                query_execution_plan,
+                cursor,
+            };
+            (stream.query_request()?, Some(stream))
+        }
+    };
     Ok(NDCSubscriptionQuery {
         query_request,
         data_connector,
         process_response_as,
         polling_interval_ms,
+        stream,
     })
 }
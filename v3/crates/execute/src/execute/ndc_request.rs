@@ -38,3 +38,76 @@ pub fn make_ndc_mutation_request(
         )?)),
     }
 }
+
+/// Combine the requests for several mutations to the same data connector into one request, which
+/// the data connector applies transactionally. The requests are all made for the same data
+/// connector, and so for the same NDC version.
+pub fn merge_ndc_mutation_requests(
+    mutation_requests: Vec<ndc::NdcMutationRequest>,
+) -> Result<ndc::NdcMutationRequest, error::FieldError> {
+    let mut mutation_requests = mutation_requests.into_iter();
+    let mut merged =
+        mutation_requests
+            .next()
+            .ok_or_else(|| error::FieldInternalError::InternalGeneric {
+                description: "no mutations to merge into a transaction".to_string(),
+            })?;
+    for mutation_request in mutation_requests {
+        match (&mut merged, mutation_request) {
+            (ndc::NdcMutationRequest::V01(merged), ndc::NdcMutationRequest::V01(request)) => {
+                merged.operations.extend(request.operations);
+                merged
+                    .collection_relationships
+                    .extend(request.collection_relationships);
+            }
+            (ndc::NdcMutationRequest::V02(merged), ndc::NdcMutationRequest::V02(request)) => {
+                merged.operations.extend(request.operations);
+                merged
+                    .collection_relationships
+                    .extend(request.collection_relationships);
+            }
+            _ => Err(error::FieldInternalError::InternalGeneric {
+                description: "mutations in a transaction use different NDC versions".to_string(),
+            })?,
+        }
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndc_models as ndc_models_v02;
+
+    fn mutation_request(procedure: &str) -> ndc::NdcMutationRequest {
+        ndc::NdcMutationRequest::V02(ndc_models_v02::MutationRequest {
+            operations: vec![ndc_models_v02::MutationOperation::Procedure {
+                name: ndc_models_v02::ProcedureName::new(procedure.into()),
+                arguments: std::collections::BTreeMap::new(),
+                fields: None,
+            }],
+            collection_relationships: std::collections::BTreeMap::new(),
+            request_arguments: None,
+        })
+    }
+
+    #[test]
+    fn test_merge_ndc_mutation_requests() {
+        let merged = merge_ndc_mutation_requests(vec![
+            mutation_request("insert_author"),
+            mutation_request("insert_article"),
+        ])
+        .unwrap();
+        let ndc::NdcMutationRequest::V02(merged) = merged else {
+            panic!("expected a v0.2.x request");
+        };
+        let procedures: Vec<_> = merged
+            .operations
+            .iter()
+            .map(|ndc_models_v02::MutationOperation::Procedure { name, .. }| name.to_string())
+            .collect();
+        assert_eq!(procedures, vec!["insert_author", "insert_article"]);
+
+        assert!(merge_ndc_mutation_requests(vec![]).is_err());
+    }
+}
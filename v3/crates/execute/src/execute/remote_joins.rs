@@ -64,13 +64,25 @@
 //!    this algorithm.
 //!
 //! 5. Perform join on LHS response and RHS response
+//!
+//! Steps 3 and 4 are performed concurrently for all the remote joins found at the
+//! same level of the join tree. If a maximum batch size is configured for remote
+//! joins, the variable sets of a single join are split into batches which are
+//! sent as separate NDC queries, also concurrently. At most
+//! `HttpContext::remote_join_concurrency` queries are in flight at once at each
+//! of these levels.
+//!
+//! If the target data connector does not support variables, the NDC query is
+//! sent once per variable set, with the variables replaced by their values.
+use futures::{StreamExt, TryStreamExt, stream};
 use metadata_resolve::{Qualified, QualifiedTypeName, QualifiedTypeReference};
 use open_dds::types::CustomTypeName;
 use serde_json as json;
 use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroUsize;
 use tracing_util::SpanVisibility;
 
-use plan_types::{ProcessResponseAs, RemoteJoinObjectFieldMapping};
+use plan_types::{ProcessResponseAs, QueryExecutionPlan, RemoteJoin, RemoteJoinObjectFieldMapping};
 
 use crate::error;
 use crate::ndc::execute_ndc_query;
@@ -82,11 +94,19 @@ use collect::ExecutableJoinNode;
 use plan_types::{JoinLocations, RemoteJoinVariableSet};
 mod collect;
 mod join;
+mod variables;
 
 use async_recursion::async_recursion;
 
+/// The number of remote join queries executed at once, if it is not configured
+pub const DEFAULT_REMOTE_JOIN_CONCURRENCY: usize = 10;
+
 /// Execute remote joins. As an entry-point it assumes the response is available
 /// for the top-level query, and executes further remote joins recursively.
+///
+/// The RHS queries of sibling join locations do not depend on each other, so
+/// they are executed concurrently, and joined into the LHS response once they
+/// have all returned.
 #[async_recursion]
 pub async fn execute_join_locations(
     http_context: &HttpContext,
@@ -117,93 +137,39 @@ pub async fn execute_join_locations(
         },
     )?;
 
-    for executable_join_node in next_join_nodes {
-        let ExecutableJoinNode {
-            variable_sets,
-            location_path,
-            mut join_node,
-            sub_tree,
-            remote_alias,
-        } = executable_join_node;
-
-        // if we do not get any join arguments back, we have nothing on the RHS
-        // to execute. Skip execution.
-        if variable_sets.is_empty() {
-            continue;
-        }
+    let executed_join_nodes = execute_concurrently(
+        next_join_nodes
+            .into_iter()
+            // if we do not get any join arguments back, we have nothing on the RHS
+            // to execute. Skip execution.
+            .filter(|executable_join_node| !executable_join_node.variable_sets.is_empty())
+            .map(|executable_join_node| {
+                execute_join_node(
+                    http_context,
+                    plugins,
+                    session,
+                    request_headers,
+                    execution_span_attribute,
+                    executable_join_node,
+                    project_id,
+                )
+            }),
+        remote_join_concurrency(http_context),
+    )
+    .await?;
 
-        // patch the target/RHS IR with variable values
-        let foreach_variables: Vec<BTreeMap<plan_types::VariableName, json::Value>> = variable_sets
-            .iter()
-            .map(|variable_set| {
-                variable_set
-                    .iter()
-                    .map(|(variable_name, variable)| {
-                        let mapped_variable_value =
-                            map_remote_join_variable_value_to_target_connector(
-                                &variable.value,
-                                &variable.variable_type,
-                                &join_node.object_type_field_mappings,
-                            )?;
-                        Ok((variable_name.clone(), mapped_variable_value))
-                    })
-                    .collect::<Result<_, _>>()
-            })
-            .collect::<Result<_, error::FieldError>>()?;
-
-        join_node.target_ndc_execution.variables = Some(foreach_variables);
-
-        let ndc_query =
-            super::ndc_request::make_ndc_query_request(join_node.target_ndc_execution.clone())?;
-
-        // execute the remote query
-        let mut target_response = tracer
-            .in_span_async(
-                "execute_remote_join_query",
-                "Execute remote query for join",
-                SpanVisibility::Internal,
-                || {
-                    Box::pin(execute_ndc_query(
-                        http_context,
-                        plugins,
-                        session,
-                        request_headers,
-                        &ndc_query,
-                        &join_node.target_data_connector,
-                        execution_span_attribute,
-                        remote_alias.clone(),
-                        project_id,
-                    ))
-                },
-            )
-            .await?
-            .as_latest_rowsets();
-
-        // if the sub-tree is not empty, recursively process the sub-tree; which
-        // will modify the `target_response` with all joins down the tree
-        if !sub_tree.locations.is_empty() {
-            execute_join_locations(
-                http_context,
-                plugins,
-                session,
-                request_headers,
-                execution_span_attribute,
-                &mut target_response,
-                &join_node.process_response_as,
-                &sub_tree,
-                project_id,
-            )
-            .await?;
-        }
+    for executed_join_node in executed_join_nodes {
+        let ExecutedJoinNode {
+            join_node,
+            remote_alias,
+            location_path,
+            rhs_response,
+        } = executed_join_node;
         tracer.in_span(
             "response_join",
             "Join responses for remote query",
             SpanVisibility::Internal,
             || {
-                // from `Vec<RowSet>` create `HashMap<Argument, RowSet>`
-                let rhs_response: HashMap<RemoteJoinVariableSet, ndc_models::RowSet> =
-                    variable_sets.into_iter().zip(target_response).collect();
-
                 join::join_responses(
                     &location_path,
                     &join_node,
@@ -218,6 +184,208 @@ pub async fn execute_join_locations(
     Ok(())
 }
 
+/// A join node whose RHS query has been executed, along with any joins further
+/// down its sub-tree, and is ready to be joined into the LHS response
+struct ExecutedJoinNode {
+    join_node: RemoteJoin,
+    remote_alias: String,
+    location_path: Vec<collect::LocationInfo>,
+    rhs_response: HashMap<RemoteJoinVariableSet, ndc_models::RowSet>,
+}
+
+/// Execute the RHS query of a join node, then the remote joins in its sub-tree
+async fn execute_join_node(
+    http_context: &HttpContext,
+    plugins: &LifecyclePluginConfigs,
+    session: &Session,
+    request_headers: &http::HeaderMap,
+    execution_span_attribute: &'static str,
+    executable_join_node: ExecutableJoinNode,
+    project_id: Option<&ProjectId>,
+) -> Result<ExecutedJoinNode, error::FieldError> {
+    let ExecutableJoinNode {
+        variable_sets,
+        location_path,
+        join_node,
+        sub_tree,
+        remote_alias,
+    } = executable_join_node;
+
+    // fix the order of the variable sets, so the rowsets returned for each
+    // batch can be matched back up with them
+    let variable_sets = variable_sets.into_iter().collect::<Vec<_>>();
+
+    // patch the target/RHS IR with variable values
+    let foreach_variables: Vec<BTreeMap<plan_types::VariableName, json::Value>> = variable_sets
+        .iter()
+        .map(|variable_set| {
+            variable_set
+                .iter()
+                .map(|(variable_name, variable)| {
+                    let mapped_variable_value = map_remote_join_variable_value_to_target_connector(
+                        &variable.value,
+                        &variable.variable_type,
+                        &join_node.object_type_field_mappings,
+                    )?;
+                    Ok((variable_name.clone(), mapped_variable_value))
+                })
+                .collect::<Result<_, _>>()
+        })
+        .collect::<Result<_, error::FieldError>>()?;
+
+    let target_ndc_executions = if join_node
+        .target_data_connector
+        .capabilities
+        .supports_query_variables
+    {
+        // split the variable sets into batches, so that a large LHS response
+        // does not produce one giant foreach query. Each batch is a separate
+        // NDC request.
+        batch_variable_sets(foreach_variables, http_context.remote_join_batch_size)
+            .into_iter()
+            .map(|variables_batch| {
+                let mut target_ndc_execution = join_node.target_ndc_execution.clone();
+                target_ndc_execution.variables = Some(variables_batch);
+                target_ndc_execution
+            })
+            .collect::<Vec<_>>()
+    } else {
+        // the target data connector does not support variables, so we make a
+        // request per variable set with the variables replaced by their values
+        foreach_variables
+            .iter()
+            .map(|variables| {
+                variables::substitute_variables(&join_node.target_ndc_execution, variables)
+            })
+            .collect::<Result<Vec<_>, error::FieldError>>()?
+    };
+
+    let batch_responses = execute_concurrently(
+        target_ndc_executions
+            .into_iter()
+            .map(|target_ndc_execution| {
+                execute_remote_join_query(
+                    http_context,
+                    plugins,
+                    session,
+                    request_headers,
+                    execution_span_attribute,
+                    &join_node,
+                    target_ndc_execution,
+                    &remote_alias,
+                    project_id,
+                )
+            }),
+        remote_join_concurrency(http_context),
+    )
+    .await?;
+
+    // the batches are returned in order, so their rowsets line up with the
+    // variable sets again once concatenated
+    let mut target_response = batch_responses.into_iter().flatten().collect::<Vec<_>>();
+
+    // if the sub-tree is not empty, recursively process the sub-tree; which
+    // will modify the `target_response` with all joins down the tree
+    if !sub_tree.locations.is_empty() {
+        execute_join_locations(
+            http_context,
+            plugins,
+            session,
+            request_headers,
+            execution_span_attribute,
+            &mut target_response,
+            &join_node.process_response_as,
+            &sub_tree,
+            project_id,
+        )
+        .await?;
+    }
+
+    // from `Vec<RowSet>` create `HashMap<Argument, RowSet>`
+    let rhs_response: HashMap<RemoteJoinVariableSet, ndc_models::RowSet> =
+        variable_sets.into_iter().zip(target_response).collect();
+
+    Ok(ExecutedJoinNode {
+        join_node,
+        remote_alias,
+        location_path,
+        rhs_response,
+    })
+}
+
+fn remote_join_concurrency(http_context: &HttpContext) -> usize {
+    http_context
+        .remote_join_concurrency
+        .map_or(DEFAULT_REMOTE_JOIN_CONCURRENCY, NonZeroUsize::get)
+}
+
+/// Split the variable sets of a join into batches of at most `batch_size`,
+/// keeping their order. Without a batch size they are all sent at once.
+fn batch_variable_sets<T>(variable_sets: Vec<T>, batch_size: Option<NonZeroUsize>) -> Vec<Vec<T>> {
+    let Some(batch_size) = batch_size else {
+        return vec![variable_sets];
+    };
+    let mut batches = Vec::new();
+    let mut variable_sets = variable_sets.into_iter().peekable();
+    while variable_sets.peek().is_some() {
+        batches.push(variable_sets.by_ref().take(batch_size.get()).collect());
+    }
+    batches
+}
+
+/// Execute queries with at most `concurrency` of them in flight at once. The
+/// results are returned in the order of the queries, whatever order they
+/// complete in.
+async fn execute_concurrently<T>(
+    queries: impl IntoIterator<Item = impl Future<Output = Result<T, error::FieldError>>>,
+    concurrency: usize,
+) -> Result<Vec<T>, error::FieldError> {
+    stream::iter(queries)
+        .buffered(concurrency)
+        .try_collect()
+        .await
+}
+
+/// Execute the RHS query of a join node for one batch of variable sets
+async fn execute_remote_join_query(
+    http_context: &HttpContext,
+    plugins: &LifecyclePluginConfigs,
+    session: &Session,
+    request_headers: &http::HeaderMap,
+    execution_span_attribute: &'static str,
+    join_node: &RemoteJoin,
+    target_ndc_execution: QueryExecutionPlan,
+    remote_alias: &str,
+    project_id: Option<&ProjectId>,
+) -> Result<Vec<ndc_models::RowSet>, error::FieldError> {
+    let ndc_query = super::ndc_request::make_ndc_query_request(target_ndc_execution)?;
+
+    // execute the remote query
+    let target_response = tracing_util::global_tracer()
+        .in_span_async(
+            "execute_remote_join_query",
+            "Execute remote query for join",
+            SpanVisibility::Internal,
+            || {
+                Box::pin(execute_ndc_query(
+                    http_context,
+                    plugins,
+                    session,
+                    request_headers,
+                    &ndc_query,
+                    &join_node.target_data_connector,
+                    execution_span_attribute,
+                    remote_alias.to_owned(),
+                    project_id,
+                ))
+            },
+        )
+        .await?
+        .as_latest_rowsets();
+
+    Ok(target_response)
+}
+
 fn map_remote_join_variable_value_to_target_connector(
     value: &serde_json::Value,
     value_type: &QualifiedTypeReference,
@@ -303,3 +471,90 @@ fn get_json_value_type(value: &serde_json::Value) -> &'static str {
         serde_json::Value::Object(_) => "object",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{batch_variable_sets, execute_concurrently};
+    use indexmap::IndexMap;
+    use pretty_assertions::assert_eq;
+    use std::num::NonZeroUsize;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn row_set(id: usize) -> ndc_models::RowSet {
+        ndc_models::RowSet {
+            aggregates: None,
+            rows: Some(vec![IndexMap::from([(
+                "id".into(),
+                ndc_models::RowFieldValue(serde_json::json!(id)),
+            )])]),
+            groups: None,
+        }
+    }
+
+    #[test]
+    fn test_batch_variable_sets() {
+        assert_eq!(
+            batch_variable_sets(vec![1, 2, 3, 4, 5], NonZeroUsize::new(2)),
+            vec![vec![1, 2], vec![3, 4], vec![5]]
+        );
+        assert_eq!(
+            batch_variable_sets(vec![1, 2, 3], None),
+            vec![vec![1, 2, 3]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batched_rowsets_are_returned_in_order() {
+        let variable_sets = (0..7).collect::<Vec<usize>>();
+        let batches = batch_variable_sets(variable_sets.clone(), NonZeroUsize::new(3));
+        let batch_count = batches.len();
+
+        // later batches return first, so the rowsets are only in order if the
+        // batches are put back in order
+        let batch_responses = execute_concurrently(
+            batches
+                .into_iter()
+                .enumerate()
+                .map(|(index, batch)| async move {
+                    let delay = u64::try_from(batch_count - index).unwrap() * 10;
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    Ok(batch.into_iter().map(row_set).collect::<Vec<_>>())
+                }),
+            batch_count,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            batch_responses.into_iter().flatten().collect::<Vec<_>>(),
+            variable_sets.into_iter().map(row_set).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_is_bounded() {
+        let in_flight = AtomicUsize::new(0);
+        let most_in_flight = AtomicUsize::new(0);
+
+        let results = execute_concurrently(
+            (0..6).map(|index| {
+                let in_flight = &in_flight;
+                let most_in_flight = &most_in_flight;
+                async move {
+                    let now_in_flight = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    most_in_flight.fetch_max(now_in_flight, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    Ok(index)
+                }
+            }),
+            2,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(most_in_flight.load(Ordering::SeqCst), 2);
+    }
+}
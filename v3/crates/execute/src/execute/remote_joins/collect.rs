@@ -105,7 +105,7 @@ fn collect_argument_from_rows(
                     ProcessResponseAs::Array { .. } | ProcessResponseAs::Object { .. } => {
                         collect_argument_from_row(row, join_fields, path, &mut arguments)?;
                     }
-                    ProcessResponseAs::Aggregates => {
+                    ProcessResponseAs::Aggregates | ProcessResponseAs::Groups => {
                         return Err(error::FieldInternalError::InternalGeneric {
                             description:
                                 "Unexpected aggregate response on the LHS of a remote join"
@@ -0,0 +1,395 @@
+//! Remote joins against data connectors without the `query.variables`
+//! capability.
+//!
+//! The RHS query of a join refers to the join values as variables. If the
+//! target data connector cannot accept variable sets, the query is executed
+//! once per variable set instead, with the variables replaced by their values.
+//! Each request returns a single rowset, so the rowsets line up with the
+//! variable sets exactly as they would for a foreach query. Like batches, the
+//! requests are bounded by `HttpContext::remote_join_concurrency`.
+
+use indexmap::IndexMap;
+use open_dds::types::DataConnectorArgumentName;
+use plan_types::{
+    Argument, ComparisonValue, Field, FieldsSelection, LocalFieldComparison, NdcFieldAlias,
+    NestedArray, NestedField, NestedObject, OrderByElement, OrderByTarget, QueryExecutionPlan,
+    QueryNode, RelationshipPathElement, ResolvedFilterExpression, VariableName,
+};
+use serde_json as json;
+use std::collections::BTreeMap;
+
+use crate::error::{FieldError, FieldInternalError};
+
+/// Replace the variables in the RHS query of a join with the values from one
+/// variable set, wherever they are used: in predicates, arguments and orderings,
+/// at the top level of the query or in the queries of nested relationships.
+pub(crate) fn substitute_variables(
+    query_execution_plan: &QueryExecutionPlan,
+    variables: &BTreeMap<VariableName, json::Value>,
+) -> Result<QueryExecutionPlan, FieldError> {
+    let mut query_execution_plan = query_execution_plan.clone();
+    query_execution_plan.query_node =
+        substitute_variables_in_query_node(query_execution_plan.query_node, variables)?;
+    query_execution_plan.arguments =
+        substitute_variables_in_arguments(query_execution_plan.arguments, variables)?;
+    query_execution_plan.variables = None;
+    Ok(query_execution_plan)
+}
+
+fn substitute_variables_in_query_node(
+    query_node: QueryNode,
+    variables: &BTreeMap<VariableName, json::Value>,
+) -> Result<QueryNode, FieldError> {
+    let QueryNode {
+        limit,
+        offset,
+        order_by,
+        predicate,
+        aggregates,
+        fields,
+        group_by,
+    } = query_node;
+    Ok(QueryNode {
+        limit,
+        offset,
+        order_by: order_by
+            .map(|order_by| {
+                order_by
+                    .into_iter()
+                    .map(|element| substitute_variables_in_order_by_element(element, variables))
+                    .collect::<Result<Vec<_>, FieldError>>()
+            })
+            .transpose()?,
+        predicate: predicate
+            .map(|predicate| substitute_variables_in_filter_expression(predicate, variables))
+            .transpose()?,
+        // aggregates and groups do not refer to variables
+        aggregates,
+        fields: fields
+            .map(|fields| {
+                Ok::<_, FieldError>(FieldsSelection {
+                    fields: substitute_variables_in_fields(fields.fields, variables)?,
+                })
+            })
+            .transpose()?,
+        group_by,
+    })
+}
+
+fn substitute_variables_in_fields(
+    fields: IndexMap<NdcFieldAlias, Field>,
+    variables: &BTreeMap<VariableName, json::Value>,
+) -> Result<IndexMap<NdcFieldAlias, Field>, FieldError> {
+    fields
+        .into_iter()
+        .map(|(alias, field)| Ok((alias, substitute_variables_in_field(field, variables)?)))
+        .collect()
+}
+
+fn substitute_variables_in_field(
+    field: Field,
+    variables: &BTreeMap<VariableName, json::Value>,
+) -> Result<Field, FieldError> {
+    Ok(match field {
+        Field::Column {
+            column,
+            fields,
+            arguments,
+        } => Field::Column {
+            column,
+            fields: fields
+                .map(|fields| substitute_variables_in_nested_field(fields, variables))
+                .transpose()?,
+            arguments: substitute_variables_in_arguments(arguments, variables)?,
+        },
+        Field::Relationship {
+            query_node,
+            relationship,
+            arguments,
+        } => Field::Relationship {
+            query_node: Box::new(substitute_variables_in_query_node(*query_node, variables)?),
+            relationship,
+            arguments: substitute_variables_in_arguments(arguments, variables)?,
+        },
+    })
+}
+
+fn substitute_variables_in_nested_field(
+    nested_field: NestedField,
+    variables: &BTreeMap<VariableName, json::Value>,
+) -> Result<NestedField, FieldError> {
+    Ok(match nested_field {
+        NestedField::Object(NestedObject { fields }) => NestedField::Object(NestedObject {
+            fields: substitute_variables_in_fields(fields, variables)?,
+        }),
+        NestedField::Array(NestedArray { fields }) => NestedField::Array(NestedArray {
+            fields: Box::new(substitute_variables_in_nested_field(*fields, variables)?),
+        }),
+    })
+}
+
+fn substitute_variables_in_order_by_element(
+    element: OrderByElement<ResolvedFilterExpression>,
+    variables: &BTreeMap<VariableName, json::Value>,
+) -> Result<OrderByElement<ResolvedFilterExpression>, FieldError> {
+    let OrderByElement {
+        order_direction,
+        target:
+            OrderByTarget::Column {
+                relationship_path,
+                name,
+                field_path,
+            },
+    } = element;
+    Ok(OrderByElement {
+        order_direction,
+        target: OrderByTarget::Column {
+            relationship_path: relationship_path
+                .into_iter()
+                .map(|path_element| {
+                    Ok(RelationshipPathElement {
+                        field_path: path_element.field_path,
+                        relationship_name: path_element.relationship_name,
+                        filter_predicate: path_element
+                            .filter_predicate
+                            .map(|predicate| {
+                                substitute_variables_in_filter_expression(predicate, variables)
+                            })
+                            .transpose()?,
+                    })
+                })
+                .collect::<Result<Vec<_>, FieldError>>()?,
+            name,
+            field_path,
+        },
+    })
+}
+
+fn substitute_variables_in_arguments(
+    arguments: BTreeMap<DataConnectorArgumentName, Argument>,
+    variables: &BTreeMap<VariableName, json::Value>,
+) -> Result<BTreeMap<DataConnectorArgumentName, Argument>, FieldError> {
+    arguments
+        .into_iter()
+        .map(|(argument_name, argument)| {
+            Ok((
+                argument_name,
+                substitute_variables_in_argument(argument, variables)?,
+            ))
+        })
+        .collect()
+}
+
+fn substitute_variables_in_argument(
+    argument: Argument,
+    variables: &BTreeMap<VariableName, json::Value>,
+) -> Result<Argument, FieldError> {
+    Ok(match argument {
+        Argument::Variable { name } => Argument::Literal {
+            value: get_variable_value(&name, variables)?,
+        },
+        Argument::BooleanExpression { predicate } => Argument::BooleanExpression {
+            predicate: substitute_variables_in_filter_expression(predicate, variables)?,
+        },
+        Argument::Literal { value } => Argument::Literal { value },
+    })
+}
+
+fn substitute_variables_in_filter_expression(
+    filter_expression: ResolvedFilterExpression,
+    variables: &BTreeMap<VariableName, json::Value>,
+) -> Result<ResolvedFilterExpression, FieldError> {
+    Ok(match filter_expression {
+        ResolvedFilterExpression::And { expressions } => ResolvedFilterExpression::And {
+            expressions: expressions
+                .into_iter()
+                .map(|expression| substitute_variables_in_filter_expression(expression, variables))
+                .collect::<Result<Vec<_>, FieldError>>()?,
+        },
+        ResolvedFilterExpression::Or { expressions } => ResolvedFilterExpression::Or {
+            expressions: expressions
+                .into_iter()
+                .map(|expression| substitute_variables_in_filter_expression(expression, variables))
+                .collect::<Result<Vec<_>, FieldError>>()?,
+        },
+        ResolvedFilterExpression::Not { expression } => ResolvedFilterExpression::Not {
+            expression: Box::new(substitute_variables_in_filter_expression(
+                *expression,
+                variables,
+            )?),
+        },
+        ResolvedFilterExpression::LocalFieldComparison(
+            LocalFieldComparison::BinaryComparison {
+                column,
+                operator,
+                value: ComparisonValue::Variable { name },
+            },
+        ) => {
+            ResolvedFilterExpression::LocalFieldComparison(LocalFieldComparison::BinaryComparison {
+                column,
+                operator,
+                value: ComparisonValue::Scalar {
+                    value: get_variable_value(&name, variables)?,
+                },
+            })
+        }
+        ResolvedFilterExpression::LocalNestedArray {
+            column,
+            field_path,
+            predicate,
+        } => ResolvedFilterExpression::LocalNestedArray {
+            column,
+            field_path,
+            predicate: Box::new(substitute_variables_in_filter_expression(
+                *predicate, variables,
+            )?),
+        },
+        ResolvedFilterExpression::LocalNestedScalarArray {
+            column,
+            field_path,
+            predicate,
+        } => ResolvedFilterExpression::LocalNestedScalarArray {
+            column,
+            field_path,
+            predicate: Box::new(substitute_variables_in_filter_expression(
+                *predicate, variables,
+            )?),
+        },
+        ResolvedFilterExpression::LocalRelationshipComparison {
+            field_path,
+            relationship,
+            predicate,
+        } => ResolvedFilterExpression::LocalRelationshipComparison {
+            field_path,
+            relationship,
+            predicate: Box::new(substitute_variables_in_filter_expression(
+                *predicate, variables,
+            )?),
+        },
+        expression @ (ResolvedFilterExpression::LocalFieldComparison(_)
+        | ResolvedFilterExpression::RemoteRelationshipComparison { .. }) => expression,
+    })
+}
+
+fn get_variable_value(
+    name: &VariableName,
+    variables: &BTreeMap<VariableName, json::Value>,
+) -> Result<json::Value, FieldError> {
+    variables.get(name).cloned().ok_or_else(|| {
+        FieldError::InternalError(FieldInternalError::InternalGeneric {
+            description: format!("variable {} not found in remote join variable set", name.0),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{substitute_variables_in_arguments, substitute_variables_in_query_node};
+    use indexmap::IndexMap;
+    use open_dds::data_connector::{DataConnectorColumnName, DataConnectorOperatorName};
+    use open_dds::types::DataConnectorArgumentName;
+    use plan_types::{
+        Argument, ComparisonTarget, ComparisonValue, Field, FieldsSelection, LocalFieldComparison,
+        NdcRelationshipName, QueryNode, ResolvedFilterExpression, VariableName,
+    };
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn query_node(
+        predicate: Option<ResolvedFilterExpression>,
+        fields: IndexMap<plan_types::NdcFieldAlias, Field>,
+    ) -> QueryNode {
+        QueryNode {
+            limit: None,
+            offset: None,
+            order_by: None,
+            predicate,
+            aggregates: None,
+            fields: Some(FieldsSelection { fields }),
+            group_by: None,
+        }
+    }
+
+    fn id_equals(value: ComparisonValue) -> ResolvedFilterExpression {
+        ResolvedFilterExpression::LocalFieldComparison(LocalFieldComparison::BinaryComparison {
+            column: ComparisonTarget::Column {
+                name: DataConnectorColumnName::from("id"),
+                field_path: vec![],
+            },
+            operator: DataConnectorOperatorName::from("_eq"),
+            value,
+        })
+    }
+
+    fn variables() -> BTreeMap<VariableName, serde_json::Value> {
+        BTreeMap::from([(VariableName("$field_id".to_string()), json!(1))])
+    }
+
+    fn variable() -> VariableName {
+        VariableName("$field_id".to_string())
+    }
+
+    // the query of a nested relationship, and the arguments of its fields, are rewritten as well as
+    // the top-level predicate
+    #[test]
+    fn test_nested_variables_are_substituted() {
+        let query = |value: ComparisonValue, argument: Argument| {
+            query_node(
+                Some(id_equals(value.clone())),
+                IndexMap::from([(
+                    "movies".into(),
+                    Field::Relationship {
+                        query_node: Box::new(query_node(
+                            Some(id_equals(value)),
+                            IndexMap::from([(
+                                "title".into(),
+                                Field::Column {
+                                    column: DataConnectorColumnName::from("title"),
+                                    fields: None,
+                                    arguments: BTreeMap::from([(
+                                        DataConnectorArgumentName::from("language"),
+                                        argument.clone(),
+                                    )]),
+                                },
+                            )]),
+                        )),
+                        relationship: NdcRelationshipName("movies".into()),
+                        arguments: BTreeMap::from([(
+                            DataConnectorArgumentName::from("actor_id"),
+                            argument,
+                        )]),
+                    },
+                )]),
+            )
+        };
+
+        let substituted = substitute_variables_in_query_node(
+            query(
+                ComparisonValue::Variable { name: variable() },
+                Argument::Variable { name: variable() },
+            ),
+            &variables(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            substituted,
+            query(
+                ComparisonValue::Scalar { value: json!(1) },
+                Argument::Literal { value: json!(1) },
+            )
+        );
+    }
+
+    #[test]
+    fn test_missing_variables_are_an_error() {
+        let arguments = BTreeMap::from([(
+            DataConnectorArgumentName::from("actor_id"),
+            Argument::Variable {
+                name: VariableName("$argument_actor_id".to_string()),
+            },
+        )]);
+        assert!(substitute_variables_in_arguments(arguments, &variables()).is_err());
+    }
+}
@@ -1,6 +1,8 @@
 pub mod client;
 pub mod migration;
 pub mod plugins;
+mod replicas;
+mod request_policy;
 pub mod types;
 use hasura_authn_core::Session;
 use metadata_resolve::LifecyclePluginConfigs;
@@ -16,6 +18,7 @@ pub use types::*;
 
 use std::borrow::Cow;
 use std::sync::Arc;
+use std::time::Instant;
 
 use http::HeaderMap;
 
@@ -109,6 +112,7 @@ pub async fn fetch_from_data_connector(
         Some(PreNdcRequestPluginResponse::NdcResponse(ndc_response)) => return Ok(ndc_response),
     };
 
+    let start = Instant::now();
     let response = tracer
         .in_span_async(
             "fetch_from_data_connector",
@@ -121,19 +125,35 @@ pub async fn fetch_from_data_connector(
                         project_id,
                         data_connector,
                     )?;
-                    let ndc_config = client::Configuration {
-                        base_path: data_connector.url.get_url(ast::OperationType::Query),
-                        // This is isn't expensive, reqwest::Client is behind an Arc
-                        client: http_context.client.clone(),
-                        headers,
-                        response_size_limit: http_context.ndc_response_size_limit,
-                    };
-                    client::query_post(ndc_config, query_request).await
+                    let failover_request_policy =
+                        replicas::failover_request_policy(data_connector.request_policy.as_ref());
+                    replicas::send_read(
+                        &http_context.client,
+                        data_connector,
+                        |base_path, can_fail_over| {
+                            let ndc_config = client::Configuration {
+                                base_path,
+                                // This is isn't expensive, reqwest::Client is behind an Arc
+                                client: http_context.client.clone(),
+                                headers: headers.clone(),
+                                response_size_limit: http_context.ndc_response_size_limit,
+                                request_policy: if can_fail_over {
+                                    failover_request_policy.as_ref()
+                                } else {
+                                    data_connector.request_policy.as_ref()
+                                },
+                            };
+                            client::query_post(ndc_config, query_request)
+                        },
+                    )
+                    .await
                     // .map_err(error::RequestError::from) // error::Error -> InternalError -> Error
                 })
             },
         )
-        .await?;
+        .await;
+    record_ndc_request_metrics(data_connector, "query", start, &response);
+    let response = response?;
 
     let response = execute_pre_ndc_query_response_plugins(
         &plugins.pre_ndc_response_plugins,
@@ -149,6 +169,22 @@ pub async fn fetch_from_data_connector(
     Ok(response)
 }
 
+/// Records the duration of a request to a data connector, and counts it as an error if it failed
+fn record_ndc_request_metrics<T>(
+    data_connector: &metadata_resolve::DataConnectorLink,
+    operation: &str,
+    start: Instant,
+    response: &Result<T, client::Error>,
+) {
+    let subgraph = data_connector.name.subgraph.to_string();
+    let name = data_connector.name.name.to_string();
+    let labels = [subgraph.as_str(), name.as_str(), operation];
+    metrics_util::NDC_REQUEST_DURATION.observe(&labels, start.elapsed());
+    if response.is_err() {
+        metrics_util::NDC_REQUEST_ERRORS.inc(&labels);
+    }
+}
+
 // This function appends engine-managed protocol headers to the HeaderMap defined
 // by the data_connector object.
 pub fn append_request_context_headers<'a>(
@@ -258,6 +294,7 @@ pub async fn fetch_from_data_connector_mutation(
         Some(PreNdcRequestPluginResponse::NdcResponse(ndc_response)) => return Ok(ndc_response),
     };
 
+    let start = Instant::now();
     let response = tracer
         .in_span_async(
             "fetch_from_data_connector_mutation",
@@ -276,12 +313,15 @@ pub async fn fetch_from_data_connector_mutation(
                         client: http_context.client.clone(),
                         headers,
                         response_size_limit: http_context.ndc_response_size_limit,
+                        request_policy: data_connector.request_policy.as_ref(),
                     };
                     client::mutation_post(ndc_config, query_request).await
                 })
             },
         )
-        .await?;
+        .await;
+    record_ndc_request_metrics(data_connector, "mutation", start, &response);
+    let response = response?;
 
     let response = execute_pre_ndc_mutation_response_plugins(
         &plugins.pre_ndc_response_plugins,
@@ -341,14 +381,28 @@ pub async fn fetch_from_data_connector_explain(
                         project_id,
                         data_connector,
                     )?;
-                    let ndc_config = client::Configuration {
-                        base_path: data_connector.url.get_url(ast::OperationType::Query),
-                        // This is isn't expensive, reqwest::Client is behind an Arc
-                        client: http_context.client.clone(),
-                        headers,
-                        response_size_limit: http_context.ndc_response_size_limit,
-                    };
-                    client::explain_query_post(ndc_config, query_request).await
+                    let failover_request_policy =
+                        replicas::failover_request_policy(data_connector.request_policy.as_ref());
+                    replicas::send_read(
+                        &http_context.client,
+                        data_connector,
+                        |base_path, can_fail_over| {
+                            let ndc_config = client::Configuration {
+                                base_path,
+                                // This is isn't expensive, reqwest::Client is behind an Arc
+                                client: http_context.client.clone(),
+                                headers: headers.clone(),
+                                response_size_limit: http_context.ndc_response_size_limit,
+                                request_policy: if can_fail_over {
+                                    failover_request_policy.as_ref()
+                                } else {
+                                    data_connector.request_policy.as_ref()
+                                },
+                            };
+                            client::explain_query_post(ndc_config, query_request)
+                        },
+                    )
+                    .await
                 })
             },
         )
@@ -421,6 +475,7 @@ pub async fn fetch_from_data_connector_mutation_explain(
                         client: http_context.client.clone(),
                         headers,
                         response_size_limit: http_context.ndc_response_size_limit,
+                        request_policy: data_connector.request_policy.as_ref(),
                     };
                     client::explain_mutation_post(ndc_config, query_request).await
                 })
@@ -470,6 +525,7 @@ pub async fn fetch_from_data_connector_insert_rel(
                         client: http_context.client.clone(),
                         headers,
                         response_size_limit: http_context.ndc_response_size_limit,
+                        request_policy: data_connector.request_policy.as_ref(),
                     };
                     client::mutation_relational_insert_post(ndc_config, insert_request).await
                 })
@@ -506,6 +562,7 @@ pub async fn fetch_from_data_connector_update_rel(
                         client: http_context.client.clone(),
                         headers,
                         response_size_limit: http_context.ndc_response_size_limit,
+                        request_policy: data_connector.request_policy.as_ref(),
                     };
                     client::mutation_relational_update_post(ndc_config, update_request).await
                 })
@@ -542,6 +599,7 @@ pub async fn fetch_from_data_connector_delete_rel(
                         client: http_context.client.clone(),
                         headers,
                         response_size_limit: http_context.ndc_response_size_limit,
+                        request_policy: data_connector.request_policy.as_ref(),
                     };
                     client::mutation_relational_delete_post(ndc_config, delete_request).await
                 })
@@ -11,6 +11,7 @@ use tracing_util::{SpanVisibility, Successful};
 use super::{
     NdcErrorResponse, NdcExplainResponse, NdcMutationRequest, NdcMutationResponse, NdcQueryRequest,
     NdcQueryResponse,
+    request_policy::{self, Idempotency, SendPolicy},
 };
 
 // Add the new type imports
@@ -49,6 +50,15 @@ pub enum Error {
     #[error("response received from connector is too large: {0}")]
     ResponseTooLarge(String),
 
+    #[error("request to connector timed out after {}ms", .timeout.as_millis())]
+    Timeout { timeout: std::time::Duration },
+
+    #[error(
+        "requests to connector are paused after repeated failures, and will resume in {}ms",
+        .retry_after.as_millis()
+    )]
+    CircuitOpen { retry_after: std::time::Duration },
+
     #[error("{0}")]
     Connector(ConnectorError),
 
@@ -73,6 +83,9 @@ impl tracing_util::TraceableError for Error {
                 tracing_util::ErrorVisibility::User
             }
 
+            // The connector being slow or unavailable is not an engine issue
+            Self::Timeout { .. } | Self::CircuitOpen { .. } => tracing_util::ErrorVisibility::User,
+
             // TODO some of these other cases seem like User errors also...
 
             // All other errors are internal
@@ -133,6 +146,45 @@ pub struct Configuration<'s> {
     pub client: reqwest::Client,
     pub headers: Cow<'s, HeaderMap<HeaderValue>>,
     pub response_size_limit: Option<usize>,
+    pub request_policy: Option<&'s metadata_resolve::RequestPolicy>,
+}
+
+impl<'s> Configuration<'s> {
+    fn send_policy(&self, idempotency: Idempotency) -> SendPolicy<'s> {
+        SendPolicy {
+            base_path: self.base_path,
+            request_policy: self.request_policy,
+            idempotency,
+        }
+    }
+}
+
+/// GET on /health endpoint
+///
+/// <https://hasura.github.io/ndc-spec/specification/health.html>
+pub(crate) async fn health_get(
+    client: &reqwest::Client,
+    base_path: &reqwest::Url,
+    headers: HeaderMap<HeaderValue>,
+    timeout: std::time::Duration,
+) -> Result<(), Error> {
+    let url = append_path(base_path, &["health"])?;
+    let response = client
+        .get(url)
+        .headers(headers)
+        .headers(tracing_util::get_trace_headers())
+        .timeout(timeout)
+        .send()
+        .await?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(Error::InvalidConnector(InvalidConnectorError {
+            status,
+            content: serde_json::Value::Null,
+        }))
+    }
 }
 
 /// POST on /query/explain endpoint
@@ -152,6 +204,7 @@ pub(crate) async fn explain_query_post(
                 Box::pin(async {
                     let url = append_path(configuration.base_path, &["query", "explain"])?;
                     let response_size_limit = configuration.response_size_limit;
+                    let send_policy = configuration.send_policy(Idempotency::Idempotent);
 
                     match query_request {
                         NdcQueryRequest::V01(req) => {
@@ -165,6 +218,7 @@ pub(crate) async fn explain_query_post(
                             let response = execute_request(
                                 request,
                                 response_size_limit,
+                                send_policy,
                                 NdcErrorResponse::V01,
                             )
                             .await?;
@@ -181,6 +235,7 @@ pub(crate) async fn explain_query_post(
                             let response = execute_request(
                                 request,
                                 response_size_limit,
+                                send_policy,
                                 NdcErrorResponse::V02,
                             )
                             .await?;
@@ -210,6 +265,7 @@ pub(crate) async fn explain_mutation_post(
                 Box::pin(async {
                     let url = append_path(configuration.base_path, &["mutation", "explain"])?;
                     let response_size_limit = configuration.response_size_limit;
+                    let send_policy = configuration.send_policy(Idempotency::Idempotent);
 
                     match mutation_request {
                         NdcMutationRequest::V01(req) => {
@@ -223,6 +279,7 @@ pub(crate) async fn explain_mutation_post(
                             let response = execute_request(
                                 request,
                                 response_size_limit,
+                                send_policy,
                                 NdcErrorResponse::V01,
                             )
                             .await?;
@@ -239,6 +296,7 @@ pub(crate) async fn explain_mutation_post(
                             let response = execute_request(
                                 request,
                                 response_size_limit,
+                                send_policy,
                                 NdcErrorResponse::V02,
                             )
                             .await?;
@@ -268,6 +326,7 @@ pub(crate) async fn mutation_post(
                 Box::pin(async {
                     let url = append_path(configuration.base_path, &["mutation"])?;
                     let response_size_limit = configuration.response_size_limit;
+                    let send_policy = configuration.send_policy(Idempotency::NonIdempotent);
 
                     match mutation_request {
                         NdcMutationRequest::V01(req) => {
@@ -281,6 +340,7 @@ pub(crate) async fn mutation_post(
                             let response = execute_request(
                                 request,
                                 response_size_limit,
+                                send_policy,
                                 NdcErrorResponse::V01,
                             )
                             .await?;
@@ -297,6 +357,7 @@ pub(crate) async fn mutation_post(
                             let response = execute_request(
                                 request,
                                 response_size_limit,
+                                send_policy,
                                 NdcErrorResponse::V02,
                             )
                             .await?;
@@ -326,6 +387,7 @@ pub(crate) async fn query_post(
                 Box::pin(async {
                     let url = append_path(configuration.base_path, &["query"])?;
                     let response_size_limit = configuration.response_size_limit;
+                    let send_policy = configuration.send_policy(Idempotency::Idempotent);
 
                     match query_request {
                         NdcQueryRequest::V01(req) => {
@@ -339,6 +401,7 @@ pub(crate) async fn query_post(
                             let response = execute_request(
                                 request,
                                 response_size_limit,
+                                send_policy,
                                 NdcErrorResponse::V01,
                             )
                             .await?;
@@ -355,6 +418,7 @@ pub(crate) async fn query_post(
                             let response = execute_request(
                                 request,
                                 response_size_limit,
+                                send_policy,
                                 NdcErrorResponse::V02,
                             )
                             .await?;
@@ -383,6 +447,7 @@ pub async fn query_relational_post(
                 Box::pin(async {
                     let url = append_path(configuration.base_path, &["query", "relational"])?;
                     let response_size_limit = configuration.response_size_limit;
+                    let send_policy = configuration.send_policy(Idempotency::Idempotent);
 
                     let request = construct_request(
                         configuration,
@@ -391,9 +456,13 @@ pub async fn query_relational_post(
                         url,
                         |r| r.json(request),
                     );
-                    let response =
-                        execute_request(request, response_size_limit, NdcErrorResponse::V02)
-                            .await?;
+                    let response = execute_request(
+                        request,
+                        response_size_limit,
+                        send_policy,
+                        NdcErrorResponse::V02,
+                    )
+                    .await?;
                     Ok(response)
                 })
             },
@@ -419,6 +488,7 @@ pub async fn mutation_relational_insert_post(
                 Box::pin(async {
                     let url = append_path(configuration.base_path, &["mutation", "rel", "insert"])?;
                     let response_size_limit = configuration.response_size_limit;
+                    let send_policy = configuration.send_policy(Idempotency::NonIdempotent);
 
                     let request = construct_request(
                         configuration,
@@ -427,9 +497,13 @@ pub async fn mutation_relational_insert_post(
                         url,
                         |r| r.json(request),
                     );
-                    let response =
-                        execute_request(request, response_size_limit, NdcErrorResponse::V02)
-                            .await?;
+                    let response = execute_request(
+                        request,
+                        response_size_limit,
+                        send_policy,
+                        NdcErrorResponse::V02,
+                    )
+                    .await?;
                     Ok(response)
                 })
             },
@@ -455,6 +529,7 @@ pub async fn mutation_relational_update_post(
                 Box::pin(async {
                     let url = append_path(configuration.base_path, &["mutation", "rel", "update"])?;
                     let response_size_limit = configuration.response_size_limit;
+                    let send_policy = configuration.send_policy(Idempotency::NonIdempotent);
 
                     let request = construct_request(
                         configuration,
@@ -463,9 +538,13 @@ pub async fn mutation_relational_update_post(
                         url,
                         |r| r.json(request),
                     );
-                    let response =
-                        execute_request(request, response_size_limit, NdcErrorResponse::V02)
-                            .await?;
+                    let response = execute_request(
+                        request,
+                        response_size_limit,
+                        send_policy,
+                        NdcErrorResponse::V02,
+                    )
+                    .await?;
                     Ok(response)
                 })
             },
@@ -491,6 +570,7 @@ pub async fn mutation_relational_delete_post(
                 Box::pin(async {
                     let url = append_path(configuration.base_path, &["mutation", "rel", "delete"])?;
                     let response_size_limit = configuration.response_size_limit;
+                    let send_policy = configuration.send_policy(Idempotency::NonIdempotent);
 
                     let request = construct_request(
                         configuration,
@@ -499,9 +579,13 @@ pub async fn mutation_relational_delete_post(
                         url,
                         |r| r.json(request),
                     );
-                    let response =
-                        execute_request(request, response_size_limit, NdcErrorResponse::V02)
-                            .await?;
+                    let response = execute_request(
+                        request,
+                        response_size_limit,
+                        send_policy,
+                        NdcErrorResponse::V02,
+                    )
+                    .await?;
                     Ok(response)
                 })
             },
@@ -648,6 +732,7 @@ fn construct_request(
 async fn execute_request<TResponse, TResponseError, F>(
     request: reqwest::RequestBuilder,
     response_size_limit: Option<usize>,
+    send_policy: SendPolicy<'_>,
     to_error: F,
 ) -> Result<TResponse, Error>
 where
@@ -665,11 +750,11 @@ where
             || {
                 Box::pin(async {
                     // We inject the trace headers here so they are a child of this span.
-                    request
-                        .headers(tracing_util::get_trace_headers())
-                        .send()
-                        .await
-                        .map_err(Error::from)
+                    request_policy::send(
+                        request.headers(tracing_util::get_trace_headers()),
+                        send_policy,
+                    )
+                    .await
                 })
             },
         )
@@ -0,0 +1,386 @@
+//! Spreading the requests that read from a data connector over its replicas, as set by the
+//! `readReplicaUrls` of a `DataConnectorLink`.
+//!
+//! A replica that a request couldn't reach is left out until its `/health` endpoint responds
+//! successfully, which is checked every `healthCheckIntervalMs` in the background. A request is
+//! only retried on the last replica it is sent to, so it is sent at most once to the others.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use lang_graphql::ast::common as ast;
+use metadata_resolve::{RequestPolicy, ResolvedDataConnectorUrl, ResolvedReadReplicaUrls};
+use open_dds::data_connector::ReplicaSelection;
+use reqwest::StatusCode;
+use tracing_util::{AttributeVisibility, set_attribute_on_active_span};
+
+use super::client::{self, ConnectorError, InvalidConnectorError};
+
+/// The replica sets of the data connectors that have read replicas, by their read URLs.
+static REPLICA_SETS: LazyLock<ReplicaSets> = LazyLock::new(ReplicaSets::default);
+
+/// Sends a request that reads to a data connector. If the data connector has read replicas, the
+/// request is sent to one of them, and to each of the others in turn while the replica it was
+/// sent to is unavailable.
+///
+/// `send` is told whether the request can still fail over to another replica, in which case it
+/// should be sent with the `failover_request_policy`.
+pub(crate) async fn send_read<'s, T, F, Fut>(
+    http_client: &reqwest::Client,
+    data_connector: &'s metadata_resolve::DataConnectorLink,
+    send: F,
+) -> Result<T, client::Error>
+where
+    F: Fn(&'s reqwest::Url, bool) -> Fut,
+    Fut: Future<Output = Result<T, client::Error>>,
+{
+    let ResolvedDataConnectorUrl::ReadReplicaUrls(urls) = &data_connector.url else {
+        return send(data_connector.url.get_url(ast::OperationType::Query), false).await;
+    };
+    send_to_replicas(http_client, &data_connector.headers.0, urls, send).await
+}
+
+/// The request policy to send a request with while it can fail over to another replica. It isn't
+/// retried, as sending it to the next replica is more likely to succeed.
+pub(crate) fn failover_request_policy(
+    request_policy: Option<&RequestPolicy>,
+) -> Option<RequestPolicy> {
+    request_policy.map(|request_policy| RequestPolicy {
+        retry: None,
+        ..request_policy.clone()
+    })
+}
+
+async fn send_to_replicas<'s, T, F, Fut>(
+    http_client: &reqwest::Client,
+    headers: &http::HeaderMap,
+    urls: &'s ResolvedReadReplicaUrls,
+    send: F,
+) -> Result<T, client::Error>
+where
+    F: Fn(&'s reqwest::Url, bool) -> Fut,
+    Fut: Future<Output = Result<T, client::Error>>,
+{
+    let replica_set = REPLICA_SETS.get(urls);
+
+    let (order, health_checks) = replica_set.order(urls.selection, Instant::now());
+    for replica in health_checks {
+        spawn_health_check(
+            http_client.clone(),
+            headers.clone(),
+            urls.read[replica].0.clone(),
+            urls.health_check_interval,
+            replica_set.clone(),
+            replica,
+        );
+    }
+
+    let mut last_error = None;
+    let replicas = order.len();
+    for (attempt, replica) in order.into_iter().enumerate() {
+        let url = &urls.read[replica].0;
+        set_attribute_on_active_span(AttributeVisibility::Default, "ndc.replica", url.to_string());
+        let start = Instant::now();
+        match send(url, attempt + 1 < replicas).await {
+            Err(error) if is_unavailable(&error) => {
+                replica_set
+                    .record_unavailable(replica, Instant::now() + urls.health_check_interval);
+                last_error = Some(error);
+            }
+            result => {
+                replica_set.record_response(replica, start.elapsed());
+                return result;
+            }
+        }
+    }
+    Err(last_error.expect("a data connector with read replicas has at least one"))
+}
+
+/// Whether a request failed because the replica it was sent to is unavailable, in which case it
+/// can be sent to another
+fn is_unavailable(error: &client::Error) -> bool {
+    match error {
+        client::Error::Reqwest(error) => error.is_connect() || error.is_timeout(),
+        client::Error::Timeout { .. } | client::Error::CircuitOpen { .. } => true,
+        client::Error::InvalidConnector(InvalidConnectorError { status, .. })
+        | client::Error::Connector(ConnectorError { status, .. }) => matches!(
+            *status,
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+        ),
+        _ => false,
+    }
+}
+
+/// Checks whether an unavailable replica has recovered, and puts it back into use if it has
+fn spawn_health_check(
+    http_client: reqwest::Client,
+    headers: http::HeaderMap,
+    url: reqwest::Url,
+    interval: Duration,
+    replica_set: Arc<ReplicaSet>,
+    replica: usize,
+) {
+    tokio::spawn(async move {
+        let healthy = client::health_get(&http_client, &url, headers, interval)
+            .await
+            .is_ok();
+        replica_set.record_health_check(replica, healthy, Instant::now() + interval);
+    });
+}
+
+#[derive(Default)]
+struct ReplicaSets(Mutex<HashMap<Vec<metadata_resolve::http::SerializableUrl>, Arc<ReplicaSet>>>);
+
+impl ReplicaSets {
+    fn get(&self, urls: &ResolvedReadReplicaUrls) -> Arc<ReplicaSet> {
+        let mut replica_sets = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(replica_set) = replica_sets.get(urls.read.as_slice()) {
+            return replica_set.clone();
+        }
+        let replica_set = Arc::new(ReplicaSet::new(urls.read.len()));
+        replica_sets.insert(urls.read.clone(), replica_set.clone());
+        replica_set
+    }
+}
+
+struct ReplicaSet {
+    /// The turn of the next request, for round-robin selection
+    next: AtomicUsize,
+    replicas: Vec<Mutex<Replica>>,
+}
+
+#[derive(Default)]
+struct Replica {
+    /// How long the replica has been taking to respond, averaged with more weight on recent
+    /// requests
+    latency: Option<Duration>,
+    /// Set while the replica is unavailable
+    unavailable: Option<Unavailable>,
+}
+
+struct Unavailable {
+    /// When the health of the replica is next checked
+    check_at: Instant,
+    /// Whether its health is being checked now
+    checking: bool,
+}
+
+/// How much weight the latest response has in the average latency of a replica
+const LATENCY_WEIGHT: f64 = 0.2;
+
+impl ReplicaSet {
+    fn new(replicas: usize) -> Self {
+        Self {
+            next: AtomicUsize::new(0),
+            replicas: (0..replicas).map(|_| Mutex::default()).collect(),
+        }
+    }
+
+    fn replica(&self, replica: usize) -> std::sync::MutexGuard<'_, Replica> {
+        self.replicas[replica]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The replicas to send a request to, in the order to try them in, and the unavailable
+    /// replicas whose health is due to be checked. The request is only sent to the available
+    /// replicas, unless there are none, when it is sent to all of them.
+    fn order(&self, selection: ReplicaSelection, now: Instant) -> (Vec<usize>, Vec<usize>) {
+        let mut available = Vec::new();
+        let mut health_checks = Vec::new();
+        for replica in 0..self.replicas.len() {
+            match &mut self.replica(replica).unavailable {
+                None => available.push(replica),
+                Some(unavailable) => {
+                    if !unavailable.checking && now >= unavailable.check_at {
+                        unavailable.checking = true;
+                        health_checks.push(replica);
+                    }
+                }
+            }
+        }
+        let mut order = if available.is_empty() {
+            (0..self.replicas.len()).collect()
+        } else {
+            available
+        };
+        match selection {
+            ReplicaSelection::RoundRobin => {
+                let turn = self.next.fetch_add(1, Ordering::Relaxed);
+                let len = order.len();
+                order.rotate_left(turn % len);
+            }
+            // replicas that haven't responded yet are tried first, to find out how fast they are
+            ReplicaSelection::LeastLatency => {
+                order.sort_by_key(|replica| self.replica(*replica).latency);
+            }
+        }
+        (order, health_checks)
+    }
+
+    fn record_response(&self, replica: usize, latency: Duration) {
+        let mut replica = self.replica(replica);
+        replica.latency = Some(match replica.latency {
+            None => latency,
+            Some(average) => {
+                average.mul_f64(1.0 - LATENCY_WEIGHT) + latency.mul_f64(LATENCY_WEIGHT)
+            }
+        });
+        replica.unavailable = None;
+    }
+
+    fn record_unavailable(&self, replica: usize, check_at: Instant) {
+        let mut replica = self.replica(replica);
+        let checking = replica
+            .unavailable
+            .as_ref()
+            .is_some_and(|unavailable| unavailable.checking);
+        replica.unavailable = Some(Unavailable { check_at, checking });
+    }
+
+    fn record_health_check(&self, replica: usize, healthy: bool, check_at: Instant) {
+        let mut replica = self.replica(replica);
+        replica.unavailable = if healthy {
+            None
+        } else {
+            Some(Unavailable {
+                check_at,
+                checking: false,
+            })
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata_resolve::http::SerializableUrl;
+    use std::sync::atomic::AtomicU32;
+
+    #[test]
+    fn test_round_robin() {
+        let replica_set = ReplicaSet::new(3);
+        let now = Instant::now();
+        let orders: Vec<Vec<usize>> = (0..4)
+            .map(|_| replica_set.order(ReplicaSelection::RoundRobin, now).0)
+            .collect();
+        assert_eq!(
+            orders,
+            vec![vec![0, 1, 2], vec![1, 2, 0], vec![2, 0, 1], vec![0, 1, 2]]
+        );
+    }
+
+    #[test]
+    fn test_least_latency() {
+        let replica_set = ReplicaSet::new(3);
+        let now = Instant::now();
+        replica_set.record_response(0, Duration::from_millis(50));
+        replica_set.record_response(2, Duration::from_millis(10));
+        assert_eq!(
+            replica_set.order(ReplicaSelection::LeastLatency, now).0,
+            vec![1, 2, 0]
+        );
+        replica_set.record_response(1, Duration::from_millis(30));
+        assert_eq!(
+            replica_set.order(ReplicaSelection::LeastLatency, now).0,
+            vec![2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn test_unavailable_replicas() {
+        let replica_set = ReplicaSet::new(2);
+        let now = Instant::now();
+        let check_at = now + Duration::from_secs(10);
+
+        // unavailable replicas are left out until their health is checked
+        replica_set.record_unavailable(0, check_at);
+        assert_eq!(
+            replica_set.order(ReplicaSelection::LeastLatency, now),
+            (vec![1], vec![])
+        );
+        assert_eq!(
+            replica_set.order(ReplicaSelection::LeastLatency, check_at),
+            (vec![1], vec![0])
+        );
+        // and the health of each is only checked once at a time
+        assert_eq!(
+            replica_set.order(ReplicaSelection::LeastLatency, check_at),
+            (vec![1], vec![])
+        );
+
+        // requests are sent to all replicas when none are available
+        replica_set.record_unavailable(1, check_at);
+        assert_eq!(
+            replica_set.order(ReplicaSelection::LeastLatency, now).0,
+            vec![0, 1]
+        );
+
+        // replicas are used again once they are found to be healthy
+        replica_set.record_health_check(0, true, check_at);
+        assert_eq!(
+            replica_set.order(ReplicaSelection::LeastLatency, now).0,
+            vec![0]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_to_replicas_fails_over() {
+        // nothing listens on the port of a listener that has been dropped
+        let refused = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let mut server = mockito::Server::new_async().await;
+        let available = server
+            .mock("POST", "/query")
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+        let urls = ResolvedReadReplicaUrls {
+            read: vec![
+                SerializableUrl::new(&refused).unwrap(),
+                SerializableUrl::new(&server.url()).unwrap(),
+            ],
+            write: SerializableUrl::new(&server.url()).unwrap(),
+            selection: ReplicaSelection::RoundRobin,
+            health_check_interval: Duration::from_secs(60),
+        };
+
+        let client = reqwest::Client::new();
+        let attempts = AtomicU32::new(0);
+        let can_fail_over = Mutex::new(Vec::new());
+        let status = send_to_replicas(&client, &http::HeaderMap::new(), &urls, |url, failover| {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            can_fail_over.lock().unwrap().push(failover);
+            let request = client.post(url.join("query").unwrap());
+            async move {
+                request
+                    .send()
+                    .await
+                    .map(|response| response.status())
+                    .map_err(client::Error::from)
+            }
+        })
+        .await
+        .unwrap();
+
+        // the request is sent to the replica that refused it first, and fails over to the other
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+        assert_eq!(*can_fail_over.lock().unwrap(), vec![true, false]);
+        available.assert_async().await;
+
+        // which is the only replica used while the first is unavailable
+        assert_eq!(
+            REPLICA_SETS
+                .get(&urls)
+                .order(ReplicaSelection::RoundRobin, Instant::now())
+                .0,
+            vec![1]
+        );
+    }
+}
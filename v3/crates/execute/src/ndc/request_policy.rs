@@ -0,0 +1,392 @@
+//! Timeouts, retries and circuit breaking for requests to data connectors, as set by the
+//! `requestPolicy` of a `DataConnectorLink`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use metadata_resolve::{CircuitBreakerPolicy, RequestPolicy, RetryPolicy};
+use rand::{RngExt, rng};
+use reqwest::StatusCode;
+use tracing_util::{AttributeVisibility, set_attribute_on_active_span};
+
+use super::client::Error;
+
+/// Whether a request can be sent to a data connector more than once without changing its
+/// outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Idempotency {
+    Idempotent,
+    NonIdempotent,
+}
+
+/// The request policy to send a request to a data connector with
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SendPolicy<'s> {
+    /// The URL of the data connector, which its circuit breaker is kept by
+    pub base_path: &'s reqwest::Url,
+    pub request_policy: Option<&'s RequestPolicy>,
+    pub idempotency: Idempotency,
+}
+
+/// The circuit breakers of the data connectors that have one, by URL.
+static CIRCUIT_BREAKERS: LazyLock<CircuitBreakers> = LazyLock::new(CircuitBreakers::default);
+
+/// Sends a request to a data connector. If the data connector has a request policy, the request
+/// is timed out, rejected straight away while the circuit breaker of the data connector is open,
+/// and retried if it is idempotent and fails in a way that sending it again might fix.
+pub(crate) async fn send(
+    request: reqwest::RequestBuilder,
+    send_policy: SendPolicy<'_>,
+) -> Result<reqwest::Response, Error> {
+    let Some(request_policy) = send_policy.request_policy else {
+        return request.send().await.map_err(Error::from);
+    };
+    let mut request = match request_policy.timeout {
+        Some(timeout) => request.timeout(timeout),
+        None => request,
+    };
+    let circuit_breaker = request_policy
+        .circuit_breaker
+        .as_ref()
+        .map(|policy| (CIRCUIT_BREAKERS.get(send_policy.base_path), policy));
+    let retry = match send_policy.idempotency {
+        Idempotency::Idempotent => request_policy.retry.as_ref(),
+        Idempotency::NonIdempotent => None,
+    };
+
+    let mut retries = 0;
+    loop {
+        // requests with streamed bodies can't be cloned, and so are never retried
+        let next_request = match retry {
+            Some(retry) if retries < retry.max_retries => request.try_clone(),
+            _ => None,
+        };
+
+        let permit = match &circuit_breaker {
+            Some((circuit_breaker, policy)) => {
+                match circuit_breaker.acquire(policy, Instant::now()) {
+                    Ok(permit) => Some(permit),
+                    Err(error) => {
+                        set_attribute_on_active_span(
+                            AttributeVisibility::Default,
+                            "ndc.circuit_breaker",
+                            "open",
+                        );
+                        return Err(error);
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let result = request.send().await;
+
+        if let (Some((circuit_breaker, policy)), Some(permit)) = (&circuit_breaker, permit) {
+            circuit_breaker.record(policy, permit, is_failure(&result), Instant::now());
+        }
+
+        match (retry, next_request) {
+            (Some(retry), Some(next_request)) if is_retryable(&result) => {
+                tokio::time::sleep(backoff(retry, retries)).await;
+                retries += 1;
+                set_attribute_on_active_span(
+                    AttributeVisibility::Default,
+                    "ndc.retries",
+                    i64::from(retries),
+                );
+                request = next_request;
+            }
+            _ => {
+                return result.map_err(|error| match request_policy.timeout {
+                    Some(timeout) if error.is_timeout() => {
+                        set_attribute_on_active_span(
+                            AttributeVisibility::Default,
+                            "ndc.timeout_ms",
+                            i64::try_from(timeout.as_millis()).unwrap_or(i64::MAX),
+                        );
+                        Error::Timeout { timeout }
+                    }
+                    _ => Error::from(error),
+                });
+            }
+        }
+    }
+}
+
+/// Whether the data connector failed to handle a request, as opposed to handling it and
+/// rejecting it
+fn is_failure(result: &Result<reqwest::Response, reqwest::Error>) -> bool {
+    match result {
+        Ok(response) => response.status().is_server_error(),
+        Err(error) => !error.is_builder(),
+    }
+}
+
+/// Whether sending a request again might succeed where it just failed
+fn is_retryable(result: &Result<reqwest::Response, reqwest::Error>) -> bool {
+    match result {
+        Ok(response) => matches!(
+            response.status(),
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        ),
+        Err(error) => error.is_connect() || error.is_timeout(),
+    }
+}
+
+/// How long to wait before retrying a request that has been retried `retries` times already.
+/// The backoff doubles with each retry up to the maximum, and a random half of it is waited for
+/// so that requests that failed together don't all retry together.
+fn backoff(retry: &RetryPolicy, retries: u32) -> Duration {
+    let backoff = retry
+        .initial_backoff
+        .saturating_mul(2_u32.saturating_pow(retries))
+        .min(retry.max_backoff);
+    let half = backoff / 2;
+    half + half.mul_f64(rng().random_range(0.0..=1.0))
+}
+
+#[derive(Default)]
+struct CircuitBreakers(Mutex<HashMap<reqwest::Url, Arc<CircuitBreaker>>>);
+
+impl CircuitBreakers {
+    fn get(&self, base_path: &reqwest::Url) -> Arc<CircuitBreaker> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(base_path.clone())
+            .or_default()
+            .clone()
+    }
+}
+
+#[derive(Default)]
+struct CircuitBreaker {
+    state: Mutex<CircuitState>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CircuitState {
+    /// Requests are sent, and the number of them that failed in a row is counted
+    Closed { failures: u32 },
+    /// Requests are rejected until `until`
+    Open { until: Instant },
+    /// One request was let through at `since` to check whether the data connector has recovered,
+    /// and others are rejected until it completes
+    HalfOpen { since: Instant },
+}
+
+/// How a request was let through a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Permit {
+    /// The circuit was closed
+    Closed,
+    /// The request was let through the circuit at `since` to check whether the data connector
+    /// has recovered
+    Probe { since: Instant },
+}
+
+impl Default for CircuitState {
+    fn default() -> Self {
+        CircuitState::Closed { failures: 0 }
+    }
+}
+
+impl CircuitBreaker {
+    /// Whether a request can be sent now
+    fn acquire(&self, policy: &CircuitBreakerPolicy, now: Instant) -> Result<Permit, Error> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let reopens_at = match *state {
+            CircuitState::Closed { .. } => return Ok(Permit::Closed),
+            CircuitState::Open { until } => until,
+            // the request let through may never complete, if it was cancelled, so another is let
+            // through if it has taken too long
+            CircuitState::HalfOpen { since } => since + policy.reset_timeout,
+        };
+        if now >= reopens_at {
+            *state = CircuitState::HalfOpen { since: now };
+            Ok(Permit::Probe { since: now })
+        } else {
+            Err(Error::CircuitOpen {
+                retry_after: reopens_at - now,
+            })
+        }
+    }
+
+    /// Records whether a request that was let through failed. Once the circuit has opened, only
+    /// the request let through to check on the data connector can close it or open it again:
+    /// requests sent before it opened may complete at any time after.
+    fn record(&self, policy: &CircuitBreakerPolicy, permit: Permit, failed: bool, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        *state = match (*state, failed) {
+            (CircuitState::HalfOpen { since }, false) if permit == Permit::Probe { since } => {
+                CircuitState::Closed { failures: 0 }
+            }
+            (CircuitState::HalfOpen { since }, true) if permit == Permit::Probe { since } => {
+                CircuitState::Open {
+                    until: now + policy.reset_timeout,
+                }
+            }
+            (state @ (CircuitState::Open { .. } | CircuitState::HalfOpen { .. }), _) => state,
+            (CircuitState::Closed { .. }, false) => CircuitState::Closed { failures: 0 },
+            (CircuitState::Closed { failures }, true)
+                if failures + 1 < policy.failure_threshold =>
+            {
+                CircuitState::Closed {
+                    failures: failures + 1,
+                }
+            }
+            (CircuitState::Closed { .. }, true) => CircuitState::Open {
+                until: now + policy.reset_timeout,
+            },
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circuit_breaker_policy() -> CircuitBreakerPolicy {
+        CircuitBreakerPolicy {
+            failure_threshold: 2,
+            reset_timeout: Duration::from_secs(30),
+        }
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        let policy = circuit_breaker_policy();
+        let circuit_breaker = CircuitBreaker::default();
+        let now = Instant::now();
+
+        // a success resets the count of failures
+        circuit_breaker.record(&policy, Permit::Closed, true, now);
+        circuit_breaker.record(&policy, Permit::Closed, false, now);
+        circuit_breaker.record(&policy, Permit::Closed, true, now);
+        assert_eq!(
+            circuit_breaker.acquire(&policy, now).unwrap(),
+            Permit::Closed
+        );
+
+        // the circuit opens after `failure_threshold` failures in a row
+        circuit_breaker.record(&policy, Permit::Closed, true, now);
+        assert!(matches!(
+            circuit_breaker.acquire(&policy, now + Duration::from_secs(10)),
+            Err(Error::CircuitOpen { retry_after }) if retry_after == Duration::from_secs(20)
+        ));
+
+        // one request is let through once the reset timeout has passed
+        let reset_at = now + policy.reset_timeout;
+        let probe = circuit_breaker.acquire(&policy, reset_at).unwrap();
+        assert_eq!(probe, Permit::Probe { since: reset_at });
+        assert!(circuit_breaker.acquire(&policy, reset_at).is_err());
+
+        // and the circuit opens again if it fails, or closes if it succeeds
+        circuit_breaker.record(&policy, probe, true, reset_at);
+        assert!(circuit_breaker.acquire(&policy, reset_at).is_err());
+        let reset_at = reset_at + policy.reset_timeout;
+        let probe = circuit_breaker.acquire(&policy, reset_at).unwrap();
+        circuit_breaker.record(&policy, probe, false, reset_at);
+        assert_eq!(
+            circuit_breaker.acquire(&policy, reset_at).unwrap(),
+            Permit::Closed
+        );
+        assert_eq!(
+            circuit_breaker.acquire(&policy, reset_at).unwrap(),
+            Permit::Closed
+        );
+    }
+
+    #[test]
+    fn test_circuit_breaker_ignores_late_results() {
+        let policy = circuit_breaker_policy();
+        let circuit_breaker = CircuitBreaker::default();
+        let now = Instant::now();
+        circuit_breaker.record(&policy, Permit::Closed, true, now);
+        circuit_breaker.record(&policy, Permit::Closed, true, now);
+
+        // a request sent before the circuit opened doesn't close it when it succeeds
+        circuit_breaker.record(&policy, Permit::Closed, false, now);
+        assert!(circuit_breaker.acquire(&policy, now).is_err());
+
+        // nor does it decide the probe's outcome while the circuit is half-open
+        let reset_at = now + policy.reset_timeout;
+        let probe = circuit_breaker.acquire(&policy, reset_at).unwrap();
+        circuit_breaker.record(&policy, Permit::Closed, false, reset_at);
+        circuit_breaker.record(&policy, Permit::Closed, true, reset_at);
+        assert!(circuit_breaker.acquire(&policy, reset_at).is_err());
+
+        // and neither does a probe that was given up on, once another was let through
+        let retry_at = reset_at + policy.reset_timeout;
+        let next_probe = circuit_breaker.acquire(&policy, retry_at).unwrap();
+        circuit_breaker.record(&policy, probe, false, retry_at);
+        assert!(circuit_breaker.acquire(&policy, retry_at).is_err());
+        circuit_breaker.record(&policy, next_probe, false, retry_at);
+        assert_eq!(
+            circuit_breaker.acquire(&policy, retry_at).unwrap(),
+            Permit::Closed
+        );
+    }
+
+    #[test]
+    fn test_backoff() {
+        let retry = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+        };
+        for (retries, max) in [(0, 100), (1, 200), (2, 400), (3, 800), (4, 1000), (9, 1000)] {
+            let backoff = backoff(&retry, retries);
+            assert!(
+                backoff >= Duration::from_millis(max / 2) && backoff <= Duration::from_millis(max),
+                "backoff {backoff:?} after {retries} retries"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_and_circuit_breaker() {
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server
+            .mock("POST", "/query")
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let base_path = reqwest::Url::parse(&server.url()).unwrap();
+        let request_policy = RequestPolicy {
+            timeout: None,
+            retry: Some(RetryPolicy {
+                max_retries: 3,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+            }),
+            circuit_breaker: Some(circuit_breaker_policy()),
+        };
+        let client = reqwest::Client::new();
+        let request = || client.post(base_path.join("query").unwrap());
+        let send_policy = |idempotency| SendPolicy {
+            base_path: &base_path,
+            request_policy: Some(&request_policy),
+            idempotency,
+        };
+
+        // the query is retried until the circuit breaker opens, and the last response returned
+        let response = send(request(), send_policy(Idempotency::Idempotent))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // then requests are not sent at all
+        assert!(matches!(
+            send(request(), send_policy(Idempotency::NonIdempotent)).await,
+            Err(Error::CircuitOpen { .. })
+        ));
+        unavailable.assert_async().await;
+    }
+}
@@ -0,0 +1,129 @@
+//! Restricting roles to the operations in the allow-list of the GraphQL config.
+//!
+//! A request from a restricted role must name its operation, and its query must be the document
+//! the allow-list gives for that name. Documents are compared token by token, so whitespace,
+//! commas and comments can differ.
+
+use hasura_authn_core::Session;
+use lang_graphql::ast::common as ast;
+use lang_graphql::ast::executable::ExecutableDocument;
+use lang_graphql::http::RawRequest;
+use lang_graphql::lexer::{Lexer, Token};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AllowListError {
+    #[error("only named operations from the allow-list can be executed")]
+    UnnamedOperation,
+    #[error("operation {0} is not in the allow-list")]
+    OperationNotAllowed(ast::Name),
+}
+
+impl AllowListError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AllowListError::UnnamedOperation | AllowListError::OperationNotAllowed(_) => {
+                "OPERATION_NOT_ALLOWED"
+            }
+        }
+    }
+}
+
+/// Checks that a request from a role restricted by the allow-list executes an allowed operation.
+pub fn check_allow_list(
+    allow_list: Option<&metadata_resolve::AllowList>,
+    session: &Session,
+    raw_request: &RawRequest,
+    document: &ExecutableDocument,
+) -> Result<(), AllowListError> {
+    let Some(allow_list) = allow_list.filter(|allow_list| allow_list.roles.contains(&session.role))
+    else {
+        return Ok(());
+    };
+    let Some(operation_name) =
+        crate::steps::selected_operation(document, raw_request.operation_name.as_ref())
+            .and_then(|operation| operation.name.as_ref())
+    else {
+        return Err(AllowListError::UnnamedOperation);
+    };
+    let operation_name = &operation_name.item;
+    match allow_list.operations.get(operation_name) {
+        Some(allowed_query) if tokens(allowed_query) == tokens(&raw_request.query) => Ok(()),
+        _ => Err(AllowListError::OperationNotAllowed(operation_name.clone())),
+    }
+}
+
+/// The tokens of a document, or `None` if it can't be tokenized.
+fn tokens(document: &str) -> Option<Vec<Token>> {
+    let mut lexer = Lexer::new(document);
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.read_next_token() {
+        tokens.push(token.ok()?.item);
+    }
+    Some(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+    use hasura_authn_core::{Identity, Role};
+    use lang_graphql::mk_name;
+
+    fn session(role: &str) -> Session {
+        Identity::admin(Role::new(role))
+            .get_role_authorization(None)
+            .unwrap()
+            .build_session(BTreeMap::new())
+    }
+
+    fn check(role: &str, query: &str, operation_name: Option<&str>) -> Result<(), String> {
+        let allow_list = metadata_resolve::AllowList {
+            roles: BTreeSet::from([Role::new("user")]),
+            operations: BTreeMap::from([(
+                mk_name!("Articles"),
+                "query Articles { articles { id title } }".to_string(),
+            )]),
+        };
+        let raw_request = RawRequest {
+            operation_name: operation_name.map(|name| ast::Name::new(name).unwrap()),
+            query: query.to_string(),
+            variables: None,
+            extensions: None,
+        };
+        let document = lang_graphql::parser::Parser::new(query)
+            .parse_executable_document()
+            .unwrap();
+        check_allow_list(Some(&allow_list), &session(role), &raw_request, &document)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_check_allow_list() {
+        assert_eq!(
+            check(
+                "user",
+                "# articles\nquery Articles {\n  articles { id, title }\n}",
+                None
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check("user", "query Articles { articles { id } }", None),
+            Err("operation Articles is not in the allow-list".to_string())
+        );
+        assert_eq!(
+            check("user", "query { articles { id title } }", None),
+            Err(AllowListError::UnnamedOperation.to_string())
+        );
+        assert_eq!(
+            check("user", "query Authors { authors { id } }", Some("Authors")),
+            Err("operation Authors is not in the allow-list".to_string())
+        );
+        // roles not in the allow-list are not restricted
+        assert_eq!(
+            check("admin", "query Authors { authors { id } }", None),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Caching responses to query operations.
+//!
+//! A query operation opts in to caching with the `@cached` directive, optionally giving the
+//! number of seconds to keep its response for:
+//!
+//! ```graphql
+//! query ArticleCounts @cached(ttl: 120) { ... }
+//! ```
+//!
+//! Responses are keyed on the query text, the operation name, the variables, the role, and the
+//! session variables and request headers the metadata says can affect the response (see
+//! [`key`]). Only responses without errors or response headers are cached. Where responses are
+//! kept is up to a [`CacheBackend`].
+//!
+//! Caching is opted into per operation only: there is no per-model cache policy in the metadata,
+//! so a model's responses are only cached by operations that ask for it.
+
+mod key;
+mod memory;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::HeaderMap;
+use blake2::{Blake2b, Digest};
+use hasura_authn_core::Session;
+use indexmap::IndexMap;
+use lang_graphql as gql;
+use lang_graphql::ast::common as ast;
+use lang_graphql::ast::executable::ExecutableDocument;
+use lang_graphql::ast::value::{SimpleValue, Value};
+
+pub use key::KeyInputs;
+pub use memory::InMemoryCache;
+
+/// The time to live of a response cached with a `@cached` directive that does not give one.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+/// The longest time to live a `@cached` directive can give.
+pub const MAX_TTL: Duration = Duration::from_secs(3600);
+
+const CACHED_DIRECTIVE: &str = "cached";
+const TTL_ARGUMENT: &str = "ttl";
+
+/// Identifies a cached response.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey(pub [u8; 64]);
+
+/// The data of a response without errors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedResponse {
+    pub data: IndexMap<ast::Alias, serde_json::Value>,
+}
+
+impl CachedResponse {
+    fn from_response(response: &gql::http::Response) -> Option<Self> {
+        // headers set by commands are not kept, so responses with them are not cached
+        match (&response.data, &response.errors) {
+            (Some(data), None) if response.headers.is_empty() => {
+                Some(CachedResponse { data: data.clone() })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Where cached responses are kept.
+#[async_trait::async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// The response cached for `key`, if it has not expired.
+    async fn get(&self, key: &CacheKey) -> Option<CachedResponse>;
+
+    /// Cache `response` for `key` for `ttl`.
+    async fn set(&self, key: CacheKey, response: CachedResponse, ttl: Duration);
+}
+
+/// A response cache for a particular metadata.
+pub struct ResponseCache {
+    backend: Arc<dyn CacheBackend>,
+    key_inputs: key::KeyInputs,
+    metadata_hash: [u8; 64],
+}
+
+impl ResponseCache {
+    /// Fails if the metadata cannot be serialized, as its hash keeps responses cached for other
+    /// metadata from being served.
+    pub fn new(
+        backend: Arc<dyn CacheBackend>,
+        metadata: &metadata_resolve::Metadata,
+    ) -> Result<Self, serde_json::Error> {
+        let mut hasher = Blake2b::new();
+        hasher.update(serde_json::to_vec(metadata)?);
+        Ok(ResponseCache {
+            backend,
+            key_inputs: key::KeyInputs::new(metadata),
+            metadata_hash: hasher.finalize().into(),
+        })
+    }
+
+    /// A cache for new metadata, keeping responses in the same backend. Responses cached for
+    /// other metadata are never served.
+    pub fn for_metadata(
+        &self,
+        metadata: &metadata_resolve::Metadata,
+    ) -> Result<Self, serde_json::Error> {
+        ResponseCache::new(self.backend.clone(), metadata)
+    }
+
+    /// The key of the response to a request, or `None` if the request cannot be serialized, in
+    /// which case its response is not cached.
+    fn key(
+        &self,
+        raw_request: &gql::http::RawRequest,
+        session: &Session,
+        request_headers: &HeaderMap,
+    ) -> Option<CacheKey> {
+        let (session_variables, headers) = self.key_inputs.collect(session, request_headers);
+        let request =
+            serde_json::to_vec(&(raw_request, &session.role, session_variables, headers)).ok()?;
+        let mut hasher = Blake2b::new();
+        hasher.update(self.metadata_hash);
+        hasher.update(&request);
+        Some(CacheKey(hasher.finalize().into()))
+    }
+}
+
+/// A cacheable request, with the key and time to live of its response.
+pub(crate) struct CacheEntry<'a> {
+    cache: &'a ResponseCache,
+    key: CacheKey,
+    ttl: Duration,
+}
+
+impl<'a> CacheEntry<'a> {
+    /// Returns the cache entry for a request, if it is a query operation with a `@cached`
+    /// directive and a key can be computed for it.
+    pub(crate) fn new(
+        cache: &'a ResponseCache,
+        document: &ExecutableDocument,
+        raw_request: &gql::http::RawRequest,
+        session: &Session,
+        request_headers: &HeaderMap,
+    ) -> Result<Option<Self>, CacheDirectiveError> {
+        let Some(ttl) = cache_ttl(document, raw_request.operation_name.as_ref())? else {
+            return Ok(None);
+        };
+        Ok(cache
+            .key(raw_request, session, request_headers)
+            .map(|key| CacheEntry { cache, key, ttl }))
+    }
+
+    pub(crate) async fn get(&self) -> Option<gql::http::Response> {
+        let cached = self.cache.backend.get(&self.key).await;
+        let result = if cached.is_some() { "hit" } else { "miss" };
+        metrics_util::RESPONSE_CACHE_REQUESTS.inc(&[result]);
+        cached.map(|cached| gql::http::Response::ok(cached.data))
+    }
+
+    pub(crate) async fn set(self, response: &gql::http::Response) {
+        if let Some(cached) = CachedResponse::from_response(response) {
+            self.cache.backend.set(self.key, cached, self.ttl).await;
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CacheDirectiveError {
+    #[error("the @cached directive may only be given once")]
+    DuplicateDirective,
+    #[error("unexpected argument {0} to the @cached directive")]
+    UnexpectedArgument(ast::Name),
+    #[error(
+        "the ttl argument to the @cached directive must be a positive integer of at most {} seconds",
+        MAX_TTL.as_secs()
+    )]
+    InvalidTtl,
+}
+
+/// The time to live given by the `@cached` directive of the operation to execute, if it is a
+/// query.
+fn cache_ttl(
+    document: &ExecutableDocument,
+    operation_name: Option<&ast::Name>,
+) -> Result<Option<Duration>, CacheDirectiveError> {
+    let Some(operation) = crate::steps::selected_operation(document, operation_name)
+        .filter(|operation| operation.ty == ast::OperationType::Query)
+    else {
+        return Ok(None);
+    };
+
+    let mut directives = operation
+        .directives
+        .iter()
+        .filter(|directive| directive.item.name.item.as_str() == CACHED_DIRECTIVE);
+    let Some(directive) = directives.next() else {
+        return Ok(None);
+    };
+    if directives.next().is_some() {
+        return Err(CacheDirectiveError::DuplicateDirective);
+    }
+
+    let mut ttl = DEFAULT_TTL;
+    for argument in directive
+        .item
+        .arguments
+        .iter()
+        .flat_map(|arguments| &arguments.item)
+    {
+        let argument = &argument.item;
+        if argument.key.item.as_str() != TTL_ARGUMENT {
+            return Err(CacheDirectiveError::UnexpectedArgument(
+                argument.key.item.clone(),
+            ));
+        }
+        ttl = match argument.value.item {
+            Value::SimpleValue(SimpleValue::Integer(seconds)) => u64::try_from(seconds)
+                .ok()
+                .map(Duration::from_secs)
+                .filter(|ttl| !ttl.is_zero() && *ttl <= MAX_TTL)
+                .ok_or(CacheDirectiveError::InvalidTtl)?,
+            _ => return Err(CacheDirectiveError::InvalidTtl),
+        };
+    }
+    Ok(Some(ttl))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ttl(query: &str, operation_name: Option<&str>) -> Result<Option<Duration>, String> {
+        let document = gql::parser::Parser::new(query)
+            .parse_executable_document()
+            .unwrap();
+        let operation_name = operation_name.map(|name| ast::Name::new(name).unwrap());
+        cache_ttl(&document, operation_name.as_ref()).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_cache_ttl() {
+        assert_eq!(ttl("query { a }", None), Ok(None));
+        assert_eq!(ttl("query @cached { a }", None), Ok(Some(DEFAULT_TTL)));
+        assert_eq!(
+            ttl("query @cached(ttl: 5) { a }", None),
+            Ok(Some(Duration::from_secs(5)))
+        );
+        assert_eq!(
+            ttl("query A @cached(ttl: 5) { a } query B { b }", Some("A")),
+            Ok(Some(Duration::from_secs(5)))
+        );
+        assert_eq!(
+            ttl("query A @cached(ttl: 5) { a } query B { b }", Some("B")),
+            Ok(None)
+        );
+        assert_eq!(ttl("mutation @cached { a }", None), Ok(None));
+        assert_eq!(
+            ttl("query @cached(ttl: 0) { a }", None),
+            Err(CacheDirectiveError::InvalidTtl.to_string())
+        );
+        assert_eq!(
+            ttl("query @cached(ttl: $ttl) { a }", None),
+            Err(CacheDirectiveError::InvalidTtl.to_string())
+        );
+        assert_eq!(
+            ttl("query @cached(refresh: true) { a }", None),
+            Err("unexpected argument refresh to the @cached directive".to_string())
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! Working out which session variables and request headers a response can depend on.
+//!
+//! Session variables reach a data connector through the permissions of the session's role
+//! (model filters, argument presets and field presets) and through data connector argument
+//! presets, and request headers reach it when an argument preset forwards them. Two requests
+//! for the same role that agree on those session variables and headers get the same response,
+//! so only they are part of a cache key. Authorization rules and pre-NDC plugins can depend on
+//! any session variable or header, so when they are used every one is part of the key.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use axum::http::{HeaderMap, HeaderName};
+use hasura_authn_core::{Role, Session, SessionVariableName};
+use metadata_resolve::{
+    ArgumentPresetValue, FilterPermission, ModelPredicate, ValueExpression,
+    ValueExpressionOrPredicate,
+};
+
+/// The session variables and request headers that can affect a response, besides the request
+/// itself and the role. Subscription pollers are grouped on the same inputs.
+#[derive(Default)]
+pub struct KeyInputs {
+    /// Session variables referenced by the permissions of each role
+    session_variables_by_role: BTreeMap<Role, BTreeSet<SessionVariableName>>,
+    /// Session variables referenced by data connector argument presets, for every role
+    session_variables: BTreeSet<SessionVariableName>,
+    /// Request headers forwarded to data connectors by argument presets
+    headers: BTreeSet<HeaderName>,
+    /// Every session variable and request header can affect the response
+    everything: bool,
+}
+
+impl KeyInputs {
+    pub fn new(metadata: &metadata_resolve::Metadata) -> Self {
+        let mut inputs = KeyInputs {
+            session_variables_by_role: BTreeMap::new(),
+            session_variables: BTreeSet::new(),
+            headers: BTreeSet::new(),
+            everything: !metadata.plugin_configs.pre_ndc_request_plugins.is_empty()
+                || !metadata.plugin_configs.pre_ndc_response_plugins.is_empty(),
+        };
+
+        for model in metadata.models.values() {
+            inputs.everything |= !model.permissions.authorization_rules.is_empty();
+            for (role, permission) in &model.permissions.by_role {
+                let referenced = inputs
+                    .session_variables_by_role
+                    .entry(role.clone())
+                    .or_default();
+                if let Some(select) = &permission.select
+                    && let FilterPermission::Filter(predicate) = &select.filter
+                {
+                    collect_from_predicate(predicate, referenced);
+                }
+                if let Some(input) = &permission.input {
+                    for (_, argument_preset) in input.argument_presets.values() {
+                        collect_from_value_expression_or_predicate(argument_preset, referenced);
+                    }
+                }
+            }
+            if let Some(source) = &model.model.source {
+                inputs.collect_from_argument_presets(
+                    source.data_connector_link_argument_presets.values(),
+                );
+            }
+        }
+
+        for command in metadata.commands.values() {
+            inputs.everything |= !command.permissions.authorization_rules.is_empty();
+            for (role, permission) in &command.permissions.by_role {
+                let referenced = inputs
+                    .session_variables_by_role
+                    .entry(role.clone())
+                    .or_default();
+                for (_, argument_preset) in permission.argument_presets.values() {
+                    collect_from_value_expression_or_predicate(argument_preset, referenced);
+                }
+            }
+            if let Some(source) = &command.command.source {
+                inputs.collect_from_argument_presets(
+                    source.data_connector_link_argument_presets.values(),
+                );
+            }
+        }
+
+        for object_type in metadata.object_types.values() {
+            let input_permissions = &object_type.type_input_permissions;
+            inputs.everything |= input_permissions.uses_rules_based_auth;
+            for (role, permission) in &input_permissions.by_role {
+                let referenced = inputs
+                    .session_variables_by_role
+                    .entry(role.clone())
+                    .or_default();
+                for field_preset in permission.field_presets.values() {
+                    collect_from_value_expression(&field_preset.value, referenced);
+                }
+            }
+        }
+
+        inputs
+    }
+
+    fn collect_from_argument_presets<'a>(
+        &mut self,
+        argument_presets: impl Iterator<Item = &'a ArgumentPresetValue>,
+    ) {
+        for argument_preset in argument_presets {
+            let http_headers = &argument_preset.http_headers;
+            self.headers
+                .extend(http_headers.forward.iter().map(|header| header.0.clone()));
+            for value in http_headers.additional.values() {
+                collect_from_value_expression(value, &mut self.session_variables);
+            }
+        }
+    }
+
+    /// The session variable values and request header values that can affect the response to a
+    /// request from this session, in a stable order.
+    pub fn collect<'a>(
+        &self,
+        session: &'a Session,
+        request_headers: &'a HeaderMap,
+    ) -> (
+        BTreeMap<&'a SessionVariableName, String>,
+        BTreeMap<&'a str, Vec<&'a [u8]>>,
+    ) {
+        let session_variables = session
+            .variables
+            .iter()
+            .filter(|(name, _)| {
+                self.everything
+                    || self.session_variables.contains(*name)
+                    || self
+                        .session_variables_by_role
+                        .get(&session.role)
+                        .is_some_and(|referenced| referenced.contains(*name))
+            })
+            .map(|(name, value)| (name, value.to_string()))
+            .collect();
+
+        let mut headers = BTreeMap::<_, Vec<_>>::new();
+        for (name, value) in request_headers {
+            if self.everything || self.headers.contains(name) {
+                headers
+                    .entry(name.as_str())
+                    .or_default()
+                    .push(value.as_bytes());
+            }
+        }
+
+        (session_variables, headers)
+    }
+}
+
+fn collect_from_predicate(
+    predicate: &ModelPredicate,
+    referenced: &mut BTreeSet<SessionVariableName>,
+) {
+    match predicate {
+        ModelPredicate::UnaryFieldComparison { .. } => {}
+        ModelPredicate::BinaryFieldComparison { value, .. } => {
+            collect_from_value_expression(value, referenced);
+        }
+        ModelPredicate::Relationship { predicate, .. } | ModelPredicate::Not(predicate) => {
+            collect_from_predicate(predicate, referenced);
+        }
+        ModelPredicate::And(predicates) | ModelPredicate::Or(predicates) => {
+            for predicate in predicates {
+                collect_from_predicate(predicate, referenced);
+            }
+        }
+    }
+}
+
+fn collect_from_value_expression_or_predicate(
+    value: &ValueExpressionOrPredicate,
+    referenced: &mut BTreeSet<SessionVariableName>,
+) {
+    match value {
+        ValueExpressionOrPredicate::Literal(_) => {}
+        ValueExpressionOrPredicate::SessionVariable(session_variable) => {
+            referenced.insert(session_variable.name.clone());
+        }
+        ValueExpressionOrPredicate::BooleanExpression(predicate) => {
+            collect_from_predicate(predicate, referenced);
+        }
+    }
+}
+
+fn collect_from_value_expression(
+    value: &ValueExpression,
+    referenced: &mut BTreeSet<SessionVariableName>,
+) {
+    match value {
+        ValueExpression::Literal(_) => {}
+        ValueExpression::SessionVariable(session_variable) => {
+            referenced.insert(session_variable.name.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::cache::{CacheKey, InMemoryCache, ResponseCache};
+    use hasura_authn_core::{Identity, SessionVariableValue};
+    use lang_graphql::http::RawRequest;
+
+    /// Key inputs where the permissions of the `user` role reference `x-hasura-user-id`, a data
+    /// connector argument preset references `x-hasura-tenant-id` and forwards `x-forwarded`
+    fn key_inputs() -> KeyInputs {
+        KeyInputs {
+            session_variables_by_role: BTreeMap::from([(
+                Role::new("user"),
+                BTreeSet::from(["x-hasura-user-id".parse().unwrap()]),
+            )]),
+            session_variables: BTreeSet::from(["x-hasura-tenant-id".parse().unwrap()]),
+            headers: BTreeSet::from([HeaderName::from_static("x-forwarded")]),
+            everything: false,
+        }
+    }
+
+    fn session(role: &str, variables: &[(&str, &str)]) -> Session {
+        Identity::admin(Role::new(role))
+            .get_role_authorization(None)
+            .unwrap()
+            .build_session(
+                variables
+                    .iter()
+                    .map(|(name, value)| (name.parse().unwrap(), SessionVariableValue::new(value)))
+                    .collect(),
+            )
+    }
+
+    fn headers(headers: &[(&'static str, &'static str)]) -> HeaderMap {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                (
+                    HeaderName::from_static(name),
+                    axum::http::HeaderValue::from_static(value),
+                )
+            })
+            .collect()
+    }
+
+    fn key(key_inputs: KeyInputs, session: &Session, request_headers: &HeaderMap) -> CacheKey {
+        let cache = ResponseCache {
+            backend: Arc::new(InMemoryCache::new(NonZeroUsize::MIN)),
+            key_inputs,
+            metadata_hash: [0; 64],
+        };
+        let raw_request = RawRequest {
+            operation_name: None,
+            query: "query @cached { articles { id } }".to_string(),
+            variables: None,
+            extensions: None,
+        };
+        cache.key(&raw_request, session, request_headers).unwrap()
+    }
+
+    #[test]
+    fn test_key_separates_roles() {
+        let no_headers = HeaderMap::new();
+        assert_ne!(
+            key(key_inputs(), &session("user", &[]), &no_headers),
+            key(key_inputs(), &session("guest", &[]), &no_headers)
+        );
+    }
+
+    #[test]
+    fn test_key_separates_referenced_session_variables() {
+        let no_headers = HeaderMap::new();
+        let key_for = |role: &str, variables: &[(&str, &str)]| {
+            key(key_inputs(), &session(role, variables), &no_headers)
+        };
+
+        // referenced by the permissions of the role
+        assert_ne!(
+            key_for("user", &[("x-hasura-user-id", "1")]),
+            key_for("user", &[("x-hasura-user-id", "2")])
+        );
+        // only referenced by the permissions of another role
+        assert_eq!(
+            key_for("guest", &[("x-hasura-user-id", "1")]),
+            key_for("guest", &[("x-hasura-user-id", "2")])
+        );
+        // referenced by a data connector argument preset, for every role
+        assert_ne!(
+            key_for("guest", &[("x-hasura-tenant-id", "a")]),
+            key_for("guest", &[("x-hasura-tenant-id", "b")])
+        );
+        // not referenced at all
+        assert_eq!(
+            key_for("user", &[("x-hasura-other", "a")]),
+            key_for("user", &[("x-hasura-other", "b")])
+        );
+    }
+
+    #[test]
+    fn test_key_separates_forwarded_headers() {
+        let user = session("user", &[]);
+        assert_ne!(
+            key(key_inputs(), &user, &headers(&[("x-forwarded", "a")])),
+            key(key_inputs(), &user, &headers(&[("x-forwarded", "b")]))
+        );
+        assert_eq!(
+            key(key_inputs(), &user, &headers(&[("x-other", "a")])),
+            key(key_inputs(), &user, &headers(&[("x-other", "b")]))
+        );
+    }
+
+    #[test]
+    fn test_key_separates_everything_when_everything_can_affect_the_response() {
+        let everything = || KeyInputs {
+            everything: true,
+            ..key_inputs()
+        };
+        assert_ne!(
+            key(
+                everything(),
+                &session("user", &[("x-hasura-other", "a")]),
+                &HeaderMap::new()
+            ),
+            key(
+                everything(),
+                &session("user", &[("x-hasura-other", "b")]),
+                &HeaderMap::new()
+            )
+        );
+        let user = session("user", &[]);
+        assert_ne!(
+            key(everything(), &user, &headers(&[("x-other", "a")])),
+            key(everything(), &user, &headers(&[("x-other", "b")]))
+        );
+    }
+}
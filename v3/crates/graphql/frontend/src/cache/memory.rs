@@ -0,0 +1,146 @@
+//! An in-memory, least recently used response cache.
+
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use super::{CacheBackend, CacheKey, CachedResponse};
+
+/// Keeps up to `capacity` responses in memory, evicting the least recently used response when
+/// full.
+pub struct InMemoryCache {
+    capacity: NonZeroUsize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    by_key: HashMap<CacheKey, Entry>,
+    /// Keys by the tick they were last used at, oldest first
+    by_last_used: BTreeMap<u64, CacheKey>,
+    tick: u64,
+}
+
+struct Entry {
+    response: CachedResponse,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+impl Entries {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.by_key.remove(key) {
+            self.by_last_used.remove(&entry.last_used);
+        }
+    }
+}
+
+impl InMemoryCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        InMemoryCache {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    fn get_now(&self, key: &CacheKey, now: Instant) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let tick = entries.next_tick();
+        let entry = entries.by_key.get_mut(key)?;
+        if entry.expires_at <= now {
+            entries.remove(key);
+            return None;
+        }
+        let last_used = std::mem::replace(&mut entry.last_used, tick);
+        let response = entry.response.clone();
+        entries.by_last_used.remove(&last_used);
+        entries.by_last_used.insert(tick, key.clone());
+        Some(response)
+    }
+
+    fn set_now(&self, key: CacheKey, response: CachedResponse, ttl: Duration, now: Instant) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.remove(&key);
+        let tick = entries.next_tick();
+        entries.by_last_used.insert(tick, key.clone());
+        entries.by_key.insert(
+            key,
+            Entry {
+                response,
+                expires_at: now + ttl,
+                last_used: tick,
+            },
+        );
+        while entries.by_key.len() > self.capacity.get() {
+            let Some((_, oldest)) = entries.by_last_used.pop_first() else {
+                break;
+            };
+            entries.by_key.remove(&oldest);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheBackend for InMemoryCache {
+    async fn get(&self, key: &CacheKey) -> Option<CachedResponse> {
+        self.get_now(key, Instant::now())
+    }
+
+    async fn set(&self, key: CacheKey, response: CachedResponse, ttl: Duration) {
+        self.set_now(key, response, ttl, Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    fn key(byte: u8) -> CacheKey {
+        CacheKey([byte; 64])
+    }
+
+    fn response(value: i64) -> CachedResponse {
+        CachedResponse {
+            data: IndexMap::from([(
+                lang_graphql::ast::common::Alias::new(lang_graphql::mk_name!("count")),
+                serde_json::json!(value),
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = InMemoryCache::new(NonZeroUsize::new(2).unwrap());
+        let now = Instant::now();
+        let ttl = Duration::from_secs(60);
+        cache.set_now(key(1), response(1), ttl, now);
+        cache.set_now(key(2), response(2), ttl, now);
+        // using the first response makes the second the least recently used
+        assert_eq!(cache.get_now(&key(1), now), Some(response(1)));
+        cache.set_now(key(3), response(3), ttl, now);
+
+        assert_eq!(cache.get_now(&key(1), now), Some(response(1)));
+        assert_eq!(cache.get_now(&key(2), now), None);
+        assert_eq!(cache.get_now(&key(3), now), Some(response(3)));
+    }
+
+    #[test]
+    fn test_expires_responses() {
+        let cache = InMemoryCache::new(NonZeroUsize::new(2).unwrap());
+        let now = Instant::now();
+        cache.set_now(key(1), response(1), Duration::from_secs(10), now);
+
+        assert_eq!(
+            cache.get_now(&key(1), now + Duration::from_secs(9)),
+            Some(response(1))
+        );
+        assert_eq!(cache.get_now(&key(1), now + Duration::from_secs(10)), None);
+    }
+}
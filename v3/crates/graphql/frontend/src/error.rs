@@ -22,6 +22,24 @@ pub enum RequestError {
 
     #[error("explain error: {0}")]
     ExplainError(String),
+
+    #[error("{0}")]
+    CacheDirectiveError(#[from] crate::cache::CacheDirectiveError),
+
+    #[error("{0}")]
+    TransactionDirectiveError(#[from] crate::transaction::TransactionDirectiveError),
+
+    #[error("{0}")]
+    PersistedQueryError(#[from] crate::persisted_queries::PersistedQueryError),
+
+    #[error("{0}")]
+    AllowListError(#[from] crate::allow_list::AllowListError),
+
+    #[error("{0}")]
+    LimitError(#[from] crate::limits::LimitError),
+
+    #[error("mutations cannot be sent with GET requests")]
+    MutationOverGet,
 }
 
 impl RequestError {
@@ -37,10 +55,17 @@ impl RequestError {
         // We are using the visibility of the error to determine if it is an internal error or not. We are assuming that
         // if we are showing the error message to the user, it is something that they can fix on their end.
         let is_internal = self.visibility() == ErrorVisibility::Internal;
+        // clients tell these errors apart by their code
+        let details = match self {
+            Self::PersistedQueryError(error) => Some(serde_json::json!({ "code": error.code() })),
+            Self::AllowListError(error) => Some(serde_json::json!({ "code": error.code() })),
+            Self::LimitError(error) => Some(error.details()),
+            _ => None,
+        };
         GraphQLError {
             message,
             path: None,
-            extensions: None,
+            extensions: details.map(|details| gql::http::Extensions { details }),
             is_internal,
         }
     }
@@ -52,9 +77,15 @@ impl TraceableError for RequestError {
             Self::IRConversionError(ir_error) => ir_error.visibility(),
             Self::GraphQlPlanError(plan_error) => plan_error.visibility(),
             // Rest all errors are visible to users via traces
-            Self::ParseFailure(_) | Self::ValidationFailed(_) | Self::ExplainError(_) => {
-                ErrorVisibility::User
-            }
+            Self::ParseFailure(_)
+            | Self::ValidationFailed(_)
+            | Self::ExplainError(_)
+            | Self::CacheDirectiveError(_)
+            | Self::TransactionDirectiveError(_)
+            | Self::PersistedQueryError(_)
+            | Self::AllowListError(_)
+            | Self::LimitError(_)
+            | Self::MutationOverGet => ErrorVisibility::User,
         }
     }
 }
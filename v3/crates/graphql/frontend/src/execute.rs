@@ -3,11 +3,13 @@ mod types;
 use crate::process_response::{ProcessedResponse, process_mutation_response, process_response};
 use engine_types::{HttpContext, ProjectId};
 use execute::FieldError;
-use execute::{resolve_ndc_mutation_execution, resolve_ndc_query_execution};
+use execute::{
+    resolve_ndc_mutation_execution, resolve_ndc_mutation_transaction, resolve_ndc_query_execution,
+};
 use gql::normalized_ast;
 use gql::schema::NamespacedGetter;
-use graphql_ir::MutationPlan;
 use graphql_ir::{ApolloFederationSelect, NodeQueryPlan, QueryPlan};
+use graphql_ir::{MutationGroup, MutationPlan};
 use graphql_schema::GDS;
 use graphql_schema::GDSRoleNamespaceGetter;
 use hasura_authn_core::Session;
@@ -16,6 +18,7 @@ use lang_graphql as gql;
 use lang_graphql::ast::common as ast;
 use metadata_resolve::LifecyclePluginConfigs;
 use plan_types::{NDCMutationExecution, NDCQueryExecution};
+use std::sync::Arc;
 use tracing_util::{AttributeVisibility, set_attribute_on_active_span};
 pub use types::{ExecuteQueryResult, RootFieldResult};
 
@@ -269,9 +272,106 @@ async fn execute_mutation_field_plan(
         .await
 }
 
+/// Execute consecutive root fields of a mutation against the same data connector in one
+/// transactional request. If the transaction fails, the first root field gets its error and the
+/// others are reported as not applied.
+async fn execute_mutation_transaction_plan(
+    http_context: &HttpContext,
+    plugins: &LifecyclePluginConfigs,
+    session: &Session,
+    request_headers: &http::HeaderMap,
+    mutation_group: MutationGroup<'_, '_>,
+    project_id: Option<&ProjectId>,
+) -> Vec<(ast::Alias, RootFieldResult)> {
+    let tracer = tracing_util::global_tracer();
+    tracer
+        .in_span_async(
+            "execute_mutation_transaction_plan",
+            format!(
+                "Execute request plan for mutation transaction using data connector {}",
+                mutation_group.data_connector.name
+            ),
+            tracing_util::SpanVisibility::User,
+            || {
+                Box::pin(async {
+                    let mut fields = Vec::new();
+                    let mut mutation_executions = Vec::new();
+                    for (alias, field_plan) in mutation_group.fields {
+                        fields.push((
+                            alias,
+                            field_plan.selection_set,
+                            field_plan.mutation_execution.process_response_as.clone(),
+                        ));
+                        mutation_executions.push(field_plan.mutation_execution);
+                    }
+
+                    match resolve_ndc_mutation_transaction(
+                        http_context,
+                        plugins,
+                        session,
+                        request_headers,
+                        mutation_executions,
+                        project_id,
+                    )
+                    .await
+                    {
+                        Ok(mutation_responses) => fields
+                            .into_iter()
+                            .zip(mutation_responses)
+                            .map(
+                                |(
+                                    (alias, selection_set, process_response_as),
+                                    mutation_response,
+                                )| {
+                                    let processed_response =
+                                        mutation_response.and_then(|mutation_response| {
+                                            process_mutation_response(
+                                                selection_set,
+                                                mutation_response,
+                                                &process_response_as,
+                                            )
+                                        });
+                                    (
+                                        alias,
+                                        RootFieldResult::from_processed_response(
+                                            process_response_as.is_nullable(),
+                                            processed_response,
+                                        ),
+                                    )
+                                },
+                            )
+                            .collect(),
+                        Err(error) => {
+                            // the data connector does not tell us which mutation of the
+                            // transaction failed, so every field reports the failure
+                            let error = Arc::new(error);
+                            fields
+                                .into_iter()
+                                .map(|(alias, _, process_response_as)| {
+                                    let error = FieldError::TransactionFailed {
+                                        error: error.clone(),
+                                    };
+                                    (
+                                        alias,
+                                        RootFieldResult::new(
+                                            process_response_as.is_nullable(),
+                                            Err(error),
+                                        ),
+                                    )
+                                })
+                                .collect()
+                        }
+                    }
+                })
+            },
+        )
+        .await
+}
+
 /// Given an entire plan for a mutation, produce a result. We do this by executing the singular
-/// root fields of the mutation sequentially rather than concurrently, in the order defined by the
-/// `IndexMap`'s keys.
+/// root fields of the mutation sequentially rather than concurrently, in the order they appear in
+/// the request. Consecutive root fields against a data connector that supports transactional
+/// mutations are sent to it together, in one request.
 pub async fn execute_mutation_plan(
     http_context: &HttpContext,
     plugins: &LifecyclePluginConfigs,
@@ -295,8 +395,22 @@ pub async fn execute_mutation_plan(
         ));
     }
 
-    for (_, mutation_group) in mutation_plan.nodes {
-        for (alias, field_plan) in mutation_group {
+    for mutation_group in mutation_plan.nodes {
+        if mutation_group.is_transactional() {
+            executed_root_fields.extend(
+                execute_mutation_transaction_plan(
+                    http_context,
+                    plugins,
+                    session,
+                    request_headers,
+                    mutation_group,
+                    project_id,
+                )
+                .await,
+            );
+            continue;
+        }
+        for (alias, field_plan) in mutation_group.fields {
             executed_root_fields.push((
                 alias,
                 execute_mutation_field_plan(
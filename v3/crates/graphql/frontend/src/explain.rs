@@ -1,5 +1,7 @@
 pub mod types;
 use super::steps;
+use crate::allow_list::check_allow_list;
+use crate::persisted_queries::{PersistedQueries, resolve_persisted_query};
 
 use std::collections::BTreeMap;
 use std::sync::Arc;
@@ -29,6 +31,7 @@ pub async fn execute_explain(
     session: &Session,
     request_headers: &http::HeaderMap,
     request: RawRequest,
+    persisted_queries: Option<&PersistedQueries>,
 ) -> (Option<ast::OperationType>, types::ExplainResponse) {
     explain_query_internal(
         expose_internal_errors,
@@ -38,6 +41,7 @@ pub async fn execute_explain(
         session,
         request_headers,
         request,
+        persisted_queries,
     )
     .await
     .map_or_else(
@@ -51,7 +55,8 @@ pub async fn execute_explain(
     )
 }
 
-/// Explains (query plan) a GraphQL query
+/// Explains (query plan) a GraphQL query. Persisted queries and the allow-list apply as they do
+/// to executing the query, so explaining can't be used to run other operations.
 async fn explain_query_internal(
     expose_internal_errors: ExposeInternalErrors,
     http_context: &HttpContext,
@@ -59,8 +64,11 @@ async fn explain_query_internal(
     metadata: &Arc<metadata_resolve::Metadata>,
     session: &Session,
     request_headers: &http::HeaderMap,
-    raw_request: gql::http::RawRequest,
+    mut raw_request: gql::http::RawRequest,
+    persisted_queries: Option<&PersistedQueries>,
 ) -> Result<(ast::OperationType, types::ExplainResponse), crate::RequestError> {
+    // fill in the query of a request that only sends the hash of a persisted query
+    resolve_persisted_query(persisted_queries, &mut raw_request)?;
     let tracer = tracing_util::global_tracer();
     tracer
         .in_span_async(
@@ -82,6 +90,14 @@ async fn explain_query_internal(
                     // parse the raw request into a GQL query
                     let query = steps::parse_query(&raw_request.query)?;
 
+                    // restrict roles in the allow-list to the operations it gives
+                    check_allow_list(
+                        metadata.graphql_config.allow_list.as_ref(),
+                        session,
+                        &raw_request,
+                        &query,
+                    )?;
+
                     // normalize the parsed GQL query
                     let normalized_request = steps::normalize_request(
                         schema,
@@ -320,8 +336,8 @@ pub(crate) async fn explain_mutation_plan(
         ));
     }
 
-    for (_, mutation_group) in mutation_plan.nodes {
-        for (alias, ndc_mutation_execution) in mutation_group {
+    for mutation_group in mutation_plan.nodes {
+        for (alias, ndc_mutation_execution) in mutation_group.fields {
             // we don't have remote predicates on mutations
             // so there won't be any steps here
             let predicate_explain_steps = vec![];
@@ -408,7 +424,8 @@ async fn get_execution_steps(
         }
         ProcessResponseAs::Array { .. }
         | ProcessResponseAs::Object { .. }
-        | ProcessResponseAs::Aggregates => {
+        | ProcessResponseAs::Aggregates
+        | ProcessResponseAs::Groups => {
             // A model execution node
             let data_connector_explain = fetch_explain_from_data_connector(
                 expose_internal_errors,
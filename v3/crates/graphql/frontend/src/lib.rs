@@ -1,21 +1,36 @@
+mod allow_list;
+mod cache;
 mod error;
 mod execute;
 mod explain;
+mod limits;
+mod persisted_queries;
 mod process_response;
 mod query;
 mod query_usage;
 mod steps;
+mod transaction;
 mod types;
 
+pub use allow_list::{AllowListError, check_allow_list};
+pub use cache::{
+    CacheBackend, CacheDirectiveError, CacheKey, CachedResponse, InMemoryCache, KeyInputs,
+    ResponseCache,
+};
 pub use error::{RequestError, build_state_with_middleware_error_converter};
 pub use execute::{ExecuteQueryResult, RootFieldResult, execute_mutation_plan, execute_query_plan};
 pub use explain::execute_explain;
 pub use explain::types::{ExplainResponse, redact_ndc_explain};
+pub use limits::{LimitError, check_operation_limits};
+pub use persisted_queries::{PersistedQueries, PersistedQueryError, resolve_persisted_query};
 pub use process_response::process_response;
 pub use query::{
     execute_query, execute_query_internal, set_request_metadata_attributes, set_usage_attributes,
 };
-pub use steps::{build_ir, build_request_plan, generate_ir, normalize_request, parse_query};
+pub use steps::{
+    build_ir, build_request_plan, generate_ir, may_mutate, normalize_request, parse_query,
+};
+pub use transaction::TransactionDirectiveError;
 pub use types::{GraphQLErrors, GraphQLResponse};
 
 #[cfg(test)]
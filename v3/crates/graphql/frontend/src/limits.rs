@@ -0,0 +1,347 @@
+//! Checking the size of a normalized operation against the limits in the GraphQL config.
+//!
+//! Operations are measured after normalization, so fragments count wherever they are spread
+//! and skipped fields don't count at all. Fields of list type multiply the cost of the fields
+//! they select by the number of rows they are estimated to return.
+
+use graphql_schema::{Annotation, GDS, InputAnnotation, ModelInputAnnotation};
+use hasura_authn_core::Session;
+use lang_graphql::normalized_ast;
+use metadata_resolve::{OperationLimits, QueryLimits};
+
+/// The number of rows a list field without a `limit` argument is estimated to return.
+const DEFAULT_LIST_SIZE: u64 = 100;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LimitError {
+    #[error("the operation is nested {depth} fields deep, more than the limit of {limit}")]
+    DepthExceeded { depth: u64, limit: u32 },
+    #[error("the operation has {aliases} aliased fields, more than the limit of {limit}")]
+    AliasesExceeded { aliases: u64, limit: u32 },
+    #[error("the operation selects {nodes} fields, more than the limit of {limit}")]
+    NodesExceeded { nodes: u64, limit: u32 },
+    #[error("the operation has an estimated cost of {cost}, more than the limit of {limit}")]
+    CostExceeded { cost: u64, limit: u32 },
+}
+
+impl LimitError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            LimitError::DepthExceeded { .. } => "DEPTH_LIMIT_EXCEEDED",
+            LimitError::AliasesExceeded { .. } => "ALIAS_LIMIT_EXCEEDED",
+            LimitError::NodesExceeded { .. } => "NODE_LIMIT_EXCEEDED",
+            LimitError::CostExceeded { .. } => "COST_LIMIT_EXCEEDED",
+        }
+    }
+
+    /// The measured value and the limit it exceeds, for the error extensions.
+    pub fn details(&self) -> serde_json::Value {
+        let (value, limit) = match self {
+            LimitError::DepthExceeded { depth, limit } => (depth, limit),
+            LimitError::AliasesExceeded { aliases, limit } => (aliases, limit),
+            LimitError::NodesExceeded { nodes, limit } => (nodes, limit),
+            LimitError::CostExceeded { cost, limit } => (cost, limit),
+        };
+        serde_json::json!({ "code": self.code(), "value": value, "limit": limit })
+    }
+}
+
+/// The size of an operation.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct OperationSize {
+    depth: u64,
+    aliases: u64,
+    nodes: u64,
+    cost: u64,
+}
+
+/// Checks a normalized operation against the limits for the session's role.
+pub fn check_operation_limits(
+    limits: Option<&QueryLimits>,
+    session: &Session,
+    operation: &normalized_ast::Operation<'_, GDS>,
+) -> Result<(), LimitError> {
+    let Some(limits) = limits else {
+        return Ok(());
+    };
+    let OperationLimits {
+        max_depth,
+        max_aliases,
+        max_nodes,
+        max_cost,
+    } = *limits.for_role(&session.role);
+    if max_depth.is_none() && max_aliases.is_none() && max_nodes.is_none() && max_cost.is_none() {
+        return Ok(());
+    }
+
+    let mut size = OperationSize::default();
+    measure_selection_set(&operation.selection_set, 1, 1, &mut size);
+
+    if let Some(limit) = max_depth
+        && size.depth > u64::from(limit)
+    {
+        return Err(LimitError::DepthExceeded {
+            depth: size.depth,
+            limit,
+        });
+    }
+    if let Some(limit) = max_aliases
+        && size.aliases > u64::from(limit)
+    {
+        return Err(LimitError::AliasesExceeded {
+            aliases: size.aliases,
+            limit,
+        });
+    }
+    if let Some(limit) = max_nodes
+        && size.nodes > u64::from(limit)
+    {
+        return Err(LimitError::NodesExceeded {
+            nodes: size.nodes,
+            limit,
+        });
+    }
+    if let Some(limit) = max_cost
+        && size.cost > u64::from(limit)
+    {
+        return Err(LimitError::CostExceeded {
+            cost: size.cost,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+/// Adds the size of the fields in a selection set at `depth`, each selected for `rows` rows.
+fn measure_selection_set(
+    selection_set: &normalized_ast::SelectionSet<'_, GDS>,
+    depth: u64,
+    rows: u64,
+    size: &mut OperationSize,
+) {
+    for field in selection_set.fields.values() {
+        size.depth = size.depth.max(depth);
+        size.nodes = size.nodes.saturating_add(1);
+        size.cost = size.cost.saturating_add(rows);
+        let field_call = field.field_calls.values().next();
+        if field_call.is_some_and(|field_call| field_call.name != field.alias.0) {
+            size.aliases = size.aliases.saturating_add(1);
+        }
+        let field_rows = if field.type_container.is_list() {
+            field_call
+                .and_then(limit_argument)
+                .unwrap_or(DEFAULT_LIST_SIZE)
+        } else {
+            1
+        };
+        measure_selection_set(
+            &field.selection_set,
+            depth + 1,
+            rows.saturating_mul(field_rows),
+            size,
+        );
+    }
+}
+
+/// The value of the `limit` argument of a model field, if it is given.
+fn limit_argument(field_call: &normalized_ast::FieldCall<'_, GDS>) -> Option<u64> {
+    field_call.arguments.values().find_map(|argument| {
+        match (argument.info.generic, &argument.value) {
+            (
+                Annotation::Input(InputAnnotation::Model(
+                    ModelInputAnnotation::ModelLimitArgument
+                    | ModelInputAnnotation::ModelStreamBatchSizeArgument,
+                )),
+                normalized_ast::Value::SimpleValue(normalized_ast::SimpleValue::Integer(limit)),
+            ) => u64::try_from(*limit).ok(),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use graphql_schema::GDSRoleNamespaceGetter;
+    use hasura_authn_core::{Identity, Role};
+    use lang_graphql::http::Request;
+    use lang_graphql::parser::Parser;
+    use lang_graphql::schema::Schema;
+    use lang_graphql::validation::{NonNullGraphqlVariablesValidation, normalize_request};
+
+    fn schema() -> Schema<GDS> {
+        let metadata = open_dds::Metadata::from_json_str(include_str!(
+            "../tests/query_usage_analytics/schema.json"
+        ))
+        .unwrap();
+        GDS::new_with_default_flags(metadata)
+            .unwrap()
+            .build_schema()
+            .unwrap()
+    }
+
+    fn session(role: &str) -> Session {
+        Identity::admin(Role::new(role))
+            .get_role_authorization(None)
+            .unwrap()
+            .build_session(BTreeMap::new())
+    }
+
+    fn normalize<'s>(schema: &'s Schema<GDS>, query: &str) -> normalized_ast::Operation<'s, GDS> {
+        let request = Request {
+            operation_name: None,
+            query: Parser::new(query).parse_executable_document().unwrap(),
+            variables: BTreeMap::new(),
+        };
+        normalize_request(
+            &GDSRoleNamespaceGetter {
+                scope: Role::new("admin"),
+            },
+            schema,
+            &request,
+            NonNullGraphqlVariablesValidation::Validate,
+        )
+        .unwrap()
+    }
+
+    fn measure(query: &str) -> OperationSize {
+        let schema = schema();
+        let operation = normalize(&schema, query);
+        let mut size = OperationSize::default();
+        measure_selection_set(&operation.selection_set, 1, 1, &mut size);
+        size
+    }
+
+    #[test]
+    fn test_measure_depth_nodes_and_cost() {
+        // Album returns at most 10 rows, and Tracks is estimated to return 100 rows for each
+        assert_eq!(
+            measure("query { Album(limit: 10) { Title AlbumId Tracks { Name } } }"),
+            OperationSize {
+                depth: 3,
+                aliases: 0,
+                nodes: 5,
+                cost: 1 + 10 + 10 + 10 + 1000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_measure_aliases() {
+        assert_eq!(
+            measure("query { a: Album(limit: 1) { t: Title } b: Album(limit: 1) { Title } }"),
+            OperationSize {
+                depth: 2,
+                aliases: 3,
+                nodes: 4,
+                cost: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_measure_fragments() {
+        // fragments count where they are spread, and spreading one twice selects the same fields
+        let expected = OperationSize {
+            depth: 2,
+            aliases: 0,
+            nodes: 3,
+            cost: 1 + 2 + 2,
+        };
+        assert_eq!(
+            measure(
+                "query { Album(limit: 2) { ...AlbumFields } }
+                 fragment AlbumFields on album { Title AlbumId }"
+            ),
+            expected
+        );
+        assert_eq!(
+            measure(
+                "query { ...Albums ...Albums Album(limit: 2) { ...AlbumFields Title ...AlbumFields } }
+                 fragment Albums on Query { Album(limit: 2) { AlbumId } }
+                 fragment AlbumFields on album { Title AlbumId }"
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_check_operation_limits() {
+        let schema = schema();
+        let operation = normalize(
+            &schema,
+            "query { a: Album(limit: 10) { Title Tracks { Name } } }",
+        );
+        let check = |role: &str, limits: OperationLimits| {
+            let limits = QueryLimits {
+                default: OperationLimits::default(),
+                by_role: BTreeMap::from([(Role::new("user"), limits)]),
+            };
+            check_operation_limits(Some(&limits), &session(role), &operation)
+                .map_err(|err| err.code())
+        };
+
+        // the operation is 3 deep, with 1 alias, 4 fields and a cost of 1 + 10 + 10 + 1000
+        let at_limits = OperationLimits {
+            max_depth: Some(3),
+            max_aliases: Some(1),
+            max_nodes: Some(4),
+            max_cost: Some(1021),
+        };
+        assert_eq!(check("user", at_limits), Ok(()));
+        assert_eq!(
+            check(
+                "user",
+                OperationLimits {
+                    max_depth: Some(2),
+                    ..at_limits
+                }
+            ),
+            Err("DEPTH_LIMIT_EXCEEDED")
+        );
+        assert_eq!(
+            check(
+                "user",
+                OperationLimits {
+                    max_aliases: Some(0),
+                    ..at_limits
+                }
+            ),
+            Err("ALIAS_LIMIT_EXCEEDED")
+        );
+        assert_eq!(
+            check(
+                "user",
+                OperationLimits {
+                    max_nodes: Some(3),
+                    ..at_limits
+                }
+            ),
+            Err("NODE_LIMIT_EXCEEDED")
+        );
+        assert_eq!(
+            check(
+                "user",
+                OperationLimits {
+                    max_cost: Some(1020),
+                    ..at_limits
+                }
+            ),
+            Err("COST_LIMIT_EXCEEDED")
+        );
+        // other roles get the default limits, which don't limit anything here
+        assert_eq!(
+            check(
+                "admin",
+                OperationLimits {
+                    max_depth: Some(1),
+                    ..at_limits
+                }
+            ),
+            Ok(())
+        );
+        assert!(check_operation_limits(None, &session("user"), &operation).is_ok());
+    }
+}
@@ -0,0 +1,185 @@
+//! Automatic persisted queries.
+//!
+//! A client can send the SHA-256 hash of a query in the `persistedQuery` request extension instead
+//! of the query itself. If the hash is not known, the client gets a `PersistedQueryNotFound` error
+//! and retries with both the query and its hash, after which the hash alone is enough.
+//! <https://github.com/apollographql/apollo-link-persisted-queries#apollo-engine>
+
+use std::collections::{HashMap, VecDeque};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use lang_graphql::http::RawRequest;
+use sha2::{Digest, Sha256};
+
+/// The only version of the persisted query protocol
+const PERSISTED_QUERY_VERSION: u32 = 1;
+
+/// Keeps up to `capacity` persisted queries in memory, forgetting the oldest query when full.
+pub struct PersistedQueries {
+    capacity: NonZeroUsize,
+    queries: Mutex<Queries>,
+}
+
+#[derive(Default)]
+struct Queries {
+    by_hash: HashMap<String, Arc<str>>,
+    /// Hashes in the order their queries were persisted, oldest first
+    order: VecDeque<String>,
+}
+
+impl PersistedQueries {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        PersistedQueries {
+            capacity,
+            queries: Mutex::new(Queries::default()),
+        }
+    }
+
+    fn get(&self, hash: &str) -> Option<Arc<str>> {
+        let queries = self.queries.lock().unwrap_or_else(PoisonError::into_inner);
+        queries.by_hash.get(hash).cloned()
+    }
+
+    fn insert(&self, hash: String, query: &str) {
+        let mut queries = self.queries.lock().unwrap_or_else(PoisonError::into_inner);
+        if queries.by_hash.contains_key(&hash) {
+            return;
+        }
+        queries.by_hash.insert(hash.clone(), query.into());
+        queries.order.push_back(hash);
+        while queries.order.len() > self.capacity.get() {
+            if let Some(oldest) = queries.order.pop_front() {
+                queries.by_hash.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PersistedQueryError {
+    // Apollo clients look for these exact messages
+    #[error("PersistedQueryNotSupported")]
+    NotSupported,
+    #[error("PersistedQueryNotFound")]
+    NotFound,
+    #[error("unsupported persisted query version {0}")]
+    UnsupportedVersion(u32),
+    #[error("provided sha does not match query")]
+    HashMismatch,
+}
+
+impl PersistedQueryError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            PersistedQueryError::NotSupported => "PERSISTED_QUERY_NOT_SUPPORTED",
+            PersistedQueryError::NotFound => "PERSISTED_QUERY_NOT_FOUND",
+            PersistedQueryError::UnsupportedVersion(_) => "PERSISTED_QUERY_UNSUPPORTED_VERSION",
+            PersistedQueryError::HashMismatch => "PERSISTED_QUERY_HASH_MISMATCH",
+        }
+    }
+}
+
+/// Fills in the query of a request that only sends the hash of a persisted query, and persists
+/// the query of a request that sends both.
+pub fn resolve_persisted_query(
+    persisted_queries: Option<&PersistedQueries>,
+    raw_request: &mut RawRequest,
+) -> Result<(), PersistedQueryError> {
+    let Some(persisted_query) = raw_request
+        .extensions
+        .as_ref()
+        .and_then(|extensions| extensions.persisted_query.as_ref())
+    else {
+        return Ok(());
+    };
+    let Some(persisted_queries) = persisted_queries else {
+        // without persisted queries, a request with a query is served as usual
+        return if raw_request.query.is_empty() {
+            Err(PersistedQueryError::NotSupported)
+        } else {
+            Ok(())
+        };
+    };
+    if persisted_query.version != PERSISTED_QUERY_VERSION {
+        return Err(PersistedQueryError::UnsupportedVersion(
+            persisted_query.version,
+        ));
+    }
+
+    let hash = persisted_query.sha256_hash.to_ascii_lowercase();
+    if raw_request.query.is_empty() {
+        let query = persisted_queries
+            .get(&hash)
+            .ok_or(PersistedQueryError::NotFound)?;
+        raw_request.query = query.to_string();
+    } else {
+        let mut hasher = Sha256::new();
+        hasher.update(raw_request.query.as_bytes());
+        if format!("{:x}", hasher.finalize()) != hash {
+            return Err(PersistedQueryError::HashMismatch);
+        }
+        persisted_queries.insert(hash, &raw_request.query);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lang_graphql::http::{PersistedQuery, RequestExtensions};
+
+    const QUERY: &str = "query { __typename }";
+    const QUERY_HASH: &str = "8995e953e895e960e470a1ee90e4b29520981980dcbc5e51ce0d7a2169b7049e";
+
+    fn request(query: &str, sha256_hash: &str) -> RawRequest {
+        RawRequest {
+            operation_name: None,
+            query: query.to_string(),
+            variables: None,
+            extensions: Some(RequestExtensions {
+                persisted_query: Some(PersistedQuery {
+                    version: 1,
+                    sha256_hash: sha256_hash.to_string(),
+                }),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_resolve_persisted_query() {
+        let persisted_queries = PersistedQueries::new(NonZeroUsize::new(1).unwrap());
+
+        // the hash alone is not enough until the query has been sent
+        let mut hash_only = request("", QUERY_HASH);
+        assert!(matches!(
+            resolve_persisted_query(Some(&persisted_queries), &mut hash_only),
+            Err(PersistedQueryError::NotFound)
+        ));
+
+        let mut mismatched = request("query { other }", QUERY_HASH);
+        assert!(matches!(
+            resolve_persisted_query(Some(&persisted_queries), &mut mismatched),
+            Err(PersistedQueryError::HashMismatch)
+        ));
+
+        let mut with_query = request(QUERY, QUERY_HASH);
+        resolve_persisted_query(Some(&persisted_queries), &mut with_query).unwrap();
+
+        resolve_persisted_query(Some(&persisted_queries), &mut hash_only).unwrap();
+        assert_eq!(hash_only.query, QUERY);
+    }
+
+    #[test]
+    fn test_persisted_queries_not_supported() {
+        let mut hash_only = request("", QUERY_HASH);
+        assert!(matches!(
+            resolve_persisted_query(None, &mut hash_only),
+            Err(PersistedQueryError::NotSupported)
+        ));
+
+        let mut with_query = request(QUERY, QUERY_HASH);
+        resolve_persisted_query(None, &mut with_query).unwrap();
+        assert_eq!(with_query.query, QUERY);
+    }
+}
@@ -8,14 +8,16 @@ use std::collections::BTreeMap;
 use std::collections::hash_map::RandomState;
 
 use base64::{Engine, engine::general_purpose};
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use lang_graphql::ast::common::{self as ast, Alias, TypeName};
 use lang_graphql::normalized_ast;
 use open_dds::commands::CommandName;
 use open_dds::types::FieldName;
 
 use graphql_ir::{GLOBAL_ID_VERSION, global_id_col_format};
-use graphql_schema::{AggregateOutputAnnotation, Annotation, GDS, GlobalID, OutputAnnotation};
+use graphql_schema::{
+    AggregateOutputAnnotation, Annotation, GDS, GlobalID, GroupByOutputAnnotation, OutputAnnotation,
+};
 use metadata_resolve::Qualified;
 use metadata_resolve::data_connectors;
 use plan_types::FUNCTION_IR_VALUE_COLUMN_NAME;
@@ -512,6 +514,96 @@ fn reshape_aggregate_fields(
     Ok(response)
 }
 
+fn process_group_requested_fields(
+    row_set: ndc_models::RowSet,
+    group_output_selection_set: &normalized_ast::SelectionSet<'_, GDS>,
+) -> Result<json::Value, execute::FieldError> {
+    let groups = row_set.groups
+        .ok_or_else(|| execute::NDCUnexpectedError::BadNDCResponse {
+            summary:
+                "Unable to parse response from NDC, RowSet groups property was null when it was expected to be an array"
+                    .to_owned(),
+        })?;
+
+    // the connector returns the values of the dimensions in the order they were requested, which
+    // is the order they appear in the selection set
+    let dimension_names = get_group_dimension_names(group_output_selection_set)?;
+
+    let mut result = Vec::new();
+    for mut group in groups {
+        let dimension_values = dimension_names
+            .iter()
+            .zip(group.dimensions)
+            .collect::<IndexMap<_, _>>();
+        let group_result = group_output_selection_set.as_object_selection_set(
+            |_type_name, field, field_call| match field_call.info.generic {
+                Annotation::Output(OutputAnnotation::GroupBy(
+                    GroupByOutputAnnotation::Dimensions,
+                )) => {
+                    let dimensions = field.selection_set.as_object_selection_set(
+                        |_type_name, dimension_field, _dimension_field_call| {
+                            let dimension_name = graphql_ir::mk_alias_from_graphql_field_path(&[
+                                &field.alias,
+                                &dimension_field.alias,
+                            ]);
+                            let dimension_value = dimension_values
+                                .get(&dimension_name.as_str().to_owned())
+                                .ok_or_else(|| execute::NDCUnexpectedError::BadNDCResponse {
+                                    summary: format!("missing dimension: {dimension_name}"),
+                                })?;
+                            Ok::<_, execute::FieldError>(dimension_value.clone())
+                        },
+                    )?;
+                    Ok(json::to_value(dimensions).map_err(execute::FieldError::from)?)
+                }
+                Annotation::Output(OutputAnnotation::GroupBy(
+                    GroupByOutputAnnotation::Aggregates,
+                )) => reshape_aggregate_fields(
+                    &mut group.aggregates,
+                    &[&field.alias],
+                    &field.selection_set,
+                ),
+                annotation => Err(execute::FieldInternalError::UnexpectedAnnotation {
+                    annotation: annotation.clone(),
+                })?,
+            },
+        )?;
+        result.push(group_result);
+    }
+    let response = json::to_value(result).map_err(execute::FieldError::from)?;
+    Ok(response)
+}
+
+fn get_group_dimension_names(
+    group_output_selection_set: &normalized_ast::SelectionSet<'_, GDS>,
+) -> Result<IndexSet<String>, execute::FieldError> {
+    let mut dimension_names = IndexSet::new();
+    for field in group_output_selection_set.fields.values() {
+        let field_call = field.field_call()?;
+        if let Annotation::Output(OutputAnnotation::GroupBy(GroupByOutputAnnotation::Dimensions)) =
+            field_call.info.generic
+        {
+            for dimension_field in field.selection_set.fields.values() {
+                let dimension_field_call = dimension_field.field_call()?;
+                if let Annotation::Output(OutputAnnotation::GroupBy(
+                    GroupByOutputAnnotation::Dimension { .. },
+                )) = dimension_field_call.info.generic
+                {
+                    dimension_names.insert(
+                        graphql_ir::mk_alias_from_graphql_field_path(&[
+                            &field.alias,
+                            &dimension_field.alias,
+                        ])
+                        .as_str()
+                        .to_owned(),
+                    );
+                }
+            }
+        }
+    }
+    Ok(dimension_names)
+}
+
 pub fn process_response(
     selection_set: &normalized_ast::SelectionSet<'_, GDS>,
     rows_sets: Vec<ndc_models::RowSet>,
@@ -573,6 +665,13 @@ pub fn process_response(
                         response: result,
                     })
                 }
+                ProcessResponseAs::Groups => {
+                    let result = process_group_requested_fields(row_set, selection_set)?;
+                    Ok(ProcessedResponse {
+                        response_headers: None,
+                        response: result,
+                    })
+                }
             }
         },
     )
@@ -2,9 +2,14 @@ use super::steps;
 use indexmap::IndexMap;
 
 use super::types::GraphQLResponse;
+use crate::allow_list::check_allow_list;
+use crate::cache::{CacheEntry, ResponseCache};
 use crate::execute::{
     ExecuteQueryResult, RootFieldResult, execute_mutation_plan, execute_query_plan,
 };
+use crate::limits::check_operation_limits;
+use crate::persisted_queries::{PersistedQueries, resolve_persisted_query};
+use crate::transaction;
 use engine_types::{ExposeInternalErrors, HttpContext, ProjectId};
 use graphql_schema::GDS;
 use hasura_authn_core::Session;
@@ -12,6 +17,7 @@ use lang_graphql as gql;
 use lang_graphql::ast::common as ast;
 use lang_graphql::{http::RawRequest, schema::Schema};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing_util::set_status_on_current_span;
 use tracing_util::{AttributeVisibility, SpanVisibility, set_attribute_on_active_span};
 
@@ -24,8 +30,12 @@ pub async fn execute_query(
     request_headers: &http::HeaderMap,
     request: RawRequest,
     project_id: Option<&ProjectId>,
+    response_cache: Option<&ResponseCache>,
+    persisted_queries: Option<&PersistedQueries>,
+    http_method: &http::Method,
 ) -> (Option<ast::OperationType>, GraphQLResponse) {
-    execute_query_internal(
+    let start = Instant::now();
+    let (operation_type, response) = execute_query_internal(
         expose_internal_errors,
         http_context,
         schema,
@@ -34,6 +44,9 @@ pub async fn execute_query(
         request_headers,
         request,
         project_id,
+        response_cache,
+        persisted_queries,
+        http_method,
     )
     .await
     .map_or_else(
@@ -44,7 +57,9 @@ pub async fn execute_query(
             )
         },
         |(op_type, response)| (Some(op_type), response),
-    )
+    );
+    record_request_metrics(operation_type.as_ref(), &response, start.elapsed());
+    (operation_type, response)
 }
 
 /// Executes a GraphQL query using new pipeline
@@ -55,9 +70,15 @@ pub async fn execute_query_internal(
     metadata: &Arc<metadata_resolve::Metadata>,
     session: &Session,
     request_headers: &http::HeaderMap,
-    raw_request: gql::http::RawRequest,
+    mut raw_request: gql::http::RawRequest,
     project_id: Option<&ProjectId>,
+    response_cache: Option<&ResponseCache>,
+    persisted_queries: Option<&PersistedQueries>,
+    http_method: &http::Method,
 ) -> Result<(ast::OperationType, GraphQLResponse), crate::RequestError> {
+    let start = Instant::now();
+    // fill in the query of a request that only sends the hash of a persisted query
+    resolve_persisted_query(persisted_queries, &mut raw_request)?;
     let tracer = tracing_util::global_tracer();
     tracer
         .in_span_async(
@@ -71,6 +92,46 @@ pub async fn execute_query_internal(
                     // parse the raw request into a GQL query
                     let query = steps::parse_query(&raw_request.query)?;
 
+                    // restrict roles in the allow-list to the operations it gives
+                    check_allow_list(
+                        metadata.graphql_config.allow_list.as_ref(),
+                        session,
+                        &raw_request,
+                        &query,
+                    )?;
+
+                    // GET requests can be cached along the way, so they must not have side effects
+                    if *http_method == http::Method::GET
+                        && steps::selected_operation(&query, raw_request.operation_name.as_ref())
+                            .is_some_and(|operation| operation.ty == ast::OperationType::Mutation)
+                    {
+                        return Err(crate::RequestError::MutationOverGet);
+                    }
+
+                    // mutations marked with `@transactional` must be applied in one transaction
+                    let transactional =
+                        transaction::is_transactional(&query, raw_request.operation_name.as_ref())?;
+
+                    // serve queries marked with `@cached` from the response cache
+                    let cache_entry = match response_cache {
+                        Some(response_cache) => CacheEntry::new(
+                            response_cache,
+                            &query,
+                            &raw_request,
+                            session,
+                            request_headers,
+                        )?,
+                        None => None,
+                    };
+                    if let Some(cache_entry) = &cache_entry
+                        && let Some(response) = cache_entry.get().await
+                    {
+                        return Ok((
+                            ast::OperationType::Query,
+                            GraphQLResponse::from_response(response),
+                        ));
+                    }
+
                     // normalize the parsed GQL query
                     let normalized_request = steps::normalize_request(
                         schema,
@@ -80,6 +141,13 @@ pub async fn execute_query_internal(
                         &metadata.runtime_flags,
                     )?;
 
+                    // reject operations larger than the limits for the role
+                    check_operation_limits(
+                        metadata.graphql_config.limits.as_ref(),
+                        session,
+                        &normalized_request,
+                    )?;
+
                     // generate IR
                     let ir = steps::build_ir(
                         schema,
@@ -89,9 +157,14 @@ pub async fn execute_query_internal(
                         &normalized_request,
                     )?;
 
+                    let usage_counts = graphql_ir::get_all_usage_counts_in_query(&ir);
+
                     // construct a plan to execute the request
                     let request_plan =
-                        steps::build_request_plan(&ir, metadata, session, request_headers)?;
+                        plan_request(&ir, metadata, session, request_headers, transactional)
+                            .inspect_err(|_| {
+                                record_usage_metrics(&usage_counts, "error", start.elapsed());
+                            })?;
 
                     let display_name = match normalized_request.name {
                         Some(ref name) => std::borrow::Cow::Owned(format!("Execute {name}")),
@@ -165,6 +238,14 @@ pub async fn execute_query_internal(
                     // parent spans of error-ing spans are also error? Then handle in the tracing
                     // code.
                     set_status_on_current_span(&response);
+                    record_usage_metrics(
+                        &usage_counts,
+                        response_status(&response),
+                        start.elapsed(),
+                    );
+                    if let Some(cache_entry) = cache_entry {
+                        cache_entry.set(response.as_inner()).await;
+                    }
                     Ok((normalized_request.ty, response))
                 })
             },
@@ -231,3 +312,61 @@ pub fn set_usage_attributes<'s, 'n>(
         }
     }
 }
+
+/// Record the duration of a request by its operation type, and whether the response has errors.
+/// Requests that fail before their operation type is known are recorded as `unknown`.
+fn record_request_metrics(
+    operation_type: Option<&ast::OperationType>,
+    response: &GraphQLResponse,
+    duration: Duration,
+) {
+    let operation_type = match operation_type {
+        Some(ast::OperationType::Query) => "query",
+        Some(ast::OperationType::Mutation) => "mutation",
+        Some(ast::OperationType::Subscription) => "subscription",
+        None => "unknown",
+    };
+    metrics_util::GRAPHQL_REQUEST_DURATION
+        .observe(&[operation_type, response_status(response)], duration);
+}
+
+/// The `status` label of a request's metrics
+fn response_status(response: &GraphQLResponse) -> &'static str {
+    if response.does_contain_error() {
+        "error"
+    } else {
+        "success"
+    }
+}
+
+/// Record the duration of a request against every model and command it uses. Requests that
+/// fail after the models and commands they use are known, ie while planning or executing, are
+/// recorded with an `error` status.
+fn record_usage_metrics(usage_counts: &plan_types::UsagesCounts, status: &str, duration: Duration) {
+    for model_count in &usage_counts.models_used {
+        let subgraph = model_count.model.subgraph.to_string();
+        let model = model_count.model.name.as_str();
+        metrics_util::MODEL_REQUEST_DURATION.observe(&[&subgraph, model, status], duration);
+    }
+    for command_count in &usage_counts.commands_used {
+        let subgraph = command_count.command.subgraph.to_string();
+        let command = command_count.command.name.as_str();
+        metrics_util::COMMAND_REQUEST_DURATION.observe(&[&subgraph, command, status], duration);
+    }
+}
+
+/// Plans a request, checking that a mutation marked with `@transactional` can be applied in one
+/// transaction
+fn plan_request<'n, 's, 'ir>(
+    ir: &'ir graphql_ir::IR<'n, 's>,
+    metadata: &'s metadata_resolve::Metadata,
+    session: &Session,
+    request_headers: &http::HeaderMap,
+    transactional: bool,
+) -> Result<graphql_ir::RequestPlan<'n, 's, 'ir>, crate::RequestError> {
+    let request_plan = steps::build_request_plan(ir, metadata, session, request_headers)?;
+    if transactional && let graphql_ir::RequestPlan::MutationPlan(mutation_plan) = &request_plan {
+        transaction::check_transactional(mutation_plan)?;
+    }
+    Ok(request_plan)
+}
@@ -214,6 +214,11 @@ fn analyze_model_input_annotation(
             parent_type,
             deprecated,
             ..
+        }
+        | graphql_schema::ModelInputAnnotation::ModelStreamCursorField {
+            field_name,
+            parent_type,
+            deprecated,
         } => {
             let DeprecatedDetails {
                 is_deprecated,
@@ -254,7 +259,11 @@ fn analyze_model_input_annotation(
         | graphql_schema::ModelInputAnnotation::ModelLimitArgument
         | graphql_schema::ModelInputAnnotation::ModelOffsetArgument
         | graphql_schema::ModelInputAnnotation::ModelUniqueIdentifierArgument { .. }
-        | graphql_schema::ModelInputAnnotation::ModelFilterInputArgument => {}
+        | graphql_schema::ModelInputAnnotation::ModelFilterInputArgument
+        | graphql_schema::ModelInputAnnotation::ModelGroupByExtractionArgument
+        | graphql_schema::ModelInputAnnotation::ModelGroupByExtractionFunction { .. }
+        | graphql_schema::ModelInputAnnotation::ModelStreamBatchSizeArgument
+        | graphql_schema::ModelInputAnnotation::ModelStreamCursorArgument => {}
     }
     result
 }
@@ -352,10 +361,33 @@ fn analyze_output_annotation(annotation: &graphql_schema::OutputAnnotation) -> V
                 deprecated_reason: reason,
             }));
         }
+        graphql_schema::OutputAnnotation::GroupBy(
+            graphql_schema::GroupByOutputAnnotation::Dimension {
+                field_name,
+                parent_type,
+                deprecated,
+                ..
+            },
+        ) => {
+            let DeprecatedDetails {
+                is_deprecated,
+                reason,
+            } = get_deprecated_details(deprecated.as_ref());
+            result.push(OpenddObject::Field(FieldUsage {
+                name: field_name.to_owned(),
+                opendd_type: parent_type.to_owned(),
+                deprecated: is_deprecated,
+                deprecated_reason: reason,
+            }));
+        }
         graphql_schema::OutputAnnotation::GlobalIDField { .. }
         | graphql_schema::OutputAnnotation::RelayNodeInterfaceID { .. }
         | graphql_schema::OutputAnnotation::SDL
-        | graphql_schema::OutputAnnotation::Aggregate(_) => {}
+        | graphql_schema::OutputAnnotation::Aggregate(_)
+        | graphql_schema::OutputAnnotation::GroupBy(
+            graphql_schema::GroupByOutputAnnotation::Dimensions
+            | graphql_schema::GroupByOutputAnnotation::Aggregates,
+        ) => {}
     }
     result
 }
@@ -372,6 +404,7 @@ fn analyze_namespace_annotation(
             filter,
             argument_presets,
             allow_subscriptions: _,
+            row_limits: _,
         } => {
             result.extend(analyze_filter_permission(filter));
             result.extend(analyze_argument_presets(argument_presets));
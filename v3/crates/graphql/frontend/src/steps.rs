@@ -33,6 +33,39 @@ pub fn parse_query(
     Ok(query)
 }
 
+/// The operation of a parsed GQL query to execute: the one named `operation_name`, or the only
+/// operation if no name is given. Normalization reports the error when there is none.
+pub(crate) fn selected_operation<'a>(
+    document: &'a gql::ast::executable::ExecutableDocument,
+    operation_name: Option<&ast::Name>,
+) -> Option<&'a gql::ast::executable::OperationDefinition> {
+    let mut operations = document.items.iter().filter_map(|item| match &item.item {
+        gql::ast::executable::ExecutableDefinition::Operation(operation) => Some(operation),
+        gql::ast::executable::ExecutableDefinition::Fragment(_) => None,
+    });
+    match operation_name {
+        Some(operation_name) => operations.find(|operation| {
+            operation
+                .name
+                .as_ref()
+                .is_some_and(|name| &name.item == operation_name)
+        }),
+        None => operations.next().filter(|_| operations.next().is_none()),
+    }
+}
+
+/// Whether executing a request may run a mutation. Requests whose operation is not known before
+/// they are executed, such as those sending only the hash of a persisted query, are assumed to.
+pub fn may_mutate(raw_request: &gql::http::RawRequest) -> bool {
+    gql::parser::Parser::new(&raw_request.query)
+        .parse_executable_document()
+        .ok()
+        .is_none_or(|document| {
+            selected_operation(&document, raw_request.operation_name.as_ref())
+                .is_none_or(|operation| operation.ty == ast::OperationType::Mutation)
+        })
+}
+
 /// Normalize the parsed GQL query
 pub fn normalize_request<'s>(
     schema: &'s gql::schema::Schema<GDS>,
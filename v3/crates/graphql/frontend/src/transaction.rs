@@ -0,0 +1,142 @@
+//! Requiring the root fields of a mutation operation to be applied atomically.
+//!
+//! Consecutive root fields of a mutation that go to the same data connector are sent to it in one
+//! transactional request, if it supports transactional mutations. A mutation operation can insist
+//! that all of its root fields are applied together or not at all with the `@transactional`
+//! directive:
+//!
+//! ```graphql
+//! mutation InsertAuthorAndArticles @transactional { ... }
+//! ```
+//!
+//! Such an operation is rejected before anything is executed if its root fields can't be sent to
+//! a data connector in one transaction.
+
+use lang_graphql::ast::common as ast;
+use lang_graphql::ast::executable::ExecutableDocument;
+use metadata_resolve::Qualified;
+use open_dds::data_connector::DataConnectorName;
+
+const TRANSACTIONAL_DIRECTIVE: &str = "transactional";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionDirectiveError {
+    #[error("the @transactional directive may only be given once")]
+    DuplicateDirective,
+    #[error("unexpected argument {0} to the @transactional directive")]
+    UnexpectedArgument(ast::Name),
+    #[error(
+        "a @transactional mutation can only run mutations on a single data connector, in one transaction"
+    )]
+    MultipleDataConnectors,
+    #[error(
+        "a @transactional mutation with several root fields requires data connector {data_connector} to support transactional mutations"
+    )]
+    NonTransactionalDataConnector {
+        data_connector: Qualified<DataConnectorName>,
+    },
+}
+
+/// Whether the operation to execute is a mutation with a `@transactional` directive.
+pub(crate) fn is_transactional(
+    document: &ExecutableDocument,
+    operation_name: Option<&ast::Name>,
+) -> Result<bool, TransactionDirectiveError> {
+    let Some(operation) = crate::steps::selected_operation(document, operation_name)
+        .filter(|operation| operation.ty == ast::OperationType::Mutation)
+    else {
+        return Ok(false);
+    };
+
+    let mut directives = operation
+        .directives
+        .iter()
+        .filter(|directive| directive.item.name.item.as_str() == TRANSACTIONAL_DIRECTIVE);
+    let Some(directive) = directives.next() else {
+        return Ok(false);
+    };
+    if directives.next().is_some() {
+        return Err(TransactionDirectiveError::DuplicateDirective);
+    }
+    if let Some(argument) = directive
+        .item
+        .arguments
+        .iter()
+        .flat_map(|arguments| &arguments.item)
+        .next()
+    {
+        return Err(TransactionDirectiveError::UnexpectedArgument(
+            argument.item.key.item.clone(),
+        ));
+    }
+    Ok(true)
+}
+
+/// Checks that the root fields of a `@transactional` mutation are all applied in one
+/// transaction: they go to a single data connector, which supports transactional mutations
+/// unless there is only one root field.
+pub(crate) fn check_transactional(
+    mutation_plan: &graphql_ir::MutationPlan<'_, '_>,
+) -> Result<(), TransactionDirectiveError> {
+    match mutation_plan.nodes.as_slice() {
+        [] => Ok(()),
+        [mutation_group] => {
+            if mutation_group.fields.len() > 1 && !mutation_group.is_transactional() {
+                Err(TransactionDirectiveError::NonTransactionalDataConnector {
+                    data_connector: mutation_group.data_connector.name.clone(),
+                })
+            } else {
+                Ok(())
+            }
+        }
+        _ => Err(TransactionDirectiveError::MultipleDataConnectors),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transactional(query: &str, operation_name: Option<&str>) -> Result<bool, String> {
+        let document = lang_graphql::parser::Parser::new(query)
+            .parse_executable_document()
+            .unwrap();
+        let operation_name = operation_name.map(|name| ast::Name::new(name).unwrap());
+        is_transactional(&document, operation_name.as_ref()).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_is_transactional() {
+        assert_eq!(transactional("mutation { a }", None), Ok(false));
+        assert_eq!(
+            transactional("mutation @transactional { a }", None),
+            Ok(true)
+        );
+        assert_eq!(
+            transactional(
+                "mutation A @transactional { a } mutation B { b }",
+                Some("A")
+            ),
+            Ok(true)
+        );
+        assert_eq!(
+            transactional(
+                "mutation A @transactional { a } mutation B { b }",
+                Some("B")
+            ),
+            Ok(false)
+        );
+        assert_eq!(transactional("query @transactional { a }", None), Ok(false));
+        assert_eq!(
+            transactional("mutation @transactional @transactional { a }", None),
+            Err(TransactionDirectiveError::DuplicateDirective.to_string())
+        );
+        assert_eq!(
+            transactional(
+                "mutation @transactional(isolation: SERIALIZABLE) { a }",
+                None
+            ),
+            Err("unexpected argument isolation to the @transactional directive".to_string())
+        );
+    }
+}
@@ -40,10 +40,17 @@ impl GraphQLResponse {
     }
 
     pub fn from_error(err: &RequestError, expose_internal_errors: ExposeInternalErrors) -> Self {
-        Self(Response::error(
-            err.to_graphql_error(expose_internal_errors),
-            axum::http::HeaderMap::default(),
-        ))
+        let graphql_error = err.to_graphql_error(expose_internal_errors);
+        match err {
+            RequestError::MutationOverGet => Self(Response::error_with_status(
+                axum::http::StatusCode::METHOD_NOT_ALLOWED,
+                graphql_error,
+            )),
+            _ => Self(Response::error(
+                graphql_error,
+                axum::http::HeaderMap::default(),
+            )),
+        }
     }
 
     pub fn from_response(response: gql::http::Response) -> Self {
@@ -69,6 +76,10 @@ impl GraphQLResponse {
     pub fn inner(self) -> gql::http::Response {
         self.0
     }
+
+    pub(crate) fn as_inner(&self) -> &gql::http::Response {
+        &self.0
+    }
 }
 
 /// Implement traceable for GraphQL Response
@@ -4,6 +4,7 @@ pub(crate) mod protocol;
 pub(crate) mod websocket;
 
 pub use metrics::{NoOpWebSocketMetrics, WebSocketMetrics};
+pub use poller::cohort::SubscriptionCohorts;
 pub use protocol::types::OperationId;
 pub use websocket::{
     WebSocketServer,
@@ -13,6 +13,12 @@ pub trait WebSocketMetrics: Clone + Send + Sync + 'static {
 
     /// Records the stop of a poller associated with a specific WebSocket.
     fn record_poller_stop(&self, websocket_id: &WebSocketId);
+
+    /// Records the start of a poller shared by a cohort of identical subscriptions.
+    fn record_cohort_start(&self);
+
+    /// Records the stop of a poller shared by a cohort of identical subscriptions.
+    fn record_cohort_stop(&self);
 }
 
 /// A no-operation implementation of `WebSocketMetrics`
@@ -25,4 +31,6 @@ impl WebSocketMetrics for NoOpWebSocketMetrics {
     fn record_connection_drop(&self) {}
     fn record_poller_start(&self, _websocket_id: &WebSocketId) {}
     fn record_poller_stop(&self, _websocket_id: &WebSocketId) {}
+    fn record_cohort_start(&self) {}
+    fn record_cohort_stop(&self) {}
 }
@@ -0,0 +1,179 @@
+//! Multiplexed subscription polling.
+//!
+//! Subscribers whose polls would send the same request to the same data
+//! connector are grouped into a cohort, which runs a single polling loop and
+//! fans every result out to all of its subscribers.
+//!
+//! A cohort is keyed on the query request along with the session variables and
+//! headers that can reach the data connector and its plugins on a poll, as
+//! worked out for the response cache by [`graphql_frontend::KeyInputs`]. Other
+//! headers, such as the per-connection `Sec-WebSocket-Key`, do not split
+//! otherwise identical subscribers into separate cohorts.
+
+use axum::http;
+use blake2::{Blake2b, Digest};
+use hasura_authn_core::Session;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use tokio::sync::watch;
+
+use super::Poller;
+
+/// The result of a single poll, shared between all subscribers of a cohort.
+pub type PollResult = Result<execute::ndc::NdcQueryResponse, execute::FieldError>;
+
+/// Receives the latest poll result of a cohort. The value is `None` until the
+/// first poll completes.
+pub type PollResultReceiver = watch::Receiver<Option<Arc<PollResult>>>;
+
+/// Sends poll results to the subscribers of a cohort.
+pub type PollResultSender = watch::Sender<Option<Arc<PollResult>>>;
+
+/// Identifies the cohort a subscriber belongs to.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct CohortKey([u8; 64]);
+
+impl CohortKey {
+    pub fn new(
+        raw_request: &lang_graphql::http::RawRequest,
+        session: &Session,
+        headers: &http::HeaderMap,
+        key_inputs: &graphql_frontend::KeyInputs,
+        query_request: &execute::ndc::NdcQueryRequest,
+        data_connector: &metadata_resolve::DataConnectorLink,
+        polling_interval_ms: u64,
+    ) -> Self {
+        // The GraphQL request determines how the response is processed for the
+        // subscriber, while the role determines the schema it is processed against.
+        // Only the session variables and headers that can reach the data
+        // connector and its plugins can make the polls differ.
+        let (session_variables, headers) = key_inputs.collect(session, headers);
+        let request = serde_json::to_vec(&(
+            raw_request,
+            &session.role,
+            session_variables,
+            headers,
+            &data_connector.name,
+            query_request,
+            polling_interval_ms,
+        ))
+        .unwrap_or_default();
+        let mut hasher = Blake2b::new();
+        hasher.update(&request);
+        Self(hasher.finalize().into())
+    }
+}
+
+/// A group of subscribers sharing a single poller. The poller is stopped when
+/// the last subscriber drops its handle to the cohort.
+pub struct Cohort {
+    results: PollResultReceiver,
+    poller: Poller,
+}
+
+impl Cohort {
+    /// Returns a receiver for the poll results of this cohort. If a poll has
+    /// already completed, its result is immediately available to the receiver.
+    pub fn subscribe(&self) -> PollResultReceiver {
+        self.results.clone()
+    }
+}
+
+impl Drop for Cohort {
+    fn drop(&mut self) {
+        self.poller.handle.abort();
+    }
+}
+
+/// The cohorts of all active subscriptions on a server.
+#[derive(Clone, Default)] // Cheap to clone as the cohorts are wrapped in `Arc`
+pub struct SubscriptionCohorts(Arc<Mutex<HashMap<CohortKey, Weak<Cohort>>>>);
+
+impl SubscriptionCohorts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Joins the cohort for the given key. If no such cohort exists, a new one
+    /// is created and `start_polling` is spawned to publish its poll results.
+    pub fn join<F>(&self, key: CohortKey, start_polling: F) -> Arc<Cohort>
+    where
+        F: FnOnce(PollResultSender) -> Pin<Box<dyn Future<Output = ()> + Send>>,
+    {
+        let mut cohorts = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        // A cohort whose poller has stopped, e.g. after an error, will not publish
+        // any more results, so new subscribers start a fresh one
+        if let Some(cohort) = cohorts.get(&key).and_then(Weak::upgrade)
+            && !cohort.poller.handle.is_finished()
+        {
+            return cohort;
+        }
+        // Forget cohorts whose subscribers have all left
+        cohorts.retain(|_, cohort| cohort.strong_count() > 0);
+        let (sender, results) = watch::channel(None);
+        let cohort = Arc::new(Cohort {
+            results,
+            poller: Poller::new(|| start_polling(sender)),
+        });
+        cohorts.insert(key, Arc::downgrade(&cohort));
+        cohort
+    }
+
+    /// Returns the number of cohorts with at least one subscriber.
+    pub fn active_cohorts(&self) -> usize {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .filter(|cohort| cohort.strong_count() > 0)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cohort_key(byte: u8) -> CohortKey {
+        CohortKey([byte; 64])
+    }
+
+    #[tokio::test]
+    async fn test_identical_subscribers_share_a_poller() {
+        let cohorts = SubscriptionCohorts::new();
+        let first = cohorts.join(cohort_key(1), |sender| {
+            Box::pin(async move { sender.closed().await })
+        });
+        let second = cohorts.join(cohort_key(1), |_| {
+            panic!("a second poller was started for the same cohort")
+        });
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cohorts.active_cohorts(), 1);
+
+        let other = cohorts.join(cohort_key(2), |sender| {
+            Box::pin(async move { sender.closed().await })
+        });
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(cohorts.active_cohorts(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stopped_cohort_is_not_joined() {
+        let cohorts = SubscriptionCohorts::new();
+        // A poller that stops straight away, as it does after a poll error
+        let stopped = cohorts.join(cohort_key(1), |_sender| Box::pin(async {}));
+        while !stopped.poller.handle.is_finished() {
+            tokio::task::yield_now().await;
+        }
+
+        let mut restarted = false;
+        let joined = cohorts.join(cohort_key(1), |sender| {
+            restarted = true;
+            Box::pin(async move { sender.closed().await })
+        });
+        assert!(restarted);
+        assert!(!Arc::ptr_eq(&stopped, &joined));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod cohort;
+
 use std::future::Future;
 use std::pin::Pin;
 use tokio::task;
@@ -1,6 +1,7 @@
 use super::types::{ConnectionInitState, OperationId, ServerMessage};
 use crate::metrics::WebSocketMetrics;
 use crate::poller;
+use crate::poller::cohort::{CohortKey, PollResult, PollResultSender};
 use crate::websocket::types as ws;
 use ::pre_response_plugin::execute::PreResponsePluginResponse;
 use axum::http;
@@ -188,7 +189,7 @@ fn start_poller<M: WebSocketMetrics>(
     parent_span_link: tracing_util::SpanLink,
     runtime_flags: metadata_resolve::flags::RuntimeFlags,
 ) -> poller::Poller {
-    // Record the start of the poller in the metrics.
+    // Record the start of the operation in the metrics.
     connection
         .context
         .metrics
@@ -292,16 +293,34 @@ pub async fn execute_query_internal<M: WebSocketMetrics>(
     session: Session,
     headers: http::HeaderMap,
     connection: &ws::Connection<M>,
-    raw_request: lang_graphql::http::RawRequest,
+    mut raw_request: lang_graphql::http::RawRequest,
     runtime_flags: &metadata_resolve::flags::RuntimeFlags,
 ) -> Result<(), graphql_frontend::RequestError> {
     let schema = &connection.context.schema;
     let metadata = &connection.context.metadata;
+    // Fill in the query of a request that only sends the hash of a persisted query.
+    graphql_frontend::resolve_persisted_query(
+        connection.context.persisted_queries.as_deref(),
+        &mut raw_request,
+    )?;
     // Parse the raw GraphQL request.
     let query = graphql_frontend::parse_query(&raw_request.query)?;
+    // Restrict roles in the allow-list to the operations it gives.
+    graphql_frontend::check_allow_list(
+        metadata.graphql_config.allow_list.as_ref(),
+        &session,
+        &raw_request,
+        &query,
+    )?;
     // Normalize the parsed GraphQL query.
     let normalized_request =
         graphql_frontend::normalize_request(schema, &session, query, &raw_request, runtime_flags)?;
+    // Reject operations larger than the limits for the role.
+    graphql_frontend::check_operation_limits(
+        metadata.graphql_config.limits.as_ref(),
+        &session,
+        &normalized_request,
+    )?;
 
     // Generate Intermediate Representation (IR) from the query.
     let ir = graphql_frontend::build_ir(schema, metadata, &session, &headers, &normalized_request)?;
@@ -409,113 +428,39 @@ async fn execute<M: WebSocketMetrics>(
         // Handle subscriptions by starting a polling loop to repeatedly fetch data.
         RequestPlan::SubscriptionPlan(alias, plan) => {
             match execute::resolve_ndc_subscription_execution(plan.subscription_execution).await {
-                Ok(ndc_subscription) => {
-                    let query_request = ndc_subscription.query_request;
-                    let data_connector = ndc_subscription.data_connector;
-                    let selection_set = plan.selection_set;
-                    let process_response_as = ndc_subscription.process_response_as;
-                    let is_nullable = process_response_as.is_nullable();
-                    let polling_interval_duration =
-                        tokio::time::Duration::from_millis(ndc_subscription.polling_interval_ms);
-
-                    // Initialize a response hash to track changes in the response.
-                    let mut response_hash = ResponseHash::new();
-
-                    let tracer = tracing_util::global_tracer();
-                    let this_span_link = tracing_util::SpanLink::from_current_span();
-
-                    // A loop to periodically wait for the polling interval, then fetch data from NDC.
-                    loop {
-                        let result: Result<_, execute::FieldError> = tracer
-                            .new_trace_async_with_link(
-                                "websocket_poll_subscription",
-                                "Polling a subscription query",
-                                tracing_util::SpanVisibility::User,
-                                this_span_link.clone(),
-                                || {
-                                    tracing_util::set_attribute_on_active_span(
-                                        tracing_util::AttributeVisibility::Default,
-                                        "graphql.operation.id",
-                                        operation_id.0.clone(),
-                                    );
-                                    Box::pin(async {
-                                        // Fetch response from the connector
-                                        let response = execute::fetch_from_data_connector(
-                                            http_context,
-                                            plugins,
-                                            &session,
-                                            &headers,
-                                            &query_request,
-                                            &data_connector,
-                                            None,
-                                        )
-                                        .await?;
-                                        // Process response
-                                        let response_rowsets = response.as_latest_rowsets();
-                                        let processed_response = process_response(
-                                            selection_set,
-                                            response_rowsets,
-                                            &process_response_as,
-                                        );
-                                        let root_fields = IndexMap::from([(
-                                            alias.clone(),
-                                            RootFieldResult::from_processed_response(
-                                                is_nullable,
-                                                processed_response,
-                                            ),
-                                        )]);
-                                        // Generate a single root field query response
-                                        let query_result = ExecuteQueryResult { root_fields };
-
-                                        let graphql_response =
-                                            graphql_frontend::GraphQLResponse::from_result(
-                                                query_result,
-                                                expose_internal_errors,
-                                            )
-                                            .inner();
-                                        // Send the response
-                                        let stop_subscription =
-                                            send_subscription_operation_response(
-                                                client_address,
-                                                &mut response_hash,
-                                                operation_id.clone(),
-                                                &raw_request,
-                                                &session,
-                                                &headers,
-                                                graphql_response,
-                                                connection,
-                                            )
-                                            .await;
-                                        Ok(stop_subscription)
-                                    })
-                                },
-                            )
-                            .await;
-
-                        match result {
-                            Ok(stop_subscription) => {
-                                // Stop the subscription, if only errors sent in the current response
-                                if stop_subscription {
-                                    break;
-                                }
-                            }
-                            Err(err) => {
-                                // Send the exception as a GraphQL error and stop polling
-                                let graphql_error =
-                                    err.to_graphql_error(expose_internal_errors, None);
-                                send_graphql_errors(
-                                    operation_id,
-                                    NonEmpty::new(graphql_error),
-                                    connection,
-                                )
-                                .await;
-                                break;
-                            }
-                        }
-                        // Wait for the polling interval
-                        tokio::time::sleep(polling_interval_duration).await;
+                Ok(mut ndc_subscription) => match ndc_subscription.stream.take() {
+                    // Streams return a different batch of rows on every poll, so every
+                    // subscriber polls on its own.
+                    Some(stream) => {
+                        execute_stream_subscription(
+                            client_address,
+                            operation_id,
+                            connection,
+                            &session,
+                            &headers,
+                            &raw_request,
+                            &alias,
+                            plan.selection_set,
+                            ndc_subscription,
+                            stream,
+                        )
+                        .await;
                     }
-                }
+                    None => {
+                        execute_cohort_subscription(
+                            client_address,
+                            operation_id,
+                            connection,
+                            &session,
+                            &headers,
+                            &raw_request,
+                            &alias,
+                            plan.selection_set,
+                            ndc_subscription,
+                        )
+                        .await;
+                    }
+                },
                 // Send an error message if the subscription fails to resolve.
                 Err(e) => {
                     let graphql_error = e.to_graphql_error(expose_internal_errors, None);
@@ -527,6 +472,287 @@ async fn execute<M: WebSocketMetrics>(
     }
 }
 
+/// Joins the cohort of identical subscribers, starting its poller if this is the
+/// first subscriber, and sends every changed poll result to the client.
+async fn execute_cohort_subscription<M: WebSocketMetrics>(
+    client_address: std::net::SocketAddr,
+    operation_id: OperationId,
+    connection: &ws::Connection<M>,
+    session: &Session,
+    headers: &http::HeaderMap,
+    raw_request: &lang_graphql::http::RawRequest,
+    alias: &lang_graphql::ast::common::Alias,
+    selection_set: &lang_graphql::normalized_ast::SelectionSet<'_, graphql_schema::GDS>,
+    ndc_subscription: execute::NDCSubscriptionQuery,
+) {
+    let expose_internal_errors = connection.context.expose_internal_errors;
+    let process_response_as = ndc_subscription.process_response_as;
+    let cohort_key = CohortKey::new(
+        raw_request,
+        session,
+        headers,
+        &connection.context.key_inputs,
+        &ndc_subscription.query_request,
+        &ndc_subscription.data_connector,
+        ndc_subscription.polling_interval_ms,
+    );
+    let cohort = connection
+        .context
+        .subscription_cohorts
+        .join(cohort_key, |sender| {
+            poll_subscription(
+                sender,
+                connection.context.metrics.clone(),
+                operation_id.clone(),
+                connection.context.http_context.clone(),
+                connection.context.metadata.clone(),
+                session.clone(),
+                headers.clone(),
+                ndc_subscription.query_request,
+                ndc_subscription.data_connector,
+                ndc_subscription.polling_interval_ms,
+                tracing_util::SpanLink::from_current_span(),
+            )
+        });
+    let mut poll_results = cohort.subscribe();
+
+    // Initialize a response hash to track changes in the response.
+    let mut response_hash = ResponseHash::new();
+
+    // Wait for the cohort to publish a poll result, then send it to the client.
+    while poll_results.changed().await.is_ok() {
+        let Some(poll_result) = poll_results.borrow_and_update().clone() else {
+            continue;
+        };
+        match poll_result.as_ref() {
+            Ok(response) => {
+                let graphql_response = make_subscription_response(
+                    alias,
+                    selection_set,
+                    response.clone().as_latest_rowsets(),
+                    &process_response_as,
+                    expose_internal_errors,
+                );
+                // Only send the response if it changed since the last one sent
+                if response_hash.matches(&graphql_response) {
+                    continue;
+                }
+                let stop_subscription = send_subscription_operation_response(
+                    client_address,
+                    operation_id.clone(),
+                    raw_request,
+                    session,
+                    headers,
+                    graphql_response,
+                    connection,
+                )
+                .await;
+                // Stop the subscription, if only errors sent in the current response
+                if stop_subscription {
+                    break;
+                }
+            }
+            Err(err) => {
+                // Send the exception as a GraphQL error and stop the subscription
+                let graphql_error = err.to_graphql_error(expose_internal_errors, None);
+                send_graphql_errors(operation_id, NonEmpty::new(graphql_error), connection).await;
+                break;
+            }
+        }
+    }
+}
+
+/// Polls a streaming subscription, sending every non-empty batch of rows to the
+/// client and moving the cursor past it. Polling stops after the first error.
+async fn execute_stream_subscription<M: WebSocketMetrics>(
+    client_address: std::net::SocketAddr,
+    operation_id: OperationId,
+    connection: &ws::Connection<M>,
+    session: &Session,
+    headers: &http::HeaderMap,
+    raw_request: &lang_graphql::http::RawRequest,
+    alias: &lang_graphql::ast::common::Alias,
+    selection_set: &lang_graphql::normalized_ast::SelectionSet<'_, graphql_schema::GDS>,
+    ndc_subscription: execute::NDCSubscriptionQuery,
+    mut stream: execute::NDCSubscriptionStream,
+) {
+    let expose_internal_errors = connection.context.expose_internal_errors;
+    let polling_interval_duration =
+        tokio::time::Duration::from_millis(ndc_subscription.polling_interval_ms);
+    let parent_span_link = tracing_util::SpanLink::from_current_span();
+    let tracer = tracing_util::global_tracer();
+    let mut query_request = ndc_subscription.query_request;
+    // A loop to periodically fetch the next batch from NDC, then wait for the polling interval.
+    loop {
+        let result = tracer
+            .new_trace_async_with_link(
+                "websocket_poll_stream_subscription",
+                "Polling a streaming subscription query",
+                tracing_util::SpanVisibility::User,
+                parent_span_link.clone(),
+                || {
+                    Box::pin(async {
+                        // Fetch response from the connector
+                        execute::fetch_from_data_connector(
+                            &connection.context.http_context,
+                            &connection.context.metadata.plugin_configs,
+                            session,
+                            headers,
+                            &query_request,
+                            &ndc_subscription.data_connector,
+                            None,
+                        )
+                        .await
+                        .map_err(execute::FieldError::from)
+                    })
+                },
+            )
+            .await;
+        let next_query_request = result.and_then(|response| {
+            let rowsets = response.as_latest_rowsets();
+            // An empty batch means no rows were added after the cursor since the last poll
+            let batch_size = stream.advance(&rowsets);
+            stream
+                .query_request()
+                .map(|query_request| (query_request, batch_size, rowsets))
+        });
+        match next_query_request {
+            Ok((next_query_request, batch_size, rowsets)) => {
+                query_request = next_query_request;
+                if batch_size > 0 {
+                    let graphql_response = make_subscription_response(
+                        alias,
+                        selection_set,
+                        rowsets,
+                        &ndc_subscription.process_response_as,
+                        expose_internal_errors,
+                    );
+                    let stop_subscription = send_subscription_operation_response(
+                        client_address,
+                        operation_id.clone(),
+                        raw_request,
+                        session,
+                        headers,
+                        graphql_response,
+                        connection,
+                    )
+                    .await;
+                    // Stop the subscription, if only errors sent in the current response
+                    if stop_subscription {
+                        break;
+                    }
+                }
+            }
+            Err(err) => {
+                // Send the exception as a GraphQL error and stop the subscription
+                let graphql_error = err.to_graphql_error(expose_internal_errors, None);
+                send_graphql_errors(operation_id, NonEmpty::new(graphql_error), connection).await;
+                break;
+            }
+        }
+        // Wait for the polling interval
+        tokio::time::sleep(polling_interval_duration).await;
+    }
+}
+
+/// Processes the rows fetched for a subscription into a single root field
+/// GraphQL response.
+fn make_subscription_response(
+    alias: &lang_graphql::ast::common::Alias,
+    selection_set: &lang_graphql::normalized_ast::SelectionSet<'_, graphql_schema::GDS>,
+    rowsets: Vec<ndc_models::RowSet>,
+    process_response_as: &plan_types::ProcessResponseAs,
+    expose_internal_errors: ExposeInternalErrors,
+) -> lang_graphql::http::Response {
+    let processed_response = process_response(selection_set, rowsets, process_response_as);
+    let root_fields = IndexMap::from([(
+        alias.clone(),
+        RootFieldResult::from_processed_response(
+            process_response_as.is_nullable(),
+            processed_response,
+        ),
+    )]);
+    // Generate a single root field query response
+    let query_result = ExecuteQueryResult { root_fields };
+    graphql_frontend::GraphQLResponse::from_result(query_result, expose_internal_errors).inner()
+}
+
+/// Records the stop of a cohort's poller when it is dropped, whether its
+/// polling loop ended or was aborted.
+struct RecordCohortStop<M: WebSocketMetrics>(M);
+
+impl<M: WebSocketMetrics> Drop for RecordCohortStop<M> {
+    fn drop(&mut self) {
+        self.0.record_cohort_stop();
+    }
+}
+
+/// Polls a subscription query on behalf of a cohort of subscribers, publishing
+/// every result to the cohort. Polling stops after the first error, or when the
+/// cohort is dropped along with its last subscriber. The poll spans carry the
+/// operation id of the subscriber that started the cohort.
+fn poll_subscription<M: WebSocketMetrics>(
+    sender: PollResultSender,
+    metrics: M,
+    operation_id: OperationId,
+    http_context: engine_types::HttpContext,
+    metadata: std::sync::Arc<metadata_resolve::Metadata>,
+    session: Session,
+    headers: http::HeaderMap,
+    query_request: execute::ndc::NdcQueryRequest,
+    data_connector: std::sync::Arc<metadata_resolve::DataConnectorLink>,
+    polling_interval_ms: u64,
+    parent_span_link: tracing_util::SpanLink,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        // The guard records the stop whether the loop ends after an error or
+        // this future is aborted along with its cohort
+        metrics.record_cohort_start();
+        let _record_cohort_stop = RecordCohortStop(metrics);
+        let polling_interval_duration = tokio::time::Duration::from_millis(polling_interval_ms);
+        let tracer = tracing_util::global_tracer();
+        // A loop to periodically fetch data from NDC, then wait for the polling interval.
+        loop {
+            let result: PollResult = tracer
+                .new_trace_async_with_link(
+                    "websocket_poll_subscription",
+                    "Polling a subscription query",
+                    tracing_util::SpanVisibility::User,
+                    parent_span_link.clone(),
+                    || {
+                        tracing_util::set_attribute_on_active_span(
+                            tracing_util::AttributeVisibility::Default,
+                            "graphql.operation.id",
+                            operation_id.0.clone(),
+                        );
+                        Box::pin(async {
+                            // Fetch response from the connector
+                            execute::fetch_from_data_connector(
+                                &http_context,
+                                &metadata.plugin_configs,
+                                &session,
+                                &headers,
+                                &query_request,
+                                &data_connector,
+                                None,
+                            )
+                            .await
+                            .map_err(execute::FieldError::from)
+                        })
+                    },
+                )
+                .await;
+            let is_error = result.is_err();
+            // Publish the result to the subscribers of the cohort
+            if sender.send(Some(std::sync::Arc::new(result))).is_err() || is_error {
+                break;
+            }
+            // Wait for the polling interval
+            tokio::time::sleep(polling_interval_duration).await;
+        }
+    })
+}
+
 #[derive(PartialEq, Eq)]
 struct ResponseHash(Option<[u8; 64]>);
 
@@ -686,7 +912,6 @@ async fn send_single_result_operation_response<M: WebSocketMetrics>(
 /// Sends a subscription operation response.
 async fn send_subscription_operation_response<M: WebSocketMetrics>(
     client_address: std::net::SocketAddr,
-    response_hash: &mut ResponseHash,
     operation_id: OperationId,
     raw_request: &lang_graphql::http::RawRequest,
     session: &Session,
@@ -695,53 +920,51 @@ async fn send_subscription_operation_response<M: WebSocketMetrics>(
     connection: &ws::Connection<M>,
 ) -> bool {
     let mut stop_subscription = false;
-    if !response_hash.matches(&response) {
-        // Execute pre-response plugins before sending the response
-        let plugin_response = run_pre_response_plugins(
-            client_address,
-            raw_request,
-            session.clone(),
-            headers.clone(),
-            &response,
-            connection,
-        )
-        .await;
-        let graphql_response = match plugin_response {
-            Ok(PreResponsePluginResponse::Continue) => {
-                GraphQLResponseOrCustomResponse::GraphQLResponse(Box::new(GraphQLResponse::new(
-                    response,
-                )))
-            }
-            Ok(PreResponsePluginResponse::ReturnError { plugin_name, error }) => {
-                // Send the plugin error response to the client
-                let graphql_error = error.into_graphql_error(&plugin_name);
-                GraphQLResponseOrCustomResponse::GraphQLResponse(Box::new(GraphQLResponse::Error(
-                    NonEmpty::new(graphql_error),
-                )))
-            }
-            Ok(PreResponsePluginResponse::ReturnResponse(new_response_bytes)) => {
-                GraphQLResponseOrCustomResponse::CustomResponse(new_response_bytes)
-            }
-            Err(response) => GraphQLResponseOrCustomResponse::GraphQLResponse(Box::new(response)),
-        };
-        match graphql_response {
-            GraphQLResponseOrCustomResponse::GraphQLResponse(response) => {
-                match *response {
-                    GraphQLResponse::Ok(ok_response) => {
-                        send_graphql_ok(operation_id, ok_response, connection).await;
-                    }
-                    GraphQLResponse::Error(errors) => {
-                        // Send the errors and stop subscription
-                        send_graphql_errors(operation_id, errors, connection).await;
-                        stop_subscription = true;
-                    }
+    // Execute pre-response plugins before sending the response
+    let plugin_response = run_pre_response_plugins(
+        client_address,
+        raw_request,
+        session.clone(),
+        headers.clone(),
+        &response,
+        connection,
+    )
+    .await;
+    let graphql_response = match plugin_response {
+        Ok(PreResponsePluginResponse::Continue) => {
+            GraphQLResponseOrCustomResponse::GraphQLResponse(Box::new(GraphQLResponse::new(
+                response,
+            )))
+        }
+        Ok(PreResponsePluginResponse::ReturnError { plugin_name, error }) => {
+            // Send the plugin error response to the client
+            let graphql_error = error.into_graphql_error(&plugin_name);
+            GraphQLResponseOrCustomResponse::GraphQLResponse(Box::new(GraphQLResponse::Error(
+                NonEmpty::new(graphql_error),
+            )))
+        }
+        Ok(PreResponsePluginResponse::ReturnResponse(new_response_bytes)) => {
+            GraphQLResponseOrCustomResponse::CustomResponse(new_response_bytes)
+        }
+        Err(response) => GraphQLResponseOrCustomResponse::GraphQLResponse(Box::new(response)),
+    };
+    match graphql_response {
+        GraphQLResponseOrCustomResponse::GraphQLResponse(response) => {
+            match *response {
+                GraphQLResponse::Ok(ok_response) => {
+                    send_graphql_ok(operation_id, ok_response, connection).await;
+                }
+                GraphQLResponse::Error(errors) => {
+                    // Send the errors and stop subscription
+                    send_graphql_errors(operation_id, errors, connection).await;
+                    stop_subscription = true;
                 }
             }
-            GraphQLResponseOrCustomResponse::CustomResponse(bytes) => {
-                connection
-                    .send(ws::Message::Raw(axum::extract::ws::Message::Binary(bytes)))
-                    .await;
-            }
+        }
+        GraphQLResponseOrCustomResponse::CustomResponse(bytes) => {
+            connection
+                .send(ws::Message::Raw(axum::extract::ws::Message::Binary(bytes)))
+                .await;
         }
     }
     stop_subscription
@@ -12,6 +12,7 @@ use axum::{
 use futures_util::StreamExt;
 
 use crate::metrics::WebSocketMetrics;
+use crate::poller::cohort::SubscriptionCohorts;
 use crate::protocol;
 
 pub static SEC_WEBSOCKET_PROTOCOL: &str = "Sec-WebSocket-Protocol";
@@ -21,6 +22,8 @@ static WEBSOCKET_CHANNEL_SIZE: usize = 50;
 /// GraphQL WebSocket server implementation.
 pub struct WebSocketServer<M> {
     pub connections: types::Connections<M>,
+    // Subscription cohorts shared by all connections, so that identical subscriptions share a poller
+    pub subscription_cohorts: SubscriptionCohorts,
 }
 
 impl<M> WebSocketServer<M> {
@@ -28,6 +31,7 @@ impl<M> WebSocketServer<M> {
     pub fn new() -> Self {
         Self {
             connections: types::Connections::new(), // Initialize an empty map of active connections
+            subscription_cohorts: SubscriptionCohorts::new(),
         }
     }
 
@@ -11,6 +11,7 @@ use tokio::sync::{RwLock, mpsc::Sender};
 
 use crate::metrics::WebSocketMetrics;
 use crate::poller;
+use crate::poller::cohort::SubscriptionCohorts;
 use crate::protocol::types as protocol;
 
 /// Context required to handle a WebSocket connection
@@ -26,6 +27,11 @@ pub struct Context<M> {
     pub metrics: M,
     pub handshake_headers: Arc<HeaderMap>,
     pub auth_mode_header: String,
+    pub subscription_cohorts: SubscriptionCohorts,
+    /// The session variables and headers that can affect a subscription's polls
+    pub key_inputs: Arc<graphql_frontend::KeyInputs>,
+    /// Queries persisted by clients, shared with the HTTP endpoint
+    pub persisted_queries: Option<Arc<graphql_frontend::PersistedQueries>>,
 }
 
 /// Represents a WebSocket connection ID.
@@ -66,6 +66,41 @@ async fn start_websocket_server_inner(
     expiry: graphql_ws::ConnectionExpiry,
     headers: HeaderMap,
 ) -> TestServer {
+    let (connections, url, server_handle) = spawn_websocket_server(expiry).await;
+    let socket = connect_websocket(&url, headers).await;
+    TestServer {
+        connections,
+        socket,
+        server_handle,
+    }
+}
+
+/// Starts a server with two connections to it, for tests of state shared
+/// between connections.
+#[allow(dead_code)]
+pub(crate) async fn start_websocket_server_two_connections()
+-> (TestServer, WebSocketStream<MaybeTlsStream<TcpStream>>) {
+    let (connections, url, server_handle) =
+        spawn_websocket_server(graphql_ws::ConnectionExpiry::Never).await;
+    let socket = connect_websocket(&url, HeaderMap::new()).await;
+    let other_socket = connect_websocket(&url, HeaderMap::new()).await;
+    let server = TestServer {
+        connections,
+        socket,
+        server_handle,
+    };
+    (server, other_socket)
+}
+
+/// Spawns a server, returning its connections and URL.
+#[allow(dead_code)]
+async fn spawn_websocket_server(
+    expiry: graphql_ws::ConnectionExpiry,
+) -> (
+    graphql_ws::Connections<graphql_ws::NoOpWebSocketMetrics>,
+    String,
+    JoinHandle<()>,
+) {
     // Create a TCP listener
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
     let addr = listener.local_addr().unwrap();
@@ -96,7 +131,11 @@ async fn start_websocket_server_inner(
     let http_context = HttpContext {
         client: reqwest::Client::new(),
         ndc_response_size_limit: None,
+        remote_join_batch_size: None,
+        remote_join_concurrency: None,
     };
+    let subscription_cohorts = graphql_ws::SubscriptionCohorts::new();
+    let key_inputs = graphql_frontend::KeyInputs::new(&resolved_metadata);
     let context = Context {
         connection_expiry: expiry,
         http_context,
@@ -108,11 +147,15 @@ async fn start_websocket_server_inner(
         metrics: graphql_ws::NoOpWebSocketMetrics,
         handshake_headers: Arc::new(HeaderMap::new()), // Will be populated in "ws_handler"
         auth_mode_header: "x-hasura-auth-mode".to_string(),
+        subscription_cohorts: subscription_cohorts.clone(),
+        key_inputs: Arc::new(key_inputs),
+        persisted_queries: None,
     };
 
     let connections = graphql_ws::Connections::new();
     let ws_server = graphql_ws::WebSocketServer {
         connections: connections.clone(),
+        subscription_cohorts,
     };
     // Spawn a server
     let state = ServerState { ws_server, context };
@@ -129,7 +172,14 @@ async fn start_websocket_server_inner(
         .unwrap();
     });
 
-    let url = format!("ws://{addr}/ws");
+    (connections, format!("ws://{addr}/ws"), server_handle)
+}
+
+#[allow(dead_code)]
+async fn connect_websocket(
+    url: &str,
+    headers: HeaderMap,
+) -> WebSocketStream<MaybeTlsStream<TcpStream>> {
     let mut request = url.into_client_request().unwrap();
     request.headers_mut().insert(
         graphql_ws::SEC_WEBSOCKET_PROTOCOL,
@@ -139,12 +189,7 @@ async fn start_websocket_server_inner(
     let (socket, _response) = connect_async(request)
         .await
         .expect("Failed to connect to WebSocket server");
-
-    TestServer {
-        connections,
-        socket,
-        server_handle,
-    }
+    socket
 }
 
 #[allow(dead_code)]
@@ -184,6 +229,32 @@ pub(crate) async fn assert_zero_operations_timeout<M>(connections: &graphql_ws::
     assert!(result.is_ok(), "Operations are not empty");
 }
 
+#[allow(dead_code)]
+pub(crate) async fn assert_active_cohorts_timeout<M>(
+    connections: &graphql_ws::Connections<M>,
+    expected: usize,
+) {
+    // One connection should be present in an active test
+    let connections = connections.0.read().await;
+    let (_, connection) = connections.iter().next().unwrap();
+    let subscription_cohorts = &connection.context.subscription_cohorts;
+    // Cohorts are dropped along with their last subscriber, which is not immediate. So, we keep
+    // checking the number of active cohorts for at most 5 seconds.
+    let result = tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+        loop {
+            if subscription_cohorts.active_cohorts() == expected {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        }
+    })
+    .await;
+    assert!(
+        result.is_ok(),
+        "Expected {expected} active subscription cohorts"
+    );
+}
+
 #[allow(dead_code)]
 pub(crate) async fn expect_close_message(
     socket: &mut WebSocketStream<MaybeTlsStream<TcpStream>>,
@@ -337,6 +337,226 @@ async fn test_graphql_ws_subscribe_user_1() {
     server_handle.abort();
 }
 
+#[tokio::test]
+async fn test_graphql_ws_identical_subscriptions_share_cohort() {
+    let TestServer {
+        connections,
+        mut socket,
+        server_handle,
+    } = start_websocket_server().await;
+    // Send connection_init and check ack
+    assert_graphql_ws_connection_init(&mut socket, connection_init_admin()).await;
+
+    // Send two identical subscriptions
+    let operation_ids = ["operation-1", "operation-2"];
+    for operation_id in operation_ids {
+        let json_message = serde_json::to_string(&subscribe_article_by_id(operation_id)).unwrap();
+        socket
+            .send(tungstenite::Message::Text(json_message.into()))
+            .await
+            .unwrap();
+    }
+
+    // Both subscriptions receive the result of the shared poller
+    let mut received_ids = Vec::new();
+    for _ in operation_ids {
+        let message = expect_text_message(&mut socket).await;
+        let message_json: serde_json::Value =
+            serde_json::from_str(message.as_str()).expect("Expected a valid JSON");
+        assert_eq!(message_json["type"], "next");
+        assert_eq!(
+            message_json["payload"]["data"]["ArticleByID"]["title"],
+            "The Next 700 Programming Languages"
+        );
+        received_ids.push(message_json["id"].as_str().unwrap().to_string());
+    }
+    received_ids.sort();
+    assert_eq!(received_ids, operation_ids);
+
+    // Only one cohort polls for both subscriptions
+    assert_active_cohorts_timeout(&connections, 1).await;
+
+    // Stop one subscription, the cohort keeps polling for the other
+    let stop_message = serde_json::json!({
+        "id": operation_ids[0],
+        "type": "complete"
+    });
+    socket
+        .send(tungstenite::Message::Text(
+            serde_json::to_string(&stop_message).unwrap().into(),
+        ))
+        .await
+        .unwrap();
+    assert_active_cohorts_timeout(&connections, 1).await;
+
+    // Stop the other subscription, the cohort is dropped
+    let stop_message = serde_json::json!({
+        "id": operation_ids[1],
+        "type": "complete"
+    });
+    socket
+        .send(tungstenite::Message::Text(
+            serde_json::to_string(&stop_message).unwrap().into(),
+        ))
+        .await
+        .unwrap();
+    assert_zero_operations_timeout(&connections).await;
+    assert_active_cohorts_timeout(&connections, 0).await;
+
+    // Send close frame from client
+    socket
+        .send(tungstenite::Message::Close(None))
+        .await
+        .unwrap();
+    // Assert zero connections
+    assert_zero_connections_timeout(connections).await;
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn test_graphql_ws_identical_subscriptions_share_cohort_across_connections() {
+    let (
+        TestServer {
+            connections,
+            mut socket,
+            server_handle,
+        },
+        mut other_socket,
+    ) = start_websocket_server_two_connections().await;
+
+    // Each connection sends the same subscription, with its own handshake headers
+    for socket in [&mut socket, &mut other_socket] {
+        assert_graphql_ws_connection_init(socket, connection_init_admin()).await;
+        let json_message = serde_json::to_string(&subscribe_article_by_id("operation-1")).unwrap();
+        socket
+            .send(tungstenite::Message::Text(json_message.into()))
+            .await
+            .unwrap();
+        let message = expect_text_message(socket).await;
+        let message_json: serde_json::Value =
+            serde_json::from_str(message.as_str()).expect("Expected a valid JSON");
+        assert_eq!(message_json["type"], "next");
+        assert_eq!(
+            message_json["payload"]["data"]["ArticleByID"]["title"],
+            "The Next 700 Programming Languages"
+        );
+    }
+
+    // Only one cohort polls for both connections
+    assert_active_cohorts_timeout(&connections, 1).await;
+
+    // Send close frames from both clients
+    for socket in [&mut socket, &mut other_socket] {
+        socket
+            .send(tungstenite::Message::Close(None))
+            .await
+            .unwrap();
+    }
+    // Assert zero connections
+    assert_zero_connections_timeout(connections).await;
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn test_graphql_ws_stream_subscription_user_1() {
+    let TestServer {
+        connections,
+        mut socket,
+        server_handle,
+    } = start_websocket_server().await;
+    // Send connection_init and check ack
+    assert_graphql_ws_connection_init(&mut socket, connection_init_user_1_id_2()).await;
+
+    // Stream the articles in batches of two, starting after the first article
+    let operation_id = "some-operation-id";
+    let query = r"
+          subscription {
+            ArticleStream(batchSize: 2, cursor: {article_id: 1}){
+              article_id
+              author_id
+            }
+          }
+    ";
+    let subscribe_message = serde_json::json!({
+        "type": "subscribe",
+        "id": operation_id,
+        "payload": {
+            "query": query,
+            "variables": {}
+        }
+    });
+
+    let json_message = serde_json::to_string(&subscribe_message).unwrap();
+    socket
+        .send(tungstenite::Message::Text(json_message.into()))
+        .await
+        .unwrap();
+
+    // Expects the articles with author_id = 2, one batch at a time
+    let expected_batches = [
+        serde_json::json!([
+            {
+                "article_id": 2,
+                "author_id": 2
+            },
+            {
+                "article_id": 3,
+                "author_id": 2
+            }
+        ]),
+        serde_json::json!([
+            {
+                "article_id": 5,
+                "author_id": 2
+            }
+        ]),
+    ];
+    for expected_batch in expected_batches {
+        let message = expect_text_message(&mut socket).await;
+        let message_json: serde_json::Value =
+            serde_json::from_str(message.as_str()).expect("Expected a valid JSON");
+        let expected = serde_json::json!({
+            "type": "next",
+            "id": operation_id,
+            "payload": {
+                "data": {
+                    "ArticleStream": expected_batch
+                }
+            }
+        });
+        assert_eq!(message_json, expected);
+    }
+    // Check operation id
+    check_operation_id(operation_id, &connections).await;
+
+    // Streams are not shared between subscribers
+    assert_active_cohorts_timeout(&connections, 0).await;
+
+    // stop subscription
+    let stop_message = serde_json::json!({
+        "id": operation_id,
+        "type": "complete"
+    });
+    socket
+        .send(tungstenite::Message::Text(
+            serde_json::to_string(&stop_message).unwrap().into(),
+        ))
+        .await
+        .unwrap();
+
+    // Assert zero operations
+    assert_zero_operations_timeout(&connections).await;
+
+    // Send close frame from client
+    socket
+        .send(tungstenite::Message::Close(None))
+        .await
+        .unwrap();
+    // Assert zero connections
+    assert_zero_connections_timeout(connections).await;
+    server_handle.abort();
+}
+
 #[tokio::test]
 async fn test_graphql_ws_subscribe_user_1_validation_error() {
     let TestServer {
@@ -61,6 +61,9 @@ pub enum Error {
     #[error("{value} is not a valid offset value")]
     InvalidOffsetValue { value: u32 },
 
+    #[error("{value} is not a valid batch size value")]
+    InvalidBatchSizeValue { value: u32 },
+
     #[error("field '{field_name:} not found in entity representation")]
     FieldNotFoundInEntityRepresentation { field_name: FieldName },
 
@@ -69,12 +72,25 @@ pub enum Error {
     )]
     OrderByObjectShouldExactlyHaveOneKeyValuePair,
 
+    #[error(
+        "cursor expects an input object with exactly one field set to the value to start streaming after"
+    )]
+    StreamCursorShouldExactlyHaveOneField,
+
     #[error("missing non-nullable argument {argument_name:} for field {field_name:}")]
     MissingNonNullableArgument {
         argument_name: String,
         field_name: String,
     },
 
+    #[error(
+        "the extraction function {extraction_function:} is not supported for the field {field_name:}"
+    )]
+    UnsupportedExtractionFunction {
+        extraction_function: String,
+        field_name: FieldName,
+    },
+
     #[error("Only one subscription root field is allowed")]
     NoneOrMoreSubscriptionRootFields,
 
@@ -29,8 +29,8 @@ pub use model_tracking::get_all_usage_counts_in_query;
 pub use mutation_root::generate_ir as generate_mutation_ir;
 pub use order_by::OrderBy;
 pub use plan::{
-    ApolloFederationSelect, Error as GraphqlIrPlanError, MutationPlan, NodeQueryPlan, QueryPlan,
-    RequestPlan, generate_request_plan,
+    ApolloFederationSelect, Error as GraphqlIrPlanError, MutationGroup, MutationPlan,
+    NodeQueryPlan, QueryPlan, RequestPlan, generate_request_plan,
 };
 pub use query_root::generate_ir as generate_query_ir;
 pub use root_field::{
@@ -1,7 +1,7 @@
 //! IR for the 'model_selection' type - selecting fields from a model
 use super::{filter, order_by, selection_set};
 use crate::{error, flags::GraphqlIrFlags};
-use graphql_schema::GDS;
+use graphql_schema::{GDS, NamespaceAnnotation};
 use hasura_authn_core::SessionVariables;
 use indexmap::IndexMap;
 use lang_graphql::normalized_ast;
@@ -52,6 +52,16 @@ pub struct ModelSelection<'s> {
     pub variable_arguments: BTreeMap<DataConnectorArgumentName, VariableName>,
 }
 
+/// The limits the role's select permission puts on the number of rows a model field selects.
+pub(crate) fn row_limits(
+    field_call: &normalized_ast::FieldCall<'_, GDS>,
+) -> metadata_resolve::RowLimits {
+    match field_call.info.namespaced.as_deref() {
+        Some(NamespaceAnnotation::Model { row_limits, .. }) => *row_limits,
+        _ => metadata_resolve::RowLimits::default(),
+    }
+}
+
 /// Generates the IR fragment for selecting from a model.
 #[allow(clippy::too_many_arguments)]
 pub fn model_selection_open_dd_ir(
@@ -132,3 +142,43 @@ pub fn model_aggregate_selection_open_dd_ir(
 
     Ok(open_dds::query::ModelAggregateSelection { selection, target })
 }
+
+/// Generates the IR fragment for grouping a model and aggregating over each group.
+#[allow(clippy::too_many_arguments)]
+pub fn model_group_by_selection_open_dd_ir(
+    selection_set: &normalized_ast::SelectionSet<'_, GDS>,
+    model_name: &Qualified<ModelName>,
+    model_arguments: Option<IndexMap<open_dds::query::ArgumentName, open_dds::query::Value>>,
+    where_clause: Option<open_dds::query::BooleanExpression>,
+    order_by: Vec<open_dds::query::OrderByElement>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    group_limit: Option<usize>,
+    group_offset: Option<usize>,
+    flags: &GraphqlIrFlags,
+    usage_counts: &mut UsagesCounts,
+) -> Result<open_dds::query::ModelGroupsSelection, error::Error> {
+    count_model(model_name, usage_counts);
+    let (dimensions, selection) =
+        selection_set::generate_group_selection_set_open_dd_ir(selection_set, flags)?;
+
+    let target = open_dds::query::ModelTarget {
+        subgraph: model_name.subgraph.clone(),
+        model_name: model_name.name.clone(),
+        offset,
+        order_by,
+        arguments: model_arguments.unwrap_or_default(), // Permission presets are handled during planning
+        filter: where_clause,
+        limit,
+    };
+
+    Ok(open_dds::query::ModelGroupsSelection {
+        target,
+        selection,
+        dimensions: open_dds::query::ModelDimensions {
+            dimensions,
+            limit: group_limit,
+            offset: group_offset,
+        },
+    })
+}
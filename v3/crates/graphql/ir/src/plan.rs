@@ -1,6 +1,7 @@
 mod commands;
 mod error;
 mod types;
+use crate::query_root::select_stream::ModelSelectStream;
 use crate::{
     ApolloFederationRootFields, IR, MutationRootField, ProcedureBasedCommand, QueryRootField,
     SubscriptionRootField,
@@ -13,12 +14,14 @@ use lang_graphql as gql;
 pub use metadata_resolve::Metadata;
 use plan::PlanState;
 use plan_types::{
-    CommandReturnKind, NDCMutationExecution, NDCQueryExecution, NDCSubscriptionExecution,
-    ProcessResponseAs, QueryExecutionPlan, QueryExecutionTree,
+    CommandReturnKind, Field, FieldsSelection, NDCMutationExecution, NDCQueryExecution,
+    NDCSubscriptionExecution, NdcFieldAlias, ProcessResponseAs, QueryExecutionPlan,
+    QueryExecutionTree, StreamCursor,
 };
+use std::collections::BTreeMap;
 pub use types::{
-    ApolloFederationSelect, MutationPlan, MutationSelect, NodeQueryPlan, QueryPlan, RequestPlan,
-    SubscriptionSelect,
+    ApolloFederationSelect, MutationGroup, MutationPlan, MutationSelect, NodeQueryPlan, QueryPlan,
+    RequestPlan, SubscriptionSelect,
 };
 
 /// Build a plan to handle a given GraphQL request. This plan will either be a mutation plan or a query
@@ -44,7 +47,7 @@ pub fn generate_request_plan<'n, 's, 'ir>(
         }
         IR::Mutation(ir) => {
             let mut mutation_plan = MutationPlan {
-                nodes: IndexMap::new(),
+                nodes: Vec::new(),
                 type_names: IndexMap::new(),
             };
             for (alias, field) in ir {
@@ -63,11 +66,16 @@ pub fn generate_request_plan<'n, 's, 'ir>(
                             request_headers,
                             &mut plan_state,
                         )?;
-                        mutation_plan
-                            .nodes
-                            .entry(plan.mutation_execution.data_connector.clone())
-                            .or_default()
-                            .insert(alias.clone(), plan);
+                        let data_connector = &plan.mutation_execution.data_connector;
+                        match mutation_plan.nodes.last_mut() {
+                            Some(group) if &group.data_connector == data_connector => {
+                                group.fields.insert(alias.clone(), plan);
+                            }
+                            _ => mutation_plan.nodes.push(MutationGroup {
+                                data_connector: data_connector.clone(),
+                                fields: IndexMap::from([(alias.clone(), plan)]),
+                            }),
+                        }
                     }
                 }
             }
@@ -160,6 +168,7 @@ fn plan_subscription<'s, 'ir>(
                     process_response_as: ProcessResponseAs::Object {
                         is_nullable: ir.type_container.nullable.to_owned(),
                     },
+                    stream_cursor: None,
                 },
             })
         }
@@ -196,6 +205,7 @@ fn plan_subscription<'s, 'ir>(
                     process_response_as: ProcessResponseAs::Array {
                         is_nullable: ir.type_container.nullable.to_owned(),
                     },
+                    stream_cursor: None,
                 },
             })
         }
@@ -230,12 +240,129 @@ fn plan_subscription<'s, 'ir>(
                     execution_span_attribute: "execute_model_select_aggregate",
                     field_span_attribute: ir.field_name.to_string(),
                     process_response_as: ProcessResponseAs::Aggregates,
+                    stream_cursor: None,
+                },
+            })
+        }
+
+        SubscriptionRootField::ModelSelectStream {
+            ir,
+            selection_set,
+            polling_interval_ms,
+        } => {
+            let single_node_execution_plan = plan::query_to_plan(
+                &open_dds::query::Query::Model(ir.model_selection.clone()),
+                metadata,
+                session,
+                request_headers,
+                plan_state,
+            )?;
+            let execution_tree = match single_node_execution_plan {
+                plan::SingleNodeExecutionPlan::Query(execution_tree) => Ok(execution_tree),
+                plan::SingleNodeExecutionPlan::Mutation(_) => {
+                    // we should use a more specific planning function to avoid
+                    // this as it _should not_ happen
+                    Err(error::Error::PlanExpectedQueryGotMutation)
+                }
+            }?;
+
+            let mut query_execution_plan = reject_remote_joins(*execution_tree)?;
+            let stream_cursor = plan_stream_cursor(ir, metadata)?;
+            // fetch the cursor column in every row, so the next poll can start after the last row
+            query_execution_plan
+                .query_node
+                .fields
+                .get_or_insert_with(|| FieldsSelection {
+                    fields: IndexMap::new(),
+                })
+                .fields
+                .insert(
+                    stream_cursor.alias.clone(),
+                    Field::Column {
+                        column: stream_cursor.column.clone(),
+                        fields: None,
+                        arguments: BTreeMap::new(),
+                    },
+                );
+            Ok(SubscriptionSelect {
+                selection_set,
+                subscription_execution: NDCSubscriptionExecution {
+                    query_execution_plan,
+                    polling_interval_ms: *polling_interval_ms,
+                    execution_span_attribute: "execute_model_select_stream",
+                    field_span_attribute: ir.field_name.to_string(),
+                    process_response_as: ProcessResponseAs::Array {
+                        is_nullable: ir.type_container.nullable.to_owned(),
+                    },
+                    stream_cursor: Some(stream_cursor),
                 },
             })
         }
     }
 }
 
+/// Resolves the cursor field of a stream to the column it is mapped to, and the operator the data
+/// connector compares it with to fetch the rows after the cursor.
+fn plan_stream_cursor(
+    ir: &ModelSelectStream<'_>,
+    metadata: &Metadata,
+) -> Result<StreamCursor, error::Error> {
+    let target = &ir.model_selection.target;
+    let model_name =
+        metadata_resolve::Qualified::new(target.subgraph.clone(), target.model_name.clone());
+    let internal_error = |description: String| {
+        error::Error::Internal(error::InternalError::InternalGeneric { description })
+    };
+    let model = metadata
+        .models
+        .get(&model_name)
+        .ok_or_else(|| internal_error(format!("model {model_name} not found")))?;
+    let model_source = model
+        .model
+        .source
+        .as_ref()
+        .ok_or_else(|| internal_error(format!("model {model_name} has no source")))?;
+    let field_mapping = model_source
+        .type_mappings
+        .get(&model.model.data_type)
+        .and_then(|type_mapping| match type_mapping {
+            metadata_resolve::TypeMapping::Object { field_mappings, .. } => {
+                field_mappings.get(&ir.cursor_field)
+            }
+        })
+        .ok_or_else(|| {
+            internal_error(format!(
+                "field mapping not found for the stream cursor field {}",
+                ir.cursor_field
+            ))
+        })?;
+    let greater_than_operator = field_mapping
+        .comparison_operators
+        .as_ref()
+        .and_then(|operators| {
+            operators.get_gt_operator(
+                model_source
+                    .data_connector
+                    .capabilities
+                    .supported_ndc_version,
+            )
+        })
+        .ok_or_else(|| {
+            internal_error(format!(
+                "no greater than operator found for the stream cursor field {}",
+                ir.cursor_field
+            ))
+        })?;
+    Ok(StreamCursor {
+        column: field_mapping.column.clone(),
+        greater_than_operator: greater_than_operator.clone(),
+        alias: NdcFieldAlias::from(
+            format!("__hasura_stream_cursor__{}", field_mapping.column.as_str()).as_str(),
+        ),
+        initial_value: ir.initial_cursor_value.clone(),
+    })
+}
+
 fn reject_remote_joins(tree: QueryExecutionTree) -> Result<QueryExecutionPlan, error::Error> {
     if !tree.remote_join_executions.is_empty() {
         return Err(error::Error::RemoteJoinsAreNotSupportedSubscriptions);
@@ -363,6 +490,32 @@ fn plan_query<'n, 's, 'ir>(
                 selection_set,
             }
         }
+        QueryRootField::ModelSelectGroupBy { ir, selection_set } => {
+            let single_node_execution_plan = plan::query_to_plan(
+                &open_dds::query::Query::ModelGroups(ir.model_selection.clone()),
+                metadata,
+                session,
+                request_headers,
+                plan_state,
+            )?;
+            let execution_tree = match single_node_execution_plan {
+                plan::SingleNodeExecutionPlan::Query(execution_tree) => Ok(execution_tree),
+                plan::SingleNodeExecutionPlan::Mutation(_) => {
+                    // we should use a more specific planning function to avoid
+                    // this as it _should not_ happen
+                    Err(error::Error::PlanExpectedQueryGotMutation)
+                }
+            }?;
+            NodeQueryPlan::NDCQueryExecution {
+                query_execution: NDCQueryExecution {
+                    execution_tree: *execution_tree,
+                    execution_span_attribute: "execute_model_select_group_by",
+                    field_span_attribute: ir.field_name.to_string(),
+                    process_response_as: ProcessResponseAs::Groups,
+                },
+                selection_set,
+            }
+        }
         QueryRootField::NodeSelect(optional_ir) => match optional_ir {
             Some(ir) => {
                 // TODO: expose more specific function in `plan` for just model selections
@@ -17,18 +17,33 @@ pub type QueryPlan<'n, 's, 'ir> = IndexMap<ast::Alias, NodeQueryPlan<'n, 's, 'ir
 /// Unlike a query, the root nodes of a mutation aren't necessarily independent. Specifically, the
 /// GraphQL specification says that each root mutation must be executed sequentially. Moreover, if
 /// we want to, say, insert a parent _and_ children in one query, we want the ability to make
-/// transactional requests. In a mutation plan, we group nodes by connector, allowing us to issue
-/// transactional commands to connectors whose capabilities allow for transactional mutations.
-/// Otherwise, we can just send them one-by-one (though still sequentially).
+/// transactional requests. In a mutation plan, we group consecutive nodes by connector, allowing
+/// us to issue transactional commands to connectors whose capabilities allow for transactional
+/// mutations. Otherwise, we can just send them one-by-one (though still sequentially).
 #[derive(Debug, PartialEq)]
 pub struct MutationPlan<'n, 's> {
-    pub nodes: IndexMap<
-        Arc<metadata_resolve::DataConnectorLink>,
-        IndexMap<ast::Alias, MutationSelect<'n, 's>>,
-    >,
+    pub nodes: Vec<MutationGroup<'n, 's>>,
     pub type_names: IndexMap<ast::Alias, ast::TypeName>,
 }
 
+/// Consecutive root nodes of a mutation that go to the same connector.
+#[derive(Debug, PartialEq)]
+pub struct MutationGroup<'n, 's> {
+    pub data_connector: Arc<metadata_resolve::DataConnectorLink>,
+    pub fields: IndexMap<ast::Alias, MutationSelect<'n, 's>>,
+}
+
+impl MutationGroup<'_, '_> {
+    /// Whether the nodes of this group are sent to the connector in one transactional request
+    pub fn is_transactional(&self) -> bool {
+        self.fields.len() > 1
+            && self
+                .data_connector
+                .capabilities
+                .supports_transactional_mutations
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct MutationSelect<'n, 's> {
     pub mutation_execution: NDCMutationExecution,
@@ -26,8 +26,10 @@ use graphql_schema::{GDS, mk_typename};
 pub mod apollo_federation;
 pub mod node_field;
 pub mod select_aggregate;
+pub mod select_group_by;
 pub mod select_many;
 pub mod select_one;
+pub mod select_stream;
 
 /// Generates IR for the selection set of type 'query root'
 pub fn generate_ir<'n, 's>(
@@ -255,6 +257,16 @@ pub fn generate_model_rootfield_ir<'n, 's>(
                 field, field_call, source, model_name, flags,
             )?,
         },
+        RootFieldKind::SelectGroupBy => root_field::QueryRootField::ModelSelectGroupBy {
+            selection_set: &field.selection_set,
+            ir: select_group_by::select_group_by_generate_ir(
+                field, field_call, source, model_name, flags,
+            )?,
+        },
+        // streams are only available on the subscription root
+        RootFieldKind::SelectStream => Err(error::InternalEngineError::UnexpectedAnnotation {
+            annotation: field_call.info.generic.clone(),
+        })?,
     };
     Ok(ir)
 }
@@ -94,6 +94,24 @@ pub fn aggregate_query(
     model_source: &metadata_resolve::ModelSource,
     flags: &GraphqlIrFlags,
     usage_counts: &mut UsagesCounts,
+) -> Result<AggregateQuery, error::Error> {
+    aggregate_query_from_arguments(
+        field_call.arguments.values(),
+        model_name,
+        model_source,
+        flags,
+        usage_counts,
+    )
+}
+
+/// Like `aggregate_query`, for fields that take arguments of their own alongside the aggregate
+/// arguments
+pub(crate) fn aggregate_query_from_arguments<'a, 's: 'a>(
+    arguments: impl IntoIterator<Item = &'a normalized_ast::InputField<'s, GDS>>,
+    model_name: &Qualified<open_dds::models::ModelName>,
+    model_source: &metadata_resolve::ModelSource,
+    flags: &GraphqlIrFlags,
+    usage_counts: &mut UsagesCounts,
 ) -> Result<AggregateQuery, error::Error> {
     let mut limit = None;
     let mut offset = None;
@@ -104,7 +122,7 @@ pub fn aggregate_query(
     // Add the name of the root model
     count_model(model_name, usage_counts);
 
-    for field_call_argument in field_call.arguments.values() {
+    for field_call_argument in arguments {
         match field_call_argument.info.generic {
             // Model arguments
             Annotation::Input(InputAnnotation::Model(
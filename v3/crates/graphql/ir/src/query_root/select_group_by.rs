@@ -0,0 +1,122 @@
+//! model_source IR for 'select_group_by' operation
+//!
+//! A 'select_group_by' operation groups the rows of a model and fetches aggregates over each
+//! group
+
+use graphql_schema::{Annotation, GDS, InputAnnotation, ModelInputAnnotation};
+use lang_graphql::ast::common as ast;
+use lang_graphql::normalized_ast;
+use metadata_resolve::Qualified;
+use plan_types::UsagesCounts;
+use serde::Serialize;
+
+use super::select_aggregate::{AggregateQuery, aggregate_query_from_arguments};
+use crate::error;
+use crate::flags::GraphqlIrFlags;
+use crate::model_selection;
+
+/// IR for the 'select_group_by' operation on a model
+#[derive(Debug, Serialize)]
+pub struct ModelSelectGroupBy<'n> {
+    // The name of the field as published in the schema
+    pub field_name: ast::Name,
+
+    pub model_selection: open_dds::query::ModelGroupsSelection,
+
+    // The Graphql output type of the operation
+    pub(crate) type_container: &'n ast::TypeContainer<ast::TypeName>,
+
+    // All the models/commands used in this operation. This includes the models/commands
+    // used via relationships. And in future, the models/commands used in the filter clause
+    pub(crate) usage_counts: UsagesCounts,
+}
+
+/// Generates the IR for a 'select_group_by' operation
+pub(crate) fn select_group_by_generate_ir<'n, 's>(
+    field: &'n normalized_ast::Field<'s, GDS>,
+    field_call: &'n normalized_ast::FieldCall<'s, GDS>,
+    model_source: &'s metadata_resolve::ModelSource,
+    model_name: &'s Qualified<open_dds::models::ModelName>,
+    flags: &GraphqlIrFlags,
+) -> Result<ModelSelectGroupBy<'n>, error::Error> {
+    let mut usage_counts = UsagesCounts::new();
+
+    // limit and offset page through the groups, everything else selects the rows to group
+    let mut group_limit = None;
+    let mut group_offset = None;
+    let mut aggregate_arguments = Vec::new();
+    for field_call_argument in field_call.arguments.values() {
+        match field_call_argument.info.generic {
+            Annotation::Input(InputAnnotation::Model(ModelInputAnnotation::ModelLimitArgument)) => {
+                group_limit = field_call_argument
+                    .value
+                    .as_nullable(
+                        &flags.validate_non_null_graphql_variables,
+                        normalized_ast::Value::as_int_u32,
+                    )
+                    .map_err(error::Error::map_unexpected_value_to_external_error)?;
+            }
+            Annotation::Input(InputAnnotation::Model(
+                ModelInputAnnotation::ModelOffsetArgument,
+            )) => {
+                group_offset = field_call_argument
+                    .value
+                    .as_nullable(
+                        &flags.validate_non_null_graphql_variables,
+                        normalized_ast::Value::as_int_u32,
+                    )
+                    .map_err(error::Error::map_unexpected_value_to_external_error)?;
+            }
+            _ => aggregate_arguments.push(field_call_argument),
+        }
+    }
+
+    let AggregateQuery {
+        limit,
+        offset,
+        where_clause,
+        model_arguments,
+        order_by,
+    } = aggregate_query_from_arguments(
+        aggregate_arguments,
+        model_name,
+        model_source,
+        flags,
+        &mut usage_counts,
+    )?;
+
+    // never return more groups than the role is allowed to select rows
+    let group_limit: Option<usize> = model_selection::row_limits(field_call)
+        .cap(group_limit)
+        .map(|limit| {
+            usize::try_from(limit).map_err(|_| error::Error::InvalidLimitValue { value: limit })
+        })
+        .transpose()?;
+
+    let group_offset: Option<usize> = group_offset
+        .map(|offset| {
+            usize::try_from(offset).map_err(|_| error::Error::InvalidOffsetValue { value: offset })
+        })
+        .transpose()?;
+
+    let model_selection = model_selection::model_group_by_selection_open_dd_ir(
+        &field.selection_set,
+        model_name,
+        model_arguments,
+        where_clause,
+        order_by,
+        limit,
+        offset,
+        group_limit,
+        group_offset,
+        flags,
+        &mut usage_counts,
+    )?;
+
+    Ok(ModelSelectGroupBy {
+        field_name: field_call.name.clone(),
+        model_selection,
+        type_container: &field.type_container,
+        usage_counts,
+    })
+}
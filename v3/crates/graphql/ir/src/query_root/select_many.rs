@@ -167,7 +167,8 @@ pub fn select_many_generate_ir<'n, 's>(
         )?,
     };
 
-    let limit: Option<usize> = limit
+    let limit: Option<usize> = model_selection::row_limits(field_call)
+        .rows(limit)
         .map(|limit| {
             usize::try_from(limit).map_err(|_| error::Error::InvalidLimitValue { value: limit })
         })
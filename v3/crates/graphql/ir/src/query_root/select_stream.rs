@@ -0,0 +1,232 @@
+//! model_source IR for 'select_stream' operation
+//!
+//! A 'select_stream' operation fetches the rows of a model in batches, ordered by a cursor field.
+//! Each batch starts after the cursor value of the last row of the previous batch.
+
+use hasura_authn_core::Session;
+use indexmap::IndexMap;
+use lang_graphql::ast::common as ast;
+use lang_graphql::normalized_ast;
+
+use open_dds;
+use open_dds::types::FieldName;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::arguments;
+use crate::error;
+use crate::filter;
+use crate::flags::GraphqlIrFlags;
+use crate::model_selection;
+use graphql_schema::GDS;
+use graphql_schema::{self, Annotation, BooleanExpressionAnnotation, ModelInputAnnotation};
+use metadata_resolve;
+use metadata_resolve::Qualified;
+use plan::count_model;
+use plan_types::UsagesCounts;
+
+/// IR for the 'select_stream' operation on a model
+#[derive(Debug, Serialize)]
+pub struct ModelSelectStream<'n> {
+    // The name of the field as published in the schema
+    pub field_name: ast::Name,
+
+    // The selection of the first batch, ordered by the cursor field
+    pub model_selection: open_dds::query::ModelSelection,
+
+    // The field the rows are streamed in order of
+    pub cursor_field: FieldName,
+
+    // The cursor value to start streaming after
+    pub initial_cursor_value: serde_json::Value,
+
+    // The Graphql output type of the operation
+    pub type_container: &'n ast::TypeContainer<ast::TypeName>,
+
+    // All the models/commands used in this operation. This includes the models/commands
+    // used via relationships. And in future, the models/commands used in the filter clause
+    pub usage_counts: UsagesCounts,
+}
+
+/// Generates the IR for a 'select_stream' operation
+pub fn select_stream_generate_ir<'n, 's>(
+    field: &'n normalized_ast::Field<'s, GDS>,
+    field_call: &'n normalized_ast::FieldCall<'s, GDS>,
+    model_source: &'s metadata_resolve::ModelSource,
+    models: &'s IndexMap<
+        metadata_resolve::Qualified<open_dds::models::ModelName>,
+        metadata_resolve::ModelWithPermissions,
+    >,
+    object_types: &'s BTreeMap<
+        Qualified<open_dds::types::CustomTypeName>,
+        metadata_resolve::ObjectTypeWithRelationships,
+    >,
+    session: &Session,
+    request_headers: &reqwest::header::HeaderMap,
+    model_name: &'s Qualified<open_dds::models::ModelName>,
+    flags: &GraphqlIrFlags,
+) -> Result<ModelSelectStream<'n>, error::Error> {
+    let mut batch_size = None;
+    let mut cursor = None;
+    let mut where_input = None;
+    let mut model_arguments_input = None;
+
+    // Add the name of the root model
+    let mut usage_counts = UsagesCounts::new();
+    count_model(model_name, &mut usage_counts);
+
+    for argument in field_call.arguments.values() {
+        match argument.info.generic {
+            annotation @ Annotation::Input(graphql_schema::InputAnnotation::Model(
+                model_argument_annotation,
+            )) => match model_argument_annotation {
+                ModelInputAnnotation::ModelStreamBatchSizeArgument => {
+                    batch_size = Some(
+                        argument
+                            .value
+                            .as_int_u32()
+                            .map_err(error::Error::map_unexpected_value_to_external_error)?,
+                    );
+                }
+                ModelInputAnnotation::ModelStreamCursorArgument => {
+                    cursor = Some(resolve_stream_cursor(&argument.value, flags)?);
+                }
+                ModelInputAnnotation::ModelArgumentsExpression => match &argument.value {
+                    normalized_ast::Value::Object(arguments) => {
+                        model_arguments_input = Some(arguments);
+                    }
+                    _ => Err(error::InternalEngineError::InternalGeneric {
+                        description: "Expected object value for model arguments".into(),
+                    })?,
+                },
+                _ => {
+                    Err(error::InternalEngineError::UnexpectedAnnotation {
+                        annotation: annotation.clone(),
+                    })?;
+                }
+            },
+
+            Annotation::Input(graphql_schema::InputAnnotation::BooleanExpression(
+                BooleanExpressionAnnotation::BooleanExpressionRootField,
+            )) => {
+                // where argument is optional
+                where_input = argument.value.as_nullable(
+                    &flags.validate_non_null_graphql_variables,
+                    normalized_ast::Value::as_object,
+                )?;
+            }
+
+            annotation => {
+                Err(error::InternalEngineError::UnexpectedAnnotation {
+                    annotation: annotation.clone(),
+                })?;
+            }
+        }
+    }
+
+    let batch_size = batch_size.ok_or_else(|| error::Error::MissingNonNullableArgument {
+        argument_name: "batchSize".to_string(),
+        field_name: field_call.name.to_string(),
+    })?;
+    let (cursor_field, initial_cursor_value) =
+        cursor.ok_or_else(|| error::Error::MissingNonNullableArgument {
+            argument_name: "cursor".to_string(),
+            field_name: field_call.name.to_string(),
+        })?;
+
+    let where_clause = match where_input {
+        Some(where_input) => Some(filter::resolve_filter_expression_open_dd(
+            where_input,
+            flags,
+            &mut usage_counts,
+        )?),
+        None => None,
+    };
+
+    let model_arguments = model_arguments_input
+        .map(|arguments_input| {
+            arguments::resolve_model_arguments_input_opendd(
+                arguments_input,
+                &model_source.type_mappings,
+                flags,
+                &mut usage_counts,
+            )
+        })
+        .transpose()?;
+
+    // batches are returned in ascending order of the cursor field, so the last row of a batch
+    // holds the cursor value for the next batch
+    let order_by = vec![open_dds::query::OrderByElement {
+        operand: open_dds::query::Operand::Field(open_dds::query::ObjectFieldOperand {
+            target: Box::new(open_dds::query::ObjectFieldTarget {
+                field_name: cursor_field.clone(),
+                arguments: IndexMap::new(),
+            }),
+            nested: None,
+        }),
+        direction: open_dds::models::OrderByDirection::Asc,
+    }];
+
+    // never send more rows in a batch than the role is allowed to select
+    let batch_size = model_selection::row_limits(field_call)
+        .cap(Some(batch_size))
+        .unwrap_or(batch_size);
+    let limit = usize::try_from(batch_size)
+        .map_err(|_| error::Error::InvalidBatchSizeValue { value: batch_size })?;
+
+    let model_selection = model_selection::model_selection_open_dd_ir(
+        &field.selection_set,
+        model_name,
+        models,
+        &model_source.type_mappings,
+        object_types,
+        model_arguments,
+        where_clause,
+        order_by,
+        Some(limit),
+        None,
+        &session.variables,
+        request_headers,
+        flags,
+        // Get all the models/commands that were used as relationships
+        &mut usage_counts,
+    )?;
+
+    Ok(ModelSelectStream {
+        field_name: field_call.name.clone(),
+        model_selection,
+        cursor_field,
+        initial_cursor_value,
+        type_container: &field.type_container,
+        usage_counts,
+    })
+}
+
+/// Resolves the cursor argument to the field to stream by and the value to start after. Exactly
+/// one field of the cursor must be set.
+fn resolve_stream_cursor(
+    cursor_input: &normalized_ast::Value<'_, GDS>,
+    flags: &GraphqlIrFlags,
+) -> Result<(FieldName, serde_json::Value), error::Error> {
+    let mut cursor_fields = Vec::new();
+    for cursor_field in cursor_input.as_object()?.values() {
+        if cursor_field
+            .value
+            .is_null(&flags.validate_non_null_graphql_variables)
+        {
+            continue;
+        }
+        match cursor_field.info.generic {
+            Annotation::Input(graphql_schema::InputAnnotation::Model(
+                ModelInputAnnotation::ModelStreamCursorField { field_name, .. },
+            )) => cursor_fields.push((field_name.clone(), cursor_field.value.as_json())),
+            annotation => Err(error::InternalEngineError::UnexpectedAnnotation {
+                annotation: annotation.clone(),
+            })?,
+        }
+    }
+    match <[_; 1]>::try_from(cursor_fields) {
+        Ok([cursor]) => Ok(cursor),
+        Err(_) => Err(error::Error::StreamCursorShouldExactlyHaveOneField),
+    }
+}
@@ -3,6 +3,7 @@ use indexmap::IndexMap;
 use lang_graphql::normalized_ast::{self, Field};
 use open_dds::{
     arguments::ArgumentName,
+    relationships::RelationshipType,
     types::{CustomTypeName, FieldName},
 };
 use std::collections::BTreeMap;
@@ -14,7 +15,7 @@ use super::{
     selection_set::{self, generate_selection_set_open_dd_ir},
 };
 use crate::{
-    arguments, error,
+    arguments, error, model_selection,
     query_root::select_aggregate::{AggregateQuery, aggregate_query},
 };
 use crate::{flags::GraphqlIrFlags, order_by};
@@ -190,6 +191,12 @@ pub fn generate_model_relationship_open_dd_ir<'s>(
         usage_counts,
     )?;
 
+    // only array relationships can select more than one row
+    let limit = match relationship_annotation.relationship_type {
+        RelationshipType::Array => model_selection::row_limits(field_call).rows(limit),
+        RelationshipType::Object => limit,
+    };
+
     let limit: Option<usize> = limit
         .map(|limit| {
             usize::try_from(limit).map_err(|_| error::Error::InvalidLimitValue { value: limit })
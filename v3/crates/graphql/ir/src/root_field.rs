@@ -5,7 +5,10 @@ use open_dds::permissions::Role;
 
 use super::{
     commands,
-    query_root::{apollo_federation, node_field, select_aggregate, select_many, select_one},
+    query_root::{
+        apollo_federation, node_field, select_aggregate, select_group_by, select_many, select_one,
+        select_stream,
+    },
 };
 use graphql_schema::GDS;
 
@@ -44,6 +47,11 @@ pub enum QueryRootField<'n, 's> {
         selection_set: &'n gql::normalized_ast::SelectionSet<'s, GDS>,
         ir: select_aggregate::ModelSelectAggregate<'n>,
     },
+    // Operation that groups the rows of a model and selects aggregates of each group
+    ModelSelectGroupBy {
+        selection_set: &'n gql::normalized_ast::SelectionSet<'s, GDS>,
+        ir: select_group_by::ModelSelectGroupBy<'n>,
+    },
     // Operation that selects a single row from the model corresponding
     // to the Global Id input.
     NodeSelect(Option<node_field::NodeSelect<'n, 's>>),
@@ -101,4 +109,10 @@ pub enum SubscriptionRootField<'n, 's> {
         ir: select_aggregate::ModelSelectAggregate<'n>,
         polling_interval_ms: u64,
     },
+    // Operation that streams the rows of a model in batches
+    ModelSelectStream {
+        selection_set: &'n gql::normalized_ast::SelectionSet<'s, GDS>,
+        ir: select_stream::ModelSelectStream<'n>,
+        polling_interval_ms: u64,
+    },
 }
@@ -18,7 +18,8 @@ use crate::error;
 use crate::flags::GraphqlIrFlags;
 use crate::global_id;
 use graphql_schema::{
-    AggregateOutputAnnotation, AggregationFunctionAnnotation, InputAnnotation, TypeKind,
+    AggregateOutputAnnotation, AggregationFunctionAnnotation, GroupByOutputAnnotation,
+    InputAnnotation, ModelInputAnnotation, TypeKind,
 };
 use graphql_schema::{Annotation, GDS, OutputAnnotation, RootFieldAnnotation};
 use metadata_resolve::{ObjectTypeWithRelationships, Qualified};
@@ -497,6 +498,133 @@ fn collect_aggregate_fields(
     Ok(())
 }
 
+/// Collect the dimensions and aggregates selected from each group of a groupBy root field. Both
+/// are named after their alias path, so that the response can be matched back up with them.
+pub fn generate_group_selection_set_open_dd_ir(
+    selection_set: &normalized_ast::SelectionSet<'_, GDS>,
+    flags: &GraphqlIrFlags,
+) -> Result<
+    (
+        IndexMap<Name, open_dds::query::Dimension>,
+        IndexMap<Name, open_dds::query::Aggregate>,
+    ),
+    error::Error,
+> {
+    let mut dimensions = IndexMap::new();
+    let mut aggregates = IndexMap::new();
+    for field in selection_set.fields.values() {
+        let field_call = field.field_call()?;
+        match field_call.info.generic {
+            Annotation::Output(OutputAnnotation::GroupBy(GroupByOutputAnnotation::Dimensions)) => {
+                collect_dimension_fields(
+                    &mut dimensions,
+                    &field.alias,
+                    &field.selection_set,
+                    flags,
+                )?;
+            }
+            Annotation::Output(OutputAnnotation::GroupBy(GroupByOutputAnnotation::Aggregates)) => {
+                collect_aggregate_fields(
+                    &mut aggregates,
+                    &[],
+                    &[&field.alias],
+                    &field.selection_set,
+                )?;
+            }
+            Annotation::Output(OutputAnnotation::RootField(RootFieldAnnotation::Introspection)) => {
+            } // Skip introspection fields such as __typename, as they will be processed during response handling.
+            annotation => Err(error::InternalEngineError::UnexpectedAnnotation {
+                annotation: annotation.clone(),
+            })?,
+        }
+    }
+    Ok((dimensions, aggregates))
+}
+
+fn collect_dimension_fields(
+    dimensions: &mut IndexMap<Name, open_dds::query::Dimension>,
+    dimensions_alias: &Alias,
+    selection_set: &normalized_ast::SelectionSet<'_, GDS>,
+    flags: &GraphqlIrFlags,
+) -> Result<(), error::Error> {
+    for field in selection_set.fields.values() {
+        let field_call = field.field_call()?;
+        match field_call.info.generic {
+            Annotation::Output(OutputAnnotation::GroupBy(GroupByOutputAnnotation::Dimension {
+                field_name,
+                extraction_functions,
+                ..
+            })) => {
+                let mut extraction = None;
+                for argument in field_call.arguments.values() {
+                    match argument.info.generic {
+                        Annotation::Input(InputAnnotation::Model(
+                            ModelInputAnnotation::ModelGroupByExtractionArgument,
+                        )) => {
+                            extraction = argument
+                                .value
+                                .as_nullable(
+                                    &flags.validate_non_null_graphql_variables,
+                                    normalized_ast::Value::as_enum,
+                                )
+                                .map_err(error::Error::map_unexpected_value_to_external_error)?
+                                .map(|enum_value| match enum_value.info.generic {
+                                    Annotation::Input(InputAnnotation::Model(
+                                        ModelInputAnnotation::ModelGroupByExtractionFunction {
+                                            extraction_function,
+                                        },
+                                    )) => Ok(extraction_function.clone()),
+                                    annotation => {
+                                        Err(error::InternalEngineError::UnexpectedAnnotation {
+                                            annotation: annotation.clone(),
+                                        })
+                                    }
+                                })
+                                .transpose()?;
+                        }
+                        annotation => Err(error::InternalEngineError::UnexpectedAnnotation {
+                            annotation: annotation.clone(),
+                        })?,
+                    }
+                }
+
+                // the enum is shared between fields, so not every function applies to every field
+                if let Some(extraction_function) = &extraction
+                    && !extraction_functions.contains(extraction_function)
+                {
+                    return Err(error::Error::UnsupportedExtractionFunction {
+                        extraction_function: extraction_function.to_string(),
+                        field_name: field_name.clone(),
+                    });
+                }
+
+                let dimension_alias = make_field_alias(
+                    mk_alias_from_graphql_field_path(&[dimensions_alias, &field.alias]).as_str(),
+                )?;
+                dimensions.insert(
+                    Name::from(dimension_alias.as_str().to_owned()),
+                    open_dds::query::Dimension::Field {
+                        column: open_dds::query::Operand::Field(ObjectFieldOperand {
+                            target: Box::new(open_dds::query::ObjectFieldTarget {
+                                field_name: field_name.clone(),
+                                arguments: IndexMap::new(),
+                            }),
+                            nested: None,
+                        }),
+                        extraction,
+                    },
+                );
+            }
+            Annotation::Output(OutputAnnotation::RootField(RootFieldAnnotation::Introspection)) => {
+            } // Skip introspection fields such as __typename, as they will be processed during response handling.
+            annotation => Err(error::InternalEngineError::UnexpectedAnnotation {
+                annotation: annotation.clone(),
+            })?,
+        }
+    }
+    Ok(())
+}
+
 fn make_field_alias(alias: &str) -> Result<open_dds::query::Alias, error::Error> {
     Ok(open_dds::query::Alias::from(
         open_dds::identifier::Identifier::new(alias).map_err(|_| error::Error::InvalidAlias {
@@ -11,7 +11,7 @@ use open_dds::models;
 use crate::flags::GraphqlIrFlags;
 
 use super::error;
-use super::query_root::{select_aggregate, select_many, select_one};
+use super::query_root::{select_aggregate, select_many, select_one, select_stream};
 use super::root_field;
 use graphql_schema::GDS;
 use graphql_schema::RootFieldKind;
@@ -155,6 +155,25 @@ fn generate_model_rootfield_ir<'n, 's>(
             )?,
             polling_interval_ms: *polling_interval_ms,
         },
+        RootFieldKind::SelectStream => root_field::SubscriptionRootField::ModelSelectStream {
+            selection_set: &field.selection_set,
+            ir: select_stream::select_stream_generate_ir(
+                field,
+                field_call,
+                source,
+                models,
+                object_types,
+                session,
+                request_headers,
+                model_name,
+                flags,
+            )?,
+            polling_interval_ms: *polling_interval_ms,
+        },
+        // groups are only available on the query root
+        RootFieldKind::SelectGroupBy => Err(error::InternalEngineError::UnexpectedAnnotation {
+            annotation: field_call.info.generic.clone(),
+        })?,
     };
     Ok(ir)
 }
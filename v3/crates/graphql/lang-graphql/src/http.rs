@@ -13,8 +13,72 @@ use crate::ast::executable;
 #[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
 pub struct RawRequest {
     pub operation_name: Option<ast::Name>,
+    /// Empty if the client only sends the hash of a persisted query
+    #[serde(default)]
     pub query: String,
     pub variables: Option<BTreeMap<ast::Name, serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<RequestExtensions>,
+}
+
+/// Extensions to the GraphQL request
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct RequestExtensions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persisted_query: Option<PersistedQuery>,
+}
+
+/// An automatic persisted query, identified by the SHA-256 hash of its query string
+/// <https://github.com/apollographql/apollo-link-persisted-queries#apollo-engine>
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct PersistedQuery {
+    pub version: u32,
+    /// Hex-encoded
+    pub sha256_hash: String,
+}
+
+/// The body of a request to the GraphQL endpoint: a single request, or a batch of requests
+/// that is answered with an array of responses.
+#[derive(Clone, Debug)]
+pub enum RawRequestBody {
+    Single(RawRequest),
+    Batch(Vec<RawRequest>),
+}
+
+// Not `#[serde(untagged)]`, so that a malformed request reports what is wrong with it rather
+// than that it matches neither variant.
+impl<'de> Deserialize<'de> for RawRequestBody {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RawRequestBodyVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for RawRequestBodyVisitor {
+            type Value = RawRequestBody;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a GraphQL request or an array of GraphQL requests")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                map: A,
+            ) -> Result<Self::Value, A::Error> {
+                RawRequest::deserialize(serde::de::value::MapAccessDeserializer::new(map))
+                    .map(RawRequestBody::Single)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                Vec::deserialize(serde::de::value::SeqAccessDeserializer::new(seq))
+                    .map(RawRequestBody::Batch)
+            }
+        }
+
+        deserializer.deserialize_any(RawRequestBodyVisitor)
+    }
 }
 
 pub struct Request {
@@ -209,10 +273,31 @@ impl axum::response::IntoResponse for Response {
 
 #[cfg(test)]
 mod tests {
-    use super::PathSegment;
+    use super::{PathSegment, RawRequestBody};
     use crate::ast::common::Name;
     use serde_json;
 
+    #[test]
+    fn test_raw_request_body_deserializing() {
+        let single: RawRequestBody =
+            serde_json::from_value(serde_json::json!({ "query": "{ a }" })).unwrap();
+        assert!(matches!(single, RawRequestBody::Single(request) if request.query == "{ a }"));
+
+        let batch: RawRequestBody = serde_json::from_value(serde_json::json!([
+            { "query": "{ a }" },
+            { "query": "{ b }", "operationName": null }
+        ]))
+        .unwrap();
+        assert!(matches!(batch, RawRequestBody::Batch(requests) if requests.len() == 2));
+
+        let error = serde_json::from_value::<RawRequestBody>(serde_json::json!({ "query": 1 }))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid type: integer `1`, expected a string"
+        );
+    }
+
     #[test]
     fn test_path_serializing() {
         let path = vec![
@@ -0,0 +1,340 @@
+//! Output types for a model's groupBy root field. Each group has its `dimensions`, the values of
+//! the fields the objects were grouped by, and the `aggregates` over the objects in the group.
+use hasura_authn_core::Role;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use strum_macros::Display;
+
+use lang_graphql::{ast::common as ast, mk_name, schema as gql_schema};
+use metadata_resolve::{
+    ExtractionFunctions, Qualified, QualifiedBaseType, QualifiedTypeReference, TypeMapping,
+};
+use open_dds::{
+    models::ModelName,
+    query::ExtractionFunction,
+    types::{CustomTypeName, Deprecated, FieldName},
+};
+
+use crate::aggregates::get_aggregate_select_output_type;
+use crate::types::{self, Annotation, ModelInputAnnotation, TypeId, TypeKind, output_type};
+use crate::{Error, GDS, NamespaceAnnotation, mk_deprecation_status};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Display)]
+pub enum GroupByOutputAnnotation {
+    Dimensions,
+    Aggregates,
+    Dimension {
+        field_name: FieldName,
+        /// The parent type is required to report field usage while analyzing query usage.
+        parent_type: Qualified<CustomTypeName>,
+        /// To mark a field as deprecated in the field usage while reporting query usage analytics.
+        deprecated: Option<Deprecated>,
+        /// The extraction functions the data connector supports for this field
+        extraction_functions: Vec<ExtractionFunction>,
+    },
+}
+
+pub fn get_group_output_type(
+    builder: &mut gql_schema::Builder<GDS>,
+    model_name: &Qualified<ModelName>,
+    select_group_by: &metadata_resolve::SelectGroupByGraphQlDefinition,
+) -> gql_schema::RegisteredTypeName {
+    builder.register_type(TypeId::ModelGroupOutputType {
+        model_name: model_name.clone(),
+        graphql_type_name: select_group_by.group_type_name.clone(),
+    })
+}
+
+fn get_select_group_by<'a>(
+    gds: &'a GDS,
+    model_name: &Qualified<ModelName>,
+) -> Result<
+    (
+        &'a metadata_resolve::ModelWithPermissions,
+        &'a metadata_resolve::SelectGroupByGraphQlDefinition,
+    ),
+    Error,
+> {
+    let model =
+        gds.metadata
+            .models
+            .get(model_name)
+            .ok_or_else(|| Error::InternalModelNotFound {
+                model_name: model_name.clone(),
+            })?;
+    let select_group_by = model.graphql_api.select_group_by.as_ref().ok_or_else(|| {
+        Error::InternalNoGroupByGraphqlApi {
+            model_name: model_name.clone(),
+        }
+    })?;
+    Ok((model, select_group_by))
+}
+
+pub fn build_model_group_output_type(
+    gds: &GDS,
+    builder: &mut gql_schema::Builder<GDS>,
+    model_name: &Qualified<ModelName>,
+    graphql_type_name: &ast::TypeName,
+) -> Result<gql_schema::TypeInfo<GDS>, Error> {
+    let (_model, select_group_by) = get_select_group_by(gds, model_name)?;
+
+    let aggregate_expression = gds
+        .metadata
+        .aggregate_expressions
+        .get(&select_group_by.aggregate_expression_name)
+        .ok_or_else(|| Error::InternalAggregateExpressionNotFound {
+            aggregate_expression: select_group_by.aggregate_expression_name.clone(),
+        })?;
+
+    let mut fields = BTreeMap::new();
+
+    let dimensions_field_name = mk_name!("dimensions");
+    let dimensions_type = builder.register_type(TypeId::ModelGroupDimensionsOutputType {
+        model_name: model_name.clone(),
+        graphql_type_name: select_group_by.dimensions_type_name.clone(),
+    });
+    fields.insert(
+        dimensions_field_name.clone(),
+        builder.allow_all_namespaced(gql_schema::Field::new(
+            dimensions_field_name,
+            Some("The values of the fields the objects were grouped by".to_string()),
+            Annotation::Output(types::OutputAnnotation::GroupBy(
+                GroupByOutputAnnotation::Dimensions,
+            )),
+            ast::TypeContainer::named_non_null(dimensions_type),
+            BTreeMap::new(),
+            mk_deprecation_status(None),
+        )),
+    );
+
+    let aggregates_field_name = mk_name!("aggregates");
+    let aggregates_type = get_aggregate_select_output_type(builder, aggregate_expression)?;
+    fields.insert(
+        aggregates_field_name.clone(),
+        builder.allow_all_namespaced(gql_schema::Field::new(
+            aggregates_field_name,
+            Some("Aggregates over the objects in the group".to_string()),
+            Annotation::Output(types::OutputAnnotation::GroupBy(
+                GroupByOutputAnnotation::Aggregates,
+            )),
+            ast::TypeContainer::named_non_null(aggregates_type),
+            BTreeMap::new(),
+            mk_deprecation_status(None),
+        )),
+    );
+
+    Ok(gql_schema::TypeInfo::Object(gql_schema::Object::new(
+        builder,
+        graphql_type_name.clone(),
+        None,
+        fields,
+        BTreeMap::new(), // Interfaces
+        vec![],          // Directives
+    )))
+}
+
+/// The dimensions type has a field for each scalar field of the model's object type that takes no
+/// arguments. Groups can contain objects where the field is null, so every dimension is nullable.
+pub fn build_model_group_dimensions_output_type(
+    gds: &GDS,
+    builder: &mut gql_schema::Builder<GDS>,
+    model_name: &Qualified<ModelName>,
+    graphql_type_name: &ast::TypeName,
+) -> Result<gql_schema::TypeInfo<GDS>, Error> {
+    let (model, select_group_by) = get_select_group_by(gds, model_name)?;
+
+    let object_type_name = &model.model.data_type;
+    let object_type = gds
+        .metadata
+        .object_types
+        .get(object_type_name)
+        .ok_or_else(|| Error::InternalTypeNotFound {
+            type_name: object_type_name.clone(),
+        })?;
+
+    // models without a source have no field mappings, so no extraction functions
+    let field_mappings = model.model.source.as_ref().and_then(|model_source| {
+        model_source
+            .type_mappings
+            .get(object_type_name)
+            .map(|type_mapping| match type_mapping {
+                TypeMapping::Object { field_mappings, .. } => field_mappings,
+            })
+    });
+
+    let mut fields = BTreeMap::new();
+    for (field_name, field_definition) in &object_type.object_type.fields {
+        if !field_definition.field_arguments.is_empty()
+            || !matches!(
+                field_definition.field_type.underlying_type,
+                QualifiedBaseType::Named(_)
+            )
+            || output_type::get_type_kind(gds, &field_definition.field_type)? != TypeKind::Scalar
+        {
+            continue;
+        }
+
+        let graphql_field_name = metadata_resolve::mk_name(field_name.as_str())
+            .map_err(metadata_resolve::Error::from)
+            .map_err(metadata_resolve::WithContext::from)?;
+
+        let extraction_functions = field_mappings
+            .and_then(|field_mappings| field_mappings.get(field_name))
+            .and_then(|field_mapping| field_mapping.extraction_functions.as_ref())
+            .map(supported_extraction_functions)
+            .unwrap_or_default();
+
+        let mut arguments = BTreeMap::new();
+        if let Some(extraction_function_type_name) = &select_group_by.extraction_function_type_name
+            && !extraction_functions.is_empty()
+        {
+            let extraction_argument_name = mk_name!("extraction");
+            let extraction_function_type =
+                builder.register_type(TypeId::ExtractionFunctionEnumType {
+                    graphql_type_name: extraction_function_type_name.clone(),
+                });
+            arguments.insert(
+                extraction_argument_name.clone(),
+                builder.allow_all_namespaced(gql_schema::InputField::new(
+                    extraction_argument_name,
+                    Some("Group by part of the value, for example the year of a date".to_string()),
+                    Annotation::Input(types::InputAnnotation::Model(
+                        ModelInputAnnotation::ModelGroupByExtractionArgument,
+                    )),
+                    ast::TypeContainer::named_null(extraction_function_type),
+                    None,
+                    gql_schema::DeprecationStatus::NotDeprecated,
+                )),
+            );
+        }
+
+        let dimension_type = QualifiedTypeReference {
+            underlying_type: field_definition.field_type.underlying_type.clone(),
+            nullable: true,
+        };
+        let field = gql_schema::Field::<GDS>::new(
+            graphql_field_name.clone(),
+            field_definition.description.clone(),
+            Annotation::Output(types::OutputAnnotation::GroupBy(
+                GroupByOutputAnnotation::Dimension {
+                    field_name: field_name.clone(),
+                    parent_type: object_type_name.clone(),
+                    deprecated: field_definition.deprecated.clone(),
+                    extraction_functions,
+                },
+            )),
+            output_type::get_output_type(gds, builder, &dimension_type)?,
+            arguments,
+            mk_deprecation_status(field_definition.deprecated.as_ref()),
+        );
+
+        // Only allow grouping by the field if the type permissions allow it
+        let allowed_roles = object_type
+            .type_output_permissions
+            .by_role
+            .iter()
+            .filter(|(_role, perms)| perms.allowed_fields.contains(field_name))
+            .map(|(role, _perms)| (role.clone(), None))
+            .collect::<HashMap<Role, Option<Box<NamespaceAnnotation>>>>();
+
+        fields.insert(
+            graphql_field_name,
+            builder.conditional_namespaced(field, allowed_roles),
+        );
+    }
+
+    Ok(gql_schema::TypeInfo::Object(gql_schema::Object::new(
+        builder,
+        graphql_type_name.clone(),
+        None,
+        fields,
+        BTreeMap::new(), // Interfaces
+        vec![],          // Directives
+    )))
+}
+
+/// The enum of extraction functions is the same for every model, the dimensions only accept the
+/// functions their data connector supports. Custom extraction functions are not exposed.
+pub fn build_extraction_function_enum_type(
+    builder: &mut gql_schema::Builder<GDS>,
+    graphql_type_name: &ast::TypeName,
+) -> Result<gql_schema::TypeInfo<GDS>, Error> {
+    let mut values = BTreeMap::new();
+    for extraction_function in [
+        ExtractionFunction::Nanosecond,
+        ExtractionFunction::Microsecond,
+        ExtractionFunction::Millisecond,
+        ExtractionFunction::Second,
+        ExtractionFunction::Minute,
+        ExtractionFunction::Hour,
+        ExtractionFunction::Day,
+        ExtractionFunction::Week,
+        ExtractionFunction::Month,
+        ExtractionFunction::Quarter,
+        ExtractionFunction::Year,
+        ExtractionFunction::DayOfWeek,
+        ExtractionFunction::DayOfYear,
+    ] {
+        let value = ast::Name::new(&extraction_function.to_string())?;
+        values.insert(
+            value.clone(),
+            builder.allow_all_namespaced(gql_schema::EnumValue {
+                value,
+                description: None,
+                deprecation_status: gql_schema::DeprecationStatus::NotDeprecated,
+                info: Annotation::Input(types::InputAnnotation::Model(
+                    ModelInputAnnotation::ModelGroupByExtractionFunction {
+                        extraction_function,
+                    },
+                )),
+            }),
+        );
+    }
+
+    Ok(gql_schema::TypeInfo::Enum(gql_schema::Enum {
+        name: graphql_type_name.clone(),
+        description: Some("Extracts part of a value to group by".to_string()),
+        values,
+        directives: Vec::new(),
+    }))
+}
+
+fn supported_extraction_functions(
+    extraction_functions: &ExtractionFunctions,
+) -> Vec<ExtractionFunction> {
+    let ExtractionFunctions {
+        year_function,
+        month_function,
+        day_function,
+        nanosecond_function,
+        microsecond_function,
+        millisecond_function,
+        second_function,
+        minute_function,
+        hour_function,
+        week_function,
+        quarter_function,
+        day_of_week_function,
+        day_of_year_function,
+        other_functions: _,
+    } = extraction_functions;
+    [
+        (nanosecond_function, ExtractionFunction::Nanosecond),
+        (microsecond_function, ExtractionFunction::Microsecond),
+        (millisecond_function, ExtractionFunction::Millisecond),
+        (second_function, ExtractionFunction::Second),
+        (minute_function, ExtractionFunction::Minute),
+        (hour_function, ExtractionFunction::Hour),
+        (day_function, ExtractionFunction::Day),
+        (week_function, ExtractionFunction::Week),
+        (month_function, ExtractionFunction::Month),
+        (quarter_function, ExtractionFunction::Quarter),
+        (year_function, ExtractionFunction::Year),
+        (day_of_week_function, ExtractionFunction::DayOfWeek),
+        (day_of_year_function, ExtractionFunction::DayOfYear),
+    ]
+    .into_iter()
+    .filter(|(function, _)| function.is_some())
+    .map(|(_, extraction_function)| extraction_function)
+    .collect()
+}
@@ -4,6 +4,7 @@ mod apollo_federation;
 mod boolean_expression;
 mod commands;
 mod field_arguments;
+mod group_by;
 mod model_arguments;
 mod model_filter;
 mod model_filter_input;
@@ -12,6 +13,7 @@ mod mutation_root;
 mod permissions;
 mod query_root;
 mod relay;
+mod stream;
 mod subscription_root;
 mod types;
 
@@ -33,6 +35,7 @@ use open_dds::{
 };
 
 pub use aggregates::{AggregateOutputAnnotation, AggregationFunctionAnnotation};
+pub use group_by::GroupByOutputAnnotation;
 pub use types::output_type::relationship::{
     CommandRelationshipAnnotation, CommandTargetSource, FilterRelationshipAnnotation,
     ModelAggregateRelationshipAnnotation, ModelRelationshipAnnotation,
@@ -253,6 +256,36 @@ impl gql_schema::SchemaContext for GDS {
                 model_name,
                 graphql_type_name,
             ),
+            types::TypeId::ModelGroupOutputType {
+                model_name,
+                graphql_type_name,
+            } => group_by::build_model_group_output_type(
+                self,
+                builder,
+                model_name,
+                graphql_type_name,
+            ),
+            types::TypeId::ModelGroupDimensionsOutputType {
+                model_name,
+                graphql_type_name,
+            } => group_by::build_model_group_dimensions_output_type(
+                self,
+                builder,
+                model_name,
+                graphql_type_name,
+            ),
+            types::TypeId::ExtractionFunctionEnumType { graphql_type_name } => {
+                group_by::build_extraction_function_enum_type(builder, graphql_type_name)
+            }
+            types::TypeId::ModelStreamCursorInputType {
+                model_name,
+                graphql_type_name,
+            } => stream::build_model_stream_cursor_input_type(
+                self,
+                builder,
+                model_name,
+                graphql_type_name,
+            ),
         }
     }
 
@@ -365,6 +398,8 @@ pub enum Error {
         "internal error while building schema, filter_expression for model not found: {model_name}"
     )]
     InternalModelFilterExpressionNotFound { model_name: Qualified<ModelName> },
+    #[error("internal error while building schema, model {model_name} has no groupBy graphql API")]
+    InternalNoGroupByGraphqlApi { model_name: Qualified<ModelName> },
     #[error("internal error while building schema, boolean expression not found: {type_name}")]
     InternalBooleanExpressionNotFound {
         type_name: Qualified<CustomTypeName>,
@@ -27,6 +27,7 @@ pub(crate) fn get_select_permissions_namespace_annotations(
                     filter: select_permission.filter.clone(),
                     argument_presets,
                     allow_subscriptions: select_permission.allow_subscriptions,
+                    row_limits: select_permission.row_limits,
                 })),
             );
         }
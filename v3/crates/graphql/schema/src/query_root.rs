@@ -14,6 +14,7 @@ use self::node_field::RelayNodeFieldOutput;
 pub mod apollo_federation;
 pub mod node_field;
 pub mod select_aggregate;
+pub mod select_group_by;
 pub mod select_many;
 pub mod select_one;
 
@@ -55,6 +56,15 @@ pub fn query_root_schema(
             )?;
             fields.insert(field_name, field);
         }
+        if let Some(select_group_by) = &model.graphql_api.select_group_by {
+            let (field_name, field) = select_group_by::select_group_by_field(
+                builder,
+                model,
+                select_group_by,
+                query_root_type_name,
+            )?;
+            fields.insert(field_name, field);
+        }
     }
 
     // Add node field for only the commands which have a query root field
@@ -0,0 +1,77 @@
+//! Schema for 'select_group_by' operation
+//!
+//! A 'select_group_by' operation groups the model's data by some of its fields and fetches
+//! aggregations over each group
+//!
+use lang_graphql::{ast::common as ast, schema as gql_schema};
+
+use crate::group_by::get_group_output_type;
+use crate::query_root::select_aggregate::generate_select_aggregate_arguments;
+use crate::types::{self, Annotation};
+use crate::{
+    Error, GDS, RootFieldAnnotation, RootFieldKind, mk_deprecation_status, model_filter_input,
+    permissions,
+};
+use metadata_resolve;
+
+pub(crate) fn select_group_by_field(
+    builder: &mut gql_schema::Builder<GDS>,
+    model: &metadata_resolve::ModelWithPermissions,
+    select_group_by: &metadata_resolve::SelectGroupByGraphQlDefinition,
+    parent_type: &ast::TypeName,
+) -> Result<
+    (
+        ast::Name,
+        gql_schema::Namespaced<GDS, gql_schema::Field<GDS>>,
+    ),
+    Error,
+> {
+    let root_field_name = select_group_by.query_root_field.clone();
+
+    // the filter input selects the objects to group, limit and offset page through the groups
+    let mut arguments = generate_select_aggregate_arguments(
+        builder,
+        model,
+        &select_group_by.filter_input_field_name,
+        &select_group_by.query_root_field,
+        parent_type,
+    )?;
+    if supports_group_pagination(model) {
+        model_filter_input::add_limit_input_field(&mut arguments, builder, model)?;
+        model_filter_input::add_offset_input_field(&mut arguments, builder, model)?;
+    }
+
+    let field_permissions = permissions::get_select_permissions_namespace_annotations(model);
+
+    let output_typename = get_group_output_type(builder, &model.model.name, select_group_by);
+
+    let field = builder.conditional_namespaced(
+        gql_schema::Field::new(
+            root_field_name.clone(),
+            select_group_by.description.clone(),
+            Annotation::Output(types::OutputAnnotation::RootField(
+                RootFieldAnnotation::Model {
+                    kind: RootFieldKind::SelectGroupBy,
+                    name: model.model.name.clone(),
+                },
+            )),
+            ast::TypeContainer::list_non_null(ast::TypeContainer::named_non_null(output_typename)),
+            arguments,
+            mk_deprecation_status(select_group_by.deprecated.as_ref()),
+        ),
+        field_permissions,
+    );
+    Ok((root_field_name, field))
+}
+
+fn supports_group_pagination(model: &metadata_resolve::ModelWithPermissions) -> bool {
+    model.model.source.as_ref().is_none_or(|model_source| {
+        model_source
+            .data_connector
+            .capabilities
+            .supports_aggregates
+            .as_ref()
+            .and_then(|aggregates| aggregates.supports_grouping.as_ref())
+            .is_some_and(|grouping| grouping.supports_pagination)
+    })
+}
@@ -0,0 +1,165 @@
+//! Input types for a model's streaming subscription root field. The cursor input type has a field
+//! for each cursor field of the model's streaming subscription, and the subscriber sets exactly
+//! one of them to the value to start streaming after.
+use hasura_authn_core::Role;
+use std::collections::{BTreeMap, HashMap};
+
+use lang_graphql::{ast::common as ast, mk_name, schema as gql_schema};
+use metadata_resolve::{Qualified, QualifiedBaseType, QualifiedTypeReference};
+use open_dds::models::ModelName;
+
+use crate::model_filter_input::add_where_input_field;
+use crate::types::{self, Annotation, ModelInputAnnotation, TypeId, TypeKind, output_type};
+use crate::{Error, GDS, NamespaceAnnotation, mk_deprecation_status};
+
+/// Generates the arguments of a streaming subscription: the number of objects to return in each
+/// batch, the cursor to start streaming after, and the filter on the streamed objects.
+pub(crate) fn generate_select_stream_arguments(
+    builder: &mut gql_schema::Builder<GDS>,
+    model: &metadata_resolve::ModelWithPermissions,
+    stream_subscription: &metadata_resolve::StreamSubscriptionGraphQlDefinition,
+) -> BTreeMap<ast::Name, gql_schema::Namespaced<GDS, gql_schema::InputField<GDS>>> {
+    let mut arguments = BTreeMap::new();
+
+    let batch_size_argument_name = mk_name!("batchSize");
+    arguments.insert(
+        batch_size_argument_name.clone(),
+        builder.allow_all_namespaced(gql_schema::InputField::new(
+            batch_size_argument_name,
+            Some("The maximum number of objects to return in each batch".to_string()),
+            Annotation::Input(types::InputAnnotation::Model(
+                ModelInputAnnotation::ModelStreamBatchSizeArgument,
+            )),
+            ast::TypeContainer::named_non_null(gql_schema::RegisteredTypeName::int()),
+            None,
+            gql_schema::DeprecationStatus::NotDeprecated,
+        )),
+    );
+
+    let cursor_argument_name = mk_name!("cursor");
+    let cursor_input_type =
+        get_stream_cursor_input_type(builder, &model.model.name, stream_subscription);
+    arguments.insert(
+        cursor_argument_name.clone(),
+        builder.allow_all_namespaced(gql_schema::InputField::new(
+            cursor_argument_name,
+            Some(
+                "The field to stream the objects in order of, and the value to start after"
+                    .to_string(),
+            ),
+            Annotation::Input(types::InputAnnotation::Model(
+                ModelInputAnnotation::ModelStreamCursorArgument,
+            )),
+            ast::TypeContainer::named_non_null(cursor_input_type),
+            None,
+            gql_schema::DeprecationStatus::NotDeprecated,
+        )),
+    );
+
+    add_where_input_field(&mut arguments, builder, model);
+
+    arguments
+}
+
+pub fn get_stream_cursor_input_type(
+    builder: &mut gql_schema::Builder<GDS>,
+    model_name: &Qualified<ModelName>,
+    stream_subscription: &metadata_resolve::StreamSubscriptionGraphQlDefinition,
+) -> gql_schema::RegisteredTypeName {
+    builder.register_type(TypeId::ModelStreamCursorInputType {
+        model_name: model_name.clone(),
+        graphql_type_name: stream_subscription.cursor_input_type_name.clone(),
+    })
+}
+
+/// The cursor input type has a nullable field for each cursor field of the model's streaming
+/// subscription that is a scalar field of the model's object type and takes no arguments.
+pub fn build_model_stream_cursor_input_type(
+    gds: &GDS,
+    builder: &mut gql_schema::Builder<GDS>,
+    model_name: &Qualified<ModelName>,
+    graphql_type_name: &ast::TypeName,
+) -> Result<gql_schema::TypeInfo<GDS>, Error> {
+    let model =
+        gds.metadata
+            .models
+            .get(model_name)
+            .ok_or_else(|| Error::InternalModelNotFound {
+                model_name: model_name.clone(),
+            })?;
+
+    let object_type_name = &model.model.data_type;
+    let object_type = gds
+        .metadata
+        .object_types
+        .get(object_type_name)
+        .ok_or_else(|| Error::InternalTypeNotFound {
+            type_name: object_type_name.clone(),
+        })?;
+
+    // the cursor fields are checked to be unique and comparable with a greater than operator
+    // when the metadata is resolved
+    let cursor_fields = model
+        .graphql_api
+        .select_many
+        .as_ref()
+        .and_then(|select_many| select_many.stream_subscription.as_ref())
+        .map(|stream_subscription| stream_subscription.cursor_fields.as_slice())
+        .unwrap_or_default();
+
+    let mut fields = BTreeMap::new();
+    for (field_name, field_definition) in &object_type.object_type.fields {
+        if !field_definition.field_arguments.is_empty()
+            || !matches!(
+                field_definition.field_type.underlying_type,
+                QualifiedBaseType::Named(_)
+            )
+            || output_type::get_type_kind(gds, &field_definition.field_type)? != TypeKind::Scalar
+            || !cursor_fields.contains(field_name)
+        {
+            continue;
+        }
+
+        let graphql_field_name = metadata_resolve::mk_name(field_name.as_str())
+            .map_err(metadata_resolve::Error::from)
+            .map_err(metadata_resolve::WithContext::from)?;
+
+        // exactly one cursor field is set, so every field is nullable
+        let cursor_type = QualifiedTypeReference {
+            underlying_type: field_definition.field_type.underlying_type.clone(),
+            nullable: true,
+        };
+        let input_field = gql_schema::InputField::<GDS>::new(
+            graphql_field_name.clone(),
+            field_definition.description.clone(),
+            Annotation::Input(types::InputAnnotation::Model(
+                ModelInputAnnotation::ModelStreamCursorField {
+                    field_name: field_name.clone(),
+                    parent_type: object_type_name.clone(),
+                    deprecated: field_definition.deprecated.clone(),
+                },
+            )),
+            types::input_type::get_input_type(gds, builder, &cursor_type)?,
+            None,
+            mk_deprecation_status(field_definition.deprecated.as_ref()),
+        );
+
+        // Only allow streaming by the field if the type permissions allow it
+        let allowed_roles = object_type
+            .type_output_permissions
+            .by_role
+            .iter()
+            .filter(|(_role, perms)| perms.allowed_fields.contains(field_name))
+            .map(|(role, _perms)| (role.clone(), None))
+            .collect::<HashMap<Role, Option<Box<NamespaceAnnotation>>>>();
+
+        fields.insert(
+            graphql_field_name,
+            builder.conditional_namespaced(input_field, allowed_roles),
+        );
+    }
+
+    Ok(gql_schema::TypeInfo::InputObject(
+        gql_schema::InputObject::new(graphql_type_name.clone(), None, fields, Vec::new()),
+    ))
+}
@@ -15,6 +15,7 @@ use crate::GDS;
 use crate::aggregates::get_aggregate_select_output_type;
 use crate::mk_deprecation_status;
 use crate::model_arguments;
+use crate::stream::generate_select_stream_arguments;
 use crate::types;
 use crate::types::output_type::get_custom_output_type;
 use crate::types::output_type::get_object_type_representation;
@@ -57,6 +58,20 @@ pub fn subscription_root_schema(
             fields.insert(field_name, field);
         }
 
+        // Add select_stream fields to the subscription root
+        if let Some(select_many) = &model.graphql_api.select_many
+            && let Some(stream_subscription) = &select_many.stream_subscription
+        {
+            let (field_name, field) = select_stream_field(
+                gds,
+                builder,
+                model,
+                stream_subscription,
+                subscription_root_type_name,
+            )?;
+            fields.insert(field_name, field);
+        }
+
         // Add select_aggregate fields to the subscription root
         if let Some(select_aggregate) = &model.graphql_api.select_aggregate
             && let Some(subscription) = &select_aggregate.subscription
@@ -181,6 +196,55 @@ fn select_many_field(
     Ok((subscription_root_field, field))
 }
 
+/// Generates schema for a 'select_stream' operation
+fn select_stream_field(
+    gds: &GDS,
+    builder: &mut gql_schema::Builder<GDS>,
+    model: &metadata_resolve::ModelWithPermissions,
+    stream_subscription: &metadata_resolve::StreamSubscriptionGraphQlDefinition,
+    parent_type: &ast::TypeName,
+) -> Result<
+    (
+        ast::Name,
+        gql_schema::Namespaced<GDS, gql_schema::Field<GDS>>,
+    ),
+    crate::Error,
+> {
+    let subscription_root_field = stream_subscription.root_field.clone();
+    let mut arguments = generate_select_stream_arguments(builder, model, stream_subscription);
+
+    model_arguments::add_model_arguments_field(
+        &mut arguments,
+        builder,
+        model,
+        &stream_subscription.root_field,
+        parent_type,
+    )?;
+
+    let field_type = ast::TypeContainer::list_non_null(ast::TypeContainer::named_non_null(
+        get_custom_output_type(gds, builder, &model.model.data_type)?,
+    ));
+
+    let field = builder.conditional_namespaced(
+        gql_schema::Field::new(
+            subscription_root_field.clone(),
+            stream_subscription.description.clone(),
+            Annotation::Output(types::OutputAnnotation::RootField(
+                types::RootFieldAnnotation::ModelSubscription {
+                    kind: types::RootFieldKind::SelectStream,
+                    name: model.model.name.clone(),
+                    polling_interval_ms: stream_subscription.polling_interval_ms,
+                },
+            )),
+            field_type,
+            arguments,
+            mk_deprecation_status(stream_subscription.deprecated.as_ref()),
+        ),
+        get_select_permissions_namespace_annotations(model),
+    );
+    Ok((subscription_root_field, field))
+}
+
 /// Generates schema for a 'aggregate' operation
 fn select_aggregate_field(
     gds: &GDS,
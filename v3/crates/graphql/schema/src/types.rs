@@ -73,6 +73,8 @@ pub enum RootFieldKind {
     SelectOne,
     SelectMany,
     SelectAggregate,
+    SelectGroupBy,
+    SelectStream,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -175,6 +177,7 @@ pub enum OutputAnnotation {
     },
     SDL,
     Aggregate(crate::aggregates::AggregateOutputAnnotation),
+    GroupBy(crate::group_by::GroupByOutputAnnotation),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Display)]
@@ -212,6 +215,20 @@ pub enum ModelInputAnnotation {
         field_name: types::FieldName,
     },
     ModelFilterInputArgument,
+    ModelGroupByExtractionArgument,
+    ModelGroupByExtractionFunction {
+        extraction_function: open_dds::query::ExtractionFunction,
+    },
+    ModelStreamBatchSizeArgument,
+    ModelStreamCursorArgument,
+    ModelStreamCursorField {
+        field_name: types::FieldName,
+        /// The parent type is required to report field usage while analyzing query usage.
+        /// Field usage is reported with the name of object type where the field is defined.
+        parent_type: Qualified<types::CustomTypeName>,
+        /// To mark a field as deprecated in the field usage while reporting query usage analytics.
+        deprecated: Option<Deprecated>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Display)]
@@ -337,6 +354,8 @@ pub enum NamespaceAnnotation {
             ),
         >,
         allow_subscriptions: bool,
+        /// limits on the number of rows selected
+        row_limits: metadata_resolve::RowLimits,
     },
     /// Field presets for an input field.
     ///
@@ -409,6 +428,21 @@ pub enum TypeId {
         model_name: Qualified<models::ModelName>,
         graphql_type_name: ast::TypeName,
     },
+    ModelGroupOutputType {
+        model_name: Qualified<models::ModelName>,
+        graphql_type_name: ast::TypeName,
+    },
+    ModelGroupDimensionsOutputType {
+        model_name: Qualified<models::ModelName>,
+        graphql_type_name: ast::TypeName,
+    },
+    ExtractionFunctionEnumType {
+        graphql_type_name: ast::TypeName,
+    },
+    ModelStreamCursorInputType {
+        model_name: Qualified<models::ModelName>,
+        graphql_type_name: ast::TypeName,
+    },
 }
 
 #[derive(Serialize, Clone, Debug, Hash, PartialEq, Eq)]
@@ -457,7 +491,17 @@ impl TypeId {
             }
             | TypeId::ModelFilterInputType {
                 graphql_type_name, ..
-            } => graphql_type_name.clone(),
+            }
+            | TypeId::ModelGroupOutputType {
+                graphql_type_name, ..
+            }
+            | TypeId::ModelGroupDimensionsOutputType {
+                graphql_type_name, ..
+            }
+            | TypeId::ModelStreamCursorInputType {
+                graphql_type_name, ..
+            }
+            | TypeId::ExtractionFunctionEnumType { graphql_type_name } => graphql_type_name.clone(),
             TypeId::NodeRoot => ast::TypeName(mk_name!("Node")),
             TypeId::ModelArgumentsInput { type_name, .. } => type_name.clone(),
             TypeId::ApolloFederationType(PossibleApolloFederationTypes::Entity) => {
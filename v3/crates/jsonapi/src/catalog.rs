@@ -1,4 +1,8 @@
 mod types;
-pub use types::{Catalog, Model, ObjectType, RelationshipTarget, State, Type};
+pub use types::{
+    AggregatableField, Argument, Catalog, Command, CommandKind, Model, ModelAggregates, ObjectType,
+    RelationshipTarget, State, Type,
+};
+mod commands;
 mod models;
 mod object_types;
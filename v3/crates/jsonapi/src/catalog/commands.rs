@@ -0,0 +1,80 @@
+use super::object_types::{object_type_permission_access, type_from_type_representation};
+use super::types::{Argument, Command, CommandKind};
+use crate::types::CommandWarning;
+use hasura_authn_core::Role;
+use indexmap::IndexMap;
+use metadata_resolve::{
+    CommandWithPermissions, ObjectTypeWithRelationships, Qualified, ScalarTypeRepresentation,
+    unwrap_custom_type_name,
+};
+use open_dds::commands::DataConnectorCommand;
+use open_dds::types::CustomTypeName;
+use std::collections::BTreeMap;
+
+// look at permissions and work out whether we can call this command, and which arguments the
+// caller has to provide
+pub fn build_command(
+    command: &CommandWithPermissions,
+    role: &Role,
+    object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectTypeWithRelationships>,
+    scalar_types: &BTreeMap<Qualified<CustomTypeName>, ScalarTypeRepresentation>,
+) -> Result<Command, CommandWarning> {
+    // if we are not allowed to execute the command, ignore it
+    let permission = command
+        .permissions
+        .by_role
+        .get(role)
+        .filter(|permission| permission.allow_execution)
+        .ok_or(CommandWarning::NoExecutePermission)?;
+
+    let command_source = command
+        .command
+        .source
+        .as_ref()
+        .ok_or(CommandWarning::NoCommandSource)?;
+
+    let kind = match command_source.source {
+        DataConnectorCommand::Function(_) => CommandKind::Function,
+        DataConnectorCommand::Procedure(_) => CommandKind::Procedure,
+    };
+
+    // we can only return the output if the role can see it
+    if let Some(output_object_type) = unwrap_custom_type_name(&command.command.output_type)
+        && !scalar_types.contains_key(output_object_type)
+        && !object_type_permission_access(role, output_object_type, object_types)
+    {
+        return Err(CommandWarning::NoOutputTypePermission {
+            object_type_name: output_object_type.clone(),
+        });
+    }
+    let output_type =
+        type_from_type_representation(&command.command.output_type, scalar_types, object_types)
+            .map_err(|warning| CommandWarning::UnsupportedType { warning })?;
+
+    // arguments with presets are filled in for the caller, so we don't expose them
+    let mut arguments = IndexMap::new();
+    for (argument_name, argument_info) in &command.command.arguments {
+        if permission.argument_presets.contains_key(argument_name) {
+            continue;
+        }
+        let argument_type =
+            type_from_type_representation(&argument_info.argument_type, scalar_types, object_types)
+                .map_err(|warning| CommandWarning::UnsupportedType { warning })?;
+        arguments.insert(
+            argument_name.clone(),
+            Argument {
+                argument_type,
+                description: argument_info.description.clone(),
+                is_required: !argument_info.argument_type.nullable,
+            },
+        );
+    }
+
+    Ok(Command {
+        name: command.command.name.clone(),
+        description: command.command.description.clone(),
+        kind,
+        arguments,
+        output_type,
+    })
+}
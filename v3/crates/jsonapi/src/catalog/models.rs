@@ -1,8 +1,16 @@
-use super::types::Model;
+use super::object_types::type_from_type_representation;
+use super::types::{AggregatableField, Argument, Model, ModelAggregates};
 use crate::types::ModelWarning;
 use hasura_authn_core::Role;
-use metadata_resolve::{ModelWithPermissions, ObjectTypeWithRelationships, Qualified};
-use open_dds::types::CustomTypeName;
+use indexmap::IndexMap;
+use metadata_resolve::{
+    AggregateExpression, ModelWithPermissions, ObjectTypeWithRelationships, Qualified,
+    ScalarTypeRepresentation,
+};
+use open_dds::{
+    aggregates::AggregateExpressionName,
+    types::{CustomTypeName, FieldName},
+};
 use std::collections::BTreeMap;
 
 // look at permissions and work out which fields we're allowed to see
@@ -11,22 +19,21 @@ pub fn build_model(
     model: &ModelWithPermissions,
     role: &Role,
     object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectTypeWithRelationships>,
+    scalar_types: &BTreeMap<Qualified<CustomTypeName>, ScalarTypeRepresentation>,
+    aggregate_expressions: &BTreeMap<Qualified<AggregateExpressionName>, AggregateExpression>,
 ) -> Result<Model, ModelWarning> {
     // if we have no select permission for the model, ignore it
-    if model
+    let permission = model
         .permissions
         .by_role
         .get(role)
-        .and_then(|permissions| permissions.select.as_ref())
-        .is_none()
-    {
-        return Err(ModelWarning::NoSelectPermission);
-    }
-    object_types
-        .get(&model.model.data_type)
-        .ok_or_else(|| ModelWarning::NoObjectTypeFound {
+        .filter(|permissions| permissions.select.is_some())
+        .ok_or(ModelWarning::NoSelectPermission)?;
+    let object_type = object_types.get(&model.model.data_type).ok_or_else(|| {
+        ModelWarning::NoObjectTypeFound {
             object_type_name: model.model.data_type.clone(),
-        })?;
+        }
+    })?;
 
     let model_source = model
         .model
@@ -36,11 +43,111 @@ pub fn build_model(
 
     let data_connector_name = model_source.data_connector.name.clone();
 
+    // arguments with presets are filled in for the caller, so we don't expose them
+    let mut arguments = IndexMap::new();
+    for (argument_name, argument_info) in &model.arguments {
+        if permission
+            .input
+            .as_ref()
+            .is_some_and(|input| input.argument_presets.contains_key(argument_name))
+        {
+            continue;
+        }
+        let argument_type =
+            type_from_type_representation(&argument_info.argument_type, scalar_types, object_types)
+                .map_err(|warning| ModelWarning::UnsupportedArgumentType {
+                    argument_name: argument_name.clone(),
+                    warning,
+                })?;
+        arguments.insert(
+            argument_name.clone(),
+            Argument {
+                argument_type,
+                description: argument_info.description.clone(),
+                is_required: !argument_info.argument_type.nullable,
+            },
+        );
+    }
+
     Ok(Model {
         name: model.model.name.clone(),
         description: model.description.clone(),
         data_type: model.model.data_type.clone(),
         data_connector_name,
         filter_expression_type: model.filter_expression_type.clone(),
+        unique_identifier: model_unique_identifier(&model.model),
+        aggregates: model
+            .model
+            .aggregate_expression
+            .as_ref()
+            .and_then(|aggregate_expression| {
+                build_model_aggregates(
+                    aggregate_expression,
+                    role,
+                    object_type,
+                    aggregate_expressions,
+                )
+            }),
+        arguments,
+    })
+}
+
+// we only expose the first unique identifier as the resource `id`
+pub fn model_unique_identifier(model: &metadata_resolve::Model) -> Option<Vec<FieldName>> {
+    model
+        .unique_identifiers
+        .first()
+        .map(|fields| fields.keys().cloned().collect())
+}
+
+// work out what the role can aggregate, from the model's aggregate expression. We only support
+// aggregating scalar fields for now, so fields with nested aggregate expressions are left out.
+fn build_model_aggregates(
+    aggregate_expression_name: &Qualified<AggregateExpressionName>,
+    role: &Role,
+    object_type: &ObjectTypeWithRelationships,
+    aggregate_expressions: &BTreeMap<Qualified<AggregateExpressionName>, AggregateExpression>,
+) -> Option<ModelAggregates> {
+    let aggregate_expression = aggregate_expressions.get(aggregate_expression_name)?;
+    let output_permissions = object_type.type_output_permissions.by_role.get(role)?;
+
+    let mut fields = IndexMap::new();
+    for aggregatable_field in &aggregate_expression.operand.aggregatable_fields {
+        if !output_permissions
+            .allowed_fields
+            .contains(&aggregatable_field.field_name)
+        {
+            continue;
+        }
+        let Some(field_aggregate_expression) =
+            aggregate_expressions.get(&aggregatable_field.aggregate_expression)
+        else {
+            continue;
+        };
+        if !field_aggregate_expression
+            .operand
+            .aggregatable_fields
+            .is_empty()
+        {
+            continue;
+        }
+        fields.insert(
+            aggregatable_field.field_name.clone(),
+            AggregatableField {
+                count: field_aggregate_expression.count.enable,
+                count_distinct: field_aggregate_expression.count_distinct.enable,
+                functions: field_aggregate_expression
+                    .operand
+                    .aggregation_functions
+                    .iter()
+                    .map(|function| function.name.clone())
+                    .collect(),
+            },
+        );
+    }
+
+    Some(ModelAggregates {
+        count: aggregate_expression.count.enable,
+        fields,
     })
 }
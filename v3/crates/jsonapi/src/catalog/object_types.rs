@@ -1,12 +1,16 @@
+use super::models::model_unique_identifier;
 use super::types::{ObjectType, RelationshipTarget, ScalarTypeForDataConnector, Type};
 use crate::types::ObjectTypeWarning;
 use hasura_authn_core::Role;
 use indexmap::IndexMap;
 use metadata_resolve::{
-    ObjectTypeWithRelationships, Qualified, QualifiedBaseType, QualifiedTypeName,
-    QualifiedTypeReference, ScalarTypeRepresentation, unwrap_custom_type_name,
+    ModelWithPermissions, ObjectTypeWithRelationships, Qualified, QualifiedBaseType,
+    QualifiedTypeName, QualifiedTypeReference, ScalarTypeRepresentation, unwrap_custom_type_name,
+};
+use open_dds::{
+    models::ModelName,
+    types::{CustomTypeName, InbuiltType},
 };
-use open_dds::types::{CustomTypeName, InbuiltType};
 use std::collections::BTreeMap;
 
 // look at permissions and work out which fields we're allowed to see
@@ -14,6 +18,7 @@ use std::collections::BTreeMap;
 pub fn build_object_type(
     object_type: &ObjectTypeWithRelationships,
     role: &Role,
+    models: &IndexMap<Qualified<ModelName>, ModelWithPermissions>,
     object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectTypeWithRelationships>,
     scalar_types: &BTreeMap<Qualified<CustomTypeName>, ScalarTypeRepresentation>,
 ) -> Result<ObjectType, ObjectTypeWarning> {
@@ -55,6 +60,9 @@ pub fn build_object_type(
                     target = Some(RelationshipTarget::Model {
                         object_type: model.target_typename.clone(),
                         relationship_type: model.relationship_type.clone(),
+                        unique_identifier: models
+                            .get(&model.model_name)
+                            .and_then(|target_model| model_unique_identifier(&target_model.model)),
                     });
                 }
             }
@@ -88,7 +96,7 @@ pub fn build_object_type(
 }
 
 // Check if object_type is accessible to given role
-fn object_type_permission_access(
+pub(super) fn object_type_permission_access(
     role: &Role,
     type_name: &Qualified<CustomTypeName>,
     object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectTypeWithRelationships>,
@@ -104,7 +112,7 @@ fn object_type_permission_access(
 }
 
 // turn an OpenDD type into a type representation
-fn type_from_type_representation(
+pub(super) fn type_from_type_representation(
     qualified_type_reference: &QualifiedTypeReference,
     scalar_types: &BTreeMap<Qualified<CustomTypeName>, ScalarTypeRepresentation>,
     object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectTypeWithRelationships>,
@@ -1,3 +1,4 @@
+use super::commands::build_command;
 use super::models::build_model;
 use super::object_types::build_object_type;
 use crate::types::{RoleWarning, Warning};
@@ -8,6 +9,9 @@ use metadata_resolve::{
     deserialize_qualified_btreemap, serialize_qualified_btreemap,
 };
 use open_dds::{
+    aggregates::AggregationFunctionName,
+    arguments::ArgumentName,
+    commands::CommandName,
     data_connector::DataConnectorName,
     models::ModelName,
     relationships::{RelationshipName, RelationshipType},
@@ -46,6 +50,8 @@ impl Catalog {
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct State {
     pub routes: BTreeMap<String, Model>,
+    /// commands exposed as RPC-style endpoints, keyed by `/<subgraph>/-/commands/<command>`
+    pub commands: BTreeMap<String, Command>,
     #[serde(
         serialize_with = "serialize_qualified_btreemap",
         deserialize_with = "deserialize_qualified_btreemap"
@@ -64,6 +70,9 @@ pub enum RelationshipTarget {
     Model {
         object_type: Qualified<CustomTypeName>,
         relationship_type: RelationshipType,
+        /// fields that make up the target model's unique identifier, used for the `id` of
+        /// related resources
+        unique_identifier: Option<Vec<FieldName>>,
     },
     Command {
         type_reference: QualifiedTypeReference,
@@ -78,7 +87,13 @@ impl State {
             .models
             .iter()
             .filter_map(|(model_name, model)| {
-                match build_model(model, role, &metadata.object_types) {
+                match build_model(
+                    model,
+                    role,
+                    &metadata.object_types,
+                    &metadata.scalar_types,
+                    &metadata.aggregate_expressions,
+                ) {
                     Ok(jsonapi_model) => Some((
                         format!("/{}/{}", model_name.subgraph, model_name.name),
                         jsonapi_model,
@@ -94,6 +109,30 @@ impl State {
             })
             .collect::<BTreeMap<_, _>>();
 
+        let commands = metadata
+            .commands
+            .iter()
+            .filter_map(|(command_name, command)| {
+                match build_command(
+                    command,
+                    role,
+                    &metadata.object_types,
+                    &metadata.scalar_types,
+                ) {
+                    Ok(jsonapi_command) => {
+                        Some((crate::command::command_route(command_name), jsonapi_command))
+                    }
+                    Err(warning) => {
+                        warnings.push(RoleWarning::Command {
+                            command_name: command_name.clone(),
+                            warning,
+                        });
+                        None
+                    }
+                }
+            })
+            .collect::<BTreeMap<_, _>>();
+
         // current naive approach is to include all types that `role` has
         // access to. we could minimise required metadata by traversing the required object types
         // when building the models, and only including those.
@@ -104,6 +143,7 @@ impl State {
                 match build_object_type(
                     object_type,
                     role,
+                    &metadata.models,
                     &metadata.object_types,
                     &metadata.scalar_types,
                 ) {
@@ -123,6 +163,7 @@ impl State {
         (
             Self {
                 routes,
+                commands,
                 object_types,
             },
             warnings,
@@ -164,4 +205,55 @@ pub struct Model {
     pub data_type: Qualified<CustomTypeName>,
     pub data_connector_name: Qualified<DataConnectorName>,
     pub filter_expression_type: Option<Arc<ResolvedObjectBooleanExpressionType>>,
+    /// fields that make up the model's unique identifier, used to address a single resource
+    /// as `/<subgraph>/<model>/<id>`
+    pub unique_identifier: Option<Vec<FieldName>>,
+    /// what can be aggregated, if the model has an `AggregateExpression`
+    pub aggregates: Option<ModelAggregates>,
+    /// arguments the caller provides. Arguments with presets for the role are left out.
+    pub arguments: IndexMap<ArgumentName, Argument>,
+}
+
+// the parts of a model's `AggregateExpression` the role can use. Only scalar fields the role can
+// see are included.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct ModelAggregates {
+    /// whether the objects of the model can be counted, with `aggregate[count]=*`
+    pub count: bool,
+    pub fields: IndexMap<FieldName, AggregatableField>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct AggregatableField {
+    pub count: bool,
+    pub count_distinct: bool,
+    /// the aggregation functions defined for the field's type, such as `sum` or `avg`
+    pub functions: Vec<AggregationFunctionName>,
+}
+
+// only the parts of a Command we need to expose it as an RPC-style endpoint
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Command {
+    pub name: Qualified<CommandName>,
+    pub description: Option<String>,
+    pub kind: CommandKind,
+    /// arguments the caller provides. Arguments with presets for the role are left out.
+    pub arguments: IndexMap<ArgumentName, Argument>,
+    pub output_type: Type,
+}
+
+// functions are called with `GET` and arguments in the query string, procedures with `POST`
+// and arguments in the JSON body
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum CommandKind {
+    Function,
+    Procedure,
+}
+
+// an argument of a model or command that the caller provides
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Argument {
+    pub argument_type: Type,
+    pub description: Option<String>,
+    pub is_required: bool,
 }
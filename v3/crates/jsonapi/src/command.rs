@@ -0,0 +1,339 @@
+//! Commands exposed as RPC-style endpoints at `/<subgraph>/-/commands/<command>`. Functions are
+//! called with `GET` and take their arguments from the query string, procedures are called with
+//! `POST` and take their arguments from a JSON object in the request body.
+//!
+//! `-` is not a valid model name, so command routes can never be mistaken for a model's, even
+//! one called `commands`.
+//!
+//! JSON:API has no notion of RPC, so the response is a document with the command's result in
+//! `data`.
+use super::helpers::{get_object_type, parse_argument_value};
+use super::types::{CommandResponse, RequestError};
+use crate::catalog::{Command, CommandKind, ObjectType, Type};
+use axum::http::{HeaderMap, Method, StatusCode, Uri};
+use engine_types::HttpContext;
+use hasura_authn_core::Session;
+use indexmap::IndexMap;
+use metadata_resolve::{LifecyclePluginConfigs, Metadata, Qualified};
+use open_dds::{
+    arguments::ArgumentName,
+    commands::CommandName,
+    identifier::Identifier,
+    query::{Alias, ObjectSubSelection},
+    types::CustomTypeName,
+};
+use plan_types::{
+    CommandReturnKind, FUNCTION_IR_VALUE_COLUMN_NAME, NDCMutationExecution, NDCQueryExecution,
+    ProcessResponseAs,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("command {command_name} must be called with {expected}")]
+    MethodNotAllowed {
+        command_name: Qualified<CommandName>,
+        expected: Method,
+    },
+    #[error("unknown argument: {0}")]
+    UnknownArgument(String),
+    #[error("missing required argument: {0}")]
+    MissingArgument(ArgumentName),
+    #[error("invalid value for argument {argument_name}: {value}")]
+    InvalidArgumentValue {
+        argument_name: ArgumentName,
+        value: String,
+    },
+    #[error("invalid request body, expected a JSON object of arguments: {0}")]
+    InvalidRequestBody(serde_json::Error),
+    #[error("unexpected execution plan for command {0}")]
+    UnexpectedExecutionPlan(Qualified<CommandName>),
+    #[error("command {0} returned no result")]
+    NoResult(Qualified<CommandName>),
+}
+
+impl CommandError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::MethodNotAllowed { .. } => StatusCode::METHOD_NOT_ALLOWED,
+            Self::UnknownArgument(_)
+            | Self::MissingArgument(_)
+            | Self::InvalidArgumentValue { .. }
+            | Self::InvalidRequestBody(_) => StatusCode::BAD_REQUEST,
+            Self::UnexpectedExecutionPlan(_) | Self::NoResult(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+/// The route of a command, `/<subgraph>/-/commands/<command>`
+pub(crate) fn command_route(command_name: &Qualified<CommandName>) -> String {
+    format!(
+        "/{}/-/commands/{}",
+        command_name.subgraph, command_name.name
+    )
+}
+
+/// Whether the request path is a command route, `/<subgraph>/-/commands/<command>`
+pub fn is_command_request(uri: &Uri) -> bool {
+    let paths = uri
+        .path()
+        .split('/')
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>();
+    matches!(paths.as_slice(), [_, "-", "commands", _])
+}
+
+pub async fn execute_command(
+    http_context: &HttpContext,
+    plugins: &LifecyclePluginConfigs,
+    session: &Session,
+    request_headers: &HeaderMap,
+    metadata: &Metadata,
+    command: &Command,
+    object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
+    http_method: &Method,
+    query_parameters: &BTreeMap<String, String>,
+    request_body: &[u8],
+) -> Result<CommandResponse, RequestError> {
+    let expected_method = match command.kind {
+        CommandKind::Function => Method::GET,
+        CommandKind::Procedure => Method::POST,
+    };
+    if *http_method != expected_method {
+        return Err(CommandError::MethodNotAllowed {
+            command_name: command.name.clone(),
+            expected: expected_method,
+        }
+        .into());
+    }
+
+    let arguments = match command.kind {
+        CommandKind::Function => parse_query_arguments(command, query_parameters)?,
+        CommandKind::Procedure => parse_body_arguments(command, request_body)?,
+    };
+
+    let command_selection = open_dds::query::CommandSelection {
+        target: open_dds::query::CommandTarget {
+            subgraph: command.name.subgraph.clone(),
+            command_name: command.name.name.clone(),
+            arguments: arguments
+                .into_iter()
+                .map(|(argument_name, value)| {
+                    (argument_name, open_dds::query::Value::Literal(value))
+                })
+                .collect(),
+        },
+        selection: output_selection(object_types, &command.output_type)?,
+    };
+    let query_request = open_dds::query::QueryRequest::V1(open_dds::query::QueryRequestV1 {
+        queries: IndexMap::from_iter([(
+            Alias::new(open_dds::identifier!("jsonapi_command")),
+            open_dds::query::Query::Command(command_selection),
+        )]),
+    });
+
+    let execution_plan =
+        plan::plan_query_request(&query_request, metadata, session, request_headers)
+            .map_err(RequestError::PlanError)?;
+
+    let process_response_as = command_response_as(metadata, command);
+
+    let result = match execution_plan {
+        plan::ExecutionPlan::Queries(queries) => {
+            let (_alias, execution_tree) = queries
+                .into_iter()
+                .next()
+                .ok_or_else(|| CommandError::UnexpectedExecutionPlan(command.name.clone()))?;
+            let rowsets = execute::resolve_ndc_query_execution(
+                http_context,
+                plugins,
+                session,
+                request_headers,
+                NDCQueryExecution {
+                    execution_span_attribute: "REST",
+                    execution_tree,
+                    field_span_attribute: "REST".into(),
+                    process_response_as,
+                },
+                None,
+            )
+            .await
+            .map_err(RequestError::ExecuteError)?;
+            // functions return their result in a single row
+            rowsets
+                .into_iter()
+                .next()
+                .and_then(|rowset| rowset.rows)
+                .and_then(|rows| rows.into_iter().next())
+                .and_then(|row| {
+                    row.into_iter().find(|(field_name, _)| {
+                        field_name.as_str() == FUNCTION_IR_VALUE_COLUMN_NAME
+                    })
+                })
+                .map(|(_, value)| value.0)
+                .ok_or_else(|| CommandError::NoResult(command.name.clone()))?
+        }
+        plan::ExecutionPlan::Mutation(execution_tree) => {
+            let data_connector = execution_tree
+                .mutation_execution_plan
+                .data_connector
+                .clone();
+            let mutation_response = execute::resolve_ndc_mutation_execution(
+                http_context,
+                plugins,
+                session,
+                request_headers,
+                NDCMutationExecution {
+                    execution_tree,
+                    data_connector,
+                    execution_span_attribute: "REST",
+                    field_span_attribute: "REST".into(),
+                    process_response_as,
+                },
+                None,
+            )
+            .await
+            .map_err(RequestError::ExecuteError)?;
+            match mutation_response.operation_results.into_iter().next() {
+                Some(ndc_models::MutationOperationResults::Procedure { result }) => result,
+                None => return Err(CommandError::NoResult(command.name.clone()).into()),
+            }
+        }
+    };
+
+    Ok(CommandResponse {
+        data: extract_result(metadata, command, result),
+    })
+}
+
+fn parse_query_arguments(
+    command: &Command,
+    query_parameters: &BTreeMap<String, String>,
+) -> Result<IndexMap<ArgumentName, serde_json::Value>, CommandError> {
+    let mut arguments = IndexMap::new();
+    for (name, value) in query_parameters {
+        let (argument_name, argument) = command
+            .arguments
+            .iter()
+            .find(|(argument_name, _)| argument_name.as_str() == name)
+            .ok_or_else(|| CommandError::UnknownArgument(name.clone()))?;
+        let argument_value =
+            parse_argument_value(&argument.argument_type, value).ok_or_else(|| {
+                CommandError::InvalidArgumentValue {
+                    argument_name: argument_name.clone(),
+                    value: value.clone(),
+                }
+            })?;
+        arguments.insert(argument_name.clone(), argument_value);
+    }
+    check_required_arguments(command, &arguments)?;
+    Ok(arguments)
+}
+
+fn parse_body_arguments(
+    command: &Command,
+    request_body: &[u8],
+) -> Result<IndexMap<ArgumentName, serde_json::Value>, CommandError> {
+    // procedures without arguments can be called without a body
+    let body: serde_json::Map<String, serde_json::Value> = if request_body.is_empty() {
+        serde_json::Map::new()
+    } else {
+        serde_json::from_slice(request_body).map_err(CommandError::InvalidRequestBody)?
+    };
+    let mut arguments = IndexMap::new();
+    for (name, value) in body {
+        let argument_name = command
+            .arguments
+            .keys()
+            .find(|argument_name| argument_name.as_str() == name)
+            .ok_or_else(|| CommandError::UnknownArgument(name.clone()))?;
+        arguments.insert(argument_name.clone(), value);
+    }
+    check_required_arguments(command, &arguments)?;
+    Ok(arguments)
+}
+
+fn check_required_arguments(
+    command: &Command,
+    arguments: &IndexMap<ArgumentName, serde_json::Value>,
+) -> Result<(), CommandError> {
+    for (argument_name, argument) in &command.arguments {
+        if argument.is_required && !arguments.contains_key(argument_name) {
+            return Err(CommandError::MissingArgument(argument_name.clone()));
+        }
+    }
+    Ok(())
+}
+
+// commands return their whole output, so we select every field the role can see
+fn output_selection(
+    object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
+    output_type: &Type,
+) -> Result<Option<IndexMap<Alias, ObjectSubSelection>>, RequestError> {
+    match output_type {
+        Type::Scalar(_) | Type::ScalarForDataConnector(_) => Ok(None),
+        Type::List(inner) => output_selection(object_types, inner),
+        Type::Object(object_type_name) => {
+            let object_type = get_object_type(object_types, object_type_name)
+                .map_err(RequestError::ParseError)?;
+            let mut selection = IndexMap::new();
+            for (field_name, field_type) in &object_type.type_fields {
+                let field_name_ident = Identifier::new(field_name.as_str())
+                    .map_err(|e| RequestError::BadRequest(e.into()))?;
+                selection.insert(
+                    Alias::new(field_name_ident),
+                    ObjectSubSelection::Field(open_dds::query::ObjectFieldSelection {
+                        target: open_dds::query::ObjectFieldTarget {
+                            arguments: IndexMap::new(),
+                            field_name: field_name.clone(),
+                        },
+                        selection: output_selection(object_types, field_type)?,
+                    }),
+                );
+            }
+            Ok(Some(selection))
+        }
+    }
+}
+
+fn command_response_as(metadata: &Metadata, command: &Command) -> ProcessResponseAs {
+    let resolved_command = metadata.commands.get(&command.name);
+    ProcessResponseAs::CommandResponse {
+        command_name: Arc::new(command.name.clone()),
+        is_nullable: resolved_command
+            .is_none_or(|resolved_command| resolved_command.command.output_type.nullable),
+        return_kind: match &command.output_type {
+            Type::List(_) => CommandReturnKind::Array,
+            _ => CommandReturnKind::Object,
+        },
+        response_config: resolved_command
+            .and_then(|resolved_command| resolved_command.command.source.as_ref())
+            .and_then(|source| source.data_connector.response_config.clone()),
+    }
+}
+
+// connectors that forward response headers wrap the command's result, which we unwrap
+fn extract_result(
+    metadata: &Metadata,
+    command: &Command,
+    result: serde_json::Value,
+) -> serde_json::Value {
+    let response_config = metadata
+        .commands
+        .get(&command.name)
+        .and_then(|resolved_command| resolved_command.command.source.as_ref())
+        .and_then(|source| source.data_connector.response_config.as_ref());
+    match (response_config, result) {
+        (Some(response_config), serde_json::Value::Object(mut result_map))
+            if result_map.contains_key(response_config.result_field.as_str()) =>
+        {
+            result_map
+                .remove(response_config.result_field.as_str())
+                .unwrap_or_default()
+        }
+        (_, result) => result,
+    }
+}
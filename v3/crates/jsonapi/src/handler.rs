@@ -1,10 +1,13 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+use super::command;
+use super::mutation;
 use super::parse;
 use super::process_response;
-use super::types::{RelationshipTree, RequestError};
+use super::types::{CommandResponse, MutationResponse, RelationshipTree, RequestError};
 use crate::catalog::{Catalog, Model, State};
-use axum::http::{HeaderMap, Method, Uri};
+use axum::http::{HeaderMap, Method, StatusCode, Uri};
 use engine_types::HttpContext;
 use hasura_authn_core::Session;
 use metadata_resolve::LifecyclePluginConfigs;
@@ -55,20 +58,39 @@ pub async fn handler_internal(
                 },
             )?;
 
-            // execute the query with the query-engine
-            let rowsets = tracer
+            // aggregates come back in the rowset rather than as rows
+            let process_response_as = if query_ir.aggregate_request.is_some() {
+                ProcessResponseAs::Aggregates
+            } else {
+                ProcessResponseAs::Array { is_nullable: false }
+            };
+
+            // execute the query with the query-engine. The total, if requested, is counted at
+            // the same time.
+            let (rowsets, total) = tracer
                 .in_span_async(
                     "query_engine_execute",
                     "Execute query",
                     SpanVisibility::User,
                     || {
-                        Box::pin(query_engine_execute(
-                            &query_ir.query_request,
-                            &metadata,
-                            &session,
-                            &http_context,
-                            &plugins,
-                            &request_headers,
+                        Box::pin(futures_util::future::try_join(
+                            query_engine_execute(
+                                &query_ir.query_request,
+                                &metadata,
+                                &session,
+                                &http_context,
+                                &plugins,
+                                &request_headers,
+                                process_response_as,
+                            ),
+                            query_engine_count(
+                                query_ir.total_query_request.as_ref(),
+                                &metadata,
+                                &session,
+                                &http_context,
+                                &plugins,
+                                &request_headers,
+                            ),
                         ))
                     },
                 )
@@ -79,20 +101,153 @@ pub async fn handler_internal(
                 "process_response",
                 "Process response",
                 SpanVisibility::User,
-                || {
-                    process_response::process_result(
-                        rowsets,
-                        &query_ir.root_type_name,
-                        &relationship_tree,
-                        &query_string,
-                        &state.object_types,
-                    )
+                || match &query_ir.aggregate_request {
+                    Some(aggregate_request) => {
+                        process_response::process_aggregate_result(rowsets, aggregate_request)
+                    }
+                    None => {
+                        let mut document = process_response::process_result(
+                            rowsets,
+                            &query_ir.root_type_name,
+                            model.unique_identifier.as_deref(),
+                            &query_ir.target,
+                            query_ir.pagination.as_ref(),
+                            &relationship_tree,
+                            &query_string,
+                            &state.object_types,
+                        )?;
+                        if let Some(pagination) = &query_ir.pagination {
+                            process_response::add_pagination(
+                                &mut document,
+                                &uri,
+                                pagination,
+                                total,
+                            );
+                        }
+                        Ok(document)
+                    }
                 },
             )
         }
     }
 }
 
+/// Handle `POST`, `PATCH` and `DELETE` requests, which create, update and delete resources
+pub async fn mutation_handler_internal(
+    request_headers: Arc<HeaderMap>,
+    http_context: Arc<HttpContext>,
+    session: Arc<Session>,
+    catalog: &Catalog,
+    metadata: Arc<Metadata>,
+    http_method: Method,
+    uri: Uri,
+    request_body: &[u8],
+) -> Result<MutationResponse, RequestError> {
+    let tracer = tracing_util::global_tracer();
+
+    let state = catalog
+        .state_per_role
+        .get(&session.role)
+        .ok_or_else(|| RequestError::NotFound)?;
+
+    let model = validate_route(state, &uri).ok_or(RequestError::NotFound)?;
+    let model_info = parse::parse_url(&uri).map_err(RequestError::ParseError)?;
+
+    let mut response = tracer
+        .in_span_async(
+            "execute_mutation",
+            "Execute mutation",
+            SpanVisibility::User,
+            || {
+                Box::pin(mutation::execute_mutation(
+                    &http_context,
+                    &session,
+                    &metadata,
+                    model,
+                    &state.object_types,
+                    &http_method,
+                    model_info,
+                    request_body,
+                ))
+            },
+        )
+        .await?;
+
+    // return the created resource as the role would fetch it, including any values the data
+    // connector filled in. The resource has been created by now, so if it can't be read back
+    // we return the attributes that were written instead of failing the request. Resource ids
+    // are percent-encoded, so the id is a single path segment.
+    if response.status == StatusCode::CREATED
+        && let Some(document) = &mut response.document
+        && let Some(jsonapi_library::api::PrimaryData::Single(resource)) = &document.data
+        && let Ok(resource_uri) =
+            format!("{}/{}", uri.path().trim_end_matches('/'), resource.id).parse::<Uri>()
+        && let Ok(created) = handler_internal(
+            request_headers,
+            http_context,
+            Arc::new(metadata.plugin_configs.clone()),
+            session,
+            catalog,
+            metadata.clone(),
+            Method::GET,
+            resource_uri,
+            jsonapi_library::query::Query::from_params(""),
+        )
+        .await
+    {
+        document.data = created.data;
+    }
+    Ok(response)
+}
+
+/// Handle requests to commands, exposed at `/<subgraph>/-/commands/<command>`
+pub async fn command_handler_internal(
+    request_headers: Arc<HeaderMap>,
+    http_context: Arc<HttpContext>,
+    plugins: Arc<LifecyclePluginConfigs>,
+    session: Arc<Session>,
+    catalog: &Catalog,
+    metadata: Arc<Metadata>,
+    http_method: Method,
+    uri: Uri,
+    query_parameters: BTreeMap<String, String>,
+    request_body: &[u8],
+) -> Result<CommandResponse, RequestError> {
+    let tracer = tracing_util::global_tracer();
+
+    let state = catalog
+        .state_per_role
+        .get(&session.role)
+        .ok_or_else(|| RequestError::NotFound)?;
+
+    let command = state
+        .commands
+        .get(uri.path().trim_end_matches('/'))
+        .ok_or(RequestError::NotFound)?;
+
+    tracer
+        .in_span_async(
+            "execute_command",
+            "Execute command",
+            SpanVisibility::User,
+            || {
+                Box::pin(command::execute_command(
+                    &http_context,
+                    &plugins,
+                    &session,
+                    &request_headers,
+                    &metadata,
+                    command,
+                    &state.object_types,
+                    &http_method,
+                    &query_parameters,
+                    request_body,
+                ))
+            },
+        )
+        .await
+}
+
 fn validate_route<'a>(state: &'a State, uri: &'a Uri) -> Option<&'a Model> {
     // TODO: to_string() maybe not optimal. Optimize later
     let uri_s = uri.to_string();
@@ -104,6 +259,36 @@ fn validate_route<'a>(state: &'a State, uri: &'a Uri) -> Option<&'a Model> {
     None
 }
 
+// count the objects of a collection, for `meta.total`
+async fn query_engine_count(
+    total_query_request: Option<&open_dds::query::QueryRequest>,
+    metadata: &Metadata,
+    session: &Session,
+    http_context: &Arc<HttpContext>,
+    plugins: &LifecyclePluginConfigs,
+    request_headers: &HeaderMap,
+) -> Result<Option<u64>, RequestError> {
+    let Some(total_query_request) = total_query_request else {
+        return Ok(None);
+    };
+    let rowsets = query_engine_execute(
+        total_query_request,
+        metadata,
+        session,
+        http_context,
+        plugins,
+        request_headers,
+        ProcessResponseAs::Aggregates,
+    )
+    .await?;
+    Ok(rowsets
+        .into_iter()
+        .next()
+        .and_then(|rowset| rowset.aggregates)
+        .and_then(|aggregates| aggregates.into_values().next())
+        .and_then(|total| total.as_u64()))
+}
+
 async fn query_engine_execute(
     query_ir: &open_dds::query::QueryRequest,
     metadata: &Metadata,
@@ -111,6 +296,7 @@ async fn query_engine_execute(
     http_context: &Arc<HttpContext>,
     plugins: &LifecyclePluginConfigs,
     request_headers: &HeaderMap,
+    process_response_as: ProcessResponseAs,
 ) -> Result<Vec<ndc_models::RowSet>, RequestError> {
     let execution_plan = plan::plan_query_request(query_ir, metadata, session, request_headers)
         .map_err(RequestError::PlanError)?;
@@ -121,7 +307,7 @@ async fn query_engine_execute(
                     execution_span_attribute: "REST",
                     execution_tree: execution_tree.clone(),
                     field_span_attribute: "REST".into(),
-                    process_response_as: ProcessResponseAs::Array { is_nullable: false },
+                    process_response_as,
                 };
                 Ok(execute::resolve_ndc_query_execution(
                     http_context,
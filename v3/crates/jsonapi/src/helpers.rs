@@ -1,9 +1,33 @@
 use crate::ParseError;
-use crate::catalog::ObjectType;
+use crate::catalog::{Model, ObjectType, Type};
+use indexmap::IndexMap;
 use metadata_resolve::{Qualified, QualifiedBaseType, QualifiedTypeReference};
-use open_dds::{relationships::RelationshipType, types::CustomTypeName};
+use open_dds::{
+    relationships::RelationshipType,
+    types::{CustomTypeName, FieldName},
+};
+use percent_encoding::{AsciiSet, CONTROLS, percent_decode_str, utf8_percent_encode};
 use std::collections::BTreeMap;
 
+/// Characters escaped in each value of a unique identifier: those that separate the values or
+/// cannot appear in a URL path segment as they are.
+const UNIQUE_IDENTIFIER_VALUE: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b',')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'\\')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
 pub fn get_object_type<'a>(
     object_types: &'a BTreeMap<Qualified<CustomTypeName>, ObjectType>,
     object_type_name: &Qualified<CustomTypeName>,
@@ -22,3 +46,183 @@ pub(crate) fn type_reference_to_relationship_type(
         QualifiedBaseType::List(_) => RelationshipType::Array,
     }
 }
+
+/// Parse the `id` of a resource into a value for each field of the model's unique identifier.
+/// Compound unique identifiers are written as comma-separated values, in the order in which
+/// the fields are declared. Each value is percent-encoded, so it may contain commas.
+pub fn parse_unique_identifier(
+    model: &Model,
+    object_type: &ObjectType,
+    id: &str,
+) -> Result<IndexMap<FieldName, serde_json::Value>, ParseError> {
+    let unique_identifier = model
+        .unique_identifier
+        .as_ref()
+        .ok_or_else(|| ParseError::ModelHasNoUniqueIdentifier(model.name.clone()))?;
+
+    let id_values = id
+        .split(',')
+        .map(|id_value| percent_decode_str(id_value).decode_utf8())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ParseError::InvalidUniqueIdentifier(id.to_string()))?;
+    if id_values.len() != unique_identifier.len() {
+        return Err(ParseError::InvalidUniqueIdentifier(id.to_string()));
+    }
+
+    let mut values = IndexMap::new();
+    for (field_name, id_value) in unique_identifier.iter().zip(id_values) {
+        // the role may not be able to see every field of the unique identifier, in which case
+        // we can't let them address resources with it
+        let field_type = object_type
+            .type_fields
+            .get(field_name)
+            .ok_or_else(|| ParseError::ModelHasNoUniqueIdentifier(model.name.clone()))?;
+        let value = parse_scalar_value(field_type, &id_value)
+            .ok_or_else(|| ParseError::InvalidUniqueIdentifier(id.to_string()))?;
+        values.insert(field_name.clone(), value);
+    }
+    Ok(values)
+}
+
+/// Render the `id` of a resource from its attributes, the inverse of `parse_unique_identifier`.
+/// Returns `None` if any of the unique identifier fields is missing.
+pub fn render_unique_identifier<'a>(
+    unique_identifier: &[FieldName],
+    attributes: impl Fn(&FieldName) -> Option<&'a serde_json::Value>,
+) -> Option<String> {
+    let id_values = unique_identifier
+        .iter()
+        .map(|field_name| {
+            let value = match attributes(field_name)? {
+                serde_json::Value::String(value) => value.clone(),
+                serde_json::Value::Null => return None,
+                value => value.to_string(),
+            };
+            Some(utf8_percent_encode(&value, UNIQUE_IDENTIFIER_VALUE).to_string())
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(id_values.join(","))
+}
+
+/// Parse an argument written in the query string. Scalars are written as they are, anything
+/// else as JSON.
+pub(crate) fn parse_argument_value(argument_type: &Type, value: &str) -> Option<serde_json::Value> {
+    match argument_type {
+        Type::Scalar(_) | Type::ScalarForDataConnector(_) => {
+            parse_scalar_value(argument_type, value)
+        }
+        Type::List(_) | Type::Object(_) => serde_json::from_str(value).ok(),
+    }
+}
+
+/// Parse a scalar value written as a string, as in a URL, according to its type representation.
+/// Returns `None` for non-scalar types, or if the value does not match the type.
+#[allow(deprecated)]
+pub(crate) fn parse_scalar_value(field_type: &Type, value: &str) -> Option<serde_json::Value> {
+    let type_representation = match field_type {
+        Type::Scalar(type_representation) => Some(type_representation),
+        // if there are several representations we can't tell which one to use, so we pass a string
+        Type::ScalarForDataConnector(set_of_types) => {
+            if set_of_types.type_representations.len() == 1 {
+                set_of_types.type_representations.first()
+            } else {
+                None
+            }
+        }
+        Type::List(_) | Type::Object(_) => return None,
+    };
+    match type_representation {
+        Some(
+            ndc_models::TypeRepresentation::Int8
+            | ndc_models::TypeRepresentation::Int16
+            | ndc_models::TypeRepresentation::Int32
+            | ndc_models::TypeRepresentation::Int64,
+        ) => value.parse::<i64>().ok().map(serde_json::Value::from),
+        Some(ndc_models::TypeRepresentation::Float32 | ndc_models::TypeRepresentation::Float64) => {
+            value
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(serde_json::Value::Number)
+        }
+        Some(ndc_models::TypeRepresentation::Boolean) => {
+            value.parse::<bool>().ok().map(serde_json::Value::Bool)
+        }
+        _ => Some(serde_json::Value::String(value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use open_dds::{
+        data_connector::DataConnectorName, identifier, models::ModelName, subgraph_identifier,
+    };
+
+    fn field_name(name: &str) -> FieldName {
+        FieldName::new(open_dds::identifier::Identifier::new(name).unwrap())
+    }
+
+    fn unique_identifier() -> Vec<FieldName> {
+        vec![field_name("author"), field_name("title")]
+    }
+
+    fn model() -> Model {
+        Model {
+            name: Qualified::new(
+                subgraph_identifier!("default"),
+                ModelName::new(identifier!("Articles")),
+            ),
+            description: None,
+            data_type: Qualified::new(
+                subgraph_identifier!("default"),
+                CustomTypeName(identifier!("Article")),
+            ),
+            data_connector_name: Qualified::new(
+                subgraph_identifier!("default"),
+                DataConnectorName::new(identifier!("db")),
+            ),
+            filter_expression_type: None,
+            unique_identifier: Some(unique_identifier()),
+            aggregates: None,
+            arguments: IndexMap::new(),
+        }
+    }
+
+    fn object_type() -> ObjectType {
+        ObjectType {
+            type_fields: unique_identifier()
+                .into_iter()
+                .map(|field_name| {
+                    (
+                        field_name,
+                        Type::Scalar(ndc_models::TypeRepresentation::String),
+                    )
+                })
+                .collect(),
+            type_relationships: IndexMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_unique_identifier_values_may_contain_commas() {
+        let attributes = serde_json::json!({"author": "Doe, Jane", "title": "50% of 1/2"});
+        let id = render_unique_identifier(&unique_identifier(), |field_name| {
+            attributes.get(field_name.as_str())
+        })
+        .unwrap();
+        assert_eq!(id, "Doe%2C%20Jane,50%25%20of%201%2F2");
+
+        let values = parse_unique_identifier(&model(), &object_type(), &id).unwrap();
+        assert_eq!(
+            values,
+            IndexMap::from([
+                (field_name("author"), serde_json::json!("Doe, Jane")),
+                (field_name("title"), serde_json::json!("50% of 1/2")),
+            ])
+        );
+
+        // a comma that is not encoded still separates the values
+        assert!(parse_unique_identifier(&model(), &object_type(), "Doe, Jane,Title").is_err());
+    }
+}
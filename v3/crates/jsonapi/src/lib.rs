@@ -1,8 +1,10 @@
 mod catalog;
+mod command;
 mod endpoint;
 mod handler;
 mod helpers;
 mod middleware;
+mod mutation;
 mod parse;
 mod process_response;
 mod schema;
@@ -10,11 +12,14 @@ mod types;
 
 // explicit exports
 pub use catalog::Catalog;
+pub use command::is_command_request;
 pub use endpoint::EndPoint;
-pub use handler::handler_internal;
+pub use handler::{command_handler_internal, handler_internal, mutation_handler_internal};
 pub use middleware::{
     build_state_with_middleware_error_converter, jsonapi_request_tracing_middleware,
 };
 pub use parse::ParseError;
 pub use schema::{empty_schema, openapi_schema};
-pub use types::{InternalError, JsonApiHttpError, ModelInfo, RequestError};
+pub use types::{
+    CommandResponse, InternalError, JsonApiHttpError, ModelInfo, MutationResponse, RequestError,
+};
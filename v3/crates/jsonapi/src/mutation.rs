@@ -0,0 +1,957 @@
+//! JSON:API write requests. `POST`, `PATCH` and `DELETE` on a model route are turned into
+//! relational insert, update and delete requests against the model's data connector.
+//!
+//! spec: <https://jsonapi.org/format/#crud>
+use super::helpers::{get_object_type, parse_unique_identifier, render_unique_identifier};
+use super::process_response::render_type_name;
+use super::types::{ModelInfo, MutationResponse, RequestError};
+use crate::catalog::{Model, ObjectType};
+use axum::http::{Method, StatusCode};
+use engine_types::HttpContext;
+use hasura_authn_core::Session;
+use indexmap::IndexMap;
+use metadata_resolve::{
+    FieldMapping, Metadata, ModelPredicate, Qualified, QualifiedTypeReference, TypeMapping,
+    UnaryComparisonOperator, ValueExpression,
+};
+use open_dds::{
+    data_connector::DataConnectorColumnName, identifier::Identifier, models::ModelName,
+    query::ComparisonOperator, types::FieldName,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing_util::SpanVisibility;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MutationError {
+    #[error("{method} is not supported on this route")]
+    MethodNotSupported { method: Method },
+    #[error("a resource id is required to {operation} a resource")]
+    MissingResourceId { operation: RelationalOperation },
+    #[error("new resources must be created at the collection route, without an id")]
+    UnexpectedResourceId,
+    #[error(
+        "the unique identifier of a new resource must be provided, as its id or in its attributes"
+    )]
+    MissingCreatedResourceId,
+    #[error("relationships cannot be modified through this route")]
+    RelationshipWriteNotSupported,
+    #[error("role is not allowed to {operation} resources of model {model_name}")]
+    OperationNotAllowed {
+        operation: RelationalOperation,
+        model_name: Qualified<ModelName>,
+    },
+    #[error("invalid request document: {0}")]
+    InvalidRequestBody(serde_json::Error),
+    #[error("resource type {found} does not match the expected type {expected}")]
+    ResourceTypeMismatch { expected: String, found: String },
+    #[error("resource id {found} does not match the id {expected} in the URL")]
+    ResourceIdMismatch { expected: String, found: String },
+    #[error("unknown attribute: {0}")]
+    UnknownAttribute(String),
+    #[error("attribute {0} must be a scalar value")]
+    UnsupportedAttributeValue(String),
+    #[error("no attributes provided")]
+    NoAttributes,
+    #[error("resource not found")]
+    ResourceNotFound,
+    #[error("the select permission of model {model_name} cannot be applied to writes")]
+    UnsupportedPermissionFilter { model_name: Qualified<ModelName> },
+    #[error("model {0} has no source")]
+    ModelHasNoSource(Qualified<ModelName>),
+    #[error("field mapping not found for field {0}")]
+    FieldMappingNotFound(FieldName),
+}
+
+impl MutationError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::MethodNotSupported { .. } => StatusCode::METHOD_NOT_ALLOWED,
+            // spec: <https://jsonapi.org/format/#crud-creating-responses-403>
+            Self::OperationNotAllowed { .. }
+            | Self::UnsupportedPermissionFilter { .. }
+            | Self::MissingCreatedResourceId => StatusCode::FORBIDDEN,
+            Self::ResourceNotFound => StatusCode::NOT_FOUND,
+            // spec: <https://jsonapi.org/format/#crud-creating-responses-409>
+            Self::ResourceTypeMismatch { .. } | Self::ResourceIdMismatch { .. } => {
+                StatusCode::CONFLICT
+            }
+            Self::MissingResourceId { .. }
+            | Self::UnexpectedResourceId
+            | Self::RelationshipWriteNotSupported
+            | Self::InvalidRequestBody(_)
+            | Self::UnknownAttribute(_)
+            | Self::UnsupportedAttributeValue(_)
+            | Self::NoAttributes => StatusCode::BAD_REQUEST,
+            Self::ModelHasNoSource(_) | Self::FieldMappingNotFound(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::with_trait::Display)]
+pub enum RelationalOperation {
+    #[display("insert")]
+    Insert,
+    #[display("update")]
+    Update,
+    #[display("delete")]
+    Delete,
+}
+
+/// A JSON:API request document containing a single resource object
+#[derive(Debug, Deserialize)]
+struct RequestDocument {
+    data: RequestResource,
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestResource {
+    #[serde(rename = "type")]
+    resource_type: String,
+    id: Option<String>,
+    #[serde(default)]
+    attributes: serde_json::Map<String, serde_json::Value>,
+}
+
+pub async fn execute_mutation(
+    http_context: &HttpContext,
+    session: &Session,
+    metadata: &Metadata,
+    model: &Model,
+    object_types: &BTreeMap<Qualified<open_dds::types::CustomTypeName>, ObjectType>,
+    http_method: &Method,
+    model_info: ModelInfo,
+    request_body: &[u8],
+) -> Result<MutationResponse, RequestError> {
+    let ModelInfo {
+        subgraph: _,
+        name: _,
+        unique_identifier,
+        relationship,
+    } = model_info;
+
+    if !relationship.is_empty() {
+        return Err(MutationError::RelationshipWriteNotSupported.into());
+    }
+
+    let operation = match *http_method {
+        Method::POST => RelationalOperation::Insert,
+        Method::PATCH => RelationalOperation::Update,
+        Method::DELETE => RelationalOperation::Delete,
+        _ => {
+            return Err(MutationError::MethodNotSupported {
+                method: http_method.clone(),
+            }
+            .into());
+        }
+    };
+
+    let object_type =
+        get_object_type(object_types, &model.data_type).map_err(RequestError::ParseError)?;
+
+    // check the role is allowed to perform this operation
+    let mut plan_state = plan::PlanState::new();
+    let model_view = plan::get_model(metadata, &model.name, &session.variables, &mut plan_state)
+        .map_err(|permission_error| {
+            RequestError::PlanError(plan::PlanError::Permission(permission_error))
+        })?;
+    let is_allowed = match operation {
+        RelationalOperation::Insert => model_view.permission.relational_insert.is_some(),
+        RelationalOperation::Update => model_view.permission.relational_update.is_some(),
+        RelationalOperation::Delete => model_view.permission.relational_delete.is_some(),
+    };
+    if !is_allowed {
+        return Err(MutationError::OperationNotAllowed {
+            operation,
+            model_name: model.name.clone(),
+        }
+        .into());
+    }
+
+    let model_source = model_view.source;
+    let TypeMapping::Object { field_mappings, .. } = model_source
+        .type_mappings
+        .get(&model.data_type)
+        .ok_or_else(|| MutationError::ModelHasNoSource(model.name.clone()))?;
+
+    let collection = RelationalCollection::new(model_source, field_mappings);
+
+    // only rows the role can read may be updated or deleted
+    let permission_predicate = match operation {
+        RelationalOperation::Insert => None,
+        RelationalOperation::Update | RelationalOperation::Delete
+            if !model_view.permission.filters.is_empty() =>
+        {
+            let resolve_value = |value: &ValueExpression, value_type: &QualifiedTypeReference| {
+                plan::make_argument_from_value_expression(
+                    value,
+                    value_type,
+                    &session.variables,
+                    &model_source.type_mappings,
+                    &metadata.object_types,
+                )
+                .map_err(RequestError::PlanError)
+            };
+            let select_filter =
+                ModelPredicate::And(model_view.permission.filters.into_iter().cloned().collect());
+            Some(collection.permission_predicate(&model.name, &select_filter, &resolve_value)?)
+        }
+        // no filter, so every row can be read
+        RelationalOperation::Update | RelationalOperation::Delete => None,
+    };
+
+    let tracer = tracing_util::global_tracer();
+    match operation {
+        RelationalOperation::Insert => {
+            if unique_identifier.is_some() {
+                return Err(MutationError::UnexpectedResourceId.into());
+            }
+            let resource = parse_request_document(model, request_body, None)?;
+            let mut attributes = validate_attributes(object_type, resource.attributes)?;
+
+            // client-generated ids are written to the unique identifier fields
+            // spec: <https://jsonapi.org/format/#crud-creating-client-ids>
+            if let Some(id) = &resource.id {
+                let id_values = parse_unique_identifier(model, object_type, id)
+                    .map_err(RequestError::ParseError)?;
+                for (field_name, value) in id_values {
+                    attributes.entry(field_name).or_insert(value);
+                }
+            }
+
+            // the created resource is returned, so we must know its id before inserting it
+            let id = model
+                .unique_identifier
+                .as_deref()
+                .and_then(|unique_identifier| {
+                    render_unique_identifier(unique_identifier, |field_name| {
+                        attributes.get(field_name)
+                    })
+                })
+                .ok_or(MutationError::MissingCreatedResourceId)?;
+
+            let insert_request = collection.insert_request(&attributes)?;
+            let response = tracer
+                .in_span_async(
+                    "execute_relational_insert",
+                    "Execute relational insert",
+                    SpanVisibility::User,
+                    || {
+                        Box::pin(execute::ndc::fetch_from_data_connector_insert_rel(
+                            http_context,
+                            &insert_request,
+                            &model_source.data_connector,
+                            None,
+                        ))
+                    },
+                )
+                .await
+                .map_err(|error| RequestError::ExecuteError(error.into()))?;
+
+            Ok(MutationResponse {
+                status: StatusCode::CREATED,
+                document: Some(resource_document(
+                    model,
+                    id,
+                    attributes,
+                    response.affected_rows,
+                )),
+            })
+        }
+        RelationalOperation::Update => {
+            let id = unique_identifier.ok_or(MutationError::MissingResourceId { operation })?;
+            let id_values = parse_unique_identifier(model, object_type, &id)
+                .map_err(RequestError::ParseError)?;
+            let resource = parse_request_document(model, request_body, Some(&id))?;
+            let attributes = validate_attributes(object_type, resource.attributes)?;
+            if attributes.is_empty() {
+                return Err(MutationError::NoAttributes.into());
+            }
+
+            let update_request =
+                collection.update_request(&id_values, permission_predicate, &attributes)?;
+            let response = tracer
+                .in_span_async(
+                    "execute_relational_update",
+                    "Execute relational update",
+                    SpanVisibility::User,
+                    || {
+                        Box::pin(execute::ndc::fetch_from_data_connector_update_rel(
+                            http_context,
+                            &update_request,
+                            &model_source.data_connector,
+                            None,
+                        ))
+                    },
+                )
+                .await
+                .map_err(|error| RequestError::ExecuteError(error.into()))?;
+
+            if response.affected_rows == 0 {
+                return Err(MutationError::ResourceNotFound.into());
+            }
+            Ok(MutationResponse {
+                status: StatusCode::OK,
+                document: Some(resource_document(
+                    model,
+                    id,
+                    attributes,
+                    response.affected_rows,
+                )),
+            })
+        }
+        RelationalOperation::Delete => {
+            let id = unique_identifier.ok_or(MutationError::MissingResourceId { operation })?;
+            let id_values = parse_unique_identifier(model, object_type, &id)
+                .map_err(RequestError::ParseError)?;
+
+            let delete_request = collection.delete_request(&id_values, permission_predicate)?;
+            let response = tracer
+                .in_span_async(
+                    "execute_relational_delete",
+                    "Execute relational delete",
+                    SpanVisibility::User,
+                    || {
+                        Box::pin(execute::ndc::fetch_from_data_connector_delete_rel(
+                            http_context,
+                            &delete_request,
+                            &model_source.data_connector,
+                            None,
+                        ))
+                    },
+                )
+                .await
+                .map_err(|error| RequestError::ExecuteError(error.into()))?;
+
+            if response.affected_rows == 0 {
+                return Err(MutationError::ResourceNotFound.into());
+            }
+            // spec: <https://jsonapi.org/format/#crud-deleting-responses-204>
+            Ok(MutationResponse {
+                status: StatusCode::NO_CONTENT,
+                document: None,
+            })
+        }
+    }
+}
+
+fn parse_request_document(
+    model: &Model,
+    request_body: &[u8],
+    expected_id: Option<&str>,
+) -> Result<RequestResource, MutationError> {
+    let RequestDocument { data: resource } =
+        serde_json::from_slice(request_body).map_err(MutationError::InvalidRequestBody)?;
+
+    let expected_type = render_type_name(&model.data_type);
+    if resource.resource_type != expected_type {
+        return Err(MutationError::ResourceTypeMismatch {
+            expected: expected_type,
+            found: resource.resource_type,
+        });
+    }
+    if let (Some(expected_id), Some(found)) = (expected_id, &resource.id)
+        && expected_id != found
+    {
+        return Err(MutationError::ResourceIdMismatch {
+            expected: expected_id.to_string(),
+            found: found.clone(),
+        });
+    }
+    Ok(resource)
+}
+
+// only fields the role can see may be written, and only with scalar values
+fn validate_attributes(
+    object_type: &ObjectType,
+    attributes: serde_json::Map<String, serde_json::Value>,
+) -> Result<IndexMap<FieldName, serde_json::Value>, MutationError> {
+    attributes
+        .into_iter()
+        .map(|(attribute, value)| {
+            let field_name = Identifier::new(attribute.as_str())
+                .map(FieldName::new)
+                .ok()
+                .filter(|field_name| object_type.type_fields.contains_key(field_name))
+                .ok_or_else(|| MutationError::UnknownAttribute(attribute.clone()))?;
+            if value.is_array() || value.is_object() {
+                return Err(MutationError::UnsupportedAttributeValue(attribute));
+            }
+            Ok((field_name, value))
+        })
+        .collect()
+}
+
+// the written resource, which `mutation_handler_internal` replaces with the stored resource
+// when the role can read it back
+fn resource_document(
+    model: &Model,
+    id: String,
+    attributes: IndexMap<FieldName, serde_json::Value>,
+    affected_rows: u64,
+) -> jsonapi_library::api::DocumentData {
+    let resource = jsonapi_library::api::Resource {
+        _type: render_type_name(&model.data_type),
+        id,
+        attributes: attributes
+            .into_iter()
+            .map(|(field_name, value)| (field_name.to_string(), value))
+            .collect(),
+        links: None,
+        meta: None,
+        relationships: None,
+    };
+    jsonapi_library::api::DocumentData {
+        data: Some(jsonapi_library::api::PrimaryData::Single(Box::new(
+            resource,
+        ))),
+        included: None,
+        links: None,
+        meta: Some(
+            [("affectedRows".to_string(), serde_json::json!(affected_rows))]
+                .into_iter()
+                .collect(),
+        ),
+        jsonapi: None,
+    }
+}
+
+/// The NDC view of a model's collection: its name, and every mapped column in a fixed order so
+/// that we can refer to them by index in relational expressions.
+struct RelationalCollection<'a> {
+    collection: ndc_models::CollectionName,
+    columns: Vec<(&'a FieldName, &'a FieldMapping)>,
+}
+
+impl<'a> RelationalCollection<'a> {
+    fn new(
+        model_source: &metadata_resolve::ModelSource,
+        field_mappings: &'a BTreeMap<FieldName, FieldMapping>,
+    ) -> Self {
+        Self {
+            collection: ndc_models::CollectionName::from(model_source.collection.as_str()),
+            columns: field_mappings.iter().collect(),
+        }
+    }
+
+    fn column_name(&self, field_name: &FieldName) -> Result<ndc_models::FieldName, MutationError> {
+        self.columns
+            .iter()
+            .find(|(name, _)| *name == field_name)
+            .map(|(_, field_mapping)| ndc_models::FieldName::from(field_mapping.column.as_str()))
+            .ok_or_else(|| MutationError::FieldMappingNotFound(field_name.clone()))
+    }
+
+    fn column_index(&self, field_name: &FieldName) -> Result<u64, MutationError> {
+        (0..)
+            .zip(&self.columns)
+            .find(|(_, (name, _))| *name == field_name)
+            .map(|(index, _)| index)
+            .ok_or_else(|| MutationError::FieldMappingNotFound(field_name.clone()))
+    }
+
+    // every column of the collection
+    fn scan(&self) -> ndc_models::Relation {
+        ndc_models::Relation::From {
+            collection: self.collection.clone(),
+            columns: self
+                .columns
+                .iter()
+                .map(|(_, field_mapping)| {
+                    ndc_models::FieldName::from(field_mapping.column.as_str())
+                })
+                .collect(),
+            arguments: BTreeMap::new(),
+        }
+    }
+
+    fn permission_column(
+        &self,
+        model_name: &Qualified<ModelName>,
+        ndc_column: &DataConnectorColumnName,
+        column_path: &[DataConnectorColumnName],
+    ) -> Result<ndc_models::RelationalExpression, MutationError> {
+        let unsupported = || MutationError::UnsupportedPermissionFilter {
+            model_name: model_name.clone(),
+        };
+        // nested fields have no column of their own
+        if !column_path.is_empty() {
+            return Err(unsupported());
+        }
+        (0..)
+            .zip(&self.columns)
+            .find(|(_, (_, field_mapping))| field_mapping.column == *ndc_column)
+            .map(|(index, _)| ndc_models::RelationalExpression::Column { index })
+            .ok_or_else(unsupported)
+    }
+
+    // a model's select permission as a relational expression over the collection's columns.
+    // Comparisons across relationships, on nested fields, or with operators that have no
+    // relational equivalent are rejected rather than ignored.
+    fn permission_predicate(
+        &self,
+        model_name: &Qualified<ModelName>,
+        predicate: &ModelPredicate,
+        resolve_value: &impl Fn(
+            &ValueExpression,
+            &QualifiedTypeReference,
+        ) -> Result<serde_json::Value, RequestError>,
+    ) -> Result<ndc_models::RelationalExpression, RequestError> {
+        let unsupported = || MutationError::UnsupportedPermissionFilter {
+            model_name: model_name.clone(),
+        };
+        match predicate {
+            ModelPredicate::UnaryFieldComparison {
+                ndc_column,
+                operator: UnaryComparisonOperator::IsNull,
+                column_path,
+                ..
+            } => Ok(ndc_models::RelationalExpression::IsNull {
+                expr: Box::new(self.permission_column(model_name, ndc_column, column_path)?),
+            }),
+            ModelPredicate::BinaryFieldComparison {
+                field,
+                ndc_column,
+                operator,
+                column_path,
+                argument_type,
+                value,
+                ..
+            } => {
+                let left = Box::new(self.permission_column(model_name, ndc_column, column_path)?);
+                let right = Box::new(ndc_models::RelationalExpression::Literal {
+                    literal: to_relational_literal(field, &resolve_value(value, argument_type)?)?,
+                });
+                Ok(match operator.comparison_operator {
+                    ComparisonOperator::Equals => {
+                        ndc_models::RelationalExpression::Eq { left, right }
+                    }
+                    ComparisonOperator::NotEquals => {
+                        ndc_models::RelationalExpression::NotEq { left, right }
+                    }
+                    ComparisonOperator::LessThan => {
+                        ndc_models::RelationalExpression::Lt { left, right }
+                    }
+                    ComparisonOperator::LessThanOrEqual => {
+                        ndc_models::RelationalExpression::LtEq { left, right }
+                    }
+                    ComparisonOperator::GreaterThan => {
+                        ndc_models::RelationalExpression::Gt { left, right }
+                    }
+                    ComparisonOperator::GreaterThanOrEqual => {
+                        ndc_models::RelationalExpression::GtEq { left, right }
+                    }
+                    ComparisonOperator::Contains => ndc_models::RelationalExpression::Contains {
+                        str: left,
+                        search_str: right,
+                    },
+                    _ => return Err(unsupported().into()),
+                })
+            }
+            ModelPredicate::Relationship { .. } => Err(unsupported().into()),
+            ModelPredicate::And(predicates) => {
+                let expressions = predicates
+                    .iter()
+                    .map(|predicate| {
+                        self.permission_predicate(model_name, predicate, resolve_value)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(combine_expressions(expressions, true, |left, right| {
+                    ndc_models::RelationalExpression::And { left, right }
+                }))
+            }
+            ModelPredicate::Or(predicates) => {
+                let expressions = predicates
+                    .iter()
+                    .map(|predicate| {
+                        self.permission_predicate(model_name, predicate, resolve_value)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(combine_expressions(expressions, false, |left, right| {
+                    ndc_models::RelationalExpression::Or { left, right }
+                }))
+            }
+            ModelPredicate::Not(predicate) => Ok(ndc_models::RelationalExpression::Not {
+                expr: Box::new(self.permission_predicate(model_name, predicate, resolve_value)?),
+            }),
+        }
+    }
+
+    // the rows of the collection matching the resource's unique identifier, and the role's
+    // select permission if there is one
+    fn filter_by_unique_identifier(
+        &self,
+        operation: RelationalOperation,
+        id_values: &IndexMap<FieldName, serde_json::Value>,
+        permission_predicate: Option<ndc_models::RelationalExpression>,
+    ) -> Result<ndc_models::Relation, MutationError> {
+        let mut predicate: Option<ndc_models::RelationalExpression> = None;
+        for (field_name, value) in id_values {
+            let comparison = ndc_models::RelationalExpression::Eq {
+                left: Box::new(ndc_models::RelationalExpression::Column {
+                    index: self.column_index(field_name)?,
+                }),
+                right: Box::new(ndc_models::RelationalExpression::Literal {
+                    literal: to_relational_literal(field_name, value)?,
+                }),
+            };
+            predicate = Some(match predicate {
+                None => comparison,
+                Some(left) => ndc_models::RelationalExpression::And {
+                    left: Box::new(left),
+                    right: Box::new(comparison),
+                },
+            });
+        }
+        let mut predicate = predicate.ok_or(MutationError::MissingResourceId { operation })?;
+        if let Some(permission_predicate) = permission_predicate {
+            predicate = ndc_models::RelationalExpression::And {
+                left: Box::new(predicate),
+                right: Box::new(permission_predicate),
+            };
+        }
+        Ok(ndc_models::Relation::Filter {
+            input: Arc::new(self.scan()),
+            predicate,
+        })
+    }
+
+    fn insert_request(
+        &self,
+        attributes: &IndexMap<FieldName, serde_json::Value>,
+    ) -> Result<ndc_models::RelationalInsertRequest, MutationError> {
+        let columns = attributes
+            .keys()
+            .map(|field_name| self.column_name(field_name))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ndc_models::RelationalInsertRequest {
+            collection: self.collection.clone(),
+            columns,
+            rows: vec![attributes.values().cloned().collect()],
+        })
+    }
+
+    // an update is expressed as a projection over the rows to update, where updated columns
+    // are replaced by their new values and all other columns are kept as they are
+    fn update_request(
+        &self,
+        id_values: &IndexMap<FieldName, serde_json::Value>,
+        permission_predicate: Option<ndc_models::RelationalExpression>,
+        attributes: &IndexMap<FieldName, serde_json::Value>,
+    ) -> Result<ndc_models::RelationalUpdateRequest, MutationError> {
+        let exprs = (0..)
+            .zip(&self.columns)
+            .map(
+                |(index, (field_name, _))| match attributes.get(*field_name) {
+                    Some(value) => Ok(ndc_models::RelationalExpression::Literal {
+                        literal: to_relational_literal(field_name, value)?,
+                    }),
+                    None => Ok(ndc_models::RelationalExpression::Column { index }),
+                },
+            )
+            .collect::<Result<Vec<_>, MutationError>>()?;
+        Ok(ndc_models::RelationalUpdateRequest {
+            collection: self.collection.clone(),
+            relation: ndc_models::Relation::Project {
+                input: Arc::new(self.filter_by_unique_identifier(
+                    RelationalOperation::Update,
+                    id_values,
+                    permission_predicate,
+                )?),
+                exprs,
+            },
+        })
+    }
+
+    fn delete_request(
+        &self,
+        id_values: &IndexMap<FieldName, serde_json::Value>,
+        permission_predicate: Option<ndc_models::RelationalExpression>,
+    ) -> Result<ndc_models::RelationalDeleteRequest, MutationError> {
+        Ok(ndc_models::RelationalDeleteRequest {
+            collection: self.collection.clone(),
+            relation: self.filter_by_unique_identifier(
+                RelationalOperation::Delete,
+                id_values,
+                permission_predicate,
+            )?,
+        })
+    }
+}
+
+// `And(vec![])` is true and `Or(vec![])` is false
+fn combine_expressions(
+    expressions: Vec<ndc_models::RelationalExpression>,
+    empty: bool,
+    combine: impl Fn(
+        Box<ndc_models::RelationalExpression>,
+        Box<ndc_models::RelationalExpression>,
+    ) -> ndc_models::RelationalExpression,
+) -> ndc_models::RelationalExpression {
+    expressions
+        .into_iter()
+        .reduce(|left, right| combine(Box::new(left), Box::new(right)))
+        .unwrap_or(ndc_models::RelationalExpression::Literal {
+            literal: ndc_models::RelationalLiteral::Boolean { value: empty },
+        })
+}
+
+fn to_relational_literal(
+    field_name: &FieldName,
+    value: &serde_json::Value,
+) -> Result<ndc_models::RelationalLiteral, MutationError> {
+    match value {
+        serde_json::Value::Null => Ok(ndc_models::RelationalLiteral::Null),
+        serde_json::Value::Bool(value) => {
+            Ok(ndc_models::RelationalLiteral::Boolean { value: *value })
+        }
+        serde_json::Value::String(value) => Ok(ndc_models::RelationalLiteral::String {
+            value: value.clone(),
+        }),
+        serde_json::Value::Number(number) => {
+            if let Some(value) = number.as_i64() {
+                Ok(ndc_models::RelationalLiteral::Int64 { value })
+            } else if let Some(value) = number.as_u64() {
+                Ok(ndc_models::RelationalLiteral::UInt64 { value })
+            } else if let Some(value) = number.as_f64() {
+                Ok(ndc_models::RelationalLiteral::Float64 {
+                    value: value.into(),
+                })
+            } else {
+                Err(MutationError::UnsupportedAttributeValue(
+                    field_name.to_string(),
+                ))
+            }
+        }
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => Err(
+            MutationError::UnsupportedAttributeValue(field_name.to_string()),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata_resolve::{QualifiedBaseType, QualifiedTypeName, ResolvedOperator};
+    use open_dds::{
+        data_connector::DataConnectorOperatorName,
+        identifier,
+        session_variables::{SessionVariableName, SessionVariableReference},
+        subgraph_identifier,
+        types::{CustomTypeName, InbuiltType, OperatorName},
+    };
+    use std::str::FromStr;
+
+    fn field_mapping(column: &str) -> FieldMapping {
+        FieldMapping {
+            column: DataConnectorColumnName::from(column),
+            column_type: ndc_models::Type::Named { name: "Int".into() },
+            column_type_representation: None,
+            comparison_operators: None,
+            aggregate_functions: None,
+            extraction_functions: None,
+            argument_mappings: BTreeMap::new(),
+        }
+    }
+
+    fn model_name() -> Qualified<ModelName> {
+        Qualified::new(
+            subgraph_identifier!("default"),
+            ModelName::new(identifier!("Articles")),
+        )
+    }
+
+    fn comparison(
+        column: &str,
+        comparison_operator: ComparisonOperator,
+        value: ValueExpression,
+    ) -> ModelPredicate {
+        ModelPredicate::BinaryFieldComparison {
+            field: FieldName::new(identifier!("field")),
+            field_parent_type: Qualified::new(
+                subgraph_identifier!("default"),
+                CustomTypeName(identifier!("Article")),
+            ),
+            ndc_column: DataConnectorColumnName::from(column),
+            operator: ResolvedOperator {
+                data_connector_operator_name: DataConnectorOperatorName::from("_op"),
+                comparison_operator,
+            },
+            column_path: vec![],
+            argument_type: QualifiedTypeReference {
+                underlying_type: QualifiedBaseType::Named(QualifiedTypeName::Inbuilt(
+                    InbuiltType::Int,
+                )),
+                nullable: false,
+            },
+            value,
+            deprecated: None,
+        }
+    }
+
+    fn column(index: u64) -> Box<ndc_models::RelationalExpression> {
+        Box::new(ndc_models::RelationalExpression::Column { index })
+    }
+
+    fn literal(value: i64) -> Box<ndc_models::RelationalExpression> {
+        Box::new(ndc_models::RelationalExpression::Literal {
+            literal: ndc_models::RelationalLiteral::Int64 { value },
+        })
+    }
+
+    fn permission_predicate(
+        predicate: &ModelPredicate,
+    ) -> Result<ndc_models::RelationalExpression, RequestError> {
+        let field_mappings = BTreeMap::from([
+            (
+                FieldName::new(identifier!("article_id")),
+                field_mapping("id"),
+            ),
+            (
+                FieldName::new(identifier!("author_id")),
+                field_mapping("author_id"),
+            ),
+        ]);
+        let collection = RelationalCollection {
+            collection: "articles".into(),
+            columns: field_mappings.iter().collect(),
+        };
+        // session variables resolve to 7
+        let resolve_value = |value: &ValueExpression, _: &QualifiedTypeReference| match value {
+            ValueExpression::Literal(value) => Ok(value.clone()),
+            ValueExpression::SessionVariable(_) => Ok(serde_json::json!(7)),
+        };
+        collection.permission_predicate(&model_name(), predicate, &resolve_value)
+    }
+
+    #[test]
+    fn test_permission_predicate() {
+        let author_is_seven = comparison(
+            "author_id",
+            ComparisonOperator::Equals,
+            ValueExpression::Literal(serde_json::json!(7)),
+        );
+        let id_is_large = comparison(
+            "id",
+            ComparisonOperator::GreaterThan,
+            ValueExpression::Literal(serde_json::json!(100)),
+        );
+        let tests = vec![
+            // no filter, so every row
+            (
+                ModelPredicate::And(vec![]),
+                ndc_models::RelationalExpression::Literal {
+                    literal: ndc_models::RelationalLiteral::Boolean { value: true },
+                },
+            ),
+            (
+                ModelPredicate::Or(vec![]),
+                ndc_models::RelationalExpression::Literal {
+                    literal: ndc_models::RelationalLiteral::Boolean { value: false },
+                },
+            ),
+            (
+                ModelPredicate::And(vec![author_is_seven.clone()]),
+                ndc_models::RelationalExpression::Eq {
+                    left: column(1),
+                    right: literal(7),
+                },
+            ),
+            (
+                ModelPredicate::Or(vec![
+                    author_is_seven.clone(),
+                    ModelPredicate::Not(Box::new(id_is_large)),
+                ]),
+                ndc_models::RelationalExpression::Or {
+                    left: Box::new(ndc_models::RelationalExpression::Eq {
+                        left: column(1),
+                        right: literal(7),
+                    }),
+                    right: Box::new(ndc_models::RelationalExpression::Not {
+                        expr: Box::new(ndc_models::RelationalExpression::Gt {
+                            left: column(0),
+                            right: literal(100),
+                        }),
+                    }),
+                },
+            ),
+            (
+                ModelPredicate::UnaryFieldComparison {
+                    field: FieldName::new(identifier!("author_id")),
+                    field_parent_type: Qualified::new(
+                        subgraph_identifier!("default"),
+                        CustomTypeName(identifier!("Article")),
+                    ),
+                    ndc_column: DataConnectorColumnName::from("author_id"),
+                    operator: UnaryComparisonOperator::IsNull,
+                    column_path: vec![],
+                    deprecated: None,
+                },
+                ndc_models::RelationalExpression::IsNull { expr: column(1) },
+            ),
+        ];
+        for (predicate, expected) in tests {
+            assert_eq!(permission_predicate(&predicate).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_permission_predicate_with_session_variable() {
+        let predicate = comparison(
+            "author_id",
+            ComparisonOperator::LessThanOrEqual,
+            ValueExpression::SessionVariable(SessionVariableReference {
+                name: SessionVariableName::from_str("x-hasura-user-id").unwrap(),
+                passed_as_json: false,
+                disallow_unknown_fields: false,
+            }),
+        );
+        assert_eq!(
+            permission_predicate(&predicate).unwrap(),
+            ndc_models::RelationalExpression::LtEq {
+                left: column(1),
+                right: literal(7),
+            }
+        );
+    }
+
+    #[test]
+    fn test_unsupported_permission_predicates_are_rejected() {
+        let mut nested_field = comparison(
+            "author_id",
+            ComparisonOperator::Equals,
+            ValueExpression::Literal(serde_json::json!(7)),
+        );
+        if let ModelPredicate::BinaryFieldComparison { column_path, .. } = &mut nested_field {
+            column_path.push(DataConnectorColumnName::from("nested"));
+        }
+        let tests = vec![
+            // no relational equivalent
+            comparison(
+                "author_id",
+                ComparisonOperator::Custom(OperatorName::new("_like".into())),
+                ValueExpression::Literal(serde_json::json!("%7")),
+            ),
+            // a column that isn't mapped
+            comparison(
+                "unknown",
+                ComparisonOperator::Equals,
+                ValueExpression::Literal(serde_json::json!(7)),
+            ),
+            nested_field,
+        ];
+        for predicate in tests {
+            // unsupported filters must not be dropped, even inside another predicate
+            let predicate = ModelPredicate::And(vec![predicate]);
+            assert!(matches!(
+                permission_predicate(&predicate),
+                Err(RequestError::MutationError(
+                    MutationError::UnsupportedPermissionFilter { .. }
+                ))
+            ));
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use super::types::{ModelInfo, RelationshipNode, RelationshipTree, RequestError};
+use super::types::{
+    ModelInfo, Pagination, RelationshipNode, RelationshipTree, RequestError, RequestTarget,
+};
 use axum::http::{Method, Uri};
 use indexmap::IndexMap;
 use open_dds::{
@@ -13,9 +15,12 @@ use open_dds::{
     types::{CustomTypeName, FieldName},
 };
 use serde::{Deserialize, Serialize};
+pub(crate) mod aggregate;
+mod arguments;
+pub(crate) mod cursor;
 mod filter;
 mod include;
-use super::helpers::get_object_type;
+use super::helpers::{get_object_type, parse_unique_identifier};
 use crate::catalog::{Model, ObjectType, RelationshipTarget, Type};
 use metadata_resolve::{Qualified, unwrap_custom_type_name};
 use std::collections::BTreeMap;
@@ -23,18 +28,32 @@ use std::collections::BTreeMap;
 #[derive(Debug, derive_more::with_trait::Display, Serialize, Deserialize)]
 pub enum ParseError {
     Filter(filter::FilterError),
+    Aggregate(aggregate::AggregateError),
+    Cursor(cursor::CursorError),
     InvalidFieldName(String),
     InvalidModelName(String),
     InvalidSubgraph(String),
     PathLengthMustBeAtLeastTwo,
     CannotFindObjectType(Qualified<CustomTypeName>),
+    ModelHasNoUniqueIdentifier(Qualified<ModelName>),
+    InvalidUniqueIdentifier(String),
 }
 
 pub struct QueryIR {
     pub query_request: open_dds::query::QueryRequest,
     pub root_type_name: Qualified<CustomTypeName>,
+    pub target: RequestTarget,
+    /// set when the request asks for aggregates rather than resources
+    pub aggregate_request: Option<aggregate::AggregateRequest>,
+    /// set when fetching resources from a collection
+    pub pagination: Option<Pagination>,
+    /// counts the whole collection, for `meta.total`
+    pub total_query_request: Option<open_dds::query::QueryRequest>,
 }
 
+/// `withTotal=true` adds the size of the whole collection to the response, as `meta.total`
+const WITH_TOTAL_PARAMETER: &str = "withTotal";
+
 pub fn create_query_ir(
     model: &Model,
     object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
@@ -44,48 +63,43 @@ pub fn create_query_ir(
     query_string: &jsonapi_library::query::Query,
 ) -> Result<QueryIR, RequestError> {
     // get model info from parsing URI
+    let model_info = parse_url(uri).map_err(RequestError::ParseError)?;
+    let target = RequestTarget::from_model_info(&model_info).ok_or(RequestError::NotFound)?;
     let ModelInfo {
         subgraph,
         name: model_name,
         unique_identifier: _,
         relationship: _,
-    } = parse_url(uri).map_err(RequestError::ParseError)?;
+    } = model_info;
 
     // validate the sparse fields in the query string
     validate_sparse_fields(object_types, query_string)?;
 
+    // `jsonapi_library` only parses the parameters JSON:API defines, so we parse our own
+    let axum::extract::Query(query_parameters) =
+        axum::extract::Query::<Vec<(String, String)>>::try_from_uri(uri)
+            .map_err(|rejection| RequestError::BadRequest(rejection.body_text()))?;
+
+    let model_arguments = arguments::build_model_arguments(model, &query_parameters)?;
+
     // Parse the include relationships
     let include_relationships = query_string
         .include
         .as_ref()
         .map(|include| include::IncludeRelationships::parse(include));
 
-    let field_selection = resolve_field_selection(
-        object_types,
-        &model.data_type,
-        relationship_tree,
-        query_string,
-        include_relationships.as_ref(),
-    )?;
-
-    // create filters
-    let filter_query = match &query_string.filter {
-        Some(filter) => {
-            let boolean_expression = filter::build_boolean_expression(model, filter)
-                .map_err(|parse_error| RequestError::ParseError(ParseError::Filter(parse_error)))?;
-            Ok(Some(boolean_expression))
-        }
-        None => Ok(None),
-    }?;
-
     // create sorts
-    let sort_query = match &query_string.sort {
+    let sort_fields = match &query_string.sort {
         None => Ok(vec![]),
         Some(sort) => sort
             .iter()
-            .map(|elem| build_order_by_element(elem).map_err(RequestError::ParseError))
+            .map(|elem| parse_sort_field(elem).map_err(RequestError::ParseError))
             .collect::<Result<Vec<_>, RequestError>>(),
     }?;
+    let sort_query = sort_fields
+        .iter()
+        .map(|(field_name, direction)| build_order_by_element(field_name, direction.clone()))
+        .collect::<Vec<_>>();
 
     // pagination
     // spec: <https://jsonapi.org/format/#fetching-pagMetadata>
@@ -101,13 +115,201 @@ pub fn create_query_ir(
         .and_then(|page| usize::try_from(page.offset).ok())
         .filter(|page| *page > 0);
 
+    // aggregates replace the resources in the response, so they are planned as their own query
+    let object_type =
+        get_object_type(object_types, &model.data_type).map_err(RequestError::ParseError)?;
+    if let Some(aggregate_request) =
+        aggregate::parse_aggregate_request(model, object_type, &query_parameters).map_err(
+            |aggregate_error| RequestError::ParseError(ParseError::Aggregate(aggregate_error)),
+        )?
+    {
+        if target != RequestTarget::Collection {
+            return Err(RequestError::BadRequest(
+                "aggregates are only supported when fetching a collection".to_string(),
+            ));
+        }
+        if query_string.include.is_some() {
+            return Err(RequestError::BadRequest(
+                "relationships cannot be included when fetching aggregates".to_string(),
+            ));
+        }
+        let model_target = open_dds::query::ModelTarget {
+            arguments: model_arguments,
+            filter: build_filter(model, query_string)?,
+            order_by: sort_query,
+            limit,
+            offset,
+            model_name,
+            subgraph,
+        };
+        return Ok(build_aggregate_query_ir(
+            model,
+            model_target,
+            aggregate_request,
+        ));
+    }
+
+    let with_total = query_parameters
+        .iter()
+        .any(|(name, value)| name == WITH_TOTAL_PARAMETER && value == "true");
+    if with_total {
+        if target != RequestTarget::Collection {
+            return Err(RequestError::BadRequest(
+                "the total is only available when fetching a collection".to_string(),
+            ));
+        }
+        // the total is a count aggregate, so the model must allow counting
+        if !model
+            .aggregates
+            .as_ref()
+            .is_some_and(|model_aggregates| model_aggregates.count)
+        {
+            return Err(RequestError::BadRequest(format!(
+                "the total is not available for model {}, as it cannot be counted",
+                model.name
+            )));
+        }
+    }
+
+    let cursor_pagination = match target {
+        RequestTarget::Collection => {
+            build_cursor_pagination(model, &sort_fields, &query_parameters).map_err(
+                |cursor_error| RequestError::ParseError(ParseError::Cursor(cursor_error)),
+            )?
+        }
+        _ => None,
+    };
+
+    let (field_selection, filter, order_by, limit, offset) = match &target {
+        RequestTarget::Collection => {
+            // create filters
+            let filter_query = build_filter(model, query_string)?;
+            match &cursor_pagination {
+                // a page of a cursor-paged collection starts at the cursor, and every resource
+                // needs the fields of its own cursor
+                Some(cursor_pagination) => {
+                    let cursor_fields = cursor_pagination.fields().cloned().collect::<Vec<_>>();
+                    let field_selection = resolve_field_selection(
+                        object_types,
+                        &model.data_type,
+                        Some(&cursor_fields),
+                        relationship_tree,
+                        query_string,
+                        include_relationships.as_ref(),
+                    )?;
+                    let filter = match (filter_query, cursor_pagination.filter()) {
+                        (Some(filter_query), Some(cursor_filter)) => {
+                            Some(open_dds::query::BooleanExpression::And(vec![
+                                filter_query,
+                                cursor_filter,
+                            ]))
+                        }
+                        (filter_query, cursor_filter) => filter_query.or(cursor_filter),
+                    };
+                    (
+                        field_selection,
+                        filter,
+                        cursor_pagination.order_by(),
+                        limit,
+                        offset,
+                    )
+                }
+                None => {
+                    let field_selection = resolve_field_selection(
+                        object_types,
+                        &model.data_type,
+                        model.unique_identifier.as_deref(),
+                        relationship_tree,
+                        query_string,
+                        include_relationships.as_ref(),
+                    )?;
+                    (field_selection, filter_query, sort_query, limit, offset)
+                }
+            }
+        }
+        RequestTarget::Resource { id } => {
+            let field_selection = resolve_field_selection(
+                object_types,
+                &model.data_type,
+                model.unique_identifier.as_deref(),
+                relationship_tree,
+                query_string,
+                include_relationships.as_ref(),
+            )?;
+            let id_filter = build_unique_identifier_filter(model, object_types, id)?;
+            (field_selection, Some(id_filter), vec![], None, None)
+        }
+        // the related resources are fetched as a relationship of the resource. Sorting and
+        // pagination apply to the related resources.
+        RequestTarget::RelatedResources { id, relationship }
+        | RequestTarget::Relationship { id, relationship } => {
+            if query_string.filter.is_some() {
+                return Err(RequestError::BadRequest(
+                    "filtering is only supported when fetching a collection".to_string(),
+                ));
+            }
+            if !object_type
+                .type_relationships
+                .keys()
+                .any(|relationship_name| relationship_name.as_str() == relationship)
+            {
+                return Err(RequestError::NotFound);
+            }
+            let (field_alias, relationship_name, selection) = resolve_relationship(
+                object_type,
+                object_types,
+                relationship_tree,
+                query_string,
+                relationship,
+                include_relationships.as_ref(),
+            )?;
+            let relationship_selection = ObjectSubSelection::Relationship(RelationshipSelection {
+                target: OpenDdRelationshipTarget {
+                    relationship_name,
+                    arguments: IndexMap::new(),
+                    filter: None,
+                    order_by: sort_query,
+                    limit,
+                    offset,
+                },
+                selection: Some(selection),
+            });
+            let field_selection = IndexMap::from_iter([(field_alias, relationship_selection)]);
+            let id_filter = build_unique_identifier_filter(model, object_types, id)?;
+            (field_selection, Some(id_filter), vec![], None, None)
+        }
+    };
+
+    // the total counts everything the filter matches, regardless of the page
+    let total_query_request = with_total.then(|| {
+        build_total_query_request(open_dds::query::ModelTarget {
+            arguments: model_arguments.clone(),
+            filter: filter.clone(),
+            order_by: vec![],
+            limit: None,
+            offset: None,
+            model_name: model_name.clone(),
+            subgraph: subgraph.clone(),
+        })
+    });
+
+    // the pagination links repeat the request's query string, so we read the page from it too
+    let pagination = match target {
+        RequestTarget::Collection => Some(Pagination {
+            limit: page_parameter(&query_parameters, "page[limit]"),
+            offset: page_parameter(&query_parameters, "page[offset]"),
+            cursor: cursor_pagination,
+        }),
+        _ => None,
+    };
+
     // form the model selection
     let model_selection = open_dds::query::ModelSelection {
         selection: field_selection,
         target: open_dds::query::ModelTarget {
-            arguments: IndexMap::new(),
-            filter: filter_query,
-            order_by: sort_query,
+            arguments: model_arguments,
+            filter,
+            order_by,
             limit,
             offset,
             model_name,
@@ -124,12 +326,156 @@ pub fn create_query_ir(
             queries,
         }),
         root_type_name: model.data_type.clone(),
+        target,
+        aggregate_request: None,
+        pagination,
+        total_query_request,
     })
 }
 
+// without `groupBy`, sorting and pagination apply to the objects being aggregated. With
+// `groupBy`, pagination applies to the groups.
+fn build_aggregate_query_ir(
+    model: &Model,
+    mut model_target: open_dds::query::ModelTarget,
+    aggregate_request: aggregate::AggregateRequest,
+) -> QueryIR {
+    let selection = aggregate_request.selection();
+    let query = if aggregate_request.group_by.is_empty() {
+        open_dds::query::Query::ModelAggregate(open_dds::query::ModelAggregateSelection {
+            target: model_target,
+            selection,
+        })
+    } else {
+        let dimensions = open_dds::query::ModelDimensions {
+            dimensions: aggregate_request.dimensions(),
+            limit: model_target.limit.take(),
+            offset: model_target.offset.take(),
+        };
+        open_dds::query::Query::ModelGroups(open_dds::query::ModelGroupsSelection {
+            target: model_target,
+            selection,
+            dimensions,
+        })
+    };
+    QueryIR {
+        query_request: open_dds::query::QueryRequest::V1(open_dds::query::QueryRequestV1 {
+            queries: IndexMap::from_iter([(
+                open_dds::query::Alias::new(identifier!("jsonapi_model_query")),
+                query,
+            )]),
+        }),
+        root_type_name: model.data_type.clone(),
+        target: RequestTarget::Collection,
+        aggregate_request: Some(aggregate_request),
+        pagination: None,
+        total_query_request: None,
+    }
+}
+
+fn build_total_query_request(
+    model_target: open_dds::query::ModelTarget,
+) -> open_dds::query::QueryRequest {
+    let selection = IndexMap::from_iter([(
+        open_dds::query::Name::from("total"),
+        open_dds::query::Aggregate {
+            function: open_dds::query::AggregationFunction::Count {},
+            operand: None,
+        },
+    )]);
+    open_dds::query::QueryRequest::V1(open_dds::query::QueryRequestV1 {
+        queries: IndexMap::from_iter([(
+            open_dds::query::Alias::new(identifier!("jsonapi_model_total")),
+            open_dds::query::Query::ModelAggregate(open_dds::query::ModelAggregateSelection {
+                target: model_target,
+                selection,
+            }),
+        )]),
+    })
+}
+
+// collections of models with a unique identifier are paged with cursors when the request has a
+// `page[after]` or `page[before]` parameter, where an empty `page[after]` asks for the first page.
+// Otherwise collections are paged by offset. Like the pagination links, this goes by the
+// request's query string.
+fn build_cursor_pagination(
+    model: &Model,
+    sort: &[(FieldName, open_dds::models::OrderByDirection)],
+    query_parameters: &[(String, String)],
+) -> Result<Option<cursor::CursorPagination>, cursor::CursorError> {
+    let has_cursor = query_parameters
+        .iter()
+        .any(|(name, _)| name == cursor::AFTER_PARAMETER || name == cursor::BEFORE_PARAMETER);
+    if !has_cursor {
+        return Ok(None);
+    }
+    if page_parameter(query_parameters, "page[offset]").is_some() {
+        return Err(cursor::CursorError::CursorAndOffset);
+    }
+    let Some(unique_identifier) = &model.unique_identifier else {
+        return Err(cursor::CursorError::NoUniqueIdentifier);
+    };
+    cursor::CursorPagination::new(sort, unique_identifier, query_parameters).map(Some)
+}
+
+fn page_parameter(query_parameters: &[(String, String)], name: &str) -> Option<usize> {
+    query_parameters
+        .iter()
+        .find(|(parameter_name, _)| parameter_name == name)
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+}
+
+fn build_filter(
+    model: &Model,
+    query_string: &jsonapi_library::query::Query,
+) -> Result<Option<open_dds::query::BooleanExpression>, RequestError> {
+    query_string
+        .filter
+        .as_ref()
+        .map(|filter| {
+            filter::build_boolean_expression(model, filter)
+                .map_err(|parse_error| RequestError::ParseError(ParseError::Filter(parse_error)))
+        })
+        .transpose()
+}
+
+// select the resource whose unique identifier fields match the `id` in the URL
+fn build_unique_identifier_filter(
+    model: &Model,
+    object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
+    id: &str,
+) -> Result<open_dds::query::BooleanExpression, RequestError> {
+    let object_type =
+        get_object_type(object_types, &model.data_type).map_err(RequestError::ParseError)?;
+    let id_values =
+        parse_unique_identifier(model, object_type, id).map_err(RequestError::ParseError)?;
+    let comparisons = id_values
+        .into_iter()
+        .map(
+            |(field_name, value)| open_dds::query::BooleanExpression::Comparison {
+                operand: open_dds::query::Operand::Field(open_dds::query::ObjectFieldOperand {
+                    target: Box::new(open_dds::query::ObjectFieldTarget {
+                        field_name,
+                        arguments: IndexMap::new(),
+                    }),
+                    nested: None,
+                }),
+                operator: open_dds::query::ComparisonOperator::Equals,
+                argument: Box::new(open_dds::query::Value::Literal(value)),
+            },
+        )
+        .collect();
+    Ok(open_dds::query::BooleanExpression::And(comparisons))
+}
+
+// the fields of a resource, and the relationships to include. The required fields are always
+// selected: the unique identifier, as it makes up the `id` of the resource, and the fields of a
+// cursor.
 fn resolve_field_selection(
     object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
     object_type_name: &Qualified<CustomTypeName>,
+    required_fields: Option<&[FieldName]>,
     relationship_tree: &mut RelationshipTree,
     query_string: &jsonapi_library::query::Query,
     include_relationships: Option<&include::IncludeRelationships>,
@@ -140,7 +486,9 @@ fn resolve_field_selection(
     // create the selection fields; include all fields of the model output type
     let mut selection = IndexMap::new();
     for (field_name, field_type) in &object_type.type_fields {
-        if include_field(query_string, field_name, &object_type_name.name) {
+        if include_field(query_string, field_name, &object_type_name.name)
+            || required_fields.is_some_and(|fields| fields.contains(field_name))
+        {
             let field_name_ident = Identifier::new(field_name.as_str())
                 .map_err(|e| RequestError::BadRequest(e.into()))?;
 
@@ -192,9 +540,11 @@ fn resolve_nested_field_selection(
             inner.as_ref(),
         )?,
         Type::Object(type_name) => {
+            // nested objects are attributes rather than resources, so they have no `id`
             let object_field_selection = resolve_field_selection(
                 object_types,
                 type_name,
+                None,
                 relationship_tree,
                 query_string,
                 include_relationships,
@@ -248,69 +598,101 @@ fn resolve_include_relationships(
     let mut fields = IndexMap::new();
     if let Some(include_relationships) = include_relationships {
         for (relationship, nested_include) in &include_relationships.include {
-            // Check the presence of the relationship
-            let Some((relationship_name, target)) = object_type
-                .type_relationships
-                .iter()
-                .find(|&(relationship_name, _)| relationship_name.as_str() == relationship)
-            else {
-                return Err(RequestError::BadRequest(format!(
-                    "Relationship {relationship} not found"
-                )));
-            };
-            let field_name_ident = Identifier::new(relationship)
-                .map_err(|e| RequestError::BadRequest(format!("Invalid relationship name: {e}")))?;
-
-            let mut is_command_relationship = false;
-            let (target_type, relationship_type) = match &target {
-                RelationshipTarget::Model {
-                    object_type,
-                    relationship_type,
-                } => (object_type, relationship_type.clone()),
-                RelationshipTarget::Command { type_reference } => {
-                    is_command_relationship = true;
-                    match unwrap_custom_type_name(type_reference) {
-                        Some(object_type) => (
-                            object_type,
-                            crate::helpers::type_reference_to_relationship_type(type_reference),
-                        ),
-                        None => {
-                            return Err(RequestError::BadRequest(
-                                "Command relationship with built-in output type not supported yet"
-                                    .to_string(),
-                            ));
-                        }
-                    }
-                }
-            };
-            let mut nested_relationships = RelationshipTree::default();
-            let selection = resolve_field_selection(
+            let (field_alias, relationship_name, selection) = resolve_relationship(
+                object_type,
                 object_types,
-                target_type,
-                &mut nested_relationships,
+                relationship_tree,
                 query_string,
+                relationship,
                 nested_include.as_ref(),
             )?;
-            let relationship_node = RelationshipNode {
-                object_type: target_type.clone(),
-                relationship_type: relationship_type.clone(),
-                is_command_relationship,
-                nested: nested_relationships,
-            };
-            relationship_tree
-                .relationships
-                .insert(relationship.clone(), relationship_node);
             let sub_selection = ObjectSubSelection::Relationship(RelationshipSelection {
-                target: build_relationship_target(relationship_name.clone()),
+                target: build_relationship_target(relationship_name),
                 selection: Some(selection),
             });
-            let field_alias = open_dds::query::Alias::new(field_name_ident);
             fields.insert(field_alias, sub_selection);
         }
     }
     Ok(fields)
 }
 
+// resolve the selection of the target of a relationship, and record the relationship in the
+// relationship tree for processing the response
+fn resolve_relationship(
+    object_type: &ObjectType,
+    object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
+    relationship_tree: &mut RelationshipTree,
+    query_string: &jsonapi_library::query::Query,
+    relationship: &str,
+    nested_include: Option<&include::IncludeRelationships>,
+) -> Result<(Alias, RelationshipName, IndexMap<Alias, ObjectSubSelection>), RequestError> {
+    // Check the presence of the relationship
+    let Some((relationship_name, target)) = object_type
+        .type_relationships
+        .iter()
+        .find(|&(relationship_name, _)| relationship_name.as_str() == relationship)
+    else {
+        return Err(RequestError::BadRequest(format!(
+            "Relationship {relationship} not found"
+        )));
+    };
+    let field_name_ident = Identifier::new(relationship)
+        .map_err(|e| RequestError::BadRequest(format!("Invalid relationship name: {e}")))?;
+
+    let mut is_command_relationship = false;
+    let (target_type, relationship_type, unique_identifier) = match &target {
+        RelationshipTarget::Model {
+            object_type,
+            relationship_type,
+            unique_identifier,
+        } => (
+            object_type,
+            relationship_type.clone(),
+            unique_identifier.clone(),
+        ),
+        RelationshipTarget::Command { type_reference } => {
+            is_command_relationship = true;
+            match unwrap_custom_type_name(type_reference) {
+                Some(object_type) => (
+                    object_type,
+                    crate::helpers::type_reference_to_relationship_type(type_reference),
+                    None,
+                ),
+                None => {
+                    return Err(RequestError::BadRequest(
+                        "Command relationship with built-in output type not supported yet"
+                            .to_string(),
+                    ));
+                }
+            }
+        }
+    };
+    let mut nested_relationships = RelationshipTree::default();
+    let selection = resolve_field_selection(
+        object_types,
+        target_type,
+        unique_identifier.as_deref(),
+        &mut nested_relationships,
+        query_string,
+        nested_include,
+    )?;
+    let relationship_node = RelationshipNode {
+        object_type: target_type.clone(),
+        relationship_type: relationship_type.clone(),
+        unique_identifier,
+        is_command_relationship,
+        nested: nested_relationships,
+    };
+    relationship_tree
+        .relationships
+        .insert(relationship.to_string(), relationship_node);
+    Ok((
+        open_dds::query::Alias::new(field_name_ident),
+        relationship_name.clone(),
+        selection,
+    ))
+}
+
 fn build_relationship_target(relationship_name: RelationshipName) -> OpenDdRelationshipTarget {
     OpenDdRelationshipTarget {
         relationship_name,
@@ -333,7 +715,7 @@ fn build_relationship_target(relationship_name: RelationshipName) -> OpenDdRelat
 // like we do in GraphQL
 //
 // fields[subgraphAuthors]=author_id,firstName&fields[otherAuthors]=author_id,last_name
-fn include_field(
+pub(crate) fn include_field(
     query_string: &jsonapi_library::query::Query,
     field_name: &FieldName,
     object_type_name: &CustomTypeName,
@@ -359,27 +741,31 @@ fn create_field_name(field_name: &str) -> Result<FieldName, ParseError> {
 }
 
 // Sorting spec: <https://jsonapi.org/format/#fetching-sorting>
-fn build_order_by_element(elem: &str) -> Result<open_dds::query::OrderByElement, ParseError> {
-    let (field_name, direction) = if elem.starts_with('-') {
-        (
-            elem.split_at(1).1.to_string(),
-            open_dds::models::OrderByDirection::Desc,
-        )
-    } else {
-        (elem.to_owned(), open_dds::models::OrderByDirection::Asc)
+fn parse_sort_field(
+    elem: &str,
+) -> Result<(FieldName, open_dds::models::OrderByDirection), ParseError> {
+    let (field_name, direction) = match elem.strip_prefix('-') {
+        Some(field_name) => (field_name, open_dds::models::OrderByDirection::Desc),
+        None => (elem, open_dds::models::OrderByDirection::Asc),
     };
+    Ok((create_field_name(field_name)?, direction))
+}
 
+fn build_order_by_element(
+    field_name: &FieldName,
+    direction: open_dds::models::OrderByDirection,
+) -> open_dds::query::OrderByElement {
     let operand = open_dds::query::Operand::Field(open_dds::query::ObjectFieldOperand {
         target: Box::new(open_dds::query::ObjectFieldTarget {
-            field_name: create_field_name(&field_name)?,
+            field_name: field_name.clone(),
             arguments: IndexMap::new(),
         }),
         nested: None,
     });
-    Ok(open_dds::query::OrderByElement { operand, direction })
+    open_dds::query::OrderByElement { operand, direction }
 }
 
-fn parse_url(uri: &Uri) -> Result<ModelInfo, ParseError> {
+pub(crate) fn parse_url(uri: &Uri) -> Result<ModelInfo, ParseError> {
     let path = uri.path();
     let paths = path
         .split('/')
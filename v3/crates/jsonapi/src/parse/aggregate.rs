@@ -0,0 +1,222 @@
+//! Aggregates over a collection, requested with `aggregate[<function>]=<fields>` and grouped
+//! with `groupBy=<fields>`, eg `?aggregate[count]=*&aggregate[avg]=rating&groupBy=genre`.
+//!
+//! `count` and `countDistinct` are always available, where enabled in the model's
+//! `AggregateExpression`, and `count` can be applied to `*` to count objects. Any other function
+//! must be one of the aggregation functions the `AggregateExpression` defines for the field.
+use crate::catalog::{Model, ObjectType, Type};
+use indexmap::IndexMap;
+use metadata_resolve::Qualified;
+use open_dds::{
+    models::ModelName,
+    query::{
+        Aggregate, AggregationFunction, Dimension, Name, ObjectFieldOperand, ObjectFieldTarget,
+        Operand,
+    },
+    types::FieldName,
+};
+use serde::{Deserialize, Serialize};
+
+pub const COUNT_FUNCTION: &str = "count";
+pub const COUNT_DISTINCT_FUNCTION: &str = "countDistinct";
+/// `aggregate[count]=*` counts the objects themselves
+pub const ALL_OBJECTS: &str = "*";
+
+const AGGREGATE_PARAMETER_PREFIX: &str = "aggregate[";
+const GROUP_BY_PARAMETER: &str = "groupBy";
+
+#[derive(Debug, thiserror::Error, Serialize, Deserialize)]
+pub enum AggregateError {
+    #[error("model {0} has no aggregate expression defined")]
+    NoAggregateExpressionDefined(Qualified<ModelName>),
+    #[error("no fields given for aggregate {0}")]
+    NoFieldsForAggregate(String),
+    #[error("cannot aggregate {field_name} with {function}")]
+    UnsupportedAggregate {
+        function: String,
+        field_name: String,
+    },
+    #[error("cannot group by {0}")]
+    UnsupportedGroupByField(String),
+}
+
+/// The aggregates and dimensions requested, in the order they appear in the query string
+pub struct AggregateRequest {
+    pub aggregates: Vec<RequestedAggregate>,
+    pub group_by: Vec<FieldName>,
+}
+
+pub struct RequestedAggregate {
+    /// the function as written in the request, eg `count` or `avg`
+    pub function: String,
+    /// the field as written in the request, or `*`
+    pub field: String,
+    pub aggregate: Aggregate,
+}
+
+/// The name an aggregate is selected with, and so the key of its result. The names in the
+/// request are not necessarily valid names, so aggregates are named by their position instead.
+pub fn aggregate_alias(index: usize) -> String {
+    format!("aggregate_{index}")
+}
+
+impl AggregateRequest {
+    // aggregates and dimensions are selected with generated names, as the ones in the request
+    // are not necessarily valid names. Aggregates are looked up by `aggregate_alias`, and
+    // dimensions come back in the order they were selected.
+    pub fn selection(&self) -> IndexMap<Name, Aggregate> {
+        self.aggregates
+            .iter()
+            .enumerate()
+            .map(|(index, requested)| {
+                (
+                    Name::from(aggregate_alias(index)),
+                    requested.aggregate.clone(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn dimensions(&self) -> IndexMap<Name, Dimension> {
+        self.group_by
+            .iter()
+            .enumerate()
+            .map(|(index, field_name)| {
+                (
+                    Name::from(format!("dimension_{index}")),
+                    Dimension::Field {
+                        column: field_operand(field_name.clone()),
+                        extraction: None,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// Parse the aggregate parameters from the query string, if there are any
+pub fn parse_aggregate_request(
+    model: &Model,
+    object_type: &ObjectType,
+    query_parameters: &[(String, String)],
+) -> Result<Option<AggregateRequest>, AggregateError> {
+    let mut requested_aggregates = vec![];
+    let mut requested_group_by = vec![];
+    for (name, value) in query_parameters {
+        if name == GROUP_BY_PARAMETER {
+            requested_group_by.extend(split_fields(value));
+        } else if let Some(function) = name
+            .strip_prefix(AGGREGATE_PARAMETER_PREFIX)
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            let fields = split_fields(value);
+            if fields.is_empty() {
+                return Err(AggregateError::NoFieldsForAggregate(function.to_string()));
+            }
+            requested_aggregates.extend(
+                fields
+                    .into_iter()
+                    .map(|field| (function.to_string(), field)),
+            );
+        }
+    }
+
+    if requested_aggregates.is_empty() && requested_group_by.is_empty() {
+        return Ok(None);
+    }
+
+    let model_aggregates = model
+        .aggregates
+        .as_ref()
+        .ok_or_else(|| AggregateError::NoAggregateExpressionDefined(model.name.clone()))?;
+
+    let mut aggregates = vec![];
+    for (function, field) in requested_aggregates {
+        let unsupported = || AggregateError::UnsupportedAggregate {
+            function: function.clone(),
+            field_name: field.clone(),
+        };
+
+        let aggregate = if field == ALL_OBJECTS {
+            if function != COUNT_FUNCTION || !model_aggregates.count {
+                return Err(unsupported());
+            }
+            Aggregate {
+                function: AggregationFunction::Count {},
+                operand: None,
+            }
+        } else {
+            let (field_name, aggregatable_field) = model_aggregates
+                .fields
+                .iter()
+                .find(|(field_name, _)| field_name.as_str() == field)
+                .ok_or_else(unsupported)?;
+            let aggregation_function = match function.as_str() {
+                COUNT_FUNCTION if aggregatable_field.count => AggregationFunction::Count {},
+                COUNT_DISTINCT_FUNCTION if aggregatable_field.count_distinct => {
+                    AggregationFunction::CountDistinct {}
+                }
+                _ => aggregatable_field
+                    .functions
+                    .iter()
+                    .find(|function_name| function_name.as_str() == function)
+                    .map(|function_name| AggregationFunction::Custom {
+                        name: function_name.clone(),
+                    })
+                    .ok_or_else(unsupported)?,
+            };
+            Aggregate {
+                function: aggregation_function,
+                operand: Some(field_operand(field_name.clone())),
+            }
+        };
+        aggregates.push(RequestedAggregate {
+            function,
+            field,
+            aggregate,
+        });
+    }
+
+    // we can group by any scalar field the role can see
+    let group_by = requested_group_by
+        .into_iter()
+        .map(|field| {
+            object_type
+                .type_fields
+                .iter()
+                .find(|(field_name, field_type)| {
+                    field_name.as_str() == field
+                        && matches!(
+                            field_type,
+                            Type::Scalar(_) | Type::ScalarForDataConnector(_)
+                        )
+                })
+                .map(|(field_name, _)| field_name.clone())
+                .ok_or(AggregateError::UnsupportedGroupByField(field))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Some(AggregateRequest {
+        aggregates,
+        group_by,
+    }))
+}
+
+fn split_fields(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn field_operand(field_name: FieldName) -> Operand {
+    Operand::Field(ObjectFieldOperand {
+        target: Box::new(ObjectFieldTarget {
+            field_name,
+            arguments: IndexMap::new(),
+        }),
+        nested: None,
+    })
+}
@@ -0,0 +1,50 @@
+//! Model arguments are passed in the query string as `arguments[<name>]=<value>`, eg
+//! `?arguments[country]=NL&arguments[tags]=["a","b"]`. Scalars are written as they are, anything
+//! else as JSON.
+use crate::catalog::Model;
+use crate::helpers::parse_argument_value;
+use crate::types::RequestError;
+use indexmap::IndexMap;
+use open_dds::{arguments::ArgumentName, query::Value};
+
+const ARGUMENT_PARAMETER_PREFIX: &str = "arguments[";
+
+pub fn build_model_arguments(
+    model: &Model,
+    query_parameters: &[(String, String)],
+) -> Result<IndexMap<ArgumentName, Value>, RequestError> {
+    let mut arguments = IndexMap::new();
+    for (name, value) in query_parameters {
+        let Some(name) = name
+            .strip_prefix(ARGUMENT_PARAMETER_PREFIX)
+            .and_then(|rest| rest.strip_suffix(']'))
+        else {
+            continue;
+        };
+        let (argument_name, argument) = model
+            .arguments
+            .iter()
+            .find(|(argument_name, _)| argument_name.as_str() == name)
+            .ok_or_else(|| {
+                RequestError::BadRequest(format!(
+                    "unknown argument {name} for model {}",
+                    model.name
+                ))
+            })?;
+        let argument_value =
+            parse_argument_value(&argument.argument_type, value).ok_or_else(|| {
+                RequestError::BadRequest(format!("invalid value for argument {name}: {value}"))
+            })?;
+        arguments.insert(argument_name.clone(), Value::Literal(argument_value));
+    }
+
+    for (argument_name, argument) in &model.arguments {
+        if argument.is_required && !arguments.contains_key(argument_name) {
+            return Err(RequestError::BadRequest(format!(
+                "missing required argument {argument_name} for model {}",
+                model.name
+            )));
+        }
+    }
+    Ok(arguments)
+}
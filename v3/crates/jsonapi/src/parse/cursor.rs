@@ -0,0 +1,372 @@
+//! Cursor pagination, following the JSON:API cursor pagination profile
+//! <https://jsonapi.org/profiles/ethanresnick/cursor-pagination/>. A page is requested with
+//! `page[limit]`, and `page[after]=<cursor>` or `page[before]=<cursor>` to start after or end
+//! before a resource. The first page is requested with an empty `page[after]`. Each resource of a paged collection has its cursor in `meta.page.cursor`,
+//! and the `next` and `prev` links are written with cursors.
+//!
+//! A cursor holds the values of the fields the collection is sorted by, followed by the model's
+//! unique identifier, so that it names a single position even when sort values repeat. Only
+//! models with a unique identifier can be paged with cursors, and their sort and unique
+//! identifier fields must be comparable with `_gt` and `_lt`. Rows with a null sort field cannot
+//! be compared, so paging past them is not supported.
+use base64::Engine;
+use indexmap::IndexMap;
+use open_dds::{
+    models::OrderByDirection,
+    query::{
+        BooleanExpression, ComparisonOperator, ObjectFieldOperand, ObjectFieldTarget, Operand,
+    },
+    types::FieldName,
+};
+use serde::{Deserialize, Serialize};
+
+pub const AFTER_PARAMETER: &str = "page[after]";
+pub const BEFORE_PARAMETER: &str = "page[before]";
+
+#[derive(Debug, thiserror::Error, Serialize, Deserialize)]
+pub enum CursorError {
+    #[error("invalid cursor: {0}")]
+    InvalidCursor(String),
+    #[error("page[after] and page[before] cannot be used together")]
+    AfterAndBefore,
+    #[error("cursors cannot be used together with page[offset]")]
+    CursorAndOffset,
+    #[error("this model has no unique identifier, so it cannot be paged with cursors")]
+    NoUniqueIdentifier,
+}
+
+/// The resource a page starts after, or ends before, as the values of its `CursorPagination`
+/// fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cursor {
+    After(Vec<serde_json::Value>),
+    Before(Vec<serde_json::Value>),
+}
+
+/// The order a collection is paged in, and the cursor the page was requested from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorPagination {
+    /// the sort fields, followed by any unique identifier fields that are not sorted by
+    pub order: Vec<(FieldName, OrderByDirection)>,
+    pub cursor: Option<Cursor>,
+}
+
+impl CursorPagination {
+    /// Read the cursor, if any, from the query string. `sort` is the order the request asked
+    /// for, which the unique identifier then breaks ties in.
+    pub fn new(
+        sort: &[(FieldName, OrderByDirection)],
+        unique_identifier: &[FieldName],
+        query_parameters: &[(String, String)],
+    ) -> Result<Self, CursorError> {
+        let mut order = sort.to_vec();
+        for field_name in unique_identifier {
+            if !order
+                .iter()
+                .any(|(sorted_field, _)| sorted_field == field_name)
+            {
+                order.push((field_name.clone(), OrderByDirection::Asc));
+            }
+        }
+
+        let parameter = |name: &str| {
+            query_parameters
+                .iter()
+                .find(|(parameter_name, _)| parameter_name == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let cursor = match (parameter(AFTER_PARAMETER), parameter(BEFORE_PARAMETER)) {
+            (Some(_), Some(_)) => return Err(CursorError::AfterAndBefore),
+            // an empty `page[after]` starts at the beginning of the collection
+            (Some(""), None) | (None, None) => None,
+            (Some(after), None) => Some(Cursor::After(decode_cursor(after, order.len())?)),
+            (None, Some(before)) => Some(Cursor::Before(decode_cursor(before, order.len())?)),
+        };
+        Ok(Self { order, cursor })
+    }
+
+    /// A page that ends before a cursor is fetched backwards from it, and then put back in order
+    pub fn is_reversed(&self) -> bool {
+        matches!(self.cursor, Some(Cursor::Before(_)))
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = &FieldName> {
+        self.order.iter().map(|(field_name, _)| field_name)
+    }
+
+    pub fn order_by(&self) -> Vec<open_dds::query::OrderByElement> {
+        self.order
+            .iter()
+            .map(|(field_name, direction)| open_dds::query::OrderByElement {
+                operand: field_operand(field_name),
+                direction: match (direction, self.is_reversed()) {
+                    (OrderByDirection::Asc, false) | (OrderByDirection::Desc, true) => {
+                        OrderByDirection::Asc
+                    }
+                    (OrderByDirection::Desc, false) | (OrderByDirection::Asc, true) => {
+                        OrderByDirection::Desc
+                    }
+                },
+            })
+            .collect()
+    }
+
+    /// The rows that come after (or before) the cursor, in the paging order. For an order of
+    /// `(a, b)` and a cursor of `(x, y)`, rows after the cursor have `a > x`, or `a = x` and
+    /// `b > y`.
+    pub fn filter(&self) -> Option<BooleanExpression> {
+        let (values, after) = match self.cursor.as_ref()? {
+            Cursor::After(values) => (values, true),
+            Cursor::Before(values) => (values, false),
+        };
+        let position = |index: usize| {
+            let mut conditions = self.order[..index]
+                .iter()
+                .zip(values)
+                .map(|((field_name, _), value)| {
+                    comparison(field_name, ComparisonOperator::Equals, value)
+                })
+                .collect::<Vec<_>>();
+            let (field_name, direction) = &self.order[index];
+            let operator = match (direction, after) {
+                (OrderByDirection::Asc, true) | (OrderByDirection::Desc, false) => {
+                    ComparisonOperator::GreaterThan
+                }
+                (OrderByDirection::Desc, true) | (OrderByDirection::Asc, false) => {
+                    ComparisonOperator::LessThan
+                }
+            };
+            conditions.push(comparison(field_name, operator, &values[index]));
+            BooleanExpression::And(conditions)
+        };
+        Some(BooleanExpression::Or(
+            (0..self.order.len()).map(position).collect(),
+        ))
+    }
+
+    /// The cursor the page was requested from
+    pub fn request_cursor(&self) -> Option<String> {
+        match self.cursor.as_ref()? {
+            Cursor::After(values) | Cursor::Before(values) => Some(encode_cursor(values)),
+        }
+    }
+
+    /// The cursor of a row, if it has all of the fields the collection is ordered by
+    pub fn cursor_of(&self, row: &serde_json::Map<String, serde_json::Value>) -> Option<String> {
+        let values = self
+            .fields()
+            .map(|field_name| row.get(field_name.as_str()).cloned())
+            .collect::<Option<Vec<_>>>()?;
+        Some(encode_cursor(&values))
+    }
+}
+
+// cursors are opaque to clients, and must be safe to put in a URL
+fn encode_cursor(values: &[serde_json::Value]) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .encode(serde_json::Value::Array(values.to_vec()).to_string())
+}
+
+fn decode_cursor(cursor: &str, length: usize) -> Result<Vec<serde_json::Value>, CursorError> {
+    let invalid = || CursorError::InvalidCursor(cursor.to_string());
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .map_err(|_| invalid())?;
+    match serde_json::from_slice(&bytes) {
+        Ok(serde_json::Value::Array(values)) if values.len() == length => Ok(values),
+        _ => Err(invalid()),
+    }
+}
+
+fn field_operand(field_name: &FieldName) -> Operand {
+    Operand::Field(ObjectFieldOperand {
+        target: Box::new(ObjectFieldTarget {
+            field_name: field_name.clone(),
+            arguments: IndexMap::new(),
+        }),
+        nested: None,
+    })
+}
+
+fn comparison(
+    field_name: &FieldName,
+    operator: ComparisonOperator,
+    value: &serde_json::Value,
+) -> BooleanExpression {
+    BooleanExpression::Comparison {
+        operand: field_operand(field_name),
+        operator,
+        argument: Box::new(open_dds::query::Value::Literal(value.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use open_dds::identifier::Identifier;
+
+    fn field_name(name: &str) -> FieldName {
+        FieldName::new(Identifier::new(name).unwrap())
+    }
+
+    fn parameters(parameters: &[(&str, &str)]) -> Vec<(String, String)> {
+        parameters
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    fn compare(
+        field: &str,
+        operator: ComparisonOperator,
+        value: serde_json::Value,
+    ) -> BooleanExpression {
+        comparison(&field_name(field), operator, &value)
+    }
+
+    #[test]
+    fn test_unique_identifier_breaks_ties() {
+        let pagination = CursorPagination::new(
+            &[(field_name("rating"), OrderByDirection::Desc)],
+            &[field_name("id")],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            pagination.order,
+            vec![
+                (field_name("rating"), OrderByDirection::Desc),
+                (field_name("id"), OrderByDirection::Asc),
+            ]
+        );
+        assert_eq!(pagination.cursor, None);
+        assert_eq!(pagination.filter(), None);
+    }
+
+    #[test]
+    fn test_sorting_by_unique_identifier_is_not_repeated() {
+        let pagination = CursorPagination::new(
+            &[(field_name("id"), OrderByDirection::Desc)],
+            &[field_name("id")],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            pagination.order,
+            vec![(field_name("id"), OrderByDirection::Desc)]
+        );
+    }
+
+    #[test]
+    fn test_cursor_round_trips() {
+        let order = CursorPagination::new(
+            &[(field_name("rating"), OrderByDirection::Desc)],
+            &[field_name("id")],
+            &[],
+        )
+        .unwrap();
+        let row = serde_json::json!({"id": 7, "rating": 4.5, "title": "Titanic"});
+        let cursor = order.cursor_of(row.as_object().unwrap()).unwrap();
+
+        let pagination = CursorPagination::new(
+            &[(field_name("rating"), OrderByDirection::Desc)],
+            &[field_name("id")],
+            &parameters(&[("page[after]", &cursor)]),
+        )
+        .unwrap();
+        assert_eq!(
+            pagination.cursor,
+            Some(Cursor::After(vec![
+                serde_json::json!(4.5),
+                serde_json::json!(7)
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_rows_after_cursor() {
+        let cursor = encode_cursor(&[serde_json::json!(4.5), serde_json::json!(7)]);
+        let pagination = CursorPagination::new(
+            &[(field_name("rating"), OrderByDirection::Desc)],
+            &[field_name("id")],
+            &parameters(&[("page[after]", &cursor)]),
+        )
+        .unwrap();
+        assert!(!pagination.is_reversed());
+        assert_eq!(
+            pagination.filter(),
+            Some(BooleanExpression::Or(vec![
+                BooleanExpression::And(vec![compare(
+                    "rating",
+                    ComparisonOperator::LessThan,
+                    serde_json::json!(4.5)
+                )]),
+                BooleanExpression::And(vec![
+                    compare("rating", ComparisonOperator::Equals, serde_json::json!(4.5)),
+                    compare("id", ComparisonOperator::GreaterThan, serde_json::json!(7)),
+                ]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_rows_before_cursor_are_fetched_backwards() {
+        let cursor = encode_cursor(&[serde_json::json!(4.5), serde_json::json!(7)]);
+        let pagination = CursorPagination::new(
+            &[(field_name("rating"), OrderByDirection::Desc)],
+            &[field_name("id")],
+            &parameters(&[("page[before]", &cursor)]),
+        )
+        .unwrap();
+        assert!(pagination.is_reversed());
+        assert_eq!(
+            pagination
+                .order_by()
+                .into_iter()
+                .map(|element| element.direction)
+                .collect::<Vec<_>>(),
+            vec![OrderByDirection::Asc, OrderByDirection::Desc]
+        );
+        assert_eq!(
+            pagination.filter(),
+            Some(BooleanExpression::Or(vec![
+                BooleanExpression::And(vec![compare(
+                    "rating",
+                    ComparisonOperator::GreaterThan,
+                    serde_json::json!(4.5)
+                )]),
+                BooleanExpression::And(vec![
+                    compare("rating", ComparisonOperator::Equals, serde_json::json!(4.5)),
+                    compare("id", ComparisonOperator::LessThan, serde_json::json!(7)),
+                ]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_invalid_cursors_are_rejected() {
+        let wrong_length = encode_cursor(&[serde_json::json!(7)]);
+        for cursor in ["not a cursor", "bm90IGpzb24", wrong_length.as_str()] {
+            let result = CursorPagination::new(
+                &[(field_name("rating"), OrderByDirection::Desc)],
+                &[field_name("id")],
+                &parameters(&[("page[after]", cursor)]),
+            );
+            assert!(
+                matches!(result, Err(CursorError::InvalidCursor(_))),
+                "{cursor}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_after_and_before_are_rejected() {
+        let cursor = encode_cursor(&[serde_json::json!(7)]);
+        let result = CursorPagination::new(
+            &[],
+            &[field_name("id")],
+            &parameters(&[("page[after]", &cursor), ("page[before]", &cursor)]),
+        );
+        assert!(matches!(result, Err(CursorError::AfterAndBefore)));
+    }
+}
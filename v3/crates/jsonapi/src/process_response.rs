@@ -1,72 +1,177 @@
-use super::helpers::get_object_type;
-use super::types::{RelationshipNode, RelationshipTree};
+use super::helpers::{get_object_type, render_unique_identifier};
+use super::parse::{
+    aggregate::{AggregateRequest, aggregate_alias},
+    cursor::{self, Cursor, CursorPagination},
+    include_field,
+};
+use super::types::{InternalError, Pagination, RelationshipNode, RelationshipTree, RequestTarget};
 use crate::RequestError;
 use crate::catalog::ObjectType;
+use axum::http::Uri;
+use indexmap::IndexMap;
 use jsonapi_library::query::Query;
 use metadata_resolve::Qualified;
-use open_dds::{relationships::RelationshipType, types::CustomTypeName};
-use std::collections::BTreeMap;
+use open_dds::{
+    relationships::RelationshipType,
+    types::{CustomTypeName, FieldName},
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{DefaultHasher, Hasher};
 
-// a cheap way to get a unique id for each resource. we should probably get this from the
-// underlying data instead.
+// the resources of a compound document. Each resource is included once, however many times it
+// is related to, and not at all if it is already in the primary data.
 //
+// https://jsonapi.org/format/#document-compound-documents
+//
+// "A compound document MUST NOT include more than one resource object for each type and id
+// pair."
+#[derive(Default)]
+struct IncludedResources {
+    identifiers: BTreeSet<(String, String)>,
+    resources: Vec<jsonapi_library::model::Resource>,
+}
+
+impl IncludedResources {
+    fn insert(&mut self, resource: jsonapi_library::model::Resource) {
+        if self
+            .identifiers
+            .insert((resource._type.clone(), resource.id.clone()))
+        {
+            self.resources.push(resource);
+        }
+    }
+
+    fn into_included(
+        self,
+        data: Option<&jsonapi_library::api::PrimaryData>,
+    ) -> Option<Vec<jsonapi_library::model::Resource>> {
+        let primary_resources = match data {
+            Some(jsonapi_library::api::PrimaryData::Single(resource)) => {
+                std::slice::from_ref(resource.as_ref())
+            }
+            Some(jsonapi_library::api::PrimaryData::Multiple(resources)) => resources.as_slice(),
+            _ => &[],
+        };
+        let included = self
+            .resources
+            .into_iter()
+            .filter(|resource| {
+                !primary_resources.iter().any(|primary_resource| {
+                    primary_resource._type == resource._type && primary_resource.id == resource.id
+                })
+            })
+            .collect::<Vec<_>>();
+        if included.is_empty() {
+            None
+        } else {
+            Some(included)
+        }
+    }
+}
+
 // https://jsonapi.org/format/#document-resource-object-identification
 //
 // "Within a given API, each resource object’s type and id pair MUST identify a single, unique
 // resource."
-fn fresh_id(unique_id: &mut i32) -> i32 {
-    let id = *unique_id;
-
-    // increment counter
-    *unique_id += 1;
+//
+// resources of models are identified by their unique identifier, the same `id` they are fetched
+// by. Anything else (eg the results of commands) has no identity of its own, so it is identified
+// by its contents: the same values make the same resource.
+fn resource_id(
+    unique_identifier: Option<&[FieldName]>,
+    row: &serde_json::Map<String, serde_json::Value>,
+) -> String {
+    if let Some(unique_identifier) = unique_identifier
+        && let Some(id) =
+            render_unique_identifier(unique_identifier, |field_name| row.get(field_name.as_str()))
+    {
+        return id;
+    }
+    let mut hasher = DefaultHasher::new();
+    hasher.write(
+        sorted_json(&serde_json::Value::Object(row.clone()))
+            .to_string()
+            .as_bytes(),
+    );
+    format!("{:016x}", hasher.finish())
+}
 
-    id
+// the same value with the keys of every object sorted, so that the same values hash the same
+// whatever order the fields were selected in
+fn sorted_json(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(object) => serde_json::Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), sorted_json(value)))
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .collect(),
+        ),
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.iter().map(sorted_json).collect())
+        }
+        value => value.clone(),
+    }
 }
 
+// resources of a collection paged with cursors have their cursor in `meta.page.cursor`
 fn to_resource(
-    unique_id: &mut i32,
     rowset: ndc_models::RowSet,
     type_name: &Qualified<CustomTypeName>,
+    unique_identifier: Option<&[FieldName]>,
+    cursor_pagination: Option<&CursorPagination>,
     relationship_tree: &RelationshipTree,
     query: &Query,
     object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
-    collect_relationships: &mut Vec<jsonapi_library::model::Resource>,
+    included: &mut IncludedResources,
 ) -> Result<Vec<jsonapi_library::model::Resource>, RequestError> {
     let mut resources = vec![];
     if let Some(rows) = rowset.rows {
         for row in rows {
-            let resource_id = fresh_id(unique_id);
-            let resource = row_to_resource(
-                unique_id,
+            let row = row
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.0))
+                .collect::<serde_json::Map<_, _>>();
+            let cursor =
+                cursor_pagination.and_then(|cursor_pagination| cursor_pagination.cursor_of(&row));
+            let mut resource = row_to_resource(
                 relationship_tree,
-                collect_relationships,
-                resource_id,
+                included,
                 type_name,
+                unique_identifier,
                 query,
                 object_types,
-                row.into_iter().map(|(k, v)| (k.to_string(), v.0)),
+                row,
             )?;
+            if let Some(cursor) = cursor {
+                resource.meta = Some(
+                    [("page".to_string(), serde_json::json!({ "cursor": cursor }))]
+                        .into_iter()
+                        .collect(),
+                );
+            }
             resources.push(resource);
         }
     }
     Ok(resources)
 }
 
-fn render_type_name(type_name: &Qualified<CustomTypeName>) -> String {
+pub(crate) fn render_type_name(type_name: &Qualified<CustomTypeName>) -> String {
     format!("{}_{}", type_name.subgraph, type_name.name)
 }
 
 fn row_to_resource(
-    unique_id: &mut i32,
     relationship_tree: &RelationshipTree,
-    collect_relationships: &mut Vec<jsonapi_library::model::Resource>,
-    resource_id: i32,
+    included: &mut IncludedResources,
     row_type: &Qualified<CustomTypeName>,
+    unique_identifier: Option<&[FieldName]>,
     query: &Query,
     object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
-    row: impl Iterator<Item = (String, serde_json::Value)>,
+    row: serde_json::Map<String, serde_json::Value>,
 ) -> Result<jsonapi_library::model::Resource, RequestError> {
-    let mut attributes = BTreeMap::new();
+    let object_type = get_object_type(object_types, row_type).map_err(RequestError::ParseError)?;
+    let mut fields = serde_json::Map::new();
     let mut relationships = BTreeMap::new();
 
     for (key, mut value) in row {
@@ -76,64 +181,46 @@ fn row_to_resource(
                 nested,
                 relationship_type,
                 object_type,
+                unique_identifier: related_unique_identifier,
                 is_command_relationship,
             } = relationship_node;
-            let relationship_type_name = render_type_name(object_type);
-            let relationship_identifier_data = match value
-                .get_mut("rows")
-                .and_then(|rows| rows.as_array_mut())
-            {
-                None => jsonapi_library::model::IdentifierData::None,
-                Some(relationship_rows) => match relationship_type {
-                    RelationshipType::Object => {
-                        if let Some(object_row_value) = relationship_rows.pop() {
-                            let relationship_id = fresh_id(unique_id);
-                            let resource_identifier = jsonapi_library::model::ResourceIdentifier {
-                                _type: relationship_type_name,
-                                id: relationship_id.to_string(),
-                            };
-                            // collect this relationship value
-                            collect_relationship_value(
-                                unique_id,
-                                nested,
-                                collect_relationships,
-                                relationship_id,
-                                object_type,
-                                *is_command_relationship,
-                                query,
-                                object_types,
-                                object_row_value,
-                            )?;
-                            jsonapi_library::model::IdentifierData::Single(resource_identifier)
-                        } else {
-                            jsonapi_library::model::IdentifierData::None
-                        }
-                    }
-                    RelationshipType::Array => {
-                        let mut resource_identifiers = vec![];
-                        for object_row_value in relationship_rows.iter_mut() {
-                            let relationship_id = fresh_id(unique_id);
-                            let resource_identifier = jsonapi_library::model::ResourceIdentifier {
-                                _type: relationship_type_name.clone(),
-                                id: relationship_id.to_string(),
-                            };
-                            // collect this relationship value
-                            collect_relationship_value(
-                                unique_id,
-                                nested,
-                                collect_relationships,
-                                relationship_id,
-                                object_type,
-                                *is_command_relationship,
-                                query,
-                                object_types,
-                                object_row_value.take(),
-                            )?;
-                            resource_identifiers.push(resource_identifier);
-                        }
-                        jsonapi_library::model::IdentifierData::Multiple(resource_identifiers)
-                    }
-                },
+            let relationship_rows = value.get_mut("rows").and_then(|rows| rows.as_array_mut());
+            let has_rows = relationship_rows.is_some();
+            let mut resource_identifiers = vec![];
+            if let Some(relationship_rows) = relationship_rows {
+                // an object relationship has at most one row
+                if let RelationshipType::Object = relationship_type {
+                    relationship_rows.truncate(1);
+                }
+                for object_row_value in relationship_rows.iter_mut() {
+                    let related_resource = relationship_value_to_resource(
+                        nested,
+                        included,
+                        object_type,
+                        related_unique_identifier.as_deref(),
+                        *is_command_relationship,
+                        query,
+                        object_types,
+                        object_row_value.take(),
+                    )?;
+                    resource_identifiers.push(jsonapi_library::model::ResourceIdentifier {
+                        _type: related_resource._type.clone(),
+                        id: related_resource.id.clone(),
+                    });
+                    // collect this relationship value
+                    included.insert(related_resource);
+                }
+            }
+            let relationship_identifier_data = match relationship_type {
+                RelationshipType::Array if has_rows => {
+                    jsonapi_library::model::IdentifierData::Multiple(resource_identifiers)
+                }
+                RelationshipType::Object | RelationshipType::Array => {
+                    resource_identifiers.pop().map_or(
+                        jsonapi_library::model::IdentifierData::None,
+                        jsonapi_library::model::IdentifierData::Single,
+                    )
+                }
             };
             let relationship = jsonapi_library::model::Relationship {
                 data: Some(relationship_identifier_data),
@@ -141,21 +228,32 @@ fn row_to_resource(
             };
             relationships.insert(key.clone(), relationship);
         } else {
-            let object_type =
-                get_object_type(object_types, row_type).map_err(RequestError::ParseError)?;
             let identifier = open_dds::identifier::Identifier::new(key.as_str()).unwrap();
             let field_name = open_dds::types::FieldName::new(identifier);
 
             if object_type.type_fields.contains_key(&field_name) {
-                attributes.insert(key.clone(), value);
+                fields.insert(key.clone(), value);
             }
         }
     }
 
+    let id = resource_id(unique_identifier, &fields);
+
+    // unique identifier fields are always fetched to make the `id`, but are only attributes
+    // if they were asked for
+    let attributes = fields
+        .into_iter()
+        .filter(|(key, _)| {
+            open_dds::identifier::Identifier::new(key.as_str()).is_ok_and(|identifier| {
+                include_field(query, &FieldName::new(identifier), &row_type.name)
+            })
+        })
+        .collect();
+
     let rendered_type_name = render_type_name(row_type);
     Ok(jsonapi_library::api::Resource {
         _type: rendered_type_name,
-        id: resource_id.to_string(),
+        id,
         attributes,
         links: None,
         meta: None,
@@ -167,18 +265,16 @@ fn row_to_resource(
     })
 }
 
-fn collect_relationship_value(
-    unique_id: &mut i32,
+fn relationship_value_to_resource(
     relationship_tree: &RelationshipTree,
-    collect_relationships: &mut Vec<jsonapi_library::model::Resource>,
-    resource_id: i32,
+    included: &mut IncludedResources,
     row_type: &Qualified<CustomTypeName>,
+    unique_identifier: Option<&[FieldName]>,
     is_command_relationship: bool,
     query: &Query,
     object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
-
     mut value: serde_json::Value,
-) -> Result<(), RequestError> {
+) -> Result<jsonapi_library::model::Resource, RequestError> {
     if is_command_relationship {
         // If this is a command relationship, we need to extract the value from the 'FUNCTION_IR_VALUE_COLUMN_NAME' key
         // We are ignoring the other keys
@@ -191,56 +287,813 @@ fn collect_relationship_value(
     if let serde_json::Value::Object(object) = value {
         row_object = object;
     }
-    let relationship_resource = row_to_resource(
-        unique_id,
+    row_to_resource(
         relationship_tree,
-        collect_relationships,
-        resource_id,
+        included,
         row_type,
+        unique_identifier,
         query,
         object_types,
-        row_object.into_iter(),
-    )?;
-    // collect this relationship resource
-    collect_relationships.push(relationship_resource);
+        row_object,
+    )
+}
+
+// the resources of a relationship of a single row, returned as primary data when fetching
+// related resources. Resources included from them are collected as usual.
+fn related_resources(
+    rowset: ndc_models::RowSet,
+    relationship: &str,
+    relationship_tree: &RelationshipTree,
+    query: &Query,
+    object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
+    included: &mut IncludedResources,
+) -> Result<(RelationshipType, Vec<jsonapi_library::model::Resource>), RequestError> {
+    let RelationshipNode {
+        nested,
+        relationship_type,
+        object_type,
+        unique_identifier,
+        is_command_relationship,
+    } = relationship_tree
+        .relationships
+        .get(relationship)
+        .ok_or(RequestError::NotFound)?;
 
-    Ok(())
+    let row = rowset
+        .rows
+        .and_then(|rows| rows.into_iter().next())
+        .ok_or(RequestError::ResourceNotFound)?;
+    let mut value = row
+        .into_iter()
+        .find(|(key, _)| key.as_str() == relationship)
+        .map_or(serde_json::Value::Null, |(_, value)| value.0);
+
+    let mut resources = vec![];
+    if let Some(relationship_rows) = value.get_mut("rows").and_then(|rows| rows.as_array_mut()) {
+        for object_row_value in relationship_rows.iter_mut() {
+            resources.push(relationship_value_to_resource(
+                nested,
+                included,
+                object_type,
+                unique_identifier.as_deref(),
+                *is_command_relationship,
+                query,
+                object_types,
+                object_row_value.take(),
+            )?);
+        }
+    }
+    Ok((relationship_type.clone(), resources))
+}
+
+// a resource linkage only carries the type and id of each resource
+// spec: <https://jsonapi.org/format/#fetching-relationships-responses-200>
+fn to_resource_linkage(
+    resource: jsonapi_library::model::Resource,
+) -> jsonapi_library::model::Resource {
+    jsonapi_library::model::Resource {
+        _type: resource._type,
+        id: resource.id,
+        attributes: BTreeMap::new(),
+        links: None,
+        meta: None,
+        relationships: None,
+    }
 }
 
 pub fn process_result(
     rowsets: Vec<ndc_models::RowSet>,
     root_type_name: &Qualified<CustomTypeName>,
+    root_unique_identifier: Option<&[FieldName]>,
+    target: &RequestTarget,
+    pagination: Option<&Pagination>,
     relationship_tree: &RelationshipTree,
     query: &Query,
     object_types: &BTreeMap<Qualified<CustomTypeName>, ObjectType>,
 ) -> Result<jsonapi_library::api::DocumentData, RequestError> {
-    let mut unique_id = 1;
+    let cursor_pagination = pagination.and_then(|pagination| pagination.cursor.as_ref());
+    let mut included = IncludedResources::default();
+    let first_rowset = rowsets.into_iter().next();
+    let data = match target {
+        RequestTarget::Collection => {
+            let mut resources = vec![];
+            if let Some(first_rowset) = first_rowset {
+                resources.extend(to_resource(
+                    first_rowset,
+                    root_type_name,
+                    root_unique_identifier,
+                    cursor_pagination,
+                    relationship_tree,
+                    query,
+                    object_types,
+                    &mut included,
+                )?);
+            }
+            // a page that ends before a cursor was fetched backwards
+            if cursor_pagination.is_some_and(CursorPagination::is_reversed) {
+                resources.reverse();
+            }
+            Some(jsonapi_library::api::PrimaryData::Multiple(resources))
+        }
+        RequestTarget::Resource { .. } => {
+            let first_rowset = first_rowset.ok_or(RequestError::ResourceNotFound)?;
+            let resource = to_resource(
+                first_rowset,
+                root_type_name,
+                root_unique_identifier,
+                None,
+                relationship_tree,
+                query,
+                object_types,
+                &mut included,
+            )?
+            .into_iter()
+            .next()
+            .ok_or(RequestError::ResourceNotFound)?;
+            Some(jsonapi_library::api::PrimaryData::Single(Box::new(
+                resource,
+            )))
+        }
+        RequestTarget::RelatedResources { relationship, .. }
+        | RequestTarget::Relationship { relationship, .. } => {
+            let first_rowset = first_rowset.ok_or(RequestError::ResourceNotFound)?;
+            let (relationship_type, mut resources) = related_resources(
+                first_rowset,
+                relationship,
+                relationship_tree,
+                query,
+                object_types,
+                &mut included,
+            )?;
+            if let RequestTarget::Relationship { .. } = target {
+                resources = resources.into_iter().map(to_resource_linkage).collect();
+            }
+            match relationship_type {
+                // an empty to-one relationship has no primary data
+                RelationshipType::Object => resources
+                    .pop()
+                    .map(|resource| jsonapi_library::api::PrimaryData::Single(Box::new(resource))),
+                RelationshipType::Array => {
+                    Some(jsonapi_library::api::PrimaryData::Multiple(resources))
+                }
+            }
+        }
+    };
 
-    let mut resources = vec![];
-    let mut collect_relationships = vec![];
-    if let Some(first_rowset) = rowsets.into_iter().next() {
-        resources.extend(to_resource(
-            &mut unique_id,
-            first_rowset,
-            root_type_name,
-            relationship_tree,
-            query,
-            object_types,
-            &mut collect_relationships,
-        )?);
-    }
+    let included = included.into_included(data.as_ref());
+
+    Ok(jsonapi_library::api::DocumentData {
+        data,
+        included,
+        links: None,
+        meta: None,
+        jsonapi: None,
+    })
+}
 
-    let included = if collect_relationships.is_empty() {
-        None
+// there are no resources to return for aggregates, so the results go in `meta`, keyed by
+// function and then by field:
+// {"meta": {"aggregates": {"count": {"*": 3}, "avg": {"rating": 4.5}}}}
+// when grouping, each group has its dimensions and aggregates:
+// {"meta": {"groups": [{"dimensions": {"genre": "Rock"}, "aggregates": {"count": {"*": 3}}}]}}
+pub fn process_aggregate_result(
+    rowsets: Vec<ndc_models::RowSet>,
+    aggregate_request: &AggregateRequest,
+) -> Result<jsonapi_library::api::DocumentData, RequestError> {
+    let rowset = rowsets
+        .into_iter()
+        .next()
+        .ok_or(RequestError::InternalError(InternalError::EmptyQuerySet))?;
+
+    let meta = if aggregate_request.group_by.is_empty() {
+        let aggregates = rowset.aggregates.unwrap_or_default();
+        (
+            "aggregates".to_string(),
+            aggregates_to_json(aggregate_request, aggregates),
+        )
     } else {
-        Some(collect_relationships)
+        let groups = rowset
+            .groups
+            .unwrap_or_default()
+            .into_iter()
+            .map(|group| {
+                let dimensions = aggregate_request
+                    .group_by
+                    .iter()
+                    .map(ToString::to_string)
+                    .zip(group.dimensions)
+                    .collect::<serde_json::Map<_, _>>();
+                serde_json::json!({
+                    "dimensions": dimensions,
+                    "aggregates": aggregates_to_json(aggregate_request, group.aggregates),
+                })
+            })
+            .collect();
+        ("groups".to_string(), serde_json::Value::Array(groups))
     };
 
     Ok(jsonapi_library::api::DocumentData {
-        data: Some(jsonapi_library::api::PrimaryData::Multiple(resources)),
-        included,
+        data: None,
+        included: None,
         links: None,
-        meta: None,
+        meta: Some([meta].into_iter().collect()),
         jsonapi: None,
     })
 }
+
+// each aggregate is found by the name it was selected with, whatever order the results are in.
+// An aggregate missing from the results is `null`.
+fn aggregates_to_json(
+    aggregate_request: &AggregateRequest,
+    mut values: IndexMap<ndc_models::FieldName, serde_json::Value>,
+) -> serde_json::Value {
+    let mut result = serde_json::Map::new();
+    for (index, requested) in aggregate_request.aggregates.iter().enumerate() {
+        let value = values
+            .swap_remove(aggregate_alias(index).as_str())
+            .unwrap_or_default();
+        if let serde_json::Value::Object(fields) = result
+            .entry(requested.function.clone())
+            .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
+        {
+            fields.insert(requested.field.clone(), value);
+        }
+    }
+    serde_json::Value::Object(result)
+}
+
+/// Add `links.next` and `links.prev` for paging through a collection, and `meta.total` if the
+/// total was requested. The links only change the query string, so they are written relative to
+/// the request URL, eg `?page[limit]=10&page[after]=WzEwXQ`. Collections paged with cursors are
+/// linked by the cursors of their first and last resources, others by offset.
+pub fn add_pagination(
+    document: &mut jsonapi_library::api::DocumentData,
+    uri: &Uri,
+    pagination: &Pagination,
+    total: Option<u64>,
+) {
+    if let Some(total) = total {
+        document
+            .meta
+            .get_or_insert_with(Default::default)
+            .insert("total".to_string(), serde_json::json!(total));
+    }
+
+    let resources = match &document.data {
+        Some(jsonapi_library::api::PrimaryData::Multiple(resources)) => resources.as_slice(),
+        _ => &[],
+    };
+    let offset = pagination.offset.unwrap_or_default();
+    // without a total, a full page means there may be more
+    let has_more = match (total, pagination.limit) {
+        (Some(total), _) => u64::try_from(offset + resources.len()).is_ok_and(|seen| seen < total),
+        (None, Some(limit)) => resources.len() == limit,
+        // without a limit, everything is returned at once
+        (None, None) => false,
+    };
+
+    let mut links = vec![];
+    match &pagination.cursor {
+        Some(cursor_pagination) => {
+            // a page is fetched away from its cursor, so there is always something on the other
+            // side of it
+            let (has_next, has_prev) = match cursor_pagination.cursor {
+                None => (has_more, offset > 0),
+                Some(Cursor::After(_)) => (
+                    pagination
+                        .limit
+                        .is_some_and(|limit| resources.len() == limit),
+                    true,
+                ),
+                Some(Cursor::Before(_)) => (
+                    true,
+                    pagination
+                        .limit
+                        .is_some_and(|limit| resources.len() == limit),
+                ),
+            };
+            // an empty page is linked back to by the cursor it was requested with
+            let first_cursor = resources
+                .first()
+                .and_then(resource_cursor)
+                .or_else(|| cursor_pagination.request_cursor());
+            let last_cursor = resources
+                .last()
+                .and_then(resource_cursor)
+                .or_else(|| cursor_pagination.request_cursor());
+            if has_next && let Some(last_cursor) = last_cursor {
+                links.push((
+                    "next".to_string(),
+                    page_link(uri, Some((cursor::AFTER_PARAMETER, last_cursor))),
+                ));
+            }
+            if has_prev && let Some(first_cursor) = first_cursor {
+                links.push((
+                    "prev".to_string(),
+                    page_link(uri, Some((cursor::BEFORE_PARAMETER, first_cursor))),
+                ));
+            }
+        }
+        None => {
+            let Some(limit) = pagination.limit else {
+                return;
+            };
+            if has_more {
+                links.push(("next".to_string(), offset_link(uri, offset + limit)));
+            }
+            if offset > 0 {
+                links.push((
+                    "prev".to_string(),
+                    offset_link(uri, offset.saturating_sub(limit)),
+                ));
+            }
+        }
+    }
+    if !links.is_empty() {
+        document.links = Some(links.into_iter().collect());
+    }
+}
+
+fn resource_cursor(resource: &jsonapi_library::model::Resource) -> Option<String> {
+    resource
+        .meta
+        .as_ref()?
+        .get("page")?
+        .get("cursor")?
+        .as_str()
+        .map(ToString::to_string)
+}
+
+fn offset_link(uri: &Uri, offset: usize) -> serde_json::Value {
+    page_link(
+        uri,
+        (offset > 0).then(|| ("page[offset]", offset.to_string())),
+    )
+}
+
+// the query string of the request, with the position in the collection replaced
+fn page_link(uri: &Uri, position: Option<(&str, String)>) -> serde_json::Value {
+    let mut parameters = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|parameter| !parameter.is_empty() && !is_page_position_parameter(parameter))
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if let Some((name, value)) = position {
+        parameters.push(format!("{name}={value}"));
+    }
+    serde_json::Value::String(format!("?{}", parameters.join("&")))
+}
+
+fn is_page_position_parameter(parameter: &str) -> bool {
+    let name = parameter.split('=').next().unwrap_or_default();
+    ["offset", "after", "before"].iter().any(|position| {
+        name == format!("page[{position}]")
+            || name.eq_ignore_ascii_case(&format!("page%5B{position}%5D"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Type;
+    use crate::parse::aggregate::RequestedAggregate;
+    use open_dds::query::{Aggregate, AggregationFunction};
+    use open_dds::subgraph_identifier;
+
+    fn type_name(name: &str) -> Qualified<CustomTypeName> {
+        Qualified::new(
+            subgraph_identifier!("default"),
+            CustomTypeName(open_dds::identifier::Identifier::new(name).unwrap()),
+        )
+    }
+
+    fn field_name(name: &str) -> FieldName {
+        FieldName::new(open_dds::identifier::Identifier::new(name).unwrap())
+    }
+
+    fn object_type(fields: &[&str]) -> ObjectType {
+        ObjectType {
+            type_fields: fields
+                .iter()
+                .map(|field| {
+                    (
+                        field_name(field),
+                        Type::Scalar(ndc_models::TypeRepresentation::String),
+                    )
+                })
+                .collect::<IndexMap<_, _>>(),
+            type_relationships: IndexMap::new(),
+        }
+    }
+
+    fn object_types() -> BTreeMap<Qualified<CustomTypeName>, ObjectType> {
+        BTreeMap::from([
+            (
+                type_name("Article"),
+                object_type(&["article_id", "title", "author_id"]),
+            ),
+            (type_name("Author"), object_type(&["author_id", "name"])),
+        ])
+    }
+
+    // articles, with their author included
+    fn relationship_tree() -> RelationshipTree {
+        RelationshipTree {
+            relationships: BTreeMap::from([(
+                "author".to_string(),
+                RelationshipNode {
+                    object_type: type_name("Author"),
+                    relationship_type: RelationshipType::Object,
+                    unique_identifier: Some(vec![field_name("author_id")]),
+                    is_command_relationship: false,
+                    nested: RelationshipTree::default(),
+                },
+            )]),
+        }
+    }
+
+    fn rowset(rows: serde_json::Value) -> Vec<ndc_models::RowSet> {
+        vec![serde_json::from_value(serde_json::json!({ "rows": rows })).unwrap()]
+    }
+
+    fn articles(
+        rows: serde_json::Value,
+        unique_identifier: Option<&[FieldName]>,
+        relationship_tree: &RelationshipTree,
+        query: &str,
+    ) -> (
+        Vec<jsonapi_library::model::Resource>,
+        Vec<jsonapi_library::model::Resource>,
+    ) {
+        let document = process_result(
+            rowset(rows),
+            &type_name("Article"),
+            unique_identifier,
+            &RequestTarget::Collection,
+            None,
+            relationship_tree,
+            &Query::from_params(query),
+            &object_types(),
+        )
+        .unwrap();
+        let Some(jsonapi_library::api::PrimaryData::Multiple(resources)) = document.data else {
+            panic!("expected a collection of resources");
+        };
+        (resources, document.included.unwrap_or_default())
+    }
+
+    fn resource_ids(resources: &[jsonapi_library::model::Resource]) -> Vec<&str> {
+        resources
+            .iter()
+            .map(|resource| resource.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_resources_are_identified_by_their_unique_identifier() {
+        let unique_identifier = [field_name("article_id")];
+        let (resources, _) = articles(
+            serde_json::json!([
+                {"article_id": 3, "title": "The Next Article"},
+                {"article_id": 1, "title": "The First Article"},
+            ]),
+            Some(&unique_identifier),
+            &RelationshipTree::default(),
+            "",
+        );
+        assert_eq!(resource_ids(&resources), vec!["3", "1"]);
+        assert_eq!(resources[0]._type, "default_Article");
+        assert_eq!(
+            resources[0].attributes.get("article_id"),
+            Some(&serde_json::json!(3))
+        );
+    }
+
+    #[test]
+    fn test_unique_identifier_is_not_an_attribute_unless_requested() {
+        let unique_identifier = [field_name("article_id")];
+        let (resources, _) = articles(
+            serde_json::json!([{"article_id": 3, "title": "The Next Article"}]),
+            Some(&unique_identifier),
+            &RelationshipTree::default(),
+            "fields[Article]=title",
+        );
+        assert_eq!(resource_ids(&resources), vec!["3"]);
+        assert_eq!(
+            resources[0].attributes.keys().collect::<Vec<_>>(),
+            vec!["title"]
+        );
+    }
+
+    #[test]
+    fn test_included_resources_are_identified_and_included_once() {
+        let unique_identifier = [field_name("article_id")];
+        let author = serde_json::json!({"rows": [{"author_id": 7, "name": "Jane"}]});
+        let (resources, included) = articles(
+            serde_json::json!([
+                {"article_id": 1, "author": author},
+                {"article_id": 2, "author": author},
+            ]),
+            Some(&unique_identifier),
+            &relationship_tree(),
+            "include=author",
+        );
+
+        // both articles link to the same author, by its unique identifier
+        for resource in &resources {
+            let relationship = &resource.relationships.as_ref().unwrap()["author"];
+            let Some(jsonapi_library::model::IdentifierData::Single(identifier)) =
+                &relationship.data
+            else {
+                panic!("expected a single resource identifier");
+            };
+            assert_eq!(identifier._type, "default_Author");
+            assert_eq!(identifier.id, "7");
+        }
+
+        // which is only included once
+        assert_eq!(resource_ids(&included), vec!["7"]);
+        assert_eq!(included[0]._type, "default_Author");
+    }
+
+    #[test]
+    fn test_resources_without_unique_identifier_are_identified_by_their_contents() {
+        let rows = serde_json::json!([
+            {"title": "The First Article"},
+            {"title": "The Next Article"},
+            {"title": "The First Article"},
+        ]);
+        let (resources, _) = articles(rows.clone(), None, &RelationshipTree::default(), "");
+        let (resources_again, _) = articles(rows, None, &RelationshipTree::default(), "");
+
+        let ids = resource_ids(&resources);
+        assert_eq!(ids, resource_ids(&resources_again));
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(ids[0], ids[2]);
+    }
+
+    #[test]
+    fn test_contents_are_identified_whatever_the_field_order() {
+        let id = |row: serde_json::Value| {
+            let serde_json::Value::Object(row) = row else {
+                panic!("expected an object");
+            };
+            resource_id(None, &row)
+        };
+        assert_eq!(
+            id(
+                serde_json::json!({"title": "Titanic", "location": {"city": "London", "country": "UK"}})
+            ),
+            id(
+                serde_json::json!({"location": {"country": "UK", "city": "London"}, "title": "Titanic"})
+            ),
+        );
+    }
+
+    #[test]
+    fn test_composite_unique_identifier() {
+        let unique_identifier = [field_name("author_id"), field_name("article_id")];
+        let (resources, _) = articles(
+            serde_json::json!([{"article_id": 3, "author_id": "jane"}]),
+            Some(&unique_identifier),
+            &RelationshipTree::default(),
+            "",
+        );
+        assert_eq!(resource_ids(&resources), vec!["jane,3"]);
+    }
+
+    fn aggregate_request(aggregates: &[(&str, &str)], group_by: &[&str]) -> AggregateRequest {
+        AggregateRequest {
+            aggregates: aggregates
+                .iter()
+                .map(|(function, field)| RequestedAggregate {
+                    function: (*function).to_string(),
+                    field: (*field).to_string(),
+                    aggregate: Aggregate {
+                        function: AggregationFunction::Count {},
+                        operand: None,
+                    },
+                })
+                .collect(),
+            group_by: group_by.iter().map(|field| field_name(field)).collect(),
+        }
+    }
+
+    fn aggregate_meta(
+        aggregate_request: &AggregateRequest,
+        rowset: serde_json::Value,
+    ) -> serde_json::Value {
+        let document = process_aggregate_result(
+            vec![serde_json::from_value(rowset).unwrap()],
+            aggregate_request,
+        )
+        .unwrap();
+        serde_json::to_value(document.meta).unwrap()
+    }
+
+    #[test]
+    fn test_aggregates_are_matched_by_name() {
+        let aggregate_request =
+            aggregate_request(&[("count", "*"), ("avg", "rating"), ("max", "rating")], &[]);
+        // the connector is free to return the aggregates in any order
+        let meta = aggregate_meta(
+            &aggregate_request,
+            serde_json::json!({
+                "aggregates": {"aggregate_2": 5, "aggregate_0": 3, "aggregate_1": 4.5},
+            }),
+        );
+        assert_eq!(
+            meta,
+            serde_json::json!({
+                "aggregates": {"count": {"*": 3}, "avg": {"rating": 4.5}, "max": {"rating": 5}},
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_aggregates_are_null() {
+        let aggregate_request = aggregate_request(&[("count", "*"), ("avg", "rating")], &[]);
+        let meta = aggregate_meta(
+            &aggregate_request,
+            serde_json::json!({"aggregates": {"aggregate_1": 4.5}}),
+        );
+        assert_eq!(
+            meta,
+            serde_json::json!({"aggregates": {"count": {"*": null}, "avg": {"rating": 4.5}}})
+        );
+    }
+
+    #[test]
+    fn test_group_aggregates_are_matched_by_name() {
+        let aggregate_request = aggregate_request(&[("count", "*"), ("avg", "rating")], &["genre"]);
+        let meta = aggregate_meta(
+            &aggregate_request,
+            serde_json::json!({
+                "groups": [
+                    {"dimensions": ["Rock"], "aggregates": {"aggregate_1": 4.5, "aggregate_0": 3}},
+                    {"dimensions": ["Jazz"], "aggregates": {"aggregate_0": 1, "aggregate_1": 2.0}},
+                ],
+            }),
+        );
+        assert_eq!(
+            meta,
+            serde_json::json!({
+                "groups": [
+                    {
+                        "dimensions": {"genre": "Rock"},
+                        "aggregates": {"count": {"*": 3}, "avg": {"rating": 4.5}},
+                    },
+                    {
+                        "dimensions": {"genre": "Jazz"},
+                        "aggregates": {"count": {"*": 1}, "avg": {"rating": 2.0}},
+                    },
+                ],
+            })
+        );
+    }
+
+    // the articles of a page, with the pagination links of `uri`
+    fn paged_articles(
+        uri: &str,
+        pagination: &Pagination,
+        rows: serde_json::Value,
+        total: Option<u64>,
+    ) -> jsonapi_library::api::DocumentData {
+        let uri = uri.parse::<Uri>().unwrap();
+        let mut document = process_result(
+            rowset(rows),
+            &type_name("Article"),
+            Some(&[field_name("article_id")]),
+            &RequestTarget::Collection,
+            Some(pagination),
+            &RelationshipTree::default(),
+            &Query::from_params(uri.query().unwrap_or_default()),
+            &object_types(),
+        )
+        .unwrap();
+        add_pagination(&mut document, &uri, pagination, total);
+        document
+    }
+
+    fn cursor_pagination(limit: usize, cursor: Option<Cursor>) -> Pagination {
+        Pagination {
+            limit: Some(limit),
+            offset: None,
+            cursor: Some(CursorPagination {
+                order: vec![(
+                    field_name("article_id"),
+                    open_dds::models::OrderByDirection::Asc,
+                )],
+                cursor,
+            }),
+        }
+    }
+
+    fn resource_cursors(document: &jsonapi_library::api::DocumentData) -> Vec<String> {
+        let Some(jsonapi_library::api::PrimaryData::Multiple(resources)) = &document.data else {
+            panic!("expected a collection of resources");
+        };
+        resources.iter().filter_map(resource_cursor).collect()
+    }
+
+    // cursors are base64url encoded JSON arrays: `WzJd` is `[2]`
+    #[test]
+    fn test_first_page_is_linked_by_cursor() {
+        let document = paged_articles(
+            "/default/Articles?page[limit]=2",
+            &cursor_pagination(2, None),
+            serde_json::json!([
+                {"article_id": 1, "title": "The First Article"},
+                {"article_id": 2, "title": "The Second Article"},
+            ]),
+            None,
+        );
+        assert_eq!(resource_cursors(&document), vec!["WzFd", "WzJd"]);
+        assert_eq!(
+            serde_json::to_value(&document.links).unwrap(),
+            serde_json::json!({"next": "?page[limit]=2&page[after]=WzJd"})
+        );
+    }
+
+    #[test]
+    fn test_page_before_cursor_is_put_back_in_order() {
+        // fetched backwards from the cursor, `[5]`
+        let document = paged_articles(
+            "/default/Articles?page[limit]=2&page[before]=WzVd",
+            &cursor_pagination(2, Some(Cursor::Before(vec![serde_json::json!(5)]))),
+            serde_json::json!([
+                {"article_id": 4, "title": "The Fourth Article"},
+                {"article_id": 3, "title": "The Third Article"},
+            ]),
+            None,
+        );
+        assert_eq!(resource_cursors(&document), vec!["WzNd", "WzRd"]);
+        assert_eq!(
+            serde_json::to_value(&document.links).unwrap(),
+            serde_json::json!({
+                "next": "?page[limit]=2&page[after]=WzRd",
+                "prev": "?page[limit]=2&page[before]=WzNd",
+            })
+        );
+    }
+
+    #[test]
+    fn test_last_page_after_cursor_has_no_next_link() {
+        let document = paged_articles(
+            "/default/Articles?page[limit]=2&page[after]=WzJd",
+            &cursor_pagination(2, Some(Cursor::After(vec![serde_json::json!(2)]))),
+            serde_json::json!([{"article_id": 3, "title": "The Third Article"}]),
+            None,
+        );
+        assert_eq!(
+            serde_json::to_value(&document.links).unwrap(),
+            serde_json::json!({"prev": "?page[limit]=2&page[before]=WzNd"})
+        );
+    }
+
+    #[test]
+    fn test_empty_page_links_back_to_its_cursor() {
+        let document = paged_articles(
+            "/default/Articles?page[limit]=2&page[after]=WzVd",
+            &cursor_pagination(2, Some(Cursor::After(vec![serde_json::json!(5)]))),
+            serde_json::json!([]),
+            None,
+        );
+        assert_eq!(
+            serde_json::to_value(&document.links).unwrap(),
+            serde_json::json!({"prev": "?page[limit]=2&page[before]=WzVd"})
+        );
+    }
+
+    #[test]
+    fn test_offset_links_and_total() {
+        let pagination = Pagination {
+            limit: Some(2),
+            offset: Some(2),
+            cursor: None,
+        };
+        let document = paged_articles(
+            "/default/Articles?withTotal=true&page[limit]=2&page%5Boffset%5D=2",
+            &pagination,
+            serde_json::json!([
+                {"article_id": 3, "title": "The Third Article"},
+                {"article_id": 4, "title": "The Fourth Article"},
+            ]),
+            Some(5),
+        );
+        assert!(resource_cursors(&document).is_empty());
+        assert_eq!(
+            serde_json::to_value(&document.meta).unwrap(),
+            serde_json::json!({"total": 5})
+        );
+        assert_eq!(
+            serde_json::to_value(&document.links).unwrap(),
+            serde_json::json!({
+                "next": "?withTotal=true&page[limit]=2&page[offset]=4",
+                "prev": "?withTotal=true&page[limit]=2",
+            })
+        );
+    }
+}
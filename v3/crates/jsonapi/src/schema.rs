@@ -1,4 +1,4 @@
-use crate::catalog::{Model, ObjectType, State};
+use crate::catalog::{Command, CommandKind, Model, ObjectType, State};
 use std::collections::BTreeMap;
 use std::sync::Arc;
 mod output;
@@ -65,15 +65,47 @@ fn get_route_for_model(
     let mut parameters = vec![
         oas3::spec::ObjectOrReference::Object(parameters::page_limit_parameter()),
         oas3::spec::ObjectOrReference::Object(parameters::page_offset_parameter()),
+    ];
+    if model.unique_identifier.is_some() {
+        parameters.extend(
+            parameters::page_cursor_parameters()
+                .into_iter()
+                .map(oas3::spec::ObjectOrReference::Object),
+        );
+    }
+    parameters.extend([
         oas3::spec::ObjectOrReference::Object(parameters::ordering_parameter(model, object_type)),
         oas3::spec::ObjectOrReference::Object(parameters::include_parameter(model, object_type)),
-    ];
+    ]);
     if let Some(filter_parameter) =
         parameters::filter_parameters(model, object_type, schemas, filter_boolean_expression_types)
     {
         parameters.push(oas3::spec::ObjectOrReference::Object(filter_parameter));
     }
 
+    if let Some(model_aggregates) = &model.aggregates {
+        parameters.extend(
+            parameters::aggregate_parameters(model, model_aggregates)
+                .into_iter()
+                .map(oas3::spec::ObjectOrReference::Object),
+        );
+        parameters.push(oas3::spec::ObjectOrReference::Object(
+            parameters::group_by_parameter(model, object_type),
+        ));
+        if model_aggregates.count {
+            parameters.push(oas3::spec::ObjectOrReference::Object(
+                parameters::with_total_parameter(model),
+            ));
+        }
+    }
+
+    parameters.extend(model.arguments.iter().map(|(argument_name, argument)| {
+        oas3::spec::ObjectOrReference::Object(parameters::argument_parameter(
+            format!("arguments[{argument_name}]"),
+            argument,
+        ))
+    }));
+
     let fields_parameters =
         parameters::fields_parameters(&model.data_type, object_type, object_types)
             .into_iter()
@@ -104,6 +136,91 @@ fn get_route_for_model(
     }
 }
 
+fn get_route_for_command(command: &Command) -> oas3::spec::PathItem {
+    let schema = oas3::spec::ObjectOrReference::Object(output::command_response_schema(command));
+    let media_type = oas3::spec::MediaType {
+        encoding: BTreeMap::new(),
+        examples: None,
+        schema: Some(schema),
+        extensions: BTreeMap::new(),
+    };
+    let mut content = BTreeMap::new();
+    content.insert(JSONAPI_MEDIA_TYPE.into(), media_type);
+
+    let mut responses = BTreeMap::new();
+    responses.insert(
+        "200".into(),
+        oas3::spec::ObjectOrReference::Object(oas3::spec::Response {
+            description: Some(format!("Successful {} response", command.name.name)),
+            extensions: BTreeMap::new(),
+            headers: BTreeMap::new(),
+            links: BTreeMap::new(),
+            content,
+        }),
+    );
+
+    let (parameters, request_body, summary) = match command.kind {
+        CommandKind::Function => (
+            command
+                .arguments
+                .iter()
+                .map(|(argument_name, argument)| {
+                    oas3::spec::ObjectOrReference::Object(parameters::argument_parameter(
+                        argument_name.to_string(),
+                        argument,
+                    ))
+                })
+                .collect(),
+            None,
+            format!("Call function {}", command.name.name),
+        ),
+        CommandKind::Procedure => (
+            vec![],
+            Some(oas3::spec::ObjectOrReference::Object(
+                parameters::command_request_body(command),
+            )),
+            format!("Call procedure {}", command.name.name),
+        ),
+    };
+
+    let operation = oas3::spec::Operation {
+        callbacks: BTreeMap::new(),
+        deprecated: None,
+        description: command.description.clone(),
+        extensions: BTreeMap::new(),
+        external_docs: None,
+        operation_id: None,
+        parameters,
+        request_body,
+        responses: Some(responses),
+        security: vec![],
+        servers: vec![],
+        summary: Some(summary),
+        tags: vec![],
+    };
+
+    let (get, post) = match command.kind {
+        CommandKind::Function => (Some(operation), None),
+        CommandKind::Procedure => (None, Some(operation)),
+    };
+    oas3::spec::PathItem {
+        delete: None,
+        description: None,
+        extensions: BTreeMap::new(),
+        get,
+        head: None,
+        options: None,
+        parameters: vec![],
+        patch: None,
+        post,
+        put: None,
+        reference: None,
+        servers: vec![],
+        summary: None,
+        trace: None,
+    }
+}
+
 // output when we make a request for an unknown role
 pub fn empty_schema() -> oas3::Spec {
     let info = oas3::spec::Info {
@@ -194,6 +311,13 @@ pub fn openapi_schema(state: &State) -> Result<oas3::Spec, SchemaError> {
         paths.insert(full_route_path, path_item);
     }
 
+    for (route_name, command) in &state.commands {
+        paths.insert(
+            format!("/v1/rest{route_name}"),
+            get_route_for_command(command),
+        );
+    }
+
     for (object_type_name, object_type) in &state.object_types {
         schemas.insert(
             pretty_typename(object_type_name),
@@ -1,5 +1,5 @@
-use super::shared::{any_of_schema, pretty_typename};
-use crate::catalog::{Model, ObjectType, RelationshipTarget, Type};
+use super::shared::{aggregate_fields_by_function, any_of_schema, pretty_typename};
+use crate::catalog::{Command, Model, ModelAggregates, ObjectType, RelationshipTarget, Type};
 use crate::schema::{
     array_schema, bool_schema, enum_schema, float_schema, int_schema, json_schema, object_schema,
     string_schema,
@@ -23,7 +23,7 @@ use std::collections::BTreeMap;
 // ]}%
 
 // an OpenDD type represented in OpenAPI
-fn type_schema(ty: &Type) -> ObjectOrReference<ObjectSchema> {
+pub fn type_schema(ty: &Type) -> ObjectOrReference<ObjectSchema> {
     match ty {
         Type::ScalarForDataConnector(set_of_types) => {
             // if there is only one, use it, otherwise, JSON
@@ -47,7 +47,7 @@ fn type_schema(ty: &Type) -> ObjectOrReference<ObjectSchema> {
         }
         Type::List(field_type) => ObjectOrReference::Object(array_schema(type_schema(field_type))),
         Type::Object(object_type_name) => ObjectOrReference::Ref {
-            ref_path: pretty_typename(object_type_name),
+            ref_path: format!("#/components/schemas/{}", pretty_typename(object_type_name)),
             summary: None,
             description: None,
         },
@@ -99,6 +99,7 @@ fn jsonapi_relationships_property(object_type: &ObjectType) -> Option<ObjectSche
             RelationshipTarget::Model {
                 object_type,
                 relationship_type,
+                unique_identifier: _,
             } => (object_type, relationship_type),
             RelationshipTarget::Command { type_reference } => {
                 match unwrap_custom_type_name(type_reference) {
@@ -206,6 +207,7 @@ fn jsonapi_included_schema(
             RelationshipTarget::Model {
                 object_type,
                 relationship_type: _,
+                unique_identifier: _,
             } => object_type,
             RelationshipTarget::Command { type_reference } => {
                 match unwrap_custom_type_name(type_reference) {
@@ -248,5 +250,89 @@ pub fn jsonapi_document_schema(
         );
     }
 
+    // links for paging through the collection, relative to the request URL
+    let mut links = BTreeMap::new();
+    links.insert(
+        "next".into(),
+        ObjectOrReference::Object(string_schema(None)),
+    );
+    links.insert(
+        "prev".into(),
+        ObjectOrReference::Object(string_schema(None)),
+    );
+    properties.insert(
+        "links".into(),
+        ObjectOrReference::Object(object_schema(links, vec![])),
+    );
+
+    // requests for aggregates return them in `meta` instead of resources in `data`
+    match &model.aggregates {
+        Some(model_aggregates) => {
+            properties.insert(
+                "meta".into(),
+                ObjectOrReference::Object(aggregates_meta_schema(model_aggregates, object_type)),
+            );
+            object_schema(properties, vec![])
+        }
+        None => object_schema(properties, vec!["data".into()]),
+    }
+}
+
+// `meta.aggregates` has the result of each function for each field, `meta.groups` has the
+// dimensions and aggregates of each group when using `groupBy`, and `meta.total` has the size of
+// the collection when using `withTotal=true`
+fn aggregates_meta_schema(
+    model_aggregates: &ModelAggregates,
+    object_type: &ObjectType,
+) -> ObjectSchema {
+    let mut functions = BTreeMap::new();
+    for (function, fields) in aggregate_fields_by_function(model_aggregates) {
+        let field_results = fields
+            .into_iter()
+            .map(|field| (field, ObjectOrReference::Object(ObjectSchema::default())))
+            .collect();
+        functions.insert(
+            function,
+            ObjectOrReference::Object(object_schema(field_results, vec![])),
+        );
+    }
+    let aggregates = object_schema(functions, vec![]);
+
+    let mut dimensions = BTreeMap::new();
+    for (field_name, field_type) in &object_type.type_fields {
+        if let Type::Scalar(_) | Type::ScalarForDataConnector(_) = field_type {
+            dimensions.insert(field_name.to_string(), type_schema(field_type));
+        }
+    }
+    let mut group = BTreeMap::new();
+    group.insert(
+        "dimensions".into(),
+        ObjectOrReference::Object(object_schema(dimensions, vec![])),
+    );
+    group.insert(
+        "aggregates".into(),
+        ObjectOrReference::Object(aggregates.clone()),
+    );
+
+    let mut properties = BTreeMap::new();
+    properties.insert("aggregates".into(), ObjectOrReference::Object(aggregates));
+    // `withTotal=true` counts the collection, so needs counting to be enabled
+    if model_aggregates.count {
+        properties.insert("total".into(), ObjectOrReference::Object(int_schema()));
+    }
+    properties.insert(
+        "groups".into(),
+        ObjectOrReference::Object(array_schema(ObjectOrReference::Object(object_schema(
+            group,
+            vec!["dimensions".into(), "aggregates".into()],
+        )))),
+    );
+    object_schema(properties, vec![])
+}
+
+// commands return their result in `data`
+pub fn command_response_schema(command: &Command) -> ObjectSchema {
+    let mut properties = BTreeMap::new();
+    properties.insert("data".into(), type_schema(&command.output_type));
     object_schema(properties, vec!["data".into()])
 }
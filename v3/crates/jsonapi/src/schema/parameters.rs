@@ -1,8 +1,12 @@
 use metadata_resolve::{Qualified, QualifiedBaseType, QualifiedTypeName, QualifiedTypeReference};
 use open_dds::types::CustomTypeName;
 
-use super::shared::{array_schema, enum_schema, int_schema, pretty_typename, string_schema};
-use crate::catalog::{Model, ObjectType, Type};
+use super::output::type_schema as output_type_schema;
+use super::shared::{
+    aggregate_fields_by_function, array_schema, bool_schema, enum_schema, int_schema,
+    object_schema, pretty_typename, string_schema,
+};
+use crate::catalog::{Argument, Command, Model, ModelAggregates, ObjectType, Type};
 use crate::schema::shared::json_schema;
 use std::collections::BTreeMap;
 use std::string::ToString;
@@ -48,6 +52,34 @@ pub fn page_limit_parameter() -> oas3::spec::Parameter {
     }
 }
 
+// Generate "page[after]" and "page[before]" parameters, for models that can be paged with cursors
+pub fn page_cursor_parameters() -> Vec<oas3::spec::Parameter> {
+    [
+        (crate::parse::cursor::AFTER_PARAMETER, "after"),
+        (crate::parse::cursor::BEFORE_PARAMETER, "before"),
+    ]
+    .into_iter()
+    .map(|(name, position)| oas3::spec::Parameter {
+        name: name.into(),
+        allow_empty_value: None,
+        allow_reserved: None,
+        content: None,
+        deprecated: None,
+        description: Some(format!(
+            "Optional cursor of the item to fetch items {position}, from meta.page.cursor"
+        )),
+        example: Some("WzEwXQ".into()),
+        explode: None,
+        examples: BTreeMap::new(),
+        extensions: BTreeMap::new(),
+        location: oas3::spec::ParameterIn::Query,
+        schema: Some(oas3::spec::ObjectOrReference::Object(string_schema(None))),
+        style: None,
+        required: None,
+    })
+    .collect()
+}
+
 /// To collect and store nested field types of an object type
 struct NestedFieldTypes<'a>(BTreeMap<&'a Qualified<CustomTypeName>, &'a ObjectType>);
 
@@ -242,6 +274,104 @@ pub fn include_parameter(model: &Model, object_type: &ObjectType) -> oas3::spec:
     }
 }
 
+// Generate an "aggregate[FUNCTION]" parameter for each aggregation function, listing the fields
+// it can be applied to
+pub fn aggregate_parameters(
+    model: &Model,
+    model_aggregates: &ModelAggregates,
+) -> Vec<oas3::spec::Parameter> {
+    aggregate_fields_by_function(model_aggregates)
+        .into_iter()
+        .map(|(function, fields)| {
+            let example = fields.first().cloned().unwrap_or_default();
+            let schema = oas3::spec::ObjectOrReference::Object(array_schema(
+                oas3::spec::ObjectOrReference::Object(enum_schema(fields)),
+            ));
+            oas3::spec::Parameter {
+                name: format!("aggregate[{function}]"),
+                allow_empty_value: None,
+                allow_reserved: None,
+                content: None,
+                deprecated: None,
+                description: Some(format!(
+                    "Optional list of fields from {} to aggregate with {function}. \
+                     Results are returned in meta.aggregates, or meta.groups when using groupBy.",
+                    model.name.name
+                )),
+                example: Some(example.into()),
+                explode: None,
+                examples: BTreeMap::new(),
+                extensions: BTreeMap::new(),
+                location: oas3::spec::ParameterIn::Query,
+                schema: Some(schema),
+                style: None,
+                required: None,
+            }
+        })
+        .collect()
+}
+
+// Generate "withTotal" parameter, for models that can be counted
+pub fn with_total_parameter(model: &Model) -> oas3::spec::Parameter {
+    oas3::spec::Parameter {
+        name: "withTotal".into(),
+        allow_empty_value: None,
+        allow_reserved: None,
+        content: None,
+        deprecated: None,
+        description: Some(format!(
+            "Optionally include the total number of {} matching the filter in meta.total",
+            model.name.name
+        )),
+        example: Some("true".into()),
+        explode: None,
+        examples: BTreeMap::new(),
+        extensions: BTreeMap::new(),
+        location: oas3::spec::ParameterIn::Query,
+        schema: Some(oas3::spec::ObjectOrReference::Object(bool_schema())),
+        style: None,
+        required: None,
+    }
+}
+
+// Generate "groupBy" parameter, listing the scalar fields of the model
+pub fn group_by_parameter(model: &Model, object_type: &ObjectType) -> oas3::spec::Parameter {
+    let fields = object_type
+        .type_fields
+        .iter()
+        .filter(|(_, field_type)| {
+            matches!(
+                field_type,
+                Type::Scalar(_) | Type::ScalarForDataConnector(_)
+            )
+        })
+        .map(|(field_name, _)| field_name.to_string())
+        .collect::<Vec<_>>();
+    let example = fields.first().cloned().unwrap_or_default();
+    let schema = oas3::spec::ObjectOrReference::Object(array_schema(
+        oas3::spec::ObjectOrReference::Object(enum_schema(fields)),
+    ));
+    oas3::spec::Parameter {
+        name: "groupBy".into(),
+        allow_empty_value: None,
+        allow_reserved: None,
+        content: None,
+        deprecated: None,
+        description: Some(format!(
+            "Optional list of fields from {} to group aggregates by",
+            model.name.name
+        )),
+        example: Some(example.into()),
+        explode: None,
+        examples: BTreeMap::new(),
+        extensions: BTreeMap::new(),
+        location: oas3::spec::ParameterIn::Query,
+        schema: Some(schema),
+        style: None,
+        required: None,
+    }
+}
+
 // Generate "filter" parameter for the given model with a given object type
 pub fn filter_parameters(
     model: &Model,
@@ -430,6 +560,70 @@ pub fn filter_parameters(
     }
 }
 
+// function and model arguments are passed in the query string. Scalars are written as they are,
+// anything else as JSON.
+pub fn argument_parameter(name: String, argument: &Argument) -> oas3::spec::Parameter {
+    let schema = output_type_schema(&argument.argument_type);
+    let (schema, content) = match &argument.argument_type {
+        Type::Scalar(_) | Type::ScalarForDataConnector(_) => (Some(schema), None),
+        Type::List(_) | Type::Object(_) => (None, Some(json_media_type(schema))),
+    };
+    oas3::spec::Parameter {
+        name,
+        allow_empty_value: None,
+        allow_reserved: None,
+        content,
+        deprecated: None,
+        description: argument.description.clone(),
+        example: None,
+        explode: None,
+        examples: BTreeMap::new(),
+        extensions: BTreeMap::new(),
+        location: oas3::spec::ParameterIn::Query,
+        schema,
+        style: None,
+        required: Some(argument.is_required),
+    }
+}
+
+// procedure arguments are passed as a JSON object in the request body
+pub fn command_request_body(command: &Command) -> oas3::spec::RequestBody {
+    let mut properties = BTreeMap::new();
+    let mut required = vec![];
+    for (argument_name, argument) in &command.arguments {
+        properties.insert(
+            argument_name.to_string(),
+            output_type_schema(&argument.argument_type),
+        );
+        if argument.is_required {
+            required.push(argument_name.to_string());
+        }
+    }
+    oas3::spec::RequestBody {
+        description: None,
+        content: json_media_type(oas3::spec::ObjectOrReference::Object(object_schema(
+            properties, required,
+        ))),
+        required: Some(true),
+    }
+}
+
+fn json_media_type(
+    schema: oas3::spec::ObjectOrReference<oas3::spec::ObjectSchema>,
+) -> BTreeMap<String, oas3::spec::MediaType> {
+    let mut content = BTreeMap::new();
+    content.insert(
+        "application/json".into(),
+        oas3::spec::MediaType {
+            encoding: BTreeMap::new(),
+            examples: None,
+            schema: Some(schema),
+            extensions: BTreeMap::new(),
+        },
+    );
+    content
+}
+
 // Generate schema for the given type
 fn type_schema(ty: &QualifiedTypeReference) -> oas3::spec::ObjectSchema {
     let mut schema = oas3::spec::ObjectSchema::default();
@@ -1,3 +1,5 @@
+use crate::catalog::ModelAggregates;
+use crate::parse::aggregate::{ALL_OBJECTS, COUNT_DISTINCT_FUNCTION, COUNT_FUNCTION};
 use metadata_resolve::Qualified;
 use oas3::spec::{ObjectOrReference, ObjectSchema, SchemaType, SchemaTypeSet};
 use open_dds::types::CustomTypeName;
@@ -7,6 +9,40 @@ pub fn pretty_typename(custom_type_name: &Qualified<CustomTypeName>) -> String {
     format!("{}_{}", custom_type_name.subgraph, custom_type_name.name)
 }
 
+// the fields each aggregation function can be applied to, including `*` for counting objects
+pub fn aggregate_fields_by_function(
+    model_aggregates: &ModelAggregates,
+) -> BTreeMap<String, Vec<String>> {
+    let mut functions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    if model_aggregates.count {
+        functions
+            .entry(COUNT_FUNCTION.to_string())
+            .or_default()
+            .push(ALL_OBJECTS.to_string());
+    }
+    for (field_name, aggregatable_field) in &model_aggregates.fields {
+        if aggregatable_field.count {
+            functions
+                .entry(COUNT_FUNCTION.to_string())
+                .or_default()
+                .push(field_name.to_string());
+        }
+        if aggregatable_field.count_distinct {
+            functions
+                .entry(COUNT_DISTINCT_FUNCTION.to_string())
+                .or_default()
+                .push(field_name.to_string());
+        }
+        for function in &aggregatable_field.functions {
+            functions
+                .entry(function.to_string())
+                .or_default()
+                .push(field_name.to_string());
+        }
+    }
+    functions
+}
+
 pub fn bool_schema() -> ObjectSchema {
     ObjectSchema {
         schema_type: Some(SchemaTypeSet::Single(SchemaType::Boolean)),
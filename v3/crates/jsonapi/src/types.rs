@@ -1,9 +1,13 @@
-use crate::parse;
+use crate::{command, mutation, parse};
 use hasura_authn_core::Role;
 use metadata_resolve::Qualified;
 use open_dds::{
-    identifier::SubgraphName, models::ModelName, relationships::RelationshipType,
-    types::CustomTypeName,
+    arguments::ArgumentName,
+    commands::CommandName,
+    identifier::SubgraphName,
+    models::ModelName,
+    relationships::RelationshipType,
+    types::{CustomTypeName, FieldName},
 };
 use std::collections::BTreeMap;
 use tracing_util::{ErrorVisibility, TraceableError};
@@ -23,6 +27,10 @@ pub enum RoleWarning {
         object_type_name: Qualified<CustomTypeName>,
         warning: ObjectTypeWarning,
     },
+    Command {
+        command_name: Qualified<CommandName>,
+        warning: CommandWarning,
+    },
 }
 
 // if we exclude something, let's say why
@@ -44,16 +52,48 @@ pub enum ModelWarning {
         object_type_name: Qualified<CustomTypeName>,
     },
     NoModelSource,
+    UnsupportedArgumentType {
+        argument_name: ArgumentName,
+        warning: ObjectTypeWarning,
+    },
+}
+
+// if we exclude something, let's say why
+#[derive(Debug, Clone)]
+pub enum CommandWarning {
+    NoExecutePermission,
+    NoCommandSource,
+    NoOutputTypePermission {
+        object_type_name: Qualified<CustomTypeName>,
+    },
+    UnsupportedType {
+        warning: ObjectTypeWarning,
+    },
 }
 
 #[derive(Debug, derive_more::with_trait::Display)]
 pub enum RequestError {
     NotFound,
+    ResourceNotFound,
     BadRequest(String),
     InternalError(InternalError),
     PlanError(plan::PlanError),
     ExecuteError(execute::FieldError),
     ParseError(parse::ParseError),
+    MutationError(mutation::MutationError),
+    CommandError(command::CommandError),
+}
+
+impl From<mutation::MutationError> for RequestError {
+    fn from(error: mutation::MutationError) -> Self {
+        RequestError::MutationError(error)
+    }
+}
+
+impl From<command::CommandError> for RequestError {
+    fn from(error: command::CommandError) -> Self {
+        RequestError::CommandError(error)
+    }
 }
 
 impl RequestError {
@@ -64,10 +104,17 @@ impl RequestError {
         let (status_code, message) = match self {
             RequestError::BadRequest(err) => (axum::http::StatusCode::BAD_REQUEST, err),
             RequestError::ParseError(err) => (axum::http::StatusCode::BAD_REQUEST, err.to_string()),
+            RequestError::MutationError(err) => (err.status_code(), err.to_string()),
+            RequestError::CommandError(err) => (err.status_code(), err.to_string()),
             RequestError::NotFound => (
                 axum::http::StatusCode::NOT_FOUND,
                 "invalid route or path".to_string(),
             ),
+            // spec: <https://jsonapi.org/format/#fetching-resources-responses-404>
+            RequestError::ResourceNotFound => (
+                axum::http::StatusCode::NOT_FOUND,
+                "resource not found".to_string(),
+            ),
             RequestError::PlanError(plan::PlanError::Permission(_err)) => (
                 axum::http::StatusCode::FORBIDDEN,
                 "Access forbidden".to_string(), // need to decide how much
@@ -154,8 +201,19 @@ impl axum::response::IntoResponse for JsonApiHttpError {
     }
 }
 
+/// Response to a JSON:API write request. Deleting a resource returns no document.
+pub struct MutationResponse {
+    pub status: axum::http::StatusCode,
+    pub document: Option<jsonapi_library::api::DocumentData>,
+}
+
+/// Response to a command request, with the command's result in `data`
+#[derive(Debug, serde::Serialize)]
+pub struct CommandResponse {
+    pub data: serde_json::Value,
+}
+
 /// Model related info derived from URI path
-#[allow(dead_code)]
 pub struct ModelInfo {
     pub subgraph: SubgraphName,
     pub name: ModelName,
@@ -166,6 +224,52 @@ pub struct ModelInfo {
     pub relationship: Vec<String>,
 }
 
+/// What a request fetches, derived from the URI path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestTarget {
+    /// `/<subgraph>/<model>`
+    Collection,
+    /// `/<subgraph>/<model>/<id>`
+    Resource { id: String },
+    /// `/<subgraph>/<model>/<id>/<relationship>`
+    RelatedResources { id: String, relationship: String },
+    /// `/<subgraph>/<model>/<id>/relationships/<relationship>`
+    Relationship { id: String, relationship: String },
+}
+
+impl RequestTarget {
+    /// Returns `None` if the path does not address a resource or relationship
+    pub fn from_model_info(model_info: &ModelInfo) -> Option<Self> {
+        match (
+            &model_info.unique_identifier,
+            model_info.relationship.as_slice(),
+        ) {
+            (None, []) => Some(Self::Collection),
+            (Some(id), []) => Some(Self::Resource { id: id.clone() }),
+            (Some(id), [relationship]) => Some(Self::RelatedResources {
+                id: id.clone(),
+                relationship: relationship.clone(),
+            }),
+            (Some(id), [keyword, relationship]) if keyword == "relationships" => {
+                Some(Self::Relationship {
+                    id: id.clone(),
+                    relationship: relationship.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The page of a collection that was requested, used to build the `next` and `prev` links
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pagination {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    /// set when the collection is paged with cursors rather than offsets
+    pub cursor: Option<parse::cursor::CursorPagination>,
+}
+
 /// A tree of relationships, used in processing of relationships in the JSON:API response creation
 #[derive(Default)]
 pub struct RelationshipTree {
@@ -175,6 +279,9 @@ pub struct RelationshipTree {
 pub struct RelationshipNode {
     pub object_type: Qualified<CustomTypeName>,
     pub relationship_type: RelationshipType,
+    /// fields that make up the `id` of the related resources, if the target is a model with
+    /// a unique identifier
+    pub unique_identifier: Option<Vec<FieldName>>,
     pub is_command_relationship: bool,
     pub nested: RelationshipTree,
 }
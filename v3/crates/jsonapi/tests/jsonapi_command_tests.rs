@@ -0,0 +1,278 @@
+//! Tests that call commands over REST against a mock data connector, checking the requests that
+//! are sent and the responses that come back
+
+use axum::http::{Method, StatusCode, Uri};
+use engine_types::HttpContext;
+use hasura_authn_core::{Identity, JsonSessionVariableValue, Role};
+use jsonapi_library::api::PrimaryData;
+use mockito::Matcher;
+use open_dds::session_variables::SESSION_VARIABLE_ROLE;
+use reqwest::header::HeaderMap;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// the URL of the data connector in the test metadata, replaced by the mock server's
+const CONNECTOR_URL: &str = "http://localhost:8080";
+
+struct TestEnvironment {
+    server: mockito::ServerGuard,
+    jsonapi_catalog: jsonapi::Catalog,
+    metadata: Arc<metadata_resolve::Metadata>,
+}
+
+async fn test_environment_setup() -> TestEnvironment {
+    let server = mockito::Server::new_async().await;
+
+    let metadata_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("static")
+        .join("commands")
+        .join("metadata.json");
+    let metadata_string = std::fs::read_to_string(&metadata_path)
+        .unwrap_or_else(|error| panic!("{}: Could not read file: {error}", metadata_path.display()))
+        .replace(CONNECTOR_URL, &server.url());
+
+    let input_metadata = open_dds::Metadata::from_json_str(&metadata_string).unwrap();
+    let configuration = metadata_resolve::configuration::Configuration {
+        unstable_features: metadata_resolve::configuration::UnstableFeatures {
+            enable_aggregation_predicates: false,
+        },
+    };
+    let (resolved_metadata, _) = metadata_resolve::resolve(input_metadata, &configuration)
+        .unwrap_or_else(|error| panic!("Could not resolve metadata: {error}"));
+    let (jsonapi_catalog, _warnings) = jsonapi::Catalog::new(&resolved_metadata);
+
+    TestEnvironment {
+        server,
+        jsonapi_catalog,
+        metadata: Arc::new(resolved_metadata),
+    }
+}
+
+fn create_session(role: &str) -> hasura_authn_core::Session {
+    Identity::admin(Role::new("admin"))
+        .get_role_authorization(Some(&Role::new(role)))
+        .unwrap()
+        .build_session(
+            [(
+                SESSION_VARIABLE_ROLE,
+                JsonSessionVariableValue(json!(role)).into(),
+            )]
+            .into_iter()
+            .collect(),
+        )
+}
+
+fn http_context() -> HttpContext {
+    HttpContext {
+        client: reqwest::Client::new(),
+        ndc_response_size_limit: None,
+        remote_join_batch_size: None,
+        remote_join_concurrency: None,
+    }
+}
+
+impl TestEnvironment {
+    async fn call(
+        &self,
+        role: &str,
+        method: Method,
+        path: &str,
+        query_parameters: &[(&str, &str)],
+        body: &[u8],
+    ) -> Result<serde_json::Value, jsonapi::JsonApiHttpError> {
+        let uri = Uri::from_str(path).unwrap();
+        assert!(jsonapi::is_command_request(&uri));
+        jsonapi::command_handler_internal(
+            Arc::new(HeaderMap::default()),
+            Arc::new(http_context()),
+            Arc::new(self.metadata.plugin_configs.clone()),
+            Arc::new(create_session(role)),
+            &self.jsonapi_catalog,
+            self.metadata.clone(),
+            method,
+            uri,
+            query_parameters
+                .iter()
+                .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+                .collect::<BTreeMap<_, _>>(),
+            body,
+        )
+        .await
+        .map(|response| response.data)
+        .map_err(|error| error.into_http_error(engine_types::ExposeInternalErrors::Expose))
+    }
+}
+
+#[tokio::test]
+async fn test_function_takes_arguments_from_query_string() {
+    let mut environment = test_environment_setup().await;
+    let query = environment
+        .server
+        .mock("POST", "/query")
+        .match_body(Matcher::PartialJson(json!({
+            "collection": "article_title",
+            "arguments": {"id": {"type": "literal", "value": 1}},
+        })))
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!([{"rows": [{"__value": "The Next 700 Programming Languages"}]}]).to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let result = environment
+        .call(
+            "admin",
+            Method::GET,
+            "/default/-/commands/article_title",
+            &[("id", "1")],
+            &[],
+        )
+        .await
+        .unwrap();
+
+    query.assert_async().await;
+    assert_eq!(result, json!("The Next 700 Programming Languages"));
+}
+
+#[tokio::test]
+async fn test_procedure_takes_arguments_from_body() {
+    let mut environment = test_environment_setup().await;
+    let mutation = environment
+        .server
+        .mock("POST", "/mutation")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex(r#""name":"set_article_title""#.to_string()),
+            Matcher::Regex(r#""arguments":\{"id":1,"title":"Renamed"\}"#.to_string()),
+        ]))
+        .with_header("content-type", "application/json")
+        .with_body(json!({"operation_results": [{"type": "procedure", "result": 1}]}).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let result = environment
+        .call(
+            "admin",
+            Method::POST,
+            "/default/-/commands/set_article_title",
+            &[],
+            &serde_json::to_vec(&json!({"id": 1, "title": "Renamed"})).unwrap(),
+        )
+        .await
+        .unwrap();
+
+    mutation.assert_async().await;
+    assert_eq!(result, json!(1));
+}
+
+#[tokio::test]
+async fn test_command_with_wrong_method_is_not_executed() {
+    let mut environment = test_environment_setup().await;
+    let query = environment
+        .server
+        .mock("POST", "/query")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let error = environment
+        .call(
+            "admin",
+            Method::POST,
+            "/default/-/commands/article_title",
+            &[],
+            &serde_json::to_vec(&json!({"id": 1})).unwrap(),
+        )
+        .await
+        .unwrap_err();
+
+    query.assert_async().await;
+    assert_eq!(error.status, StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[tokio::test]
+async fn test_missing_argument_is_rejected() {
+    let environment = test_environment_setup().await;
+    let error = environment
+        .call(
+            "admin",
+            Method::GET,
+            "/default/-/commands/article_title",
+            &[],
+            &[],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_command_without_permission_is_not_found() {
+    let mut environment = test_environment_setup().await;
+    let mutation = environment
+        .server
+        .mock("POST", "/mutation")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let error = environment
+        .call(
+            "reader",
+            Method::POST,
+            "/default/-/commands/set_article_title",
+            &[],
+            &serde_json::to_vec(&json!({"id": 1, "title": "Renamed"})).unwrap(),
+        )
+        .await
+        .unwrap_err();
+
+    mutation.assert_async().await;
+    assert_eq!(error.status, StatusCode::NOT_FOUND);
+}
+
+// a model called `commands` is served at `/<subgraph>/commands`, where command routes used to be
+#[tokio::test]
+async fn test_model_named_commands_is_not_a_command() {
+    let mut environment = test_environment_setup().await;
+    let query = environment
+        .server
+        .mock("POST", "/query")
+        .match_body(Matcher::PartialJson(json!({"collection": "articles"})))
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!([{"rows": [{"article_id": 1, "author_id": 7, "title": "Stored"}]}]).to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let uri = Uri::from_str("/default/commands/1").unwrap();
+    assert!(!jsonapi::is_command_request(&uri));
+    let document = jsonapi::handler_internal(
+        Arc::new(HeaderMap::default()),
+        Arc::new(http_context()),
+        Arc::new(environment.metadata.plugin_configs.clone()),
+        Arc::new(create_session("admin")),
+        &environment.jsonapi_catalog,
+        environment.metadata.clone(),
+        Method::GET,
+        uri,
+        jsonapi_library::query::Query::from_params(""),
+    )
+    .await
+    .unwrap_or_else(|error| panic!("{error:?}"));
+
+    query.assert_async().await;
+    let Some(PrimaryData::Single(resource)) = document.data else {
+        panic!("expected a single resource, got {:?}", document.data);
+    };
+    assert_eq!(resource._type, "default_Article");
+    assert_eq!(resource.id, "1");
+}
@@ -41,6 +41,8 @@ fn test_get_succeeding_requests() {
                 let http_context = HttpContext {
                     client: reqwest::Client::new(),
                     ndc_response_size_limit: None,
+                    remote_join_batch_size: None,
+                    remote_join_concurrency: None,
                 };
 
                 let session = create_session(path);
@@ -113,6 +115,8 @@ fn test_get_failing_requests() {
                 let http_context = HttpContext {
                     client: reqwest::Client::new(),
                     ndc_response_size_limit: None,
+                    remote_join_batch_size: None,
+                    remote_join_concurrency: None,
                 };
 
                 let session = create_session(path);
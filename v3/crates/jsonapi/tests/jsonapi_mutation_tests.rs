@@ -0,0 +1,533 @@
+//! Tests that run JSON:API writes against a mock data connector, checking the relational
+//! requests that are sent and the responses that come back
+
+use axum::http::{Method, StatusCode, Uri};
+use engine_types::HttpContext;
+use hasura_authn_core::{Identity, JsonSessionVariableValue, Role};
+use jsonapi_library::api::{DocumentData, PrimaryData};
+use open_dds::session_variables::{SESSION_VARIABLE_ROLE, SessionVariableName};
+use reqwest::header::HeaderMap;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// the URL of the data connector in the test metadata, replaced by the mock server's
+const CONNECTOR_URL: &str = "http://localhost:8080";
+
+struct TestEnvironment {
+    server: mockito::ServerGuard,
+    jsonapi_catalog: jsonapi::Catalog,
+    metadata: Arc<metadata_resolve::Metadata>,
+}
+
+async fn test_environment_setup() -> TestEnvironment {
+    let server = mockito::Server::new_async().await;
+
+    let metadata_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("static")
+        .join("mutations")
+        .join("metadata.json");
+    let metadata_string = std::fs::read_to_string(&metadata_path)
+        .unwrap_or_else(|error| panic!("{}: Could not read file: {error}", metadata_path.display()))
+        .replace(CONNECTOR_URL, &server.url());
+
+    let input_metadata = open_dds::Metadata::from_json_str(&metadata_string).unwrap();
+    let configuration = metadata_resolve::configuration::Configuration {
+        unstable_features: metadata_resolve::configuration::UnstableFeatures {
+            enable_aggregation_predicates: false,
+        },
+    };
+    let (resolved_metadata, _) = metadata_resolve::resolve(input_metadata, &configuration)
+        .unwrap_or_else(|error| panic!("Could not resolve metadata: {error}"));
+    let (jsonapi_catalog, _warnings) = jsonapi::Catalog::new(&resolved_metadata);
+
+    TestEnvironment {
+        server,
+        jsonapi_catalog,
+        metadata: Arc::new(resolved_metadata),
+    }
+}
+
+fn create_session(role: &str, session_variables: &serde_json::Value) -> hasura_authn_core::Session {
+    let mut session_variables: HashMap<SessionVariableName, JsonSessionVariableValue> =
+        serde_json::from_value(session_variables.clone()).unwrap();
+    session_variables.insert(SESSION_VARIABLE_ROLE, JsonSessionVariableValue(json!(role)));
+    Identity::admin(Role::new("admin"))
+        .get_role_authorization(Some(&Role::new(role)))
+        .unwrap()
+        .build_session(
+            session_variables
+                .into_iter()
+                .map(|(name, value)| (name, value.into()))
+                .collect(),
+        )
+}
+
+impl TestEnvironment {
+    async fn request(
+        &self,
+        session: hasura_authn_core::Session,
+        method: Method,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<jsonapi::MutationResponse, jsonapi::JsonApiHttpError> {
+        let http_context = HttpContext {
+            client: reqwest::Client::new(),
+            ndc_response_size_limit: None,
+            remote_join_batch_size: None,
+            remote_join_concurrency: None,
+        };
+        jsonapi::mutation_handler_internal(
+            Arc::new(HeaderMap::default()),
+            Arc::new(http_context),
+            Arc::new(session),
+            &self.jsonapi_catalog,
+            self.metadata.clone(),
+            method,
+            Uri::from_str(path).unwrap(),
+            &serde_json::to_vec(body).unwrap(),
+        )
+        .await
+        .map_err(|error| error.into_http_error(engine_types::ExposeInternalErrors::Expose))
+    }
+}
+
+fn article_document(id: Option<&str>, attributes: &serde_json::Value) -> serde_json::Value {
+    let mut resource = json!({"type": "default_Article", "attributes": attributes});
+    if let Some(id) = id {
+        resource["id"] = json!(id);
+    }
+    json!({ "data": resource })
+}
+
+fn single_resource(document: Option<DocumentData>) -> jsonapi_library::api::Resource {
+    match document.and_then(|document| document.data) {
+        Some(PrimaryData::Single(resource)) => *resource,
+        data => panic!("expected a single resource, got {data:?}"),
+    }
+}
+
+fn affected_rows(affected_rows: u64) -> String {
+    json!({ "affected_rows": affected_rows }).to_string()
+}
+
+fn int_literal(value: i64) -> ndc_models::RelationalExpression {
+    ndc_models::RelationalExpression::Literal {
+        literal: ndc_models::RelationalLiteral::Int64 { value },
+    }
+}
+
+// columns are numbered in field name order: article_id, author_id, title
+fn column(index: u64) -> Box<ndc_models::RelationalExpression> {
+    Box::new(ndc_models::RelationalExpression::Column { index })
+}
+
+fn articles_with(predicate: ndc_models::RelationalExpression) -> ndc_models::Relation {
+    ndc_models::Relation::Filter {
+        input: Arc::new(ndc_models::Relation::From {
+            collection: "articles".into(),
+            columns: vec!["id".into(), "author_id".into(), "title".into()],
+            arguments: std::collections::BTreeMap::new(),
+        }),
+        predicate,
+    }
+}
+
+fn article_id_is(id: i64) -> ndc_models::RelationalExpression {
+    ndc_models::RelationalExpression::Eq {
+        left: column(0),
+        right: Box::new(int_literal(id)),
+    }
+}
+
+#[tokio::test]
+async fn test_create_returns_stored_resource() {
+    let mut environment = test_environment_setup().await;
+    let insert = environment
+        .server
+        .mock("POST", "/mutation/rel/insert")
+        .match_body(mockito::Matcher::PartialJson(
+            json!({"collection": "articles"}),
+        ))
+        .with_header("content-type", "application/json")
+        .with_body(affected_rows(1))
+        .create_async()
+        .await;
+    // the data connector fills in the title as it stores it
+    let read_back = environment
+        .server
+        .mock("POST", "/query")
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!([{"rows": [{"article_id": 1, "author_id": 7, "title": "Stored"}]}]).to_string(),
+        )
+        .create_async()
+        .await;
+
+    let response = environment
+        .request(
+            create_session("admin", &json!({})),
+            Method::POST,
+            "/default/Articles",
+            &article_document(Some("1"), &json!({"title": "Written", "author_id": 7})),
+        )
+        .await
+        .unwrap();
+
+    insert.assert_async().await;
+    read_back.assert_async().await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    let resource = single_resource(response.document);
+    assert_eq!(resource.id, "1");
+    assert_eq!(resource.attributes.get("title"), Some(&json!("Stored")));
+}
+
+#[tokio::test]
+async fn test_create_takes_id_from_attributes() {
+    let mut environment = test_environment_setup().await;
+    let insert = environment
+        .server
+        .mock("POST", "/mutation/rel/insert")
+        .with_header("content-type", "application/json")
+        .with_body(affected_rows(1))
+        .create_async()
+        .await;
+    // the role can't read the resource back, so the written attributes are returned
+    let read_back = environment
+        .server
+        .mock("POST", "/query")
+        .with_header("content-type", "application/json")
+        .with_body(json!([{"rows": []}]).to_string())
+        .create_async()
+        .await;
+
+    let response = environment
+        .request(
+            create_session("user", &json!({"x-hasura-user-id": "7"})),
+            Method::POST,
+            "/default/Articles",
+            &article_document(
+                None,
+                &json!({"article_id": 2, "title": "Written", "author_id": 8}),
+            ),
+        )
+        .await
+        .unwrap();
+
+    insert.assert_async().await;
+    read_back.assert_async().await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    let resource = single_resource(response.document);
+    assert_eq!(resource.id, "2");
+    assert_eq!(resource.attributes.get("title"), Some(&json!("Written")));
+}
+
+#[tokio::test]
+async fn test_create_without_id_is_rejected() {
+    let mut environment = test_environment_setup().await;
+    let insert = environment
+        .server
+        .mock("POST", "/mutation/rel/insert")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let error = environment
+        .request(
+            create_session("admin", &json!({})),
+            Method::POST,
+            "/default/Articles",
+            &article_document(None, &json!({"title": "Written", "author_id": 7})),
+        )
+        .await
+        .unwrap_err();
+
+    insert.assert_async().await;
+    assert_eq!(error.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_create_without_permission_is_forbidden() {
+    let mut environment = test_environment_setup().await;
+    let insert = environment
+        .server
+        .mock("POST", "/mutation/rel/insert")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let error = environment
+        .request(
+            create_session("reader", &json!({})),
+            Method::POST,
+            "/default/Articles",
+            &article_document(Some("1"), &json!({"title": "Written", "author_id": 7})),
+        )
+        .await
+        .unwrap_err();
+
+    insert.assert_async().await;
+    assert_eq!(error.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_update_applies_select_permission() {
+    let mut environment = test_environment_setup().await;
+    let expected_request = ndc_models::RelationalUpdateRequest {
+        collection: "articles".into(),
+        relation: ndc_models::Relation::Project {
+            input: Arc::new(articles_with(ndc_models::RelationalExpression::And {
+                left: Box::new(article_id_is(1)),
+                right: Box::new(ndc_models::RelationalExpression::Eq {
+                    left: column(1),
+                    right: Box::new(int_literal(7)),
+                }),
+            })),
+            exprs: vec![
+                *column(0),
+                *column(1),
+                ndc_models::RelationalExpression::Literal {
+                    literal: ndc_models::RelationalLiteral::String {
+                        value: "Updated".to_string(),
+                    },
+                },
+            ],
+        },
+    };
+    let update = environment
+        .server
+        .mock("POST", "/mutation/rel/update")
+        .match_body(mockito::Matcher::Json(
+            serde_json::to_value(&expected_request).unwrap(),
+        ))
+        .with_header("content-type", "application/json")
+        .with_body(affected_rows(1))
+        .create_async()
+        .await;
+
+    let response = environment
+        .request(
+            create_session("user", &json!({"x-hasura-user-id": "7"})),
+            Method::PATCH,
+            "/default/Articles/1",
+            &article_document(Some("1"), &json!({"title": "Updated"})),
+        )
+        .await
+        .unwrap();
+
+    update.assert_async().await;
+    assert_eq!(response.status, StatusCode::OK);
+    let resource = single_resource(response.document);
+    assert_eq!(resource.attributes.get("title"), Some(&json!("Updated")));
+}
+
+#[tokio::test]
+async fn test_update_of_filtered_row_is_not_found() {
+    let mut environment = test_environment_setup().await;
+    // the row exists, but the role's select permission excludes it
+    let update = environment
+        .server
+        .mock("POST", "/mutation/rel/update")
+        .with_header("content-type", "application/json")
+        .with_body(affected_rows(0))
+        .create_async()
+        .await;
+
+    let error = environment
+        .request(
+            create_session("user", &json!({"x-hasura-user-id": "8"})),
+            Method::PATCH,
+            "/default/Articles/1",
+            &article_document(Some("1"), &json!({"title": "Updated"})),
+        )
+        .await
+        .unwrap_err();
+
+    update.assert_async().await;
+    assert_eq!(error.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_update_without_permission_is_forbidden() {
+    let mut environment = test_environment_setup().await;
+    let update = environment
+        .server
+        .mock("POST", "/mutation/rel/update")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let error = environment
+        .request(
+            create_session("reader", &json!({})),
+            Method::PATCH,
+            "/default/Articles/1",
+            &article_document(Some("1"), &json!({"title": "Updated"})),
+        )
+        .await
+        .unwrap_err();
+
+    update.assert_async().await;
+    assert_eq!(error.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_delete_without_select_filter() {
+    let mut environment = test_environment_setup().await;
+    let expected_request = ndc_models::RelationalDeleteRequest {
+        collection: "articles".into(),
+        relation: articles_with(article_id_is(1)),
+    };
+    let delete = environment
+        .server
+        .mock("POST", "/mutation/rel/delete")
+        .match_body(mockito::Matcher::Json(
+            serde_json::to_value(&expected_request).unwrap(),
+        ))
+        .with_header("content-type", "application/json")
+        .with_body(affected_rows(1))
+        .create_async()
+        .await;
+
+    let response = environment
+        .request(
+            create_session("admin", &json!({})),
+            Method::DELETE,
+            "/default/Articles/1",
+            &json!(null),
+        )
+        .await
+        .unwrap();
+
+    delete.assert_async().await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    assert!(response.document.is_none());
+}
+
+#[tokio::test]
+async fn test_delete_of_filtered_row_is_not_found() {
+    let mut environment = test_environment_setup().await;
+    let expected_request = ndc_models::RelationalDeleteRequest {
+        collection: "articles".into(),
+        relation: articles_with(ndc_models::RelationalExpression::And {
+            left: Box::new(article_id_is(1)),
+            right: Box::new(ndc_models::RelationalExpression::Eq {
+                left: column(1),
+                right: Box::new(int_literal(8)),
+            }),
+        }),
+    };
+    let delete = environment
+        .server
+        .mock("POST", "/mutation/rel/delete")
+        .match_body(mockito::Matcher::Json(
+            serde_json::to_value(&expected_request).unwrap(),
+        ))
+        .with_header("content-type", "application/json")
+        .with_body(affected_rows(0))
+        .create_async()
+        .await;
+
+    let error = environment
+        .request(
+            create_session("user", &json!({"x-hasura-user-id": "8"})),
+            Method::DELETE,
+            "/default/Articles/1",
+            &json!(null),
+        )
+        .await
+        .unwrap_err();
+
+    delete.assert_async().await;
+    assert_eq!(error.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_delete_without_permission_is_forbidden() {
+    let mut environment = test_environment_setup().await;
+    let delete = environment
+        .server
+        .mock("POST", "/mutation/rel/delete")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let error = environment
+        .request(
+            create_session("reader", &json!({})),
+            Method::DELETE,
+            "/default/Articles/1",
+            &json!(null),
+        )
+        .await
+        .unwrap_err();
+
+    delete.assert_async().await;
+    assert_eq!(error.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_malformed_bodies_are_rejected() {
+    let environment = test_environment_setup().await;
+    let admin = || create_session("admin", &json!({}));
+
+    let cases = [
+        // not a resource document
+        (
+            Method::POST,
+            "/default/Articles",
+            json!({"title": "Written"}),
+            StatusCode::BAD_REQUEST,
+        ),
+        // the wrong resource type
+        (
+            Method::POST,
+            "/default/Articles",
+            json!({"data": {"type": "default_Author", "id": "1", "attributes": {}}}),
+            StatusCode::CONFLICT,
+        ),
+        // an attribute the model doesn't have
+        (
+            Method::PATCH,
+            "/default/Articles/1",
+            article_document(Some("1"), &json!({"body": "Updated"})),
+            StatusCode::BAD_REQUEST,
+        ),
+        // a nested value
+        (
+            Method::PATCH,
+            "/default/Articles/1",
+            article_document(Some("1"), &json!({"title": {"text": "Updated"}})),
+            StatusCode::BAD_REQUEST,
+        ),
+        // an id that doesn't match the URL
+        (
+            Method::PATCH,
+            "/default/Articles/1",
+            article_document(Some("2"), &json!({"title": "Updated"})),
+            StatusCode::CONFLICT,
+        ),
+        // nothing to update
+        (
+            Method::PATCH,
+            "/default/Articles/1",
+            article_document(Some("1"), &json!({})),
+            StatusCode::BAD_REQUEST,
+        ),
+    ];
+
+    for (method, path, body, expected_status) in cases {
+        let error = environment
+            .request(admin(), method.clone(), path, &body)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.status, expected_status,
+            "{method} {path} with {body}: {}",
+            error.error
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Tests that page through collections against a mock data connector, checking the requests that
+//! are sent and the links, totals and cursors that come back. Cursors are base64url encoded JSON
+//! arrays of the values a collection is ordered by: `WzJd` is `[2]`.
+
+use axum::http::{Method, StatusCode, Uri};
+use engine_types::HttpContext;
+use hasura_authn_core::{Identity, JsonSessionVariableValue, Role};
+use mockito::Matcher;
+use open_dds::session_variables::SESSION_VARIABLE_ROLE;
+use reqwest::header::HeaderMap;
+use serde_json::json;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// the URL of the data connector in the test metadata, replaced by the mock server's
+const CONNECTOR_URL: &str = "http://localhost:8080";
+
+struct TestEnvironment {
+    server: mockito::ServerGuard,
+    jsonapi_catalog: jsonapi::Catalog,
+    metadata: Arc<metadata_resolve::Metadata>,
+}
+
+async fn test_environment_setup() -> TestEnvironment {
+    let server = mockito::Server::new_async().await;
+
+    let metadata_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("static")
+        .join("pagination")
+        .join("metadata.json");
+    let metadata_string = std::fs::read_to_string(&metadata_path)
+        .unwrap_or_else(|error| panic!("{}: Could not read file: {error}", metadata_path.display()))
+        .replace(CONNECTOR_URL, &server.url());
+
+    let input_metadata = open_dds::Metadata::from_json_str(&metadata_string).unwrap();
+    let configuration = metadata_resolve::configuration::Configuration {
+        unstable_features: metadata_resolve::configuration::UnstableFeatures {
+            enable_aggregation_predicates: false,
+        },
+    };
+    let (resolved_metadata, _) = metadata_resolve::resolve(input_metadata, &configuration)
+        .unwrap_or_else(|error| panic!("Could not resolve metadata: {error}"));
+    let (jsonapi_catalog, _warnings) = jsonapi::Catalog::new(&resolved_metadata);
+
+    TestEnvironment {
+        server,
+        jsonapi_catalog,
+        metadata: Arc::new(resolved_metadata),
+    }
+}
+
+fn create_session(role: &str) -> hasura_authn_core::Session {
+    Identity::admin(Role::new("admin"))
+        .get_role_authorization(Some(&Role::new(role)))
+        .unwrap()
+        .build_session(
+            [(
+                SESSION_VARIABLE_ROLE,
+                JsonSessionVariableValue(json!(role)).into(),
+            )]
+            .into_iter()
+            .collect(),
+        )
+}
+
+fn http_context() -> HttpContext {
+    HttpContext {
+        client: reqwest::Client::new(),
+        ndc_response_size_limit: None,
+        remote_join_batch_size: None,
+        remote_join_concurrency: None,
+    }
+}
+
+impl TestEnvironment {
+    // fetch `path`, and return the document as JSON
+    async fn get(&self, path: &str) -> Result<serde_json::Value, jsonapi::JsonApiHttpError> {
+        let uri = Uri::from_str(path).unwrap();
+        let query_string = jsonapi_library::query::Query::from_params(uri.query().unwrap_or(""));
+        jsonapi::handler_internal(
+            Arc::new(HeaderMap::default()),
+            Arc::new(http_context()),
+            Arc::new(self.metadata.plugin_configs.clone()),
+            Arc::new(create_session("admin")),
+            &self.jsonapi_catalog,
+            self.metadata.clone(),
+            Method::GET,
+            uri,
+            query_string,
+        )
+        .await
+        .map(|document| serde_json::to_value(document).unwrap())
+        .map_err(|error| error.into_http_error(engine_types::ExposeInternalErrors::Expose))
+    }
+
+    // the connector returns these articles for a query whose body matches
+    async fn articles_query(&mut self, matcher: Matcher, rows: serde_json::Value) -> mockito::Mock {
+        self.server
+            .mock("POST", "/query")
+            .match_body(matcher)
+            .with_header("content-type", "application/json")
+            .with_body(json!([{ "rows": rows }]).to_string())
+            .expect(1)
+            .create_async()
+            .await
+    }
+}
+
+fn article(article_id: i64) -> serde_json::Value {
+    json!({"article_id": article_id, "title": format!("Article {article_id}"), "author_id": 7})
+}
+
+fn resource_ids(document: &serde_json::Value) -> Vec<&str> {
+    document["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|resource| resource["id"].as_str().unwrap())
+        .collect()
+}
+
+fn resource_cursors(document: &serde_json::Value) -> Vec<&str> {
+    document["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|resource| resource["meta"]["page"]["cursor"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_first_page_links_to_the_next_by_cursor() {
+    let mut environment = test_environment_setup().await;
+    // a paged collection is ordered by its unique identifier, so that cursors name one resource
+    let query = environment
+        .articles_query(
+            Matcher::AllOf(vec![
+                Matcher::PartialJson(json!({"collection": "articles", "query": {"limit": 2}})),
+                Matcher::Regex(r#""order_direction":"asc""#.to_string()),
+            ]),
+            json!([article(1), article(2)]),
+        )
+        .await;
+
+    let document = environment
+        .get("/default/Articles?page[limit]=2&page[after]=")
+        .await
+        .unwrap();
+
+    query.assert_async().await;
+    assert_eq!(resource_ids(&document), vec!["1", "2"]);
+    assert_eq!(resource_cursors(&document), vec!["WzFd", "WzJd"]);
+    assert_eq!(
+        document["links"],
+        json!({"next": "?page[limit]=2&page[after]=WzJd"})
+    );
+}
+
+#[tokio::test]
+async fn test_next_page_starts_after_cursor() {
+    let mut environment = test_environment_setup().await;
+    let query = environment
+        .articles_query(
+            Matcher::Regex(r#""operator":"_gt","value":\{"type":"scalar","value":2\}"#.to_string()),
+            json!([article(3)]),
+        )
+        .await;
+
+    let document = environment
+        .get("/default/Articles?page[limit]=2&page[after]=WzJd")
+        .await
+        .unwrap();
+
+    query.assert_async().await;
+    assert_eq!(resource_ids(&document), vec!["3"]);
+    // a short page is the last one
+    assert_eq!(
+        document["links"],
+        json!({"prev": "?page[limit]=2&page[before]=WzNd"})
+    );
+}
+
+#[tokio::test]
+async fn test_previous_page_is_fetched_backwards_from_cursor() {
+    let mut environment = test_environment_setup().await;
+    let query = environment
+        .articles_query(
+            Matcher::AllOf(vec![
+                Matcher::Regex(
+                    r#""operator":"_lt","value":\{"type":"scalar","value":3\}"#.to_string(),
+                ),
+                Matcher::Regex(r#""order_direction":"desc""#.to_string()),
+            ]),
+            json!([article(2), article(1)]),
+        )
+        .await;
+
+    let document = environment
+        .get("/default/Articles?page[limit]=2&page[before]=WzNd")
+        .await
+        .unwrap();
+
+    query.assert_async().await;
+    assert_eq!(resource_ids(&document), vec!["1", "2"]);
+    assert_eq!(
+        document["links"],
+        json!({
+            "next": "?page[limit]=2&page[after]=WzJd",
+            "prev": "?page[limit]=2&page[before]=WzFd",
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_total_counts_the_whole_collection() {
+    let mut environment = test_environment_setup().await;
+    let query = environment
+        .articles_query(
+            Matcher::PartialJson(json!({"query": {"limit": 2}})),
+            json!([article(1), article(2)]),
+        )
+        .await;
+    let count = environment
+        .server
+        .mock("POST", "/query")
+        .match_body(Matcher::Regex(r#""star_count""#.to_string()))
+        .with_header("content-type", "application/json")
+        .with_body(json!([{"aggregates": {"total": 3}}]).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let document = environment
+        .get("/default/Articles?withTotal=true&page[limit]=2&page[after]=")
+        .await
+        .unwrap();
+
+    query.assert_async().await;
+    count.assert_async().await;
+    assert_eq!(document["meta"], json!({"total": 3}));
+    assert_eq!(
+        document["links"],
+        json!({"next": "?withTotal=true&page[limit]=2&page[after]=WzJd"})
+    );
+}
+
+#[tokio::test]
+async fn test_model_arguments_are_sent_and_kept_in_links() {
+    let mut environment = test_environment_setup().await;
+    let query = environment
+        .articles_query(
+            Matcher::PartialJson(json!({
+                "collection": "articles_by_author",
+                "arguments": {"author_id": {"type": "literal", "value": 7}},
+            })),
+            json!([article(1)]),
+        )
+        .await;
+
+    let document = environment
+        .get("/default/ArticlesByAuthor?arguments[author_id]=7&page[limit]=1&page[after]=")
+        .await
+        .unwrap();
+
+    query.assert_async().await;
+    assert_eq!(resource_ids(&document), vec!["1"]);
+    assert_eq!(
+        document["links"],
+        json!({"next": "?arguments[author_id]=7&page[limit]=1&page[after]=WzFd"})
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_cursors_are_rejected() {
+    let mut environment = test_environment_setup().await;
+    let query = environment
+        .server
+        .mock("POST", "/query")
+        .expect(0)
+        .create_async()
+        .await;
+
+    for path in [
+        "/default/Articles?page[limit]=2&page[after]=nonsense",
+        "/default/Articles?page[limit]=2&page[after]=WzJd&page[before]=WzJd",
+        "/default/Articles?page[limit]=2&page[after]=WzJd&page[offset]=2",
+    ] {
+        let error = environment.get(path).await.unwrap_err();
+        assert_eq!(error.status, StatusCode::BAD_REQUEST, "{path}");
+    }
+    query.assert_async().await;
+}
+
+// without a cursor a collection is paged by offset, and its resources have no cursors
+#[tokio::test]
+async fn test_offset_page_has_no_cursors() {
+    let mut environment = test_environment_setup().await;
+    let query = environment
+        .articles_query(
+            Matcher::PartialJson(json!({"collection": "articles", "query": {"limit": 2}})),
+            json!([article(1), article(2)]),
+        )
+        .await;
+
+    let document = environment
+        .get("/default/Articles?page[limit]=2")
+        .await
+        .unwrap();
+
+    query.assert_async().await;
+    assert_eq!(resource_ids(&document), vec!["1", "2"]);
+    assert!(
+        document["data"][0]
+            .get("meta")
+            .is_none_or(serde_json::Value::is_null)
+    );
+    assert_eq!(
+        document["links"],
+        json!({"next": "?page[limit]=2&page[offset]=2"})
+    );
+}
+
+// without a limit or a cursor the whole collection is returned, in the requested order only
+#[tokio::test]
+async fn test_unpaged_collection_has_no_cursors() {
+    let mut environment = test_environment_setup().await;
+    let query = environment
+        .articles_query(
+            Matcher::PartialJson(json!({"collection": "articles"})),
+            json!([article(1), article(2)]),
+        )
+        .await;
+
+    let document = environment.get("/default/Articles").await.unwrap();
+
+    query.assert_async().await;
+    assert_eq!(resource_ids(&document), vec!["1", "2"]);
+    assert!(
+        document["data"][0]
+            .get("meta")
+            .is_none_or(serde_json::Value::is_null)
+    );
+    assert!(document.get("links").is_none_or(serde_json::Value::is_null));
+}
@@ -1,4 +1,5 @@
-use crate::{Error, WithContext};
+use crate::types::error::ContextualError;
+use crate::{Error, Warning, WithContext};
 
 // for each error (single or multiple)
 // try and turn it into a fancy ariadne Report
@@ -27,6 +28,37 @@ pub fn to_fancy_errors<'a>(
     }
 }
 
+// for each warning, try and turn it into a fancy ariadne Report
+// or fallback to a plain one
+pub fn to_fancy_warnings<'a>(
+    raw_metadata: &'a str,
+    warnings: &[Warning],
+    config: ariadne::Config,
+) -> Vec<ariadne::Report<'a>> {
+    warnings
+        .iter()
+        .map(|warning| {
+            warning
+                .create_error_context()
+                .and_then(|context| {
+                    to_fancy_report(
+                        raw_metadata,
+                        ariadne::ReportKind::Warning,
+                        warning.to_string(),
+                        &context,
+                        config,
+                    )
+                })
+                .unwrap_or_else(|| {
+                    ariadne::Report::build(ariadne::ReportKind::Warning, 0..0)
+                        .with_message(warning.to_string())
+                        .with_config(config)
+                        .finish()
+                })
+        })
+        .collect()
+}
+
 // given a metadata resolve error, try and turn it into a fancy ariadne Report
 // this may fail if the error has no context, or we can't parse the JSON
 fn to_fancy_error<'a>(
@@ -35,36 +67,57 @@ fn to_fancy_error<'a>(
     config: ariadne::Config,
 ) -> Option<ariadne::Report<'a>> {
     if let WithContext::Contextualised { error, context } = error {
-        json_annotation_parse::parse(raw_metadata)
-            .ok()
-            .and_then(|fancy_json| {
-                let mut labels = vec![];
-
-                let mut first_location = 0..0;
-                for item in &context.0 {
-                    let location =
-                        json_annotation_parse::walk(&fancy_json, &mut item.path.clone()).ok()?;
-                    first_location = location.start.offset..location.end.offset;
-
-                    labels.push(
-                        ariadne::Label::new(location.start.offset..location.end.offset)
-                            .with_message(item.message.clone())
-                            .with_color(ariadne::Color::Red),
-                    );
-                }
-                Some(
-                    ariadne::Report::build(ariadne::ReportKind::Error, first_location)
-                        .with_message(error.to_string())
-                        .with_config(config)
-                        .with_labels(labels)
-                        .finish(),
-                )
-            })
+        to_fancy_report(
+            raw_metadata,
+            ariadne::ReportKind::Error,
+            error.to_string(),
+            context,
+            config,
+        )
     } else {
         None
     }
 }
 
+// label each step of the context at its location in the metadata
+fn to_fancy_report<'a>(
+    raw_metadata: &'a str,
+    kind: ariadne::ReportKind<'a>,
+    message: String,
+    context: &error_context::Context,
+    config: ariadne::Config,
+) -> Option<ariadne::Report<'a>> {
+    json_annotation_parse::parse(raw_metadata)
+        .ok()
+        .and_then(|fancy_json| {
+            let mut labels = vec![];
+            let color = match kind {
+                ariadne::ReportKind::Warning => ariadne::Color::Yellow,
+                _ => ariadne::Color::Red,
+            };
+
+            let mut first_location = 0..0;
+            for item in &context.0 {
+                let location =
+                    json_annotation_parse::walk(&fancy_json, &mut item.path.clone()).ok()?;
+                first_location = location.start.offset..location.end.offset;
+
+                labels.push(
+                    ariadne::Label::new(location.start.offset..location.end.offset)
+                        .with_message(item.message.clone())
+                        .with_color(color),
+                );
+            }
+            Some(
+                ariadne::Report::build(kind, first_location)
+                    .with_message(message)
+                    .with_config(config)
+                    .with_labels(labels)
+                    .finish(),
+            )
+        })
+}
+
 fn to_fallback_error(error: &Error, config: ariadne::Config) -> ariadne::Report<'static> {
     ariadne::Report::build(ariadne::ReportKind::Error, 0..0)
         .with_message(error.to_string())
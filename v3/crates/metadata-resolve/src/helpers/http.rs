@@ -28,7 +28,7 @@ impl Display for HeaderError {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SerializableUrl(pub url::Url);
 
 impl SerializableUrl {
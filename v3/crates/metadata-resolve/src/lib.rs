@@ -14,11 +14,11 @@ mod types;
 
 pub use helpers::http;
 pub use helpers::ndc_validation::NDCValidationError;
-pub use helpers::to_fancy_errors;
 pub use helpers::types::{
     NdcColumnForComparison, TypeRepresentation, get_type_representation, mk_name,
     object_type_exists, unwrap_custom_type_name,
 };
+pub use helpers::{to_fancy_errors, to_fancy_warnings};
 pub use open_dds::sql_schema_aliases::{SqlCatalogName, SqlSchemaName};
 pub use stages::aggregates::{
     AggregatableFieldInfo, AggregateExpression, AggregateExpressionGraphqlConfig, AggregateOperand,
@@ -35,21 +35,26 @@ pub use stages::boolean_expressions::{
     ScalarComparisonKind, get_comparable_relationship_execution_strategy,
 };
 pub use stages::data_connectors::{
-    ArgumentPresetValue, DataConnectorLink, DataConnectorRelationalQueryCapabilities,
-    HttpHeadersPreset, NdcVersion,
+    ArgumentPresetValue, CircuitBreakerPolicy, DataConnectorLink,
+    DataConnectorRelationalQueryCapabilities, EmbeddedSchema, HttpHeadersPreset, NdcVersion,
+    RequestPolicy, ResolvedDataConnectorUrl, ResolvedReadReplicaUrls, RetryPolicy, SchemaDrift,
+    SchemaDriftIssue, embedded_schemas, schema_drift_error,
+};
+pub use stages::graphql_config::{
+    AllowList, GlobalGraphqlConfig, MultipleOrderByInputObjectFields, OperationLimits, QueryLimits,
 };
-pub use stages::graphql_config::{GlobalGraphqlConfig, MultipleOrderByInputObjectFields};
 pub use stages::model_permissions::{
     FilterPermission, ModelAuthorizationRule, ModelPredicate, ModelTargetSource,
     ModelWithPermissions, PredicateRelationshipInfo, RelationalDeletePermission,
-    RelationalInsertPermission, RelationalOperation, RelationalUpdatePermission, SelectPermission,
-    UnaryComparisonOperator,
+    RelationalInsertPermission, RelationalOperation, RelationalUpdatePermission, ResolvedOperator,
+    RowLimits, SelectPermission, UnaryComparisonOperator,
 };
 pub use stages::models::Model;
 pub use stages::models::{ModelSource, ModelsError};
 pub use stages::models_graphql::{
     ModelGraphqlError, ModelOrderByExpression, SelectAggregateGraphQlDefinition,
-    SelectManyGraphQlDefinition, SelectUniqueGraphQlDefinition, SubscriptionGraphQlDefinition,
+    SelectGroupByGraphQlDefinition, SelectManyGraphQlDefinition, SelectUniqueGraphQlDefinition,
+    StreamSubscriptionGraphQlDefinition, SubscriptionGraphQlDefinition,
 };
 pub use stages::object_relationships::{
     AggregateRelationship, CommandRelationshipTarget, FieldNestedness, ModelRelationshipTarget,
@@ -95,7 +100,7 @@ pub use stages::{
 };
 pub use types::condition::{BinaryOperation, Condition, ConditionHash, Conditions, UnaryOperation};
 pub use types::configuration;
-pub use types::error::{Error, WithContext};
+pub use types::error::{ContextualError, Error, WithContext};
 pub use types::flags::{self, RuntimeFlags};
 pub use types::permission::{ValueExpression, ValueExpressionOrPredicate};
 pub use types::subgraph::{
@@ -43,6 +43,17 @@ pub enum DataConnectorError {
         version: String,
         requirement: semver::VersionReq,
     },
+    #[error(
+        "the initial retry backoff of {initial_backoff_ms}ms is more than the maximum retry backoff of {max_backoff_ms}ms"
+    )]
+    RetryBackoffExceedsMaximum {
+        initial_backoff_ms: u64,
+        max_backoff_ms: u64,
+    },
+    #[error("the circuit breaker failure threshold must be at least 1")]
+    ZeroCircuitBreakerFailureThreshold,
+    #[error("at least one read replica URL must be given")]
+    NoReadReplicaUrls,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -1,14 +1,18 @@
 use crate::types::subgraph::Qualified;
 mod error;
+mod schema_drift;
 mod types;
 
 pub use error::{
     DataConnectorError, DataConnectorIssue, NamedDataConnectorError, NamedDataConnectorIssue,
 };
 use open_dds::{data_connector::DataConnectorName, identifier::SubgraphName};
+pub use schema_drift::{
+    EmbeddedSchema, SchemaDrift, SchemaDriftIssue, embedded_schemas, schema_drift_error,
+};
 use std::collections::BTreeMap;
 pub use types::{
-    ArgumentPreset, ArgumentPresetValue, CommandsResponseConfig,
+    ArgumentPreset, ArgumentPresetValue, CircuitBreakerPolicy, CommandsResponseConfig,
     DataConnectorAggregateCapabilities, DataConnectorCapabilities, DataConnectorContext,
     DataConnectorLink, DataConnectorNestedRelationshipCapabilities,
     DataConnectorRelationalAggregateCapabilities,
@@ -22,7 +26,8 @@ pub use types::{
     DataConnectorRelationalScalarTypeCapabilities, DataConnectorRelationalSortCapabilities,
     DataConnectorRelationalWindowCapabilities, DataConnectorRelationalWindowExpressionCapabilities,
     DataConnectorRelationshipCapabilities, DataConnectorSchema, DataConnectors,
-    DataConnectorsOutput, HttpHeadersPreset, NdcVersion,
+    DataConnectorsOutput, HttpHeadersPreset, NdcVersion, RequestPolicy, ResolvedDataConnectorUrl,
+    ResolvedReadReplicaUrls, RetryPolicy,
 };
 
 /// Resolve data connectors.
@@ -0,0 +1,538 @@
+//! Detecting when the schema and capabilities embedded in a `DataConnectorLink` no longer match
+//! the ones the running data connector serves.
+//!
+//! The engine only ever looks at the embedded schema, so a data connector that stops serving a
+//! collection, changes the type of a column or loses a capability breaks requests that the
+//! metadata says are fine.
+
+use error_context::{Context, Step};
+use open_dds::data_connector::{DataConnectorName, VersionedSchemaAndCapabilities};
+
+use super::types::{
+    DataConnectorCapabilities, DataConnectorContext, DataConnectorLink, DataConnectorSchema,
+    NdcVersion, resolve_schema_and_capabilities,
+};
+use crate::types::error::{ContextualError, SeparatedBy};
+use crate::types::subgraph::Qualified;
+use crate::{Error, Warning, WithContext};
+
+/// The schema and capabilities a data connector was built with, to compare with the ones it
+/// serves.
+#[derive(Debug, Clone)]
+pub struct EmbeddedSchema {
+    pub data_connector: DataConnectorLink,
+    /// Where the `DataConnectorLink` is defined in the metadata
+    pub path: jsonpath::JSONPath,
+    schema: DataConnectorSchema,
+}
+
+/// The embedded schemas of the data connectors in the metadata. Data connectors that fail to
+/// resolve are left out, as the metadata as a whole does not resolve either.
+pub fn embedded_schemas(metadata: open_dds::Metadata) -> Vec<EmbeddedSchema> {
+    let metadata_accessor = open_dds::accessor::MetadataAccessor::new(metadata);
+    metadata_accessor
+        .data_connectors
+        .iter()
+        .filter_map(|data_connector| {
+            let (context, _issues) =
+                DataConnectorContext::new(&metadata_accessor, &data_connector.object).ok()?;
+            let name = Qualified::new(
+                data_connector.subgraph.clone(),
+                data_connector.object.name.clone(),
+            );
+            let link = DataConnectorLink::new(name, &context).ok()?;
+            Some(EmbeddedSchema {
+                data_connector: link,
+                path: data_connector.path.clone(),
+                schema: context.schema,
+            })
+        })
+        .collect()
+}
+
+impl EmbeddedSchema {
+    /// Compare the embedded schema with the responses of the data connector to `/capabilities`
+    /// and `/schema`. Anything the data connector no longer serves is reported; anything it has
+    /// added is not, as the metadata cannot be using it.
+    pub fn check_drift(
+        &self,
+        live_capabilities: serde_json::Value,
+        live_schema: serde_json::Value,
+    ) -> Result<Vec<SchemaDrift>, serde_json::Error> {
+        let embedded_ndc_version = self.data_connector.capabilities.supported_ndc_version;
+        let live = serde_json::json!({
+            "capabilities": live_capabilities,
+            "schema": live_schema,
+        });
+        let live = match embedded_ndc_version {
+            NdcVersion::V01 => VersionedSchemaAndCapabilities::V01(serde_json::from_value(live)?),
+            NdcVersion::V02 => VersionedSchemaAndCapabilities::V02(serde_json::from_value(live)?),
+        };
+
+        let issues = match resolve_schema_and_capabilities(&live) {
+            Ok((live_schema, live_capabilities, _issues)) => {
+                let mut issues = schema_drift(&self.schema, &live_schema);
+                issues.extend(capabilities_drift(
+                    &self.data_connector.capabilities,
+                    &live_capabilities,
+                ));
+                issues
+            }
+            Err(error) => vec![SchemaDriftIssue::IncompatibleNdcVersion {
+                expected: embedded_ndc_version,
+                error: error.to_string(),
+            }],
+        };
+
+        Ok(issues
+            .into_iter()
+            .map(|issue| SchemaDrift {
+                data_connector_name: self.data_connector.name.clone(),
+                path: self.path.clone(),
+                issue,
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("The data connector {data_connector_name} no longer matches its schema: {issue}")]
+pub struct SchemaDrift {
+    pub data_connector_name: Qualified<DataConnectorName>,
+    pub path: jsonpath::JSONPath,
+    pub issue: SchemaDriftIssue,
+}
+
+impl ContextualError for SchemaDrift {
+    fn create_error_context(&self) -> Option<Context> {
+        Some(Context::from_step(Step {
+            message: "The schema of this data connector is out of date".to_owned(),
+            path: self.path.clone(),
+            subgraph: Some(self.data_connector_name.subgraph.clone()),
+        }))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaDriftIssue {
+    #[error("the data connector does not support NDC {expected}: {error}")]
+    IncompatibleNdcVersion { expected: NdcVersion, error: String },
+    #[error("the scalar type {scalar_type} is missing")]
+    MissingScalarType {
+        scalar_type: ndc_models::ScalarTypeName,
+    },
+    #[error("the object type {object_type} is missing")]
+    MissingObjectType {
+        object_type: ndc_models::ObjectTypeName,
+    },
+    #[error("the field {field} of object type {object_type} is missing")]
+    MissingField {
+        object_type: ndc_models::ObjectTypeName,
+        field: ndc_models::FieldName,
+    },
+    #[error(
+        "the field {field} of object type {object_type} has changed type from {expected} to {actual}"
+    )]
+    ChangedFieldType {
+        object_type: ndc_models::ObjectTypeName,
+        field: ndc_models::FieldName,
+        expected: String,
+        actual: String,
+    },
+    #[error("the collection {collection} is missing")]
+    MissingCollection {
+        collection: ndc_models::CollectionName,
+    },
+    #[error("the collection {collection} has changed type from {expected} to {actual}")]
+    ChangedCollectionType {
+        collection: ndc_models::CollectionName,
+        expected: ndc_models::ObjectTypeName,
+        actual: ndc_models::ObjectTypeName,
+    },
+    #[error("the function {function} is missing")]
+    MissingFunction {
+        function: open_dds::commands::FunctionName,
+    },
+    #[error("the procedure {procedure} is missing")]
+    MissingProcedure {
+        procedure: open_dds::commands::ProcedureName,
+    },
+    #[error("the {capability} capability has been lost")]
+    LostCapability { capability: &'static str },
+}
+
+/// Report schema drift as an error, as warnings that should be errors are reported when
+/// resolving metadata.
+pub fn schema_drift_error(drift: Vec<SchemaDrift>) -> WithContext<Error> {
+    WithContext::Raw(Error::MultipleErrors {
+        errors: SeparatedBy {
+            lines_of: drift
+                .into_iter()
+                .map(|drift| {
+                    Error::CompatibilityError {
+                        warning_as_error: Warning::SchemaDrift(drift),
+                    }
+                    .add_context_if_exists()
+                })
+                .collect(),
+        },
+    })
+}
+
+fn schema_drift(
+    embedded: &DataConnectorSchema,
+    live: &DataConnectorSchema,
+) -> Vec<SchemaDriftIssue> {
+    let mut issues = Vec::new();
+
+    for scalar_type in embedded.scalar_types.keys() {
+        if !live.scalar_types.contains_key(scalar_type) {
+            issues.push(SchemaDriftIssue::MissingScalarType {
+                scalar_type: scalar_type.clone(),
+            });
+        }
+    }
+
+    for (object_type, embedded_object_type) in &embedded.object_types {
+        let Some(live_object_type) = live.object_types.get(object_type) else {
+            issues.push(SchemaDriftIssue::MissingObjectType {
+                object_type: object_type.clone(),
+            });
+            continue;
+        };
+        for (field, embedded_field) in &embedded_object_type.fields {
+            match live_object_type.fields.get(field) {
+                None => issues.push(SchemaDriftIssue::MissingField {
+                    object_type: object_type.clone(),
+                    field: field.clone(),
+                }),
+                Some(live_field) if live_field.r#type != embedded_field.r#type => {
+                    issues.push(SchemaDriftIssue::ChangedFieldType {
+                        object_type: object_type.clone(),
+                        field: field.clone(),
+                        expected: type_to_string(&embedded_field.r#type),
+                        actual: type_to_string(&live_field.r#type),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    for (collection, embedded_collection) in &embedded.collections {
+        match live.collections.get(collection) {
+            None => issues.push(SchemaDriftIssue::MissingCollection {
+                collection: collection.clone(),
+            }),
+            Some(live_collection)
+                if live_collection.collection_type != embedded_collection.collection_type =>
+            {
+                issues.push(SchemaDriftIssue::ChangedCollectionType {
+                    collection: collection.clone(),
+                    expected: embedded_collection.collection_type.clone(),
+                    actual: live_collection.collection_type.clone(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    for function in embedded.functions.keys() {
+        if !live.functions.contains_key(function) {
+            issues.push(SchemaDriftIssue::MissingFunction {
+                function: function.clone(),
+            });
+        }
+    }
+
+    for procedure in embedded.procedures.keys() {
+        if !live.procedures.contains_key(procedure) {
+            issues.push(SchemaDriftIssue::MissingProcedure {
+                procedure: procedure.clone(),
+            });
+        }
+    }
+
+    issues
+}
+
+fn capabilities_drift(
+    embedded: &DataConnectorCapabilities,
+    live: &DataConnectorCapabilities,
+) -> Vec<SchemaDriftIssue> {
+    let relation_comparisons = |capabilities: &DataConnectorCapabilities| {
+        capabilities
+            .supports_relationships
+            .as_ref()
+            .is_some_and(|relationships| relationships.supports_relation_comparisons)
+    };
+    let capabilities = [
+        (
+            "query.explain",
+            embedded.supports_explaining_queries,
+            live.supports_explaining_queries,
+        ),
+        (
+            "mutation.explain",
+            embedded.supports_explaining_mutations,
+            live.supports_explaining_mutations,
+        ),
+        (
+            "mutation.transactional",
+            embedded.supports_transactional_mutations,
+            live.supports_transactional_mutations,
+        ),
+        (
+            "query.variables",
+            embedded.supports_query_variables,
+            live.supports_query_variables,
+        ),
+        (
+            "query.aggregates",
+            embedded.supports_aggregates.is_some(),
+            live.supports_aggregates.is_some(),
+        ),
+        (
+            "query.nested_fields.filter_by",
+            embedded.supports_nested_object_filtering,
+            live.supports_nested_object_filtering,
+        ),
+        (
+            "query.nested_fields.order_by",
+            embedded.supports_nested_object_ordering,
+            live.supports_nested_object_ordering,
+        ),
+        (
+            "query.exists.nested_collections",
+            embedded.supports_nested_object_array_filtering,
+            live.supports_nested_object_array_filtering,
+        ),
+        (
+            "query.exists.nested_scalar_collections",
+            embedded.supports_nested_scalar_array_filtering,
+            live.supports_nested_scalar_array_filtering,
+        ),
+        (
+            "relationships",
+            embedded.supports_relationships.is_some(),
+            live.supports_relationships.is_some(),
+        ),
+        (
+            "relationships.relation_comparisons",
+            relation_comparisons(embedded),
+            relation_comparisons(live),
+        ),
+        (
+            "relational_query",
+            embedded.supports_relational_queries.is_some(),
+            live.supports_relational_queries.is_some(),
+        ),
+        (
+            "relational_mutation",
+            embedded.supports_relational_mutations.is_some(),
+            live.supports_relational_mutations.is_some(),
+        ),
+    ];
+    capabilities
+        .into_iter()
+        .filter(|(_, embedded, live)| *embedded && !*live)
+        .map(|(capability, _, _)| SchemaDriftIssue::LostCapability { capability })
+        .collect()
+}
+
+fn type_to_string(ndc_type: &ndc_models::Type) -> String {
+    match ndc_type {
+        ndc_models::Type::Named { name } => name.to_string(),
+        ndc_models::Type::Nullable { underlying_type } => {
+            format!("nullable {}", type_to_string(underlying_type))
+        }
+        ndc_models::Type::Array { element_type } => {
+            format!("array of {}", type_to_string(element_type))
+        }
+        ndc_models::Type::Predicate { object_type_name } => {
+            format!("predicate on {object_type_name}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema(column_type: &str) -> DataConnectorSchema {
+        DataConnectorSchema::new(
+            serde_json::from_value(json!({
+                "scalar_types": {
+                    "Int": { "aggregate_functions": {}, "comparison_operators": {} },
+                    "String": { "aggregate_functions": {}, "comparison_operators": {} }
+                },
+                "object_types": {
+                    "author": {
+                        "fields": {
+                            "id": { "type": { "type": "named", "name": "Int" } },
+                            "name": { "type": { "type": "named", "name": column_type } }
+                        },
+                        "foreign_keys": {}
+                    }
+                },
+                "collections": [
+                    {
+                        "name": "authors",
+                        "arguments": {},
+                        "type": "author",
+                        "uniqueness_constraints": {}
+                    }
+                ],
+                "functions": [],
+                "procedures": []
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_schema_drift() {
+        assert!(schema_drift(&schema("String"), &schema("String")).is_empty());
+
+        let issues = schema_drift(&schema("String"), &schema("Int"))
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            vec!["the field name of object type author has changed type from String to Int"]
+        );
+
+        let mut live = schema("String");
+        live.collections.clear();
+        live.object_types.clear();
+        let issues = schema_drift(&schema("String"), &live)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            vec![
+                "the object type author is missing",
+                "the collection authors is missing"
+            ]
+        );
+
+        // anything the data connector has added is not drift
+        assert!(schema_drift(&live, &schema("String")).is_empty());
+    }
+
+    fn schema_and_capabilities_v01(
+        version: &str,
+        capabilities: serde_json::Value,
+    ) -> serde_json::Value {
+        json!({
+            "schema": {
+                "scalar_types": {
+                    "Int": { "aggregate_functions": {}, "comparison_operators": {} }
+                },
+                "object_types": {},
+                "collections": [],
+                "functions": [],
+                "procedures": []
+            },
+            "capabilities": { "version": version, "capabilities": capabilities }
+        })
+    }
+
+    fn capabilities(capabilities: serde_json::Value) -> DataConnectorCapabilities {
+        let schema_and_capabilities = VersionedSchemaAndCapabilities::V01(
+            serde_json::from_value(schema_and_capabilities_v01("0.1.2", capabilities)).unwrap(),
+        );
+        resolve_schema_and_capabilities(&schema_and_capabilities)
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn test_capabilities_drift() {
+        let embedded = capabilities(json!({
+            "query": { "aggregates": {}, "variables": {}, "explain": {} },
+            "mutation": { "transactional": {}, "explain": {} },
+            "relationships": { "relation_comparisons": {} }
+        }));
+        let live = capabilities(json!({
+            "query": { "variables": {} },
+            "mutation": {},
+            "relationships": {}
+        }));
+        assert!(capabilities_drift(&embedded, &embedded).is_empty());
+
+        let issues = capabilities_drift(&embedded, &live)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            vec![
+                "the query.explain capability has been lost",
+                "the mutation.explain capability has been lost",
+                "the mutation.transactional capability has been lost",
+                "the query.aggregates capability has been lost",
+                "the relationships.relation_comparisons capability has been lost",
+            ]
+        );
+
+        // capabilities the data connector has gained are not drift
+        assert!(capabilities_drift(&live, &embedded).is_empty());
+    }
+
+    #[test]
+    fn test_check_drift_ndc_version() {
+        let capabilities = json!({ "query": {}, "mutation": {} });
+        let metadata = json!({
+            "version": "v2",
+            "subgraphs": [{
+                "name": "default",
+                "objects": [{
+                    "kind": "DataConnectorLink",
+                    "version": "v1",
+                    "definition": {
+                        "name": "db",
+                        "url": { "singleUrl": { "value": "http://localhost:8080" } },
+                        "headers": {},
+                        "schema": {
+                            "version": "v0.1",
+                            "schema": schema_and_capabilities_v01("0.1.2", capabilities.clone())["schema"],
+                            "capabilities": { "version": "0.1.2", "capabilities": capabilities }
+                        }
+                    }
+                }]
+            }]
+        });
+        let embedded_schemas =
+            embedded_schemas(open_dds::Metadata::from_json_str(&metadata.to_string()).unwrap());
+        assert_eq!(embedded_schemas.len(), 1);
+        let embedded_schema = &embedded_schemas[0];
+
+        let live = schema_and_capabilities_v01("0.1.2", capabilities.clone());
+        let drift = embedded_schema
+            .check_drift(live["capabilities"].clone(), live["schema"].clone())
+            .unwrap();
+        assert!(drift.is_empty());
+
+        // a data connector that has moved on to NDC v0.2 can't serve an NDC v0.1 schema
+        let live = schema_and_capabilities_v01("0.2.0", capabilities);
+        let drift = embedded_schema
+            .check_drift(live["capabilities"].clone(), live["schema"].clone())
+            .unwrap();
+        assert_eq!(drift.len(), 1);
+        assert!(matches!(
+            drift[0].issue,
+            SchemaDriftIssue::IncompatibleNdcVersion {
+                expected: NdcVersion::V01,
+                ..
+            }
+        ));
+        assert_eq!(
+            drift[0].data_connector_name.to_string(),
+            "db (in subgraph default)"
+        );
+    }
+}
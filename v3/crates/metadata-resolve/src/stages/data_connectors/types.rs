@@ -17,12 +17,14 @@ use open_dds::types::DataConnectorArgumentName;
 use open_dds::{
     commands::{FunctionName, ProcedureName},
     data_connector::{
-        self, DataConnectorName, DataConnectorUrl, ReadWriteUrls, VersionedSchemaAndCapabilities,
+        self, DataConnectorName, DataConnectorUrl, ReadReplicaUrls, ReadWriteUrls,
+        ReplicaSelection, VersionedSchemaAndCapabilities,
     },
 };
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Duration;
 use strum_macros::EnumIter;
 
 pub struct DataConnectorsOutput<'a> {
@@ -58,6 +60,7 @@ pub struct DataConnectorContext<'a> {
     pub capabilities: DataConnectorCapabilities,
     pub argument_presets: Vec<ArgumentPreset>,
     pub response_headers: Option<CommandsResponseConfig>,
+    pub request_policy: Option<RequestPolicy>,
 }
 
 impl<'a> DataConnectorContext<'a> {
@@ -65,33 +68,8 @@ impl<'a> DataConnectorContext<'a> {
         metadata_accessor: &MetadataAccessor,
         data_connector: &'a data_connector::DataConnectorLinkV1,
     ) -> Result<(Self, Vec<DataConnectorIssue>), DataConnectorError> {
-        let (resolved_schema, capabilities, issues) = match &data_connector.schema {
-            VersionedSchemaAndCapabilities::V01(schema_and_capabilities) => {
-                let issues = validate_ndc_version(
-                    NdcVersion::V01,
-                    &schema_and_capabilities.capabilities.version,
-                )?;
-                let schema =
-                    DataConnectorSchema::new(ndc_migration::v02::migrate_schema_response_from_v01(
-                        schema_and_capabilities.schema.clone(),
-                    ));
-                let capabilities =
-                    mk_ndc_01_capabilities(&schema_and_capabilities.capabilities.capabilities);
-                (schema, capabilities, issues)
-            }
-            VersionedSchemaAndCapabilities::V02(schema_and_capabilities) => {
-                let issues = validate_ndc_version(
-                    NdcVersion::V02,
-                    &schema_and_capabilities.capabilities.version,
-                )?;
-                let schema = DataConnectorSchema::new(schema_and_capabilities.schema.clone());
-                let capabilities = mk_ndc_02_capabilities(
-                    &schema_and_capabilities.capabilities.capabilities,
-                    schema_and_capabilities.schema.capabilities.as_ref(),
-                );
-                (schema, capabilities, issues)
-            }
-        };
+        let (resolved_schema, capabilities, issues) =
+            resolve_schema_and_capabilities(&data_connector.schema)?;
 
         let argument_presets = data_connector
             .argument_presets
@@ -118,6 +96,18 @@ impl<'a> DataConnectorContext<'a> {
             None
         };
 
+        if let DataConnectorUrl::ReadReplicaUrls(ReadReplicaUrls { read, .. }) = &data_connector.url
+            && read.is_empty()
+        {
+            return Err(DataConnectorError::NoReadReplicaUrls);
+        }
+
+        let request_policy = data_connector
+            .request_policy
+            .as_ref()
+            .map(RequestPolicy::new)
+            .transpose()?;
+
         let headers: IndexMap<String, String> = data_connector
             .headers
             .iter()
@@ -131,12 +121,54 @@ impl<'a> DataConnectorContext<'a> {
             capabilities,
             argument_presets,
             response_headers,
+            request_policy,
         };
 
         Ok((context, issues))
     }
 }
 
+/// Resolve the schema and capabilities of a data connector, checking that they are for a
+/// supported NDC version.
+pub(crate) fn resolve_schema_and_capabilities(
+    schema_and_capabilities: &VersionedSchemaAndCapabilities,
+) -> Result<
+    (
+        DataConnectorSchema,
+        DataConnectorCapabilities,
+        Vec<DataConnectorIssue>,
+    ),
+    DataConnectorError,
+> {
+    match schema_and_capabilities {
+        VersionedSchemaAndCapabilities::V01(schema_and_capabilities) => {
+            let issues = validate_ndc_version(
+                NdcVersion::V01,
+                &schema_and_capabilities.capabilities.version,
+            )?;
+            let schema =
+                DataConnectorSchema::new(ndc_migration::v02::migrate_schema_response_from_v01(
+                    schema_and_capabilities.schema.clone(),
+                ));
+            let capabilities =
+                mk_ndc_01_capabilities(&schema_and_capabilities.capabilities.capabilities);
+            Ok((schema, capabilities, issues))
+        }
+        VersionedSchemaAndCapabilities::V02(schema_and_capabilities) => {
+            let issues = validate_ndc_version(
+                NdcVersion::V02,
+                &schema_and_capabilities.capabilities.version,
+            )?;
+            let schema = DataConnectorSchema::new(schema_and_capabilities.schema.clone());
+            let capabilities = mk_ndc_02_capabilities(
+                &schema_and_capabilities.capabilities.capabilities,
+                schema_and_capabilities.schema.capabilities.as_ref(),
+            );
+            Ok((schema, capabilities, issues))
+        }
+    }
+}
+
 fn validate_ndc_version(
     ndc_version: NdcVersion,
     capabilities_version: &str,
@@ -198,7 +230,7 @@ pub struct DataConnectorSchema {
 }
 
 impl DataConnectorSchema {
-    fn new(schema: ndc_models::SchemaResponse) -> Self {
+    pub(crate) fn new(schema: ndc_models::SchemaResponse) -> Self {
         Self {
             scalar_types: schema.scalar_types,
             object_types: schema.object_types,
@@ -244,6 +276,10 @@ pub struct DataConnectorLink {
     /// function/procedure to the client.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub response_config: Option<Arc<CommandsResponseConfig>>,
+    /// How requests to the data connector are timed out, retried and stopped
+    /// when it keeps failing.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub request_policy: Option<RequestPolicy>,
     pub capabilities: DataConnectorCapabilities,
 }
 
@@ -284,6 +320,29 @@ impl DataConnectorLink {
                     })?,
                 })
             }
+            DataConnectorUrl::ReadReplicaUrls(ReadReplicaUrls {
+                read,
+                write,
+                selection,
+                health_check_interval_ms,
+            }) => ResolvedDataConnectorUrl::ReadReplicaUrls(ResolvedReadReplicaUrls {
+                read: read
+                    .iter()
+                    .map(|read| SerializableUrl::new(&read.value))
+                    .collect::<Result<_, _>>()
+                    .map_err(|error| NamedDataConnectorError {
+                        data_connector_name: name.clone(),
+                        error: DataConnectorError::InvalidDataConnectorUrl { error },
+                    })?,
+                write: SerializableUrl::new(&write.value).map_err(|error| {
+                    NamedDataConnectorError {
+                        data_connector_name: name.clone(),
+                        error: DataConnectorError::InvalidDataConnectorUrl { error },
+                    }
+                })?,
+                selection: *selection,
+                health_check_interval: Duration::from_millis(*health_check_interval_ms),
+            }),
         };
         let headers =
             SerializableHeaderMap::new(&context.headers).map_err(|e| NamedDataConnectorError {
@@ -296,6 +355,7 @@ impl DataConnectorLink {
             headers,
             capabilities: context.capabilities.clone(),
             response_config: context.response_headers.clone().map(Arc::new),
+            request_policy: context.request_policy.clone(),
         })
     }
 }
@@ -307,11 +367,23 @@ pub struct ResolvedReadWriteUrls {
     pub write: SerializableUrl,
 }
 
+/// The replicas of a data connector. Which of the `read` URLs a request that reads is sent to is
+/// decided when the request is executed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedReadReplicaUrls {
+    pub read: Vec<SerializableUrl>,
+    pub write: SerializableUrl,
+    pub selection: ReplicaSelection,
+    pub health_check_interval: Duration,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum ResolvedDataConnectorUrl {
     SingleUrl(SerializableUrl),
     ReadWriteUrls(ResolvedReadWriteUrls),
+    ReadReplicaUrls(ResolvedReadReplicaUrls),
 }
 
 impl ResolvedDataConnectorUrl {
@@ -324,6 +396,16 @@ impl ResolvedDataConnectorUrl {
                     OperationType::Mutation => &write.0,
                 }
             }
+            // requests that read are spread over the replicas when they are executed; otherwise
+            // they go to the first replica, as there is always at least one
+            ResolvedDataConnectorUrl::ReadReplicaUrls(ResolvedReadReplicaUrls {
+                read,
+                write,
+                ..
+            }) => match operation {
+                OperationType::Query | OperationType::Subscription => &read[0].0,
+                OperationType::Mutation => &write.0,
+            },
         }
     }
 }
@@ -413,6 +495,73 @@ impl CommandsResponseConfig {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+/// How requests to a data connector are timed out and retried, and when to
+/// stop sending them if the data connector keeps failing
+pub struct RequestPolicy {
+    pub timeout: Option<Duration>,
+    pub retry: Option<RetryPolicy>,
+    pub circuit_breaker: Option<CircuitBreakerPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+/// How failed queries to a data connector are retried
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+/// When to stop sending requests to a data connector that keeps failing, and
+/// for how long
+pub struct CircuitBreakerPolicy {
+    pub failure_threshold: u32,
+    pub reset_timeout: Duration,
+}
+
+impl RequestPolicy {
+    fn new(
+        request_policy: &open_dds::data_connector::DataConnectorRequestPolicy,
+    ) -> Result<Self, DataConnectorError> {
+        let retry = request_policy
+            .retry
+            .as_ref()
+            .map(|retry| {
+                if retry.initial_backoff_ms > retry.max_backoff_ms {
+                    return Err(DataConnectorError::RetryBackoffExceedsMaximum {
+                        initial_backoff_ms: retry.initial_backoff_ms,
+                        max_backoff_ms: retry.max_backoff_ms,
+                    });
+                }
+                Ok(RetryPolicy {
+                    max_retries: retry.max_retries,
+                    initial_backoff: Duration::from_millis(retry.initial_backoff_ms),
+                    max_backoff: Duration::from_millis(retry.max_backoff_ms),
+                })
+            })
+            .transpose()?;
+        let circuit_breaker = request_policy
+            .circuit_breaker
+            .as_ref()
+            .map(|circuit_breaker| {
+                if circuit_breaker.failure_threshold == 0 {
+                    return Err(DataConnectorError::ZeroCircuitBreakerFailureThreshold);
+                }
+                Ok(CircuitBreakerPolicy {
+                    failure_threshold: circuit_breaker.failure_threshold,
+                    reset_timeout: Duration::from_millis(circuit_breaker.reset_timeout_ms),
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            timeout: request_policy.timeout_ms.map(Duration::from_millis),
+            retry,
+            circuit_breaker,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct DataConnectorCapabilities {
@@ -428,6 +577,11 @@ pub struct DataConnectorCapabilities {
     #[serde(skip_serializing_if = "serde_ext::is_ser_default")]
     pub supports_explaining_mutations: bool,
 
+    /// Whether or not several mutations can be executed in one transaction
+    #[serde(default = "serde_ext::ser_default")]
+    #[serde(skip_serializing_if = "serde_ext::is_ser_default")]
+    pub supports_transactional_mutations: bool,
+
     /// Whether not filtering by nested object fields is supported
     #[serde(default = "serde_ext::ser_default")]
     #[serde(skip_serializing_if = "serde_ext::is_ser_default")]
@@ -1195,6 +1349,7 @@ fn mk_ndc_01_capabilities(
         supported_ndc_version: NdcVersion::V01,
         supports_explaining_queries: capabilities.query.explain.is_some(),
         supports_explaining_mutations: capabilities.mutation.explain.is_some(),
+        supports_transactional_mutations: capabilities.mutation.transactional.is_some(),
         supports_nested_object_filtering: capabilities.query.nested_fields.filter_by.is_some(),
         supports_nested_object_ordering: capabilities.query.nested_fields.order_by.is_some(),
         supports_nested_object_array_filtering: capabilities
@@ -1241,6 +1396,7 @@ fn mk_ndc_02_capabilities(
         supported_ndc_version: NdcVersion::V02,
         supports_explaining_queries: capabilities.query.explain.is_some(),
         supports_explaining_mutations: capabilities.mutation.explain.is_some(),
+        supports_transactional_mutations: capabilities.mutation.transactional.is_some(),
         supports_nested_object_filtering: capabilities.query.nested_fields.filter_by.is_some(),
         supports_nested_object_ordering: capabilities.query.nested_fields.order_by.is_some(),
         supports_nested_object_array_filtering: capabilities
@@ -1577,6 +1733,7 @@ mod tests {
             supported_ndc_version: NdcVersion::V01,
             supports_explaining_queries: false,
             supports_explaining_mutations: false,
+            supports_transactional_mutations: false,
             supports_nested_object_filtering: false,
             supports_nested_object_ordering: false,
             supports_nested_object_array_filtering: false,
@@ -1623,6 +1780,7 @@ mod tests {
             supported_ndc_version: NdcVersion::V02,
             supports_explaining_queries: false,
             supports_explaining_mutations: false,
+            supports_transactional_mutations: false,
             supports_nested_object_filtering: false,
             supports_nested_object_ordering: false,
             supports_nested_object_array_filtering: false,
@@ -46,6 +46,16 @@ pub enum GraphqlConfigError {
     MissingAggregateFilterInputFieldNameInGraphqlConfig,
     #[error("\"{name:}\" is not a valid GraphQL name.")]
     InvalidGraphQlName { name: String },
+    #[error("the operation {name:} is defined more than once in the allow-list")]
+    DuplicateAllowedOperation { name: ast::Name },
+    #[error("the query of the allowed operation {name:} could not be parsed: {error:}")]
+    InvalidAllowedOperationQuery { name: ast::Name, error: String },
+    #[error(
+        "the query of the allowed operation {name:} does not define an operation named {name:}"
+    )]
+    AllowedOperationNotDefined { name: ast::Name },
+    #[error("limits are given more than once for the role {role:}")]
+    DuplicateRoleLimits { role: open_dds::permissions::Role },
     #[error("multiple graphql types found with the same name: {graphql_type_name:}")]
     ConflictingGraphQlType { graphql_type_name: ast::TypeName },
 }
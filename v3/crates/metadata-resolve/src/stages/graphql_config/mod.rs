@@ -3,10 +3,11 @@
 mod error;
 mod types;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::OnceLock;
 
 use graphql_types as ast;
+use lang_graphql::ast::executable::ExecutableDefinition;
 use open_dds::accessor::QualifiedObject;
 use open_dds::graphql_config::{self, OrderByDirection};
 use open_dds::types::{GraphQlFieldName, GraphQlTypeName};
@@ -14,9 +15,9 @@ use open_dds::types::{GraphQlFieldName, GraphQlTypeName};
 use crate::helpers::types::mk_name;
 pub use error::GraphqlConfigError;
 pub use types::{
-    AggregateGraphqlConfig, FilterInputGraphqlConfig, FilterInputOperatorNames,
+    AggregateGraphqlConfig, AllowList, FilterInputGraphqlConfig, FilterInputOperatorNames,
     GlobalGraphqlConfig, GraphqlConfig, GraphqlTypeNames, MultipleOrderByInputObjectFields,
-    OrderByInputGraphqlConfig, QueryGraphqlConfig,
+    OperationLimits, OrderByInputGraphqlConfig, QueryGraphqlConfig, QueryLimits,
 };
 
 /// Resolve and validate the GraphQL configuration.
@@ -191,6 +192,18 @@ pub fn resolve_graphql_config(
                 .as_ref()
                 .is_some_and(|federation_config| federation_config.enable_root_fields);
 
+            let allow_list = graphql_config_metadata
+                .allow_list
+                .as_ref()
+                .map(resolve_allow_list)
+                .transpose()?;
+
+            let limits = graphql_config_metadata
+                .limits
+                .as_ref()
+                .map(resolve_limits)
+                .transpose()?;
+
             Ok(GraphqlConfig {
                 query: QueryGraphqlConfig {
                     arguments_field_name,
@@ -218,12 +231,82 @@ pub fn resolve_graphql_config(
                     } else {
                         MultipleOrderByInputObjectFields::Allow
                     },
+                    allow_list,
+                    limits,
                 },
             })
         }
     }
 }
 
+/// Resolve the allow-list, checking that each allowed query parses and defines the operation it
+/// is named after.
+fn resolve_allow_list(
+    allow_list: &graphql_config::AllowListGraphqlConfig,
+) -> Result<AllowList, GraphqlConfigError> {
+    let mut operations = BTreeMap::new();
+    for operation in &allow_list.operations {
+        let name = mk_name(&operation.name)?;
+        let document = lang_graphql::parser::Parser::new(&operation.query)
+            .parse_executable_document()
+            .map_err(|error| GraphqlConfigError::InvalidAllowedOperationQuery {
+                name: name.clone(),
+                error: error.to_string(),
+            })?;
+        let defines_operation = document.items.iter().any(|item| match &item.item {
+            ExecutableDefinition::Operation(definition) => definition
+                .name
+                .as_ref()
+                .is_some_and(|definition_name| definition_name.item == name),
+            ExecutableDefinition::Fragment(_) => false,
+        });
+        if !defines_operation {
+            return Err(GraphqlConfigError::AllowedOperationNotDefined { name });
+        }
+        if operations
+            .insert(name.clone(), operation.query.clone())
+            .is_some()
+        {
+            return Err(GraphqlConfigError::DuplicateAllowedOperation { name });
+        }
+    }
+    Ok(AllowList {
+        roles: allow_list.roles.iter().cloned().collect(),
+        operations,
+    })
+}
+
+/// Resolve the operation limits, filling in the default limits for roles that don't give their
+/// own.
+fn resolve_limits(
+    limits: &graphql_config::GraphqlLimitsConfig,
+) -> Result<QueryLimits, GraphqlConfigError> {
+    let default = OperationLimits {
+        max_depth: limits.max_depth,
+        max_aliases: limits.max_aliases,
+        max_nodes: limits.max_nodes,
+        max_cost: limits.max_cost,
+    };
+    let mut by_role = BTreeMap::new();
+    for role_limits in &limits.roles {
+        let operation_limits = OperationLimits {
+            max_depth: role_limits.max_depth.or(default.max_depth),
+            max_aliases: role_limits.max_aliases.or(default.max_aliases),
+            max_nodes: role_limits.max_nodes.or(default.max_nodes),
+            max_cost: role_limits.max_cost.or(default.max_cost),
+        };
+        if by_role
+            .insert(role_limits.role.clone(), operation_limits)
+            .is_some()
+        {
+            return Err(GraphqlConfigError::DuplicateRoleLimits {
+                role: role_limits.role.clone(),
+            });
+        }
+    }
+    Ok(QueryLimits { default, by_role })
+}
+
 fn fallback_graphql_config() -> &'static graphql_config::GraphqlConfig {
     static CELL: OnceLock<graphql_config::GraphqlConfig> = OnceLock::new();
     CELL.get_or_init(|| {
@@ -273,6 +356,8 @@ fn fallback_graphql_config() -> &'static graphql_config::GraphqlConfig {
             },
             subscription: None,
             apollo_federation: None,
+            allow_list: None,
+            limits: None,
         })
     })
 }
@@ -1,7 +1,8 @@
 use super::error::GraphqlConfigError;
 use graphql_types as ast;
+use open_dds::permissions::Role;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct GraphqlConfig {
@@ -22,6 +23,8 @@ pub struct GlobalGraphqlConfig {
     pub bypass_relation_comparisons_ndc_capability: bool,
     pub propagate_boolean_expression_deprecation_status: bool,
     pub multiple_order_by_input_object_fields: MultipleOrderByInputObjectFields,
+    pub allow_list: Option<AllowList>,
+    pub limits: Option<QueryLimits>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -38,6 +41,37 @@ pub enum MultipleOrderByInputObjectFields {
     Disallow,
 }
 
+/// The operations that some roles are restricted to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AllowList {
+    pub roles: BTreeSet<Role>,
+    /// The documents defining the allowed operations, by operation name
+    pub operations: BTreeMap<ast::Name, String>,
+}
+
+/// Limits on the size of GraphQL operations.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// Limits for roles without their own
+    pub default: OperationLimits,
+    /// Limits for particular roles, with the default limits filled in where they give none
+    pub by_role: BTreeMap<Role, OperationLimits>,
+}
+
+impl QueryLimits {
+    pub fn for_role(&self, role: &Role) -> &OperationLimits {
+        self.by_role.get(role).unwrap_or(&self.default)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OperationLimits {
+    pub max_depth: Option<u32>,
+    pub max_aliases: Option<u32>,
+    pub max_nodes: Option<u32>,
+    pub max_cost: Option<u32>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct OrderByInputGraphqlConfig {
     pub asc_direction_field_value: ast::Name,
@@ -79,13 +113,31 @@ pub struct AggregateGraphqlConfig {
 
 pub struct GraphqlTypeNames {
     type_names: BTreeSet<ast::TypeName>,
+    /// types that are generated the same way wherever they are used, so that they can be named
+    /// more than once
+    shared_type_names: BTreeSet<ast::TypeName>,
 }
 
 impl GraphqlTypeNames {
     pub fn new() -> Self {
         GraphqlTypeNames {
             type_names: BTreeSet::new(),
+            shared_type_names: BTreeSet::new(),
+        }
+    }
+
+    /// add a shared type, which can be named again by other shared types but must not conflict
+    /// with any other type
+    pub fn store_shared(
+        &mut self,
+        new_graphql_type_name: &ast::TypeName,
+    ) -> Result<(), GraphqlConfigError> {
+        if self.shared_type_names.contains(new_graphql_type_name) {
+            return Ok(());
         }
+        self.store(Some(new_graphql_type_name))?;
+        self.shared_type_names.insert(new_graphql_type_name.clone());
+        Ok(())
     }
 
     /// try to add `new_graphql_type` to `existing_graphql_types`, returning an error
@@ -68,6 +68,8 @@ pub enum ModelPermissionError {
     RelationalUpdateNotSupported,
     #[error("relational delete is not supported for this model")]
     RelationalDeleteNotSupported,
+    #[error("the default limit {default_limit} is more than the maximum of {max_rows} rows")]
+    DefaultLimitExceedsMaxRows { default_limit: u32, max_rows: u32 },
 
     #[error("{0}")]
     ModelsError(#[from] models::ModelsError),
@@ -153,7 +155,8 @@ impl ContextualError for NamedModelPermissionError {
             | ModelPermissionError::UnknownModelCollection { .. }
             | ModelPermissionError::RelationalInsertNotSupported
             | ModelPermissionError::RelationalUpdateNotSupported
-            | ModelPermissionError::RelationalDeleteNotSupported => None,
+            | ModelPermissionError::RelationalDeleteNotSupported
+            | ModelPermissionError::DefaultLimitExceedsMaxRows { .. } => None,
             ModelPermissionError::ModelsError(error) => error.create_error_context(),
         }
     }
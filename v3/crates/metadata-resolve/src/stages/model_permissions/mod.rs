@@ -16,7 +16,8 @@ pub use types::{
     FilterPermission, ModelAuthorizationRule, ModelPermissionIssue, ModelPermissionsOutput,
     ModelPredicate, ModelTargetSource, ModelWithPermissions, PredicateRelationshipInfo,
     RelationalDeletePermission, RelationalInsertPermission, RelationalOperation,
-    RelationalUpdatePermission, SelectPermission, UnaryComparisonOperator,
+    RelationalUpdatePermission, ResolvedOperator, RowLimits, SelectPermission,
+    UnaryComparisonOperator,
 };
 mod model_permission;
 pub(crate) use predicate::resolve_model_predicate_with_type;
@@ -1,6 +1,6 @@
 use super::types::{
     FilterPermission, ModelAuthorizationRule, ModelInputPermission, ModelPermission,
-    ModelPermissionIssue, ModelPermissions, SelectPermission,
+    ModelPermissionIssue, ModelPermissions, RowLimits, SelectPermission,
 };
 use super::{ModelPermissionError, NamedModelPermissionError, RelationalOperation, predicate};
 use crate::helpers::argument::resolve_value_expression_for_argument;
@@ -327,11 +327,32 @@ pub fn resolve_role_based_model_permissions(
                 models,
             )?;
 
+            let row_limits = RowLimits {
+                max_rows: select_perms.max_rows,
+                default_limit: select_perms.default_limit,
+            };
+            if let RowLimits {
+                max_rows: Some(max_rows),
+                default_limit: Some(default_limit),
+            } = row_limits
+                && default_limit > max_rows
+            {
+                return Err(Error::ModelPermissionsError(NamedModelPermissionError {
+                    model_name: model.inner.name.clone(),
+                    role: Some(model_permission.role.clone()),
+                    error: ModelPermissionError::DefaultLimitExceedsMaxRows {
+                        default_limit,
+                        max_rows,
+                    },
+                }));
+            }
+
             // create authorization rules
             authorization_rules.extend(authorization_rules_for_role(
                 &model_permission.role,
                 &filter,
                 select_perms.allow_subscriptions,
+                row_limits,
                 flags,
                 conditions,
             ));
@@ -339,6 +360,7 @@ pub fn resolve_role_based_model_permissions(
             model_permission_for_role.select = Some(SelectPermission {
                 filter,
                 allow_subscriptions: select_perms.allow_subscriptions,
+                row_limits,
             });
 
             let ArgumentPresets {
@@ -467,6 +489,7 @@ fn authorization_rules_for_role(
     role: &Role,
     filter_permission: &FilterPermission,
     allow_subscriptions: bool,
+    row_limits: RowLimits,
     flags: &open_dds::flags::OpenDdFlags,
     conditions: &mut Conditions,
 ) -> Vec<ModelAuthorizationRule> {
@@ -487,19 +510,23 @@ fn authorization_rules_for_role(
         },
     };
 
-    match filter_permission {
-        FilterPermission::AllowAll => vec![allow_rule, allow_subscription_rule],
-        FilterPermission::Filter(model_predicate) => {
-            vec![
-                allow_rule,
-                allow_subscription_rule,
-                ModelAuthorizationRule::Filter {
-                    predicate: *model_predicate.clone(),
-                    condition: Some(condition_hash),
-                },
-            ]
-        }
+    let mut rules = vec![allow_rule, allow_subscription_rule];
+
+    if let FilterPermission::Filter(model_predicate) = filter_permission {
+        rules.push(ModelAuthorizationRule::Filter {
+            predicate: *model_predicate.clone(),
+            condition: Some(condition_hash),
+        });
     }
+
+    if !row_limits.is_empty() {
+        rules.push(ModelAuthorizationRule::RowLimits {
+            condition: Some(condition_hash),
+            row_limits,
+        });
+    }
+
+    rules
 }
 
 // given a role and a preset value, return a ModelAuthorizationRule
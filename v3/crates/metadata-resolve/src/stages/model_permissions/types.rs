@@ -93,6 +93,11 @@ pub enum ModelAuthorizationRule {
         allow_or_deny: AllowOrDeny,
         relational_operation: RelationalOperation,
     },
+    // limits on the number of rows selected. if multiple items are used, the lowest limits win.
+    RowLimits {
+        condition: Option<ConditionHash>,
+        row_limits: RowLimits,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -144,6 +149,34 @@ pub struct SelectPermission {
     pub filter: FilterPermission,
     // pub allow_aggregations: bool,
     pub allow_subscriptions: bool,
+    pub row_limits: RowLimits,
+}
+
+/// Limits on the number of rows a role can select from a model.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RowLimits {
+    pub max_rows: Option<u32>,
+    pub default_limit: Option<u32>,
+}
+
+impl RowLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_rows.is_none() && self.default_limit.is_none()
+    }
+
+    /// The number of rows to select when `limit` rows are requested, or no limit is requested.
+    pub fn rows(&self, limit: Option<u32>) -> Option<u32> {
+        self.cap(limit.or(self.default_limit))
+    }
+
+    /// `limit` lowered to `max_rows`, or `max_rows` when no limit is given. Unlike `rows`, this
+    /// doesn't fall back to the default limit, for aggregates, groups and stream batches.
+    pub fn cap(&self, limit: Option<u32>) -> Option<u32> {
+        match (limit, self.max_rows) {
+            (Some(limit), Some(max_rows)) => Some(limit.min(max_rows)),
+            (limit, max_rows) => limit.or(max_rows),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
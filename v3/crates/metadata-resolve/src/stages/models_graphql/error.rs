@@ -22,6 +22,33 @@ pub enum ModelGraphqlError {
         "filter input type name graphql configuration must be specified for model {model_name:} because aggregates are used with it"
     )]
     MissingFilterInputTypeNameGraphqlConfiguration { model_name: Qualified<ModelName> },
+    #[error(
+        "the model {model_name:} has defined a groupBy graphql API, but groups can only be aggregated over if the model has an aggregate expression"
+    )]
+    GroupByWithoutAggregateExpression { model_name: Qualified<ModelName> },
+    #[error(
+        "the streaming subscription for model {model_name:} must define at least one cursor field"
+    )]
+    NoStreamCursorFields { model_name: Qualified<ModelName> },
+    #[error("unknown field {field_name:} in cursor fields defined for model {model_name:}")]
+    UnknownStreamCursorField {
+        model_name: Qualified<ModelName>,
+        field_name: FieldName,
+    },
+    #[error(
+        "the cursor field {field_name:} defined for model {model_name:} must be a unique identifier of the model on its own, as objects sharing a cursor value would be skipped when streaming"
+    )]
+    StreamCursorFieldNotUnique {
+        model_name: Qualified<ModelName>,
+        field_name: FieldName,
+    },
+    #[error(
+        "the cursor field {field_name:} defined for model {model_name:} cannot be streamed by, as the data connector does not define a greater than operator for it"
+    )]
+    StreamCursorFieldWithoutGreaterThanOperator {
+        model_name: Qualified<ModelName>,
+        field_name: FieldName,
+    },
 
     #[error("{0}")]
     GraphqlConfigError(#[from] graphql_config::GraphqlConfigError),
@@ -4,7 +4,8 @@ use super::error::ModelGraphqlError;
 use super::types::{
     LimitFieldGraphqlConfig, ModelGraphQlApi, ModelGraphqlApiArgumentsConfig, ModelGraphqlIssue,
     ModelOrderByExpression, OffsetFieldGraphqlConfig, OrderByExpressionInfo,
-    SelectAggregateGraphQlDefinition, SelectManyGraphQlDefinition, SelectUniqueGraphQlDefinition,
+    SelectAggregateGraphQlDefinition, SelectGroupByGraphQlDefinition, SelectManyGraphQlDefinition,
+    SelectUniqueGraphQlDefinition, StreamSubscriptionGraphQlDefinition,
     SubscriptionGraphQlDefinition,
 };
 use crate::Warning;
@@ -17,6 +18,7 @@ use indexmap::IndexMap;
 use open_dds::aggregates::AggregateExpressionName;
 use open_dds::models::{ModelGraphQlDefinitionV2, ModelName};
 use open_dds::relationships::{ModelRelationshipTarget, RelationshipTarget};
+use open_dds::types::FieldName;
 
 use std::collections::BTreeMap;
 
@@ -159,6 +161,20 @@ pub(crate) fn resolve_model_graphql_api(
                 .map(|s| resolve_subscription_graphql_api(s, model_name, track_root_fields, issues))
                 .transpose()?;
 
+            let stream_subscription = gql_definition
+                .stream_subscription
+                .as_ref()
+                .map(|s| {
+                    resolve_stream_subscription_graphql_api(
+                        s,
+                        model,
+                        track_root_fields,
+                        graphql_types,
+                        issues,
+                    )
+                })
+                .transpose()?;
+
             mk_name(gql_definition.query_root_field.as_str()).map(|f: ast::Name| {
                 // Let's track and check if the select_many field name is already used
                 track_root_fields.track_query_root_field(&f).unwrap_or_else(|error| {
@@ -181,6 +197,7 @@ pub(crate) fn resolve_model_graphql_api(
                     description: select_many_description,
                     deprecated: gql_definition.deprecated.clone(),
                     subscription,
+                    stream_subscription,
                 })
             })
         }
@@ -271,6 +288,101 @@ pub(crate) fn resolve_model_graphql_api(
         _ => None,
     };
 
+    // models without a source can still be used to build a schema, so we only check grouping
+    // support when there is one
+    let data_connector_without_grouping = model.source.as_ref().and_then(|model_source| {
+        model_source
+            .data_connector
+            .capabilities
+            .supports_aggregates
+            .as_ref()
+            .is_none_or(|aggregates| aggregates.supports_grouping.is_none())
+            .then_some(&model_source.data_connector.name)
+    });
+
+    // record select_group_by root field. Groups are aggregated over with the model's aggregate
+    // expression, and filtered like aggregates are.
+    graphql_api.select_group_by = match (
+        &model_graphql_definition.group_by,
+        aggregate_expression_name,
+        &graphql_config.query.aggregate_config,
+        data_connector_without_grouping,
+    ) {
+        (None, _, _, _) => None,
+        (Some(_graphql_group_by), None, _, _) => {
+            return Err(ModelGraphqlError::GroupByWithoutAggregateExpression {
+                model_name: model_name.clone(),
+            });
+        }
+        (Some(_graphql_group_by), Some(_aggregate_expression_name), None, _) => {
+            issues.push(
+                ModelGraphqlIssue::MissingGroupByFilterInputFieldNameInGraphqlConfig {
+                    model_name: model_name.clone(),
+                }
+                .into(),
+            );
+            None
+        }
+        (
+            Some(_graphql_group_by),
+            Some(_aggregate_expression_name),
+            Some(_),
+            Some(data_connector_name),
+        ) => {
+            issues.push(
+                ModelGraphqlIssue::GroupByNotSupportedByDataConnector {
+                    model_name: model_name.clone(),
+                    data_connector_name: data_connector_name.clone(),
+                }
+                .into(),
+            );
+            None
+        }
+        (Some(graphql_group_by), Some(aggregate_expression_name), Some(aggregate_config), None) => {
+            let group_by_root_field = mk_name(graphql_group_by.query_root_field.as_str())?;
+            // Let's track and check if the select_group_by field name is already used
+            track_root_fields
+                .track_query_root_field(&group_by_root_field)
+                .unwrap_or_else(|error| {
+                    issues.push(
+                        ModelGraphqlIssue::DuplicateRootField {
+                            model_name: model_name.clone(),
+                            error,
+                        }
+                        .into(),
+                    );
+                });
+
+            let group_type_name =
+                ast::TypeName(mk_name(graphql_group_by.group_type_name.as_str())?);
+            graphql_types.store(Some(&group_type_name))?;
+            let dimensions_type_name =
+                ast::TypeName(mk_name(graphql_group_by.dimensions_type_name.as_str())?);
+            graphql_types.store(Some(&dimensions_type_name))?;
+            // the extraction function enum is the same for every model, so models can share it,
+            // but it must not clash with any other type
+            let extraction_function_type_name = graphql_group_by
+                .extraction_function_type_name
+                .as_ref()
+                .map(|type_name| mk_name(type_name.as_str()).map(ast::TypeName))
+                .transpose()?;
+            if let Some(extraction_function_type_name) = &extraction_function_type_name {
+                graphql_types.store_shared(extraction_function_type_name)?;
+            }
+
+            Some(SelectGroupByGraphQlDefinition {
+                query_root_field: group_by_root_field,
+                description: graphql_group_by.description.clone(),
+                deprecated: graphql_group_by.deprecated.clone(),
+                aggregate_expression_name: aggregate_expression_name.clone(),
+                filter_input_field_name: aggregate_config.filter_input_field_name.clone(),
+                group_type_name,
+                dimensions_type_name,
+                extraction_function_type_name,
+            })
+        }
+    };
+
     // record limit and offset field names
     graphql_api.limit_field = graphql_config
         .query
@@ -380,3 +492,98 @@ fn resolve_subscription_graphql_api(
         polling_interval_ms: *polling_interval_ms,
     })
 }
+
+fn resolve_stream_subscription_graphql_api(
+    stream_subscription: &open_dds::models::StreamSubscriptionGraphQlDefinition,
+    model: &models::Model,
+    track_root_fields: &mut TrackGraphQLRootFields,
+    graphql_types: &mut graphql_config::GraphqlTypeNames,
+    issues: &mut Vec<Warning>,
+) -> Result<StreamSubscriptionGraphQlDefinition, ModelGraphqlError> {
+    let open_dds::models::StreamSubscriptionGraphQlDefinition {
+        root_field,
+        cursor_input_type_name,
+        cursor_fields,
+        description,
+        deprecated,
+        polling_interval_ms,
+    } = stream_subscription;
+    let model_name = &model.name;
+    let root_field_name = mk_name(root_field.as_str())?;
+    // Let's track and check if the stream subscription root field name is already used
+    track_root_fields
+        .track_subscription_root_field(&root_field_name)
+        .unwrap_or_else(|error| {
+            issues.push(Warning::from(ModelGraphqlIssue::DuplicateRootField {
+                model_name: model_name.clone(),
+                error,
+            }));
+        });
+    if cursor_fields.is_empty() {
+        return Err(ModelGraphqlError::NoStreamCursorFields {
+            model_name: model_name.clone(),
+        });
+    }
+    for cursor_field in cursor_fields {
+        resolve_stream_cursor_field(model, cursor_field)?;
+    }
+    let cursor_input_type_name = ast::TypeName(mk_name(cursor_input_type_name.as_str())?);
+    graphql_types.store(Some(&cursor_input_type_name))?;
+    Ok(StreamSubscriptionGraphQlDefinition {
+        root_field: root_field_name,
+        cursor_input_type_name,
+        cursor_fields: cursor_fields.clone(),
+        description: description.clone(),
+        deprecated: deprecated.clone(),
+        polling_interval_ms: *polling_interval_ms,
+    })
+}
+
+/// Each poll of a streaming subscription fetches the objects with a cursor value greater than the
+/// last one returned, so a cursor field must be unique and comparable with a greater than operator.
+fn resolve_stream_cursor_field(
+    model: &models::Model,
+    cursor_field: &FieldName,
+) -> Result<(), ModelGraphqlError> {
+    if !model.type_fields.contains_key(cursor_field) {
+        return Err(ModelGraphqlError::UnknownStreamCursorField {
+            model_name: model.name.clone(),
+            field_name: cursor_field.clone(),
+        });
+    }
+    if !model.unique_identifiers.iter().any(|unique_identifier| {
+        unique_identifier.len() == 1 && unique_identifier.contains_key(cursor_field)
+    }) {
+        return Err(ModelGraphqlError::StreamCursorFieldNotUnique {
+            model_name: model.name.clone(),
+            field_name: cursor_field.clone(),
+        });
+    }
+    // models without a source have no field mappings, so the operator can't be checked yet
+    if let Some(model_source) = &model.source {
+        let ndc_version = model_source
+            .data_connector
+            .capabilities
+            .supported_ndc_version;
+        let has_greater_than_operator = model_source
+            .type_mappings
+            .get(&model.data_type)
+            .and_then(|type_mapping| match type_mapping {
+                object_types::TypeMapping::Object { field_mappings, .. } => {
+                    field_mappings.get(cursor_field)
+                }
+            })
+            .and_then(|field_mapping| field_mapping.comparison_operators.as_ref())
+            .and_then(|operators| operators.get_gt_operator(ndc_version))
+            .is_some();
+        if !has_greater_than_operator {
+            return Err(
+                ModelGraphqlError::StreamCursorFieldWithoutGreaterThanOperator {
+                    model_name: model.name.clone(),
+                    field_name: cursor_field.clone(),
+                },
+            );
+        }
+    }
+    Ok(())
+}
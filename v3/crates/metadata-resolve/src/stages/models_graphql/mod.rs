@@ -23,7 +23,8 @@ use crate::types::subgraph::Qualified;
 pub(crate) use types::ModelWithGraphql;
 pub use types::{
     ModelGraphQlApi, ModelGraphqlIssue, ModelOrderByExpression, ModelsWithGraphqlOutput,
-    SelectAggregateGraphQlDefinition, SelectManyGraphQlDefinition, SelectUniqueGraphQlDefinition,
+    SelectAggregateGraphQlDefinition, SelectGroupByGraphQlDefinition, SelectManyGraphQlDefinition,
+    SelectUniqueGraphQlDefinition, StreamSubscriptionGraphQlDefinition,
     SubscriptionGraphQlDefinition,
 };
 
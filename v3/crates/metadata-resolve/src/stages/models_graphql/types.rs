@@ -61,6 +61,9 @@ pub struct SelectManyGraphQlDefinition {
     #[serde(default = "serde_ext::ser_default")]
     #[serde(skip_serializing_if = "serde_ext::is_ser_default")]
     pub subscription: Option<SubscriptionGraphQlDefinition>,
+    #[serde(default = "serde_ext::ser_default")]
+    #[serde(skip_serializing_if = "serde_ext::is_ser_default")]
+    pub stream_subscription: Option<StreamSubscriptionGraphQlDefinition>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -79,6 +82,24 @@ pub struct SelectAggregateGraphQlDefinition {
     pub subscription: Option<SubscriptionGraphQlDefinition>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SelectGroupByGraphQlDefinition {
+    pub query_root_field: ast::Name,
+    #[serde(default = "serde_ext::ser_default")]
+    #[serde(skip_serializing_if = "serde_ext::is_ser_default")]
+    pub description: Option<String>,
+    #[serde(default = "serde_ext::ser_default")]
+    #[serde(skip_serializing_if = "serde_ext::is_ser_default")]
+    pub deprecated: Option<Deprecated>,
+    pub aggregate_expression_name: Qualified<AggregateExpressionName>,
+    pub filter_input_field_name: ast::Name,
+    pub group_type_name: ast::TypeName,
+    pub dimensions_type_name: ast::TypeName,
+    #[serde(default = "serde_ext::ser_default")]
+    #[serde(skip_serializing_if = "serde_ext::is_ser_default")]
+    pub extraction_function_type_name: Option<ast::TypeName>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct SubscriptionGraphQlDefinition {
     pub root_field: ast::Name,
@@ -91,6 +112,20 @@ pub struct SubscriptionGraphQlDefinition {
     pub polling_interval_ms: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StreamSubscriptionGraphQlDefinition {
+    pub root_field: ast::Name,
+    pub cursor_input_type_name: ast::TypeName,
+    pub cursor_fields: Vec<FieldName>,
+    #[serde(default = "serde_ext::ser_default")]
+    #[serde(skip_serializing_if = "serde_ext::is_ser_default")]
+    pub description: Option<String>,
+    #[serde(default = "serde_ext::ser_default")]
+    #[serde(skip_serializing_if = "serde_ext::is_ser_default")]
+    pub deprecated: Option<Deprecated>,
+    pub polling_interval_ms: u64,
+}
+
 // TODO: add support for aggregates
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct OrderByExpressionInfo {
@@ -126,6 +161,7 @@ pub struct ModelGraphQlApi {
     pub select_uniques: Vec<SelectUniqueGraphQlDefinition>,
     pub select_many: Option<SelectManyGraphQlDefinition>,
     pub select_aggregate: Option<SelectAggregateGraphQlDefinition>,
+    pub select_group_by: Option<SelectGroupByGraphQlDefinition>,
     pub order_by_expression: Option<ModelOrderByExpression>,
     pub limit_field: Option<LimitFieldGraphqlConfig>,
     pub offset_field: Option<OffsetFieldGraphqlConfig>,
@@ -139,6 +175,19 @@ pub enum ModelGraphqlIssue {
     )]
     MissingAggregateFilterInputFieldNameInGraphqlConfig { model_name: Qualified<ModelName> },
 
+    #[error(
+        "the model {model_name} has defined a groupBy graphql API, but it will not appear in the GraphQL API unless query.aggregate.filterInputFieldName is also configured in GraphqlConfig"
+    )]
+    MissingGroupByFilterInputFieldNameInGraphqlConfig { model_name: Qualified<ModelName> },
+
+    #[error(
+        "the model {model_name} has defined a groupBy graphql API, but it will not appear in the GraphQL API because its data connector {data_connector_name} does not support grouping"
+    )]
+    GroupByNotSupportedByDataConnector {
+        model_name: Qualified<ModelName>,
+        data_connector_name: Qualified<DataConnectorName>,
+    },
+
     #[error("the model {model_name} has a duplicate root field in the GraphQL schema: {error:}")]
     DuplicateRootField {
         model_name: Qualified<ModelName>,
@@ -159,6 +208,8 @@ impl ShouldBeAnError for ModelGraphqlIssue {
     fn should_be_an_error(&self, flags: &open_dds::flags::OpenDdFlags) -> bool {
         match self {
             ModelGraphqlIssue::MissingAggregateFilterInputFieldNameInGraphqlConfig { .. }
+            | ModelGraphqlIssue::MissingGroupByFilterInputFieldNameInGraphqlConfig { .. }
+            | ModelGraphqlIssue::GroupByNotSupportedByDataConnector { .. }
             | ModelGraphqlIssue::UnnecessaryModelArgumentsGraphQlInputConfiguration { .. }
             | ModelGraphqlIssue::UnnecessaryFilterInputTypeNameGraphqlConfiguration { .. } => false,
             ModelGraphqlIssue::DuplicateRootField { .. } => {
@@ -108,14 +108,6 @@ pub enum RelationshipError {
         argument_name: ArgumentName,
         relationship_name: RelationshipName,
     },
-    #[error(
-        "The target data connector {data_connector_name} for relationship {relationship_name} on type {type_name} does not support the variables capability"
-    )]
-    RelationshipTargetDoesNotSupportForEach {
-        type_name: Qualified<CustomTypeName>,
-        relationship_name: RelationshipName,
-        data_connector_name: Qualified<DataConnectorName>,
-    },
     #[error(
         "The target data connector {data_connector_name} for relationship {relationship_name} on type {type_name} has not defined any capabilities"
     )]
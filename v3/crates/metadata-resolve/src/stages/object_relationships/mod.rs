@@ -584,16 +584,8 @@ fn get_relationship_capabilities(
 
     let capabilities = &resolved_data_connector.capabilities;
 
-    // if relationship is remote, error if `foreach` capability is not available
-    if capabilities.supports_query_variables
-        && Some(&data_connector.name) != target_data_connector.as_ref()
-    {
-        return Err(RelationshipError::RelationshipTargetDoesNotSupportForEach {
-            type_name: type_name.clone(),
-            relationship_name: relationship_name.clone(),
-            data_connector_name: data_connector.name.clone(),
-        });
-    }
+    // remote relationships do not need the `foreach` capability: if the target
+    // does not support variables, the join is executed with a query per join key
 
     // if relationship is local, error if relationship and variables capabilities are not available
     if Some(&data_connector.name) == target_data_connector.as_ref()
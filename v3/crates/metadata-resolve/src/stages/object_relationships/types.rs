@@ -119,8 +119,8 @@ pub struct RelationshipField {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct RelationshipCapabilities {
-    // TODO: We don't handle relationships without foreach.
-    // Change this to a bool, when we support that
+    // Remote joins fall back to a query per join key when the target does not
+    // support foreach, so this doesn't affect how relationships are executed
     pub foreach: (),
     pub supports_relationships: Option<data_connectors::DataConnectorRelationshipCapabilities>,
 }
@@ -55,6 +55,8 @@ pub enum Warning {
     ObjectRelationshipsIssue(#[from] object_relationships::ObjectRelationshipsIssue),
     #[error("{0}")]
     ArgumentIssue(#[from] arguments::ArgumentIssue),
+    #[error("{0}")]
+    SchemaDrift(#[from] data_connectors::SchemaDrift),
 }
 
 impl ShouldBeAnError for Warning {
@@ -83,6 +85,7 @@ impl ContextualError for Warning {
         match self {
             Warning::ModelPermissionIssue(issue) => issue.create_error_context(),
             Warning::BooleanExpressionIssue(issue) => issue.create_error_context(),
+            Warning::SchemaDrift(drift) => drift.create_error_context(),
             _ => None,
         }
     }
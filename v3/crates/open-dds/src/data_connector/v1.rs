@@ -18,7 +18,53 @@ pub struct ReadWriteUrls {
     pub write: EnvironmentValue,
 }
 
-/// A URL to access a data connector. This can be a single URL or a pair of read and write URLs.
+/// URLs to access a data connector that runs as several replicas. Requests that read are spread
+/// over the `read` URLs, and fail over to the other replicas when one is unavailable. Requests
+/// that write go to the `write` URL.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, opendds_derive::OpenDd)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+#[opendd(json_schema(title = "ReadReplicaUrls"))]
+pub struct ReadReplicaUrls {
+    /// The URLs of the replicas that requests that read are spread over.
+    pub read: Vec<EnvironmentValue>,
+    /// The URL that requests that write are sent to.
+    pub write: EnvironmentValue,
+    /// How the replica that a request is sent to is picked (default: roundRobin).
+    #[opendd(default)]
+    pub selection: ReplicaSelection,
+    /// How long a replica that is unavailable is left out before its health is checked again,
+    /// in milliseconds.
+    #[opendd(default = 10000)]
+    pub health_check_interval_ms: u64,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    opendds_derive::OpenDd,
+)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+#[schemars(title = "ReplicaSelection")]
+/// How the replica that a request to a data connector is sent to is picked.
+pub enum ReplicaSelection {
+    /// Take turns between the available replicas.
+    #[default]
+    RoundRobin,
+    /// Pick the available replica that has been responding the fastest.
+    LeastLatency,
+}
+
+/// A URL to access a data connector. This can be a single URL, a pair of read and write URLs, or
+/// a list of read URLs and a write URL.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, opendds_derive::OpenDd)]
 #[opendd(externally_tagged, json_schema(title = "DataConnectorUrlV1"))]
 #[serde(rename_all = "camelCase")]
@@ -27,6 +73,8 @@ pub enum DataConnectorUrlV1 {
     SingleUrl(EnvironmentValue),
     // #[opendd(json_schema(title = "ReadWriteUrls"))]
     ReadWriteUrls(ReadWriteUrls),
+    // #[opendd(json_schema(title = "ReadReplicaUrls"))]
+    ReadReplicaUrls(ReadReplicaUrls),
 }
 
 #[derive(
@@ -69,6 +117,59 @@ pub struct DataConnectorLinkV1 {
     /// HTTP response headers configuration that is forwarded from a data
     /// connector to the client.
     pub response_headers: Option<ResponseHeaders>,
+    /// How requests to the data connector are timed out and retried, and when
+    /// to stop sending them if the data connector keeps failing.
+    pub request_policy: Option<DataConnectorRequestPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, opendds_derive::OpenDd)]
+#[serde(rename_all = "camelCase")]
+#[opendd(json_schema(title = "DataConnectorRequestPolicy"))]
+/// How requests to a data connector are timed out and retried, and when to stop
+/// sending them if the data connector keeps failing.
+pub struct DataConnectorRequestPolicy {
+    /// How long to wait for the data connector to respond, in milliseconds,
+    /// before failing the request. Defaults to waiting indefinitely.
+    pub timeout_ms: Option<u64>,
+    /// How queries that fail because the data connector could not be reached,
+    /// timed out or was unavailable are retried. Mutations are never retried.
+    /// Defaults to not retrying.
+    pub retry: Option<DataConnectorRetryPolicy>,
+    /// When to stop sending requests to a data connector that keeps failing.
+    /// Defaults to always sending them.
+    pub circuit_breaker: Option<DataConnectorCircuitBreaker>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, opendds_derive::OpenDd)]
+#[serde(rename_all = "camelCase")]
+#[opendd(json_schema(title = "DataConnectorRetryPolicy"))]
+/// How failed queries to a data connector are retried. The wait before each
+/// retry doubles, from `initialBackoffMs` up to `maxBackoffMs`, and is
+/// randomised so that requests don't all retry at once.
+pub struct DataConnectorRetryPolicy {
+    /// The most times a failed query is retried.
+    pub max_retries: u32,
+    /// How long to wait before the first retry, in milliseconds.
+    #[opendd(default = 100)]
+    pub initial_backoff_ms: u64,
+    /// The longest to wait before any retry, in milliseconds.
+    #[opendd(default = 5000)]
+    pub max_backoff_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, opendds_derive::OpenDd)]
+#[serde(rename_all = "camelCase")]
+#[opendd(json_schema(title = "DataConnectorCircuitBreaker"))]
+/// Stops requests to a data connector after it fails too many times in a row.
+/// Once `resetTimeoutMs` has passed, one request is let through to check
+/// whether the data connector has recovered.
+pub struct DataConnectorCircuitBreaker {
+    /// The number of requests in a row that must fail before requests are
+    /// stopped.
+    pub failure_threshold: u32,
+    /// How long to stop requests for, in milliseconds.
+    #[opendd(default = 30000)]
+    pub reset_timeout_ms: u64,
 }
 
 #[derive(Serialize, Clone, Debug, PartialEq, opendds_derive::OpenDd)]
@@ -5,6 +5,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::permissions::Role;
 use crate::types::{GraphQlFieldName, GraphQlTypeName};
 
 #[derive(Serialize, Clone, Debug, PartialEq, opendds_derive::OpenDd)]
@@ -31,6 +32,8 @@ pub struct GraphqlConfigV1 {
     pub mutation: MutationGraphqlConfig,
     pub subscription: Option<SubscriptionGraphqlConfig>,
     pub apollo_federation: Option<GraphqlApolloFederationConfig>,
+    pub allow_list: Option<AllowListGraphqlConfig>,
+    pub limits: Option<GraphqlLimitsConfig>,
 }
 
 /// Configuration for the GraphQL schema of Hasura features for queries.
@@ -191,6 +194,67 @@ pub struct GraphqlApolloFederationConfig {
     pub enable_root_fields: bool,
 }
 
+/// Restricts the GraphQL operations some roles may run to a list of allowed operations.
+#[derive(Serialize, Clone, Debug, PartialEq, opendds_derive::OpenDd)]
+#[serde(rename_all = "camelCase")]
+#[opendd(json_schema(title = "AllowListGraphqlConfig"))]
+pub struct AllowListGraphqlConfig {
+    /// The roles that may only run allowed operations. Other roles may run any operation.
+    pub roles: Vec<Role>,
+    /// The allowed operations.
+    pub operations: Vec<AllowedOperation>,
+}
+
+/// An operation in the allow-list.
+#[derive(Serialize, Clone, Debug, PartialEq, opendds_derive::OpenDd)]
+#[serde(rename_all = "camelCase")]
+#[opendd(json_schema(title = "AllowedOperation"))]
+pub struct AllowedOperation {
+    /// The name of the operation.
+    pub name: String,
+    /// The GraphQL document defining the operation. Requests must send the same document, though
+    /// whitespace, commas and comments may differ.
+    pub query: String,
+}
+
+/// Limits on the size of GraphQL operations, checked before they are executed.
+#[derive(Serialize, Clone, Debug, PartialEq, opendds_derive::OpenDd)]
+#[serde(rename_all = "camelCase")]
+#[opendd(json_schema(title = "GraphqlLimitsConfig"))]
+pub struct GraphqlLimitsConfig {
+    /// The deepest nesting of fields an operation may select.
+    pub max_depth: Option<u32>,
+    /// The most aliased fields an operation may select.
+    pub max_aliases: Option<u32>,
+    /// The most fields an operation may select, counting each field of a fragment where it is
+    /// spread.
+    pub max_nodes: Option<u32>,
+    /// The highest estimated cost an operation may have. Each selected field costs one for every
+    /// row it is estimated to be selected for, taking the `limit` argument of a list field as
+    /// the number of rows it returns, or 100 rows if it has none.
+    pub max_cost: Option<u32>,
+    /// Limits for particular roles, used instead of the limits above where they are given.
+    #[opendd(default, json_schema(default_exp = "serde_json::json!([])"))]
+    pub roles: Vec<RoleGraphqlLimits>,
+}
+
+/// Limits on the size of GraphQL operations for a role.
+#[derive(Serialize, Clone, Debug, PartialEq, opendds_derive::OpenDd)]
+#[serde(rename_all = "camelCase")]
+#[opendd(json_schema(title = "RoleGraphqlLimits"))]
+pub struct RoleGraphqlLimits {
+    /// The role the limits apply to.
+    pub role: Role,
+    /// The deepest nesting of fields an operation may select.
+    pub max_depth: Option<u32>,
+    /// The most aliased fields an operation may select.
+    pub max_aliases: Option<u32>,
+    /// The most fields an operation may select.
+    pub max_nodes: Option<u32>,
+    /// The highest estimated cost an operation may have.
+    pub max_cost: Option<u32>,
+}
+
 /// Configuration for the GraphQL schema for aggregates.
 #[derive(Serialize, Clone, Debug, PartialEq, opendds_derive::OpenDd)]
 #[serde(rename_all = "camelCase")]
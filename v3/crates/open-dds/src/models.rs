@@ -291,6 +291,9 @@ pub struct ModelGraphQlDefinition {
     /// Configures the query root field added to the GraphQL API that can be used to
     /// aggregate over the model
     pub aggregate: Option<ModelAggregateGraphQlDefinition>,
+    /// Configures the query root field added to the GraphQL API that can be used to
+    /// group the objects of the model and aggregate over each group
+    pub group_by: Option<ModelGroupByGraphQlDefinition>,
 }
 
 impl ModelGraphQlDefinition {
@@ -325,6 +328,7 @@ impl ModelGraphQlDefinition {
             apollo_federation: self.apollo_federation,
             filter_input_type_name: self.filter_input_type_name,
             aggregate: self.aggregate,
+            group_by: self.group_by,
         }
     }
 }
@@ -355,6 +359,9 @@ pub struct ModelGraphQlDefinitionV2 {
     /// Configures the query root field added to the GraphQL API that can be used to
     /// aggregate over the model
     pub aggregate: Option<ModelAggregateGraphQlDefinition>,
+    /// Configures the query root field added to the GraphQL API that can be used to
+    /// group the objects of the model and aggregate over each group
+    pub group_by: Option<ModelGroupByGraphQlDefinition>,
 }
 
 impl ModelGraphQlDefinitionV2 {
@@ -415,6 +422,9 @@ pub struct SelectManyGraphQlDefinition {
     pub deprecated: Option<Deprecated>,
     /// Enable subscription on this select many root field.
     pub subscription: Option<SubscriptionGraphQlDefinition>,
+    /// Enable a streaming subscription on this model, which returns the objects of the model in
+    /// batches ordered by a cursor field, fetching only objects after the last one returned.
+    pub stream_subscription: Option<StreamSubscriptionGraphQlDefinition>,
 }
 
 /// The definition of the GraphQL API for enabling subscription on query root fields.
@@ -435,6 +445,48 @@ pub struct SubscriptionGraphQlDefinition {
     pub polling_interval_ms: u64,
 }
 
+/// The definition of the GraphQL API for streaming the objects of a model. The subscription root
+/// field takes a cursor field with the value to start after, and each poll returns the next batch
+/// of objects after the last cursor value seen.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, opendds_derive::OpenDd)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[opendd(json_schema(
+    title = "StreamSubscriptionGraphQlDefinition",
+    example = "StreamSubscriptionGraphQlDefinition::example"
+))]
+pub struct StreamSubscriptionGraphQlDefinition {
+    /// The name of the streaming subscription root field.
+    pub root_field: GraphQlFieldName,
+    /// The name of the GraphQL input type used to choose the cursor field and the value to
+    /// start streaming after.
+    pub cursor_input_type_name: GraphQlTypeName,
+    /// The fields that objects can be streamed in order of. Each poll only fetches objects whose
+    /// cursor value is greater than the last one returned, so each field must be a unique
+    /// identifier of the model on its own.
+    pub cursor_fields: Vec<FieldName>,
+    /// The description of the streaming subscription graphql definition.
+    /// Gets added to the description of the streaming subscription root field in the graphql schema.
+    pub description: Option<String>,
+    /// Whether this streaming subscription root field is deprecated.
+    /// If set, the deprecation status is added to the streaming subscription root field's graphql schema.
+    pub deprecated: Option<Deprecated>,
+    /// Polling interval in milliseconds for the streaming subscription.
+    #[opendd(default = 1000)]
+    pub polling_interval_ms: u64,
+}
+
+impl StreamSubscriptionGraphQlDefinition {
+    fn example() -> serde_json::Value {
+        serde_json::json!({
+            "rootField": "ArticleStream",
+            "cursorInputTypeName": "Article_stream_cursor",
+            "cursorFields": ["article_id"],
+            "description": "Stream Articles as they are added",
+            "pollingIntervalMs": 1000
+        })
+    }
+}
+
 /// A field that can be used to order the objects in a model.
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, opendds_derive::OpenDd)]
 #[serde(rename_all = "camelCase")]
@@ -497,6 +549,47 @@ pub struct ModelApolloFederationConfiguration {
     pub entity_source: bool,
 }
 
+/// The definition of the GraphQL API for grouping the objects of a model and aggregating over
+/// each group.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, opendds_derive::OpenDd)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+#[opendd(json_schema(
+    title = "ModelGroupByGraphQlDefinition",
+    example = "ModelGroupByGraphQlDefinition::example"
+))]
+pub struct ModelGroupByGraphQlDefinition {
+    /// The name of the query root field for this API.
+    pub query_root_field: GraphQlFieldName,
+    /// The name of the GraphQL type of each group, which holds its dimensions and aggregates.
+    pub group_type_name: GraphQlTypeName,
+    /// The name of the GraphQL type holding the dimensions of a group, with a field for each
+    /// field of the model that can be grouped by.
+    pub dimensions_type_name: GraphQlTypeName,
+    /// The name of the GraphQL enum type listing the extraction functions that can be applied
+    /// to a dimension, eg. to group timestamps by year. If not set, dimensions cannot use
+    /// extraction functions.
+    pub extraction_function_type_name: Option<GraphQlTypeName>,
+    /// The description of the group by graphql definition of the model.
+    /// Gets added to the description of the group by root field of the model in the graphql schema.
+    pub description: Option<String>,
+    /// Whether this group by query field is deprecated.
+    /// If set, the deprecation status is added to the group by root field's graphql schema.
+    pub deprecated: Option<Deprecated>,
+}
+
+impl ModelGroupByGraphQlDefinition {
+    fn example() -> serde_json::Value {
+        serde_json::json!({
+            "queryRootField": "ArticleGroupBy",
+            "groupTypeName": "Article_group",
+            "dimensionsTypeName": "Article_group_dimensions",
+            "extractionFunctionTypeName": "ExtractionFunction",
+            "description": "Group Articles and aggregate over each group"
+        })
+    }
+}
+
 /// The definition of the GraphQL API for aggregating over a model.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, opendds_derive::OpenDd)]
 #[serde(rename_all = "camelCase")]
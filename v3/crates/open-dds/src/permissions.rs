@@ -626,6 +626,11 @@ pub struct SelectPermission {
     /// Whether the role is allowed to subscribe to the root fields of this model.
     #[opendd(default, json_schema(default_exp = "serde_json::json!(false)"))]
     pub allow_subscriptions: bool,
+    /// The most rows this role can select from the model in one query, whatever limit is
+    /// requested.
+    pub max_rows: Option<u32>,
+    /// The number of rows selected from the model when a query does not request a limit.
+    pub default_limit: Option<u32>,
 }
 
 // We use this instead of an Option, so that we can make the filter field in
@@ -9,6 +9,10 @@ mod relationships;
 mod remote_joins;
 use std::sync::Arc;
 
+use open_dds::data_connector::{DataConnectorColumnName, DataConnectorOperatorName};
+
+use crate::{ComparisonTarget, ComparisonValue, LocalFieldComparison, NdcFieldAlias};
+
 pub use aggregates::{AggregateFieldSelection, AggregateSelectionSet, Dimension, Grouping};
 pub use arguments::{Argument, MutationArgument};
 pub use field::{Field, NestedArray, NestedField, NestedObject};
@@ -49,6 +53,48 @@ pub struct NDCSubscriptionExecution {
     pub execution_span_attribute: &'static str,
     pub field_span_attribute: String,
     pub process_response_as: ProcessResponseAs,
+    /// Set for streaming subscriptions, which fetch the rows after the cursor on every poll
+    pub stream_cursor: Option<StreamCursor>,
+}
+
+/// The cursor of a streaming subscription. Rows are fetched in ascending order of the cursor
+/// column, and the value of the column in the last row fetched is where the next poll starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamCursor {
+    pub column: DataConnectorColumnName,
+    pub greater_than_operator: DataConnectorOperatorName,
+    /// The alias the cursor column is fetched under in every row
+    pub alias: NdcFieldAlias,
+    pub initial_value: serde_json::Value,
+}
+
+impl StreamCursor {
+    /// Restricts the query to the rows after the given cursor value
+    pub fn query_execution_plan_after(
+        &self,
+        query_execution_plan: &QueryExecutionPlan,
+        cursor_value: &serde_json::Value,
+    ) -> QueryExecutionPlan {
+        let mut query_execution_plan = query_execution_plan.clone();
+        let after_cursor = ResolvedFilterExpression::LocalFieldComparison(
+            LocalFieldComparison::BinaryComparison {
+                column: ComparisonTarget::Column {
+                    name: self.column.clone(),
+                    field_path: vec![],
+                },
+                operator: self.greater_than_operator.clone(),
+                value: ComparisonValue::Scalar {
+                    value: cursor_value.clone(),
+                },
+            },
+        );
+        let query_node = &mut query_execution_plan.query_node;
+        query_node.predicate = Some(match query_node.predicate.take() {
+            Some(predicate) => ResolvedFilterExpression::mk_and(vec![predicate, after_cursor]),
+            None => after_cursor,
+        });
+        query_execution_plan
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -86,6 +132,7 @@ pub enum ProcessResponseAs {
         response_config: Option<Arc<metadata_resolve::data_connectors::CommandsResponseConfig>>,
     },
     Aggregates,
+    Groups,
 }
 
 impl ProcessResponseAs {
@@ -94,7 +141,7 @@ impl ProcessResponseAs {
             ProcessResponseAs::Object { is_nullable }
             | ProcessResponseAs::Array { is_nullable }
             | ProcessResponseAs::CommandResponse { is_nullable, .. } => *is_nullable,
-            ProcessResponseAs::Aggregates => false,
+            ProcessResponseAs::Aggregates | ProcessResponseAs::Groups => false,
         }
     }
 }
@@ -17,7 +17,7 @@ pub use execution_plan::{
     QueryExecutionTree, QueryNode, Relationship, RelationshipArgument, RemoteJoin,
     RemoteJoinFieldMapping, RemoteJoinObjectFieldMapping, RemoteJoinObjectTargetField,
     RemoteJoinType, RemoteJoinVariable, RemoteJoinVariableSet, RemotePredicateKey,
-    ResolvedFilterExpression, SourceFieldAlias, TargetField, UniqueNumber,
+    ResolvedFilterExpression, SourceFieldAlias, StreamCursor, TargetField, UniqueNumber,
     mk_argument_target_variable_name,
 };
 pub use expression::{
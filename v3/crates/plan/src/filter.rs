@@ -347,13 +347,61 @@ fn to_relationship_expression<'metadata>(
         PlanError::Internal("Custom operators require a boolean expression type".into())
     })?;
 
-    let field_name = open_dds::types::FieldName::new(relationship_name.clone().into_inner());
-
     // we need to navigate the operand up to this relationship to find the right boolean
     // expression type to start from
     let source_boolean_expression_type =
         boolean_expression_type_for_path(metadata, boolean_expression_type, operand)?;
 
+    // work out path of any nesting before the relationship
+    let column_path = column_path_for_operand(
+        operand,
+        metadata,
+        session,
+        type_mappings,
+        model_object_type,
+        plan_state,
+    )?;
+
+    to_relationship_predicate_expression(
+        relationship_name,
+        RelationshipPredicate::BooleanExpression(predicate),
+        column_path,
+        metadata,
+        session,
+        type_mappings,
+        source_boolean_expression_type,
+        data_connector,
+        plan_state,
+        usage_counts,
+    )
+}
+
+/// What to evaluate against the objects on the other side of a relationship
+#[derive(Clone, Copy)]
+enum RelationshipPredicate<'a> {
+    /// A boolean expression over the related objects, from `BooleanExpression::Relationship`
+    BooleanExpression(&'a BooleanExpression),
+    /// A comparison of a field of the related objects, from an `Operand::Relationship`
+    Comparison {
+        operand: &'a open_dds::query::Operand,
+        comparison: Comparison<'a>,
+    },
+}
+
+fn to_relationship_predicate_expression<'metadata>(
+    relationship_name: &RelationshipName,
+    predicate: RelationshipPredicate<'_>,
+    column_path: Vec<DataConnectorColumnName>,
+    metadata: &'metadata metadata_resolve::Metadata,
+    session: &Session,
+    type_mappings: &'metadata BTreeMap<Qualified<CustomTypeName>, TypeMapping>,
+    source_boolean_expression_type: &'metadata ResolvedObjectBooleanExpressionType,
+    data_connector: &'metadata DataConnectorLink,
+    plan_state: &mut PlanState,
+    usage_counts: &mut UsagesCounts,
+) -> Result<Expression<'metadata>, PlanError> {
+    let field_name = open_dds::types::FieldName::new(relationship_name.clone().into_inner());
+
     // the object type for the left hand side of the relationship
     let source_object_type = crate::metadata_accessor::get_output_object_type(
         metadata,
@@ -367,40 +415,59 @@ fn to_relationship_expression<'metadata>(
         .fields
         .relationship_fields
         .get(&field_name)
-        && let Some(target_boolean_expression_type_name) =
-            &relationship_field.boolean_expression_type
     {
-        let target_boolean_expression_type = metadata
-            .boolean_expression_types
-            .objects
-            .get(target_boolean_expression_type_name)
-            .ok_or_else(|| {
-                PlanError::Permission(PermissionError::ObjectBooleanExpressionTypeNotFound {
-                    boolean_expression_type_name: target_boolean_expression_type_name.clone(),
-                })
-            })?;
-
-        let target_model_object_type = crate::metadata_accessor::get_output_object_type(
-            metadata,
-            &target_boolean_expression_type.object_type,
-            &session.variables,
-            plan_state,
-        )?;
-
         // look up relationship on the source model
         let relationship = source_object_type
             .relationship_fields
             .get(&relationship_field.relationship_name)
             .ok_or_else(|| PermissionError::RelationshipNotFound {
-                object_type_name: target_boolean_expression_type.object_type.clone(),
+                object_type_name: source_boolean_expression_type.object_type.clone(),
                 relationship_name: relationship_field.relationship_name.clone(),
             })?;
 
+        // comparable command relationships resolve without a boolean expression
+        // type, so check the target before requiring one
         match &relationship.target {
             metadata_resolve::RelationshipTarget::Command(_) => {
-                todo!("command target not supported")
+                return Err(BooleanExpressionError::CommandRelationshipNotSupported {
+                    relationship_name: relationship.relationship_name.clone(),
+                }
+                .into());
             }
             metadata_resolve::RelationshipTarget::Model(model_target) => {
+                let Some(target_boolean_expression_type_name) =
+                    &relationship_field.boolean_expression_type
+                else {
+                    return Err(PlanError::Permission(
+                        PermissionError::RelationshipNotFoundInBooleanExpressionType {
+                            relationship_name: relationship_name.clone(),
+                            boolean_expression_type_name: source_boolean_expression_type
+                                .name
+                                .clone(),
+                        },
+                    ));
+                };
+
+                let target_boolean_expression_type = metadata
+                    .boolean_expression_types
+                    .objects
+                    .get(target_boolean_expression_type_name)
+                    .ok_or_else(|| {
+                        PlanError::Permission(
+                            PermissionError::ObjectBooleanExpressionTypeNotFound {
+                                boolean_expression_type_name: target_boolean_expression_type_name
+                                    .clone(),
+                            },
+                        )
+                    })?;
+
+                let target_model_object_type = crate::metadata_accessor::get_output_object_type(
+                    metadata,
+                    &target_boolean_expression_type.object_type,
+                    &session.variables,
+                    plan_state,
+                )?;
+
                 let target_model_source = crate::metadata_accessor::get_model(
                     metadata,
                     &model_target.model_name,
@@ -417,18 +484,39 @@ fn to_relationship_expression<'metadata>(
                 )?;
 
                 // resolve predicate inside the relationship
-                let inner = to_filter_expression_internal(
-                    metadata,
-                    session,
-                    &target_model_source.source.type_mappings,
-                    &target_model_object_type,
-                    Some(target_boolean_expression_type),
-                    predicate,
-                    &target_model_source.source.data_connector,
-                    Nesting::Relationship,
-                    plan_state,
-                    usage_counts,
-                )?;
+                let inner = match predicate {
+                    RelationshipPredicate::BooleanExpression(predicate) => {
+                        to_filter_expression_internal(
+                            metadata,
+                            session,
+                            &target_model_source.source.type_mappings,
+                            &target_model_object_type,
+                            Some(target_boolean_expression_type),
+                            predicate,
+                            &target_model_source.source.data_connector,
+                            Nesting::Relationship,
+                            plan_state,
+                            usage_counts,
+                        )?
+                    }
+                    RelationshipPredicate::Comparison {
+                        operand,
+                        comparison,
+                    } => to_comparison_expression(
+                        operand,
+                        comparison,
+                        &[], // nesting starts again inside the relationship
+                        metadata,
+                        session,
+                        &target_model_source.source.type_mappings,
+                        &target_model_object_type,
+                        Some(target_boolean_expression_type),
+                        &target_model_source.source.data_connector,
+                        Nesting::Relationship,
+                        plan_state,
+                        usage_counts,
+                    )?,
+                };
 
                 // include any predicates from model permissions
                 let predicate =
@@ -439,16 +527,6 @@ fn to_relationship_expression<'metadata>(
                         None => inner,
                     };
 
-                // work out path of any nesting before the relationship
-                let column_path = column_path_for_operand(
-                    operand,
-                    metadata,
-                    session,
-                    type_mappings,
-                    model_object_type,
-                    plan_state,
-                )?;
-
                 return Ok(crate::build_relationship_comparison_expression(
                     type_mappings,
                     column_path,
@@ -473,7 +551,7 @@ fn to_relationship_expression<'metadata>(
     Err(PlanError::Permission(
         PermissionError::RelationshipNotFoundInBooleanExpressionType {
             relationship_name: relationship_name.clone(),
-            boolean_expression_type_name: boolean_expression_type.name.clone(),
+            boolean_expression_type_name: source_boolean_expression_type.name.clone(),
         },
     ))
 }
@@ -591,11 +669,67 @@ fn to_comparison_expression<'metadata>(
             plan_state,
             usage_counts,
         ),
-        open_dds::query::Operand::Relationship(_) => {
-            todo!("Relationship is not supported")
+        // comparing a field of a related object is the same as a relationship predicate
+        // containing the comparison, so it is planned as one
+        open_dds::query::Operand::Relationship(relationship_operand) => {
+            let open_dds::query::RelationshipTarget {
+                relationship_name,
+                arguments,
+                filter,
+                order_by,
+                limit,
+                offset,
+            } = relationship_operand.target.as_ref();
+
+            if !arguments.is_empty()
+                || filter.is_some()
+                || !order_by.is_empty()
+                || limit.is_some()
+                || offset.is_some()
+            {
+                return Err(
+                    BooleanExpressionError::RelationshipOperandTargetNotSupported {
+                        relationship_name: relationship_name.clone(),
+                    }
+                    .into(),
+                );
+            }
+
+            let nested_operand = relationship_operand.nested.as_deref().ok_or_else(|| {
+                BooleanExpressionError::RelationshipOperandWithoutField {
+                    relationship_name: relationship_name.clone(),
+                }
+            })?;
+
+            // Boolean expression type is required to resolve custom operators
+            let boolean_expression_type = boolean_expression_type.ok_or_else(|| {
+                PlanError::Internal("Custom operators require a boolean expression type".into())
+            })?;
+
+            to_relationship_predicate_expression(
+                relationship_name,
+                RelationshipPredicate::Comparison {
+                    operand: nested_operand,
+                    comparison,
+                },
+                column_path.iter().map(|column| (*column).clone()).collect(),
+                metadata,
+                session,
+                type_mappings,
+                boolean_expression_type,
+                data_connector,
+                plan_state,
+                usage_counts,
+            )
         }
-        open_dds::query::Operand::RelationshipAggregate(_) => {
-            todo!("RelationshipAggregate is not implemented")
+        open_dds::query::Operand::RelationshipAggregate(relationship_aggregate_operand) => {
+            Err(BooleanExpressionError::RelationshipAggregateNotSupported {
+                relationship_name: relationship_aggregate_operand
+                    .target
+                    .relationship_name
+                    .clone(),
+            }
+            .into())
         }
     }
 }
@@ -621,6 +755,7 @@ pub(crate) fn model_permission_filter_to_expression<'metadata>(
     }
 }
 
+#[derive(Clone, Copy)]
 enum Comparison<'a> {
     Binary {
         operator: &'a ComparisonOperator,
@@ -20,9 +20,10 @@ pub use query::{
     RelationshipFieldMappingError, SingleNodeExecutionPlan, UnresolvedArgument,
     build_relationship_comparison_expression, collect_remote_join_object_type_field_mappings,
     from_command, from_model_aggregate_selection, from_model_group_by, from_model_selection,
-    get_relationship_field_mapping_of_field_name, plan_expression, plan_query_request,
-    process_argument_presets_for_command, process_argument_presets_for_model,
-    process_command_relationship_definition, process_connector_link_presets,
-    process_model_predicate, process_model_relationship_definition, query_to_plan,
+    get_relationship_field_mapping_of_field_name, make_argument_from_value_expression,
+    plan_expression, plan_query_request, process_argument_presets_for_command,
+    process_argument_presets_for_model, process_command_relationship_definition,
+    process_connector_link_presets, process_model_predicate, process_model_relationship_definition,
+    query_to_plan,
 };
 pub use types::{PermissionError, PlanError, PlanState};
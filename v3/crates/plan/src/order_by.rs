@@ -256,16 +256,16 @@ fn resolve_relationship_operand(
             let ndc_relationship_name =
                 plan_types::NdcRelationshipName::new(type_name, relationship_name);
 
-            // Reject remote relationship
-            super::query::field_selection::reject_remote_relationship(
-                relationship_name,
-                data_connector,
-                &target_model_view.source.data_connector,
-            )
-            .map_err(|e| {
-                // Convert to OrderByError for more specific error handling
-                OrderByError::RemoteRelationshipNotSupported(e.to_string()).into_plan_error()
-            })?;
+            // Ordering has to be pushed down to the source data connector, so
+            // unlike field selections, remote relationships cannot be joined in
+            // afterwards. Metadata resolution already rejects remote orderable
+            // relationships, this guards against a mismatch at runtime.
+            if target_model_view.source.data_connector.name != data_connector.name {
+                return Err(OrderByError::RemoteRelationshipNotSupported(
+                    relationship_name.clone(),
+                )
+                .into_plan_error());
+            }
 
             // Permission filter
             let target_permission_filter = super::filter::resolve_model_permission_filter(
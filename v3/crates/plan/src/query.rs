@@ -17,7 +17,9 @@ pub use command::{CommandPlan, FromCommand, from_command};
 pub use filter::{build_relationship_comparison_expression, plan_expression};
 use indexmap::IndexMap;
 pub use model::{from_model_aggregate_selection, from_model_group_by, from_model_selection};
-pub use permissions::{process_model_predicate, process_permissions};
+pub use permissions::{
+    make_argument_from_value_expression, process_model_predicate, process_permissions,
+};
 pub use relationships::{
     RelationshipFieldMappingError, collect_remote_join_object_type_field_mappings,
     get_relationship_field_mapping_of_field_name, process_command_relationship_definition,
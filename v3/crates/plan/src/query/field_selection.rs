@@ -849,19 +849,6 @@ fn record_local_model_relationship(
     Ok(ndc_relationship_name)
 }
 
-pub fn reject_remote_relationship(
-    relationship_name: &RelationshipName,
-    source_data_connector: &metadata_resolve::DataConnectorLink,
-    target_data_connector: &metadata_resolve::DataConnectorLink,
-) -> Result<(), PlanError> {
-    if target_data_connector.name != source_data_connector.name {
-        return Err(PlanError::Relationship(RelationshipError::Other(format!(
-            "Remote relationships are not supported: {relationship_name}",
-        ))));
-    }
-    Ok(())
-}
-
 /// Resolve a relationship field
 fn from_relationship_aggregate_selection(
     relationship_aggregate_selection: &RelationshipAggregateSelection,
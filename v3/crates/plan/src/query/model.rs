@@ -87,111 +87,117 @@ pub fn from_model_group_by(
                     plan_state,
                 )?;
                 let field_mapping: FieldMapping = column.field_mapping;
-                let extraction_functions = field_mapping
-                    .extraction_functions
-                    .ok_or_else(|| PlanError::Internal("no extraction functions".to_string()))?;
+                // extraction functions are only needed when extracting part of the value
                 let extraction = match extraction {
                     None => None,
-                    Some(extraction) => Some(match extraction {
-                        ExtractionFunction::Year => {
-                            extraction_functions.year_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "year extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::Month => {
-                            extraction_functions.month_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "month extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::Day => {
-                            extraction_functions.day_function.ok_or_else(|| {
-                                PlanError::Internal("day extraction function not found".to_string())
-                            })
-                        }
-                        ExtractionFunction::Nanosecond => {
-                            extraction_functions.nanosecond_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "nanosecond extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::Microsecond => {
-                            extraction_functions.microsecond_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "microsecond extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::Millisecond => {
-                            extraction_functions.millisecond_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "millisecond extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::Second => {
-                            extraction_functions.second_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "second extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::Minute => {
-                            extraction_functions.minute_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "minute extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::Hour => {
-                            extraction_functions.hour_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "hour extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::Week => {
-                            extraction_functions.week_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "week extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::Quarter => {
-                            extraction_functions.quarter_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "quarter extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::DayOfWeek => {
-                            extraction_functions.day_of_week_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "day_of_week extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::DayOfYear => {
-                            extraction_functions.day_of_year_function.ok_or_else(|| {
-                                PlanError::Internal(
-                                    "day_of_year extraction function not found".to_string(),
-                                )
-                            })
-                        }
-                        ExtractionFunction::Custom { name } => extraction_functions
-                            .other_functions
-                            .into_iter()
-                            .find(|f| f.as_str() == name.as_str())
-                            .ok_or_else(|| {
-                                PlanError::Internal(format!(
-                                    "unsupported extraction function {name:?}"
-                                ))
-                            }),
-                    }?),
+                    Some(extraction) => {
+                        let extraction_functions =
+                            field_mapping.extraction_functions.ok_or_else(|| {
+                                PlanError::Internal("no extraction functions".to_string())
+                            })?;
+                        Some(match extraction {
+                            ExtractionFunction::Year => {
+                                extraction_functions.year_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "year extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Month => {
+                                extraction_functions.month_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "month extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Day => {
+                                extraction_functions.day_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "day extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Nanosecond => {
+                                extraction_functions.nanosecond_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "nanosecond extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Microsecond => {
+                                extraction_functions.microsecond_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "microsecond extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Millisecond => {
+                                extraction_functions.millisecond_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "millisecond extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Second => {
+                                extraction_functions.second_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "second extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Minute => {
+                                extraction_functions.minute_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "minute extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Hour => {
+                                extraction_functions.hour_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "hour extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Week => {
+                                extraction_functions.week_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "week extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Quarter => {
+                                extraction_functions.quarter_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "quarter extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::DayOfWeek => {
+                                extraction_functions.day_of_week_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "day_of_week extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::DayOfYear => {
+                                extraction_functions.day_of_year_function.ok_or_else(|| {
+                                    PlanError::Internal(
+                                        "day_of_year extraction function not found".to_string(),
+                                    )
+                                })
+                            }
+                            ExtractionFunction::Custom { name } => extraction_functions
+                                .other_functions
+                                .into_iter()
+                                .find(|f| f.as_str() == name.as_str())
+                                .ok_or_else(|| {
+                                    PlanError::Internal(format!(
+                                        "unsupported extraction function {name:?}"
+                                    ))
+                                }),
+                        }?)
+                    }
                 };
                 Ok(plan_types::Dimension::Column {
                     column_path: nonempty::NonEmpty {
@@ -698,6 +704,11 @@ pub fn from_model_selection(
     // collect relationships accummulated in this scope.
     query.collection_relationships.append(&mut relationships);
 
+    // rows selected without a limit are limited to the role's default limit
+    if model_target.limit.is_none() {
+        query.limit = model_view.permission.row_limits.rows(None);
+    }
+
     let query_fields: Option<FieldsSelection> = if ndc_fields.is_empty() {
         None
     } else {
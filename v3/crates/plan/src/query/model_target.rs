@@ -146,6 +146,9 @@ pub fn model_target_to_ndc_query(
         .transpose()
         .map_err(|_| PlanError::Internal("limit out of range".into()))?;
 
+    // never select more rows than the role is allowed to
+    let limit = model_view.permission.row_limits.cap(limit);
+
     let offset: Option<u32> = model_target
         .offset
         .map(u32::try_from)
@@ -240,8 +240,8 @@ impl TraceableError for RelationshipError {
 pub enum OrderByError {
     #[error("Aggregate relationship {0} is not supported in order_by")]
     RelationshipAggregateNotSupported(RelationshipName),
-    #[error("{0}")]
-    RemoteRelationshipNotSupported(String),
+    #[error("Remote relationship {0} is not supported in order_by")]
+    RemoteRelationshipNotSupported(RelationshipName),
     #[error("Nested order by is not supported: {0}")]
     NestedOrderByNotSupported(String),
     #[error("Can't find field mapping for {field_name} in type: {object_type_name}")]
@@ -286,13 +286,31 @@ pub enum BooleanExpressionError {
     BuiltInOperatorsRequireABooleanExpressionType {
         object_type_name: Qualified<CustomTypeName>,
     },
+    #[error("Command relationship {relationship_name} cannot be used in a boolean expression")]
+    CommandRelationshipNotSupported { relationship_name: RelationshipName },
+    #[error(
+        "Aggregates over relationship {relationship_name} cannot be compared in a boolean expression"
+    )]
+    RelationshipAggregateNotSupported { relationship_name: RelationshipName },
+    #[error(
+        "Relationship {relationship_name} in a boolean expression must select a field to compare"
+    )]
+    RelationshipOperandWithoutField { relationship_name: RelationshipName },
+    #[error(
+        "Arguments, filter, order by, limit and offset are not supported for relationship {relationship_name} in a boolean expression"
+    )]
+    RelationshipOperandTargetNotSupported { relationship_name: RelationshipName },
 }
 
 impl TraceableError for BooleanExpressionError {
     fn visibility(&self) -> ErrorVisibility {
         match self {
             Self::ComparisonOperatorNotFound { .. }
-            | Self::BuiltInOperatorsRequireABooleanExpressionType { .. } => ErrorVisibility::User,
+            | Self::BuiltInOperatorsRequireABooleanExpressionType { .. }
+            | Self::CommandRelationshipNotSupported { .. }
+            | Self::RelationshipAggregateNotSupported { .. }
+            | Self::RelationshipOperandWithoutField { .. }
+            | Self::RelationshipOperandTargetNotSupported { .. } => ErrorVisibility::User,
         }
     }
 }
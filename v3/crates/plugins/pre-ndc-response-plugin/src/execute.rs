@@ -8,7 +8,7 @@ use reqwest::{
     header::{InvalidHeaderName, InvalidHeaderValue},
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+use std::{collections::BTreeMap, str::FromStr, sync::Arc, time::Instant};
 use tracing_util::{ErrorVisibility, SpanVisibility, TraceableError, set_attribute_on_active_span};
 
 #[derive(Debug, thiserror::Error)]
@@ -141,7 +141,8 @@ where
     match pre_ndc_response_plugins.get(&data_connector.name) {
         None => Ok(None),
         Some(plugin) => {
-            handle_pre_ndc_response_plugin(
+            let start = Instant::now();
+            let result = handle_pre_ndc_response_plugin(
                 plugin,
                 data_connector,
                 http_context,
@@ -151,7 +152,9 @@ where
                 operation_type,
                 ndc_version,
             )
-            .await
+            .await;
+            metrics_util::PLUGIN_HOOK_DURATION.observe(&["pre_ndc_response"], start.elapsed());
+            result
         }
     }
 }
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, collections::BTreeMap, str::FromStr};
+use std::{borrow::Cow, collections::BTreeMap, str::FromStr, time::Instant};
 
 use axum::{
     http::{HeaderMap, HeaderName, StatusCode},
@@ -313,6 +313,7 @@ pub async fn pre_parse_plugins_handler(
     let tracer = tracing_util::global_tracer();
     let raw_request = serde_json::from_slice::<RawRequest>(raw_request_bytes)
         .map_err(Error::PluginRequestParseError)?;
+    let start = Instant::now();
     let result = tracer
         .in_span_async(
             "pre_parse_plugin_middleware",
@@ -332,9 +333,10 @@ pub async fn pre_parse_plugins_handler(
                 })
             },
         )
-        .await?;
+        .await;
+    metrics_util::PLUGIN_HOOK_DURATION.observe(&["pre_parse"], start.elapsed());
 
-    match result {
+    match result? {
         PreParsePluginResponse::Return(value) => {
             let plugin_response = axum::response::Response::builder()
                 .status(StatusCode::OK)
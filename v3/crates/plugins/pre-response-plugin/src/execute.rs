@@ -3,6 +3,7 @@ mod common;
 mod synchronous;
 
 use axum::{http::HeaderMap, response::IntoResponse};
+use std::time::Instant;
 
 use hasura_authn_core::Session;
 use lang_graphql::http::RawRequest;
@@ -49,6 +50,7 @@ pub async fn pre_response_plugins_handler(
     if let Some(sync_plugins) =
         nonempty::NonEmpty::from_vec(pre_response_plugins_config.sync_hooks.clone())
     {
+        let start = Instant::now();
         let response = execute_sync_pre_response_plugins(
             client_address,
             &sync_plugins,
@@ -58,8 +60,9 @@ pub async fn pre_response_plugins_handler(
             raw_response,
             &headers_map,
         )
-        .await?;
-        match response {
+        .await;
+        metrics_util::PLUGIN_HOOK_DURATION.observe(&["pre_response"], start.elapsed());
+        match response? {
             PreResponsePluginResponse::Continue => {}
             PreResponsePluginResponse::ReturnResponse(new_response_bytes) => {
                 let plugin_response = axum::response::Response::builder()
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Instant};
 
 use axum::{
     http::{HeaderMap, HeaderName, StatusCode},
@@ -374,6 +374,7 @@ pub async fn pre_route_handler(
             _ => Err(Error::UnsupportedHTTPMethod(method.to_string())),
         }?;
         if re.is_match(uri.path()) && plugin_config.config.match_methods.contains(&request_method) {
+            let start = Instant::now();
             let plugin_response = tracer
                 .in_span_async(
                     "execute_pre_route_plugin",
@@ -400,8 +401,10 @@ pub async fn pre_route_handler(
                         })
                     },
                 )
-                .await?; // Short Circuit; stop executing remaining plugins if current one errors.
-            return Ok(plugin_response);
+                .await;
+            metrics_util::PLUGIN_HOOK_DURATION.observe(&["pre_route"], start.elapsed());
+            // Short Circuit; stop executing remaining plugins if current one errors.
+            return plugin_response;
         }
     }
     Err(Error::NotFound)
@@ -0,0 +1,200 @@
+//! Metric families: a metric with a name, help text and label names, holding a value for each
+//! combination of label values it has been recorded with.
+//!
+//! These wrap the `prometheus` crate's label vectors so that they can be declared as statics:
+//! the underlying vector is built the first time the family is recorded to. Recording an
+//! already seen combination of label values takes a read lock and doesn't allocate.
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use prometheus::core::Collector;
+use prometheus::proto::MetricFamily;
+
+struct Family<T> {
+    name: &'static str,
+    help: &'static str,
+    label_names: &'static [&'static str],
+    vec: OnceLock<T>,
+}
+
+impl<T: Collector> Family<T> {
+    const fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Family {
+            name,
+            help,
+            label_names,
+            vec: OnceLock::new(),
+        }
+    }
+
+    fn get(&self, build: impl FnOnce(&str, &str, &[&str]) -> prometheus::Result<T>) -> &T {
+        self.vec.get_or_init(|| {
+            // names and label names are constants, so this only fails on a programming error
+            build(self.name, self.help, self.label_names)
+                .unwrap_or_else(|err| panic!("invalid metric {}: {err}", self.name))
+        })
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        self.vec.get().map(Collector::collect).unwrap_or_default()
+    }
+}
+
+/// A counter for each combination of label values.
+pub struct CounterVec(Family<prometheus::IntCounterVec>);
+
+impl CounterVec {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        CounterVec(Family::new(name, help, label_names))
+    }
+
+    fn vec(&self) -> &prometheus::IntCounterVec {
+        self.0.get(|name, help, label_names| {
+            prometheus::IntCounterVec::new(prometheus::Opts::new(name, help), label_names)
+        })
+    }
+
+    pub fn inc(&self, label_values: &[&str]) {
+        self.vec().with_label_values(label_values).inc();
+    }
+
+    pub(crate) fn collect(&self) -> Vec<MetricFamily> {
+        self.0.collect()
+    }
+}
+
+/// A gauge for each combination of label values.
+pub struct GaugeVec(Family<prometheus::IntGaugeVec>);
+
+impl GaugeVec {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        GaugeVec(Family::new(name, help, label_names))
+    }
+
+    fn vec(&self) -> &prometheus::IntGaugeVec {
+        self.0.get(|name, help, label_names| {
+            prometheus::IntGaugeVec::new(prometheus::Opts::new(name, help), label_names)
+        })
+    }
+
+    pub fn inc(&self, label_values: &[&str]) {
+        self.vec().with_label_values(label_values).inc();
+    }
+
+    pub fn dec(&self, label_values: &[&str]) {
+        self.vec().with_label_values(label_values).dec();
+    }
+
+    pub(crate) fn collect(&self) -> Vec<MetricFamily> {
+        self.0.collect()
+    }
+}
+
+/// A histogram of durations, in seconds, for each combination of label values, using the
+/// default buckets of the Prometheus client libraries.
+pub struct HistogramVec(Family<prometheus::HistogramVec>);
+
+impl HistogramVec {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        HistogramVec(Family::new(name, help, label_names))
+    }
+
+    fn vec(&self) -> &prometheus::HistogramVec {
+        self.0.get(|name, help, label_names| {
+            prometheus::HistogramVec::new(prometheus::HistogramOpts::new(name, help), label_names)
+        })
+    }
+
+    pub fn observe(&self, label_values: &[&str], duration: Duration) {
+        self.vec()
+            .with_label_values(label_values)
+            .observe(duration.as_secs_f64());
+    }
+
+    pub(crate) fn collect(&self) -> Vec<MetricFamily> {
+        self.0.collect()
+    }
+}
+
+/// Encodes metric families in the Prometheus text exposition format, skipping families that
+/// have not been recorded to.
+pub(crate) fn encode(
+    families: impl IntoIterator<Item = MetricFamily>,
+) -> prometheus::Result<String> {
+    let families = families
+        .into_iter()
+        .filter(|family| !family.get_metric().is_empty())
+        .collect::<Vec<_>>();
+    prometheus::TextEncoder::new().encode_to_string(&families)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_counter() {
+        let counter = CounterVec::new("requests_total", "Requests", &["model"]);
+        counter.inc(&["app.Articles"]);
+        counter.inc(&["app.Articles"]);
+        counter.inc(&["app.\"Authors\""]);
+
+        let out = encode(counter.collect()).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert!(lines.contains(&"# HELP requests_total Requests"));
+        assert!(lines.contains(&"# TYPE requests_total counter"));
+        assert!(lines.contains(&"requests_total{model=\"app.\\\"Authors\\\"\"} 1"));
+        assert!(lines.contains(&"requests_total{model=\"app.Articles\"} 2"));
+    }
+
+    #[test]
+    fn test_encode_gauge_without_labels() {
+        let gauge = GaugeVec::new("connections", "Connections", &[]);
+        gauge.inc(&[]);
+        gauge.inc(&[]);
+        gauge.dec(&[]);
+
+        assert_eq!(
+            encode(gauge.collect()).unwrap(),
+            "# HELP connections Connections\n# TYPE connections gauge\nconnections 1\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_histogram() {
+        let histogram = HistogramVec::new("duration_seconds", "Duration", &["hook"]);
+        histogram.observe(&["pre_parse"], Duration::from_micros(31250));
+        histogram.observe(&["pre_parse"], Duration::from_secs(20));
+
+        let out = encode(histogram.collect()).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert!(lines.contains(&"duration_seconds_bucket{hook=\"pre_parse\",le=\"0.025\"} 0"));
+        assert!(lines.contains(&"duration_seconds_bucket{hook=\"pre_parse\",le=\"0.05\"} 1"));
+        assert!(lines.contains(&"duration_seconds_bucket{hook=\"pre_parse\",le=\"10\"} 1"));
+        assert!(lines.contains(&"duration_seconds_bucket{hook=\"pre_parse\",le=\"+Inf\"} 2"));
+        assert!(lines.contains(&"duration_seconds_sum{hook=\"pre_parse\"} 20.03125"));
+        assert!(lines.contains(&"duration_seconds_count{hook=\"pre_parse\"} 2"));
+    }
+
+    #[test]
+    fn test_unrecorded_families_are_skipped() {
+        let counter = CounterVec::new("unused_total", "Unused", &["model"]);
+        assert_eq!(encode(counter.collect()).unwrap(), "");
+    }
+}
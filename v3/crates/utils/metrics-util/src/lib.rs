@@ -0,0 +1,121 @@
+//! Prometheus metrics recorded by the engine.
+//!
+//! Metrics are kept in process-wide statics, so they can be recorded from any crate without
+//! threading a registry through, and are encoded in the Prometheus text exposition format by
+//! [`encode`].
+mod families;
+
+pub use families::{CounterVec, GaugeVec, HistogramVec};
+
+/// Duration of GraphQL requests, by operation type and whether the response has errors.
+pub static GRAPHQL_REQUEST_DURATION: HistogramVec = HistogramVec::new(
+    "hasura_graphql_request_duration_seconds",
+    "Duration of GraphQL requests, in seconds.",
+    &["operation_type", "status"],
+);
+
+/// Duration of GraphQL requests using a model, by subgraph, model and whether the request failed.
+pub static MODEL_REQUEST_DURATION: HistogramVec = HistogramVec::new(
+    "hasura_model_request_duration_seconds",
+    "Duration of GraphQL requests using a model, in seconds.",
+    &["subgraph", "model", "status"],
+);
+
+/// Duration of GraphQL requests using a command, by subgraph, command and whether the request
+/// failed.
+pub static COMMAND_REQUEST_DURATION: HistogramVec = HistogramVec::new(
+    "hasura_command_request_duration_seconds",
+    "Duration of GraphQL requests using a command, in seconds.",
+    &["subgraph", "command", "status"],
+);
+
+/// Duration of JSON:API requests, by operation and whether the request failed.
+pub static JSONAPI_REQUEST_DURATION: HistogramVec = HistogramVec::new(
+    "hasura_jsonapi_request_duration_seconds",
+    "Duration of JSON:API requests, in seconds.",
+    &["operation", "status"],
+);
+
+/// Duration of requests to data connectors, by subgraph, data connector and operation.
+pub static NDC_REQUEST_DURATION: HistogramVec = HistogramVec::new(
+    "hasura_ndc_request_duration_seconds",
+    "Duration of requests to data connectors, in seconds.",
+    &["subgraph", "data_connector", "operation"],
+);
+
+/// Failed requests to data connectors, by subgraph, data connector and operation.
+pub static NDC_REQUEST_ERRORS: CounterVec = CounterVec::new(
+    "hasura_ndc_request_errors_total",
+    "Number of failed requests to data connectors.",
+    &["subgraph", "data_connector", "operation"],
+);
+
+/// Duration of lifecycle plugin hooks, by hook.
+pub static PLUGIN_HOOK_DURATION: HistogramVec = HistogramVec::new(
+    "hasura_plugin_hook_duration_seconds",
+    "Duration of lifecycle plugin hooks, in seconds.",
+    &["hook"],
+);
+
+/// Failed authentications, by auth mode.
+pub static AUTH_FAILURES: CounterVec = CounterVec::new(
+    "hasura_auth_failures_total",
+    "Number of requests that failed authentication.",
+    &["mode"],
+);
+
+/// Lookups in the response cache, by whether a cached response was found.
+pub static RESPONSE_CACHE_REQUESTS: CounterVec = CounterVec::new(
+    "hasura_response_cache_requests_total",
+    "Number of lookups in the response cache.",
+    &["result"],
+);
+
+/// Open websocket connections.
+pub static WEBSOCKET_CONNECTIONS: GaugeVec = GaugeVec::new(
+    "hasura_websocket_connections",
+    "Number of open websocket connections.",
+    &[],
+);
+
+/// Running operations on websocket connections. Every query, mutation and subscription started
+/// on a websocket connection is counted until it completes or is stopped, including subscribers
+/// that share a poller with others.
+pub static WEBSOCKET_OPERATIONS: GaugeVec = GaugeVec::new(
+    "hasura_websocket_operations",
+    "Number of running operations on websocket connections.",
+    &[],
+);
+
+/// Running subscription pollers. Identical subscriptions share a cohort with a single poller, so
+/// this counts the polls being made to data connectors rather than the subscribers.
+pub static WEBSOCKET_SUBSCRIPTION_POLLERS: GaugeVec = GaugeVec::new(
+    "hasura_websocket_subscription_pollers",
+    "Number of running subscription pollers, each shared by a cohort of identical subscriptions.",
+    &[],
+);
+
+/// Encodes every metric that has been recorded in the Prometheus text exposition format.
+pub fn encode() -> Result<String, prometheus::Error> {
+    families::encode(
+        [
+            GRAPHQL_REQUEST_DURATION.collect(),
+            MODEL_REQUEST_DURATION.collect(),
+            COMMAND_REQUEST_DURATION.collect(),
+            JSONAPI_REQUEST_DURATION.collect(),
+            NDC_REQUEST_DURATION.collect(),
+            NDC_REQUEST_ERRORS.collect(),
+            PLUGIN_HOOK_DURATION.collect(),
+            AUTH_FAILURES.collect(),
+            RESPONSE_CACHE_REQUESTS.collect(),
+            WEBSOCKET_CONNECTIONS.collect(),
+            WEBSOCKET_OPERATIONS.collect(),
+            WEBSOCKET_SUBSCRIPTION_POLLERS.collect(),
+        ]
+        .into_iter()
+        .flatten(),
+    )
+}
+
+/// The content type of [`encode`]'s output.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";